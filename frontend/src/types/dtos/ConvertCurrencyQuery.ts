@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ConvertCurrencyQuery = { 
+/**
+ * Id of the currency `amount` is in.
+ */
+from: string, 
+/**
+ * Id of the currency to convert `amount` into.
+ */
+to: string, amount: string, 
+/**
+ * The date of the rates, now if not given.
+ */
+date?: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ConvertCurrencyResponse = { from: string, to: string, date: string, amount: string, 
+/**
+ * `amount` in `to`, rounded to the decimal places of `to`.
+ */
+convertedAmount: string, fromRateToBase: string, toRateToBase: string, };
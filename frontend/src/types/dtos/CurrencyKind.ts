@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a currency is used to hold.
+ */
+export type CurrencyKind = "fiat" | "crypto" | "security" | "other";
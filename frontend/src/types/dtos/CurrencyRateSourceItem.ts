@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RateSourceKind } from "./RateSourceKind";
+
+/**
+ * A source the rate of `refCurrencyId` is fetched from periodically,
+ * saved as datums expressed in `refAmountCurrencyId`.
+ */
+export type CurrencyRateSourceItem = { id: string, name: string, kind: RateSourceKind, refCurrencyId: string, refAmountCurrencyId: string, 
+/**
+ * Only given for `http` sources.
+ */
+hostname: string | null, path: string, jsonQueryString: string, 
+/**
+ * When a rate was last fetched and saved successfully.
+ */
+lastFetchDate: string | null, 
+/**
+ * Why the last fetch failed, cleared by the next successful one.
+ */
+lastError: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Machine-readable identifier of an [`EndpointsErrors`] variant.
+ * These are part of the API contract, existing codes must never be renamed.
+ */
+export type ErrorCode = "UNAUTHORIZED" | "DATABASE_ERROR" | "CURRENCY_NOT_FOUND" | "CURRENCY_RATE_SOURCE_NOT_FOUND" | "INVALID_RATE_SOURCE" | "INVALID_DECIMAL_VALUE" | "INVALID_CURRENCY_DECIMALS" | "AMOUNT_PRECISION_EXCEEDED" | "INVALID_RATE_MAX_GAP" | "DECIMAL_OVERFLOW" | "INVALID_UUID" | "INVALID_DATE" | "CYCLIC_REF_AMOUNT_CURRENCY" | "MISSING_ARG_PAIR" | "REPEATED_BASE_CURRENCY" | "INTERNAL_SERVER_ERROR" | "MISSING_USERNAME" | "MISSING_PASSWORD" | "ACCOUNT_NOT_FOUND" | "TXN_NOT_FOUND" | "WALLET_NOT_FOUND" | "INVALID_WALLET" | "UNSUPPORTED_ARCHIVE_VERSION" | "INVALID_ARCHIVE" | "INVALID_JSON_BODY" | "UNSUPPORTED_CONTENT_TYPE" | "PAYLOAD_TOO_LARGE" | "INVALID_QUERY" | "ROUTE_NOT_FOUND";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorCode } from "./ErrorCode";
+
+/**
+ * The body of every error response returned by the API.
+ */
+export type ErrorResponseBody = { code: ErrorCode, message: string, 
+/**
+ * Structured context of the error, such as the id of the missing entity.
+ */
+details: Record<string, unknown> | null, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CurrencyKind } from "./CurrencyKind";
+
+export type GetCurrencyQuery = { id: string | null, date: string | null, 
+/**
+ * Only list the currencies of this kind. Ignored when `id` is given.
+ */
+kind: CurrencyKind | null, 
+/**
+ * Also list archived currencies. Ignored when `id` is given, which finds archived currencies too.
+ */
+includeArchived: boolean | null, 
+/**
+ * Tell how every rate was found.
+ */
+explain: boolean | null, };
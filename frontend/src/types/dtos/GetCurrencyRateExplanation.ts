@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GetCurrencyRateExplanationDatum } from "./GetCurrencyRateExplanationDatum";
+import type { RateMethod } from "./RateMethod";
+
+/**
+ * How the rate of a currency was found, with the rates it was computed from.
+ */
+export type GetCurrencyRateExplanation = { currencyId: string, method: RateMethod, rateToBase: string, 
+/**
+ * The datums of the currency used, left one first.
+ */
+datums: Array<GetCurrencyRateExplanationDatum>, 
+/**
+ * Given when the fallback rate of the currency was used.
+ */
+fallbackRateAmount: string | null, 
+/**
+ * How the rates of the currencies the datums or the fallback rate refer to were found.
+ */
+sources: Array<GetCurrencyRateExplanation>, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CurrencyKind } from "./CurrencyKind";
+import type { GetCurrencyRateExplanation } from "./GetCurrencyRateExplanation";
+import type { RateStrategy } from "./RateStrategy";
+
+export type GetCurrencyResponseItem = { id: string, name: string, fallbackRateAmount: string | null, fallbackRateCurrencyId: string | null, ticker: string, isBase: boolean, owner: string, 
+/**
+ * Rates are ratios between currencies rather than amounts, they keep 20 decimal places.
+ */
+rateToBase: string, decimals: number, kind: CurrencyKind, symbol: string | null, archived: boolean, 
+/**
+ * Not given for the base currency.
+ */
+rateStrategy: RateStrategy | null, rateMaxGapDays: number | null, deriveRates: boolean, 
+/**
+ * Only given with `explain=true`.
+ */
+explanation: GetCurrencyRateExplanation | null, };
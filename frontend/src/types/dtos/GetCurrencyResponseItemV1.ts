@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `GetCurrencyResponseItem` with the snake_case field names still served under `/api/v1`.
+ */
+export type GetCurrencyResponseItemV1 = { id: string, name: string, fallback_rate_amount: string | null, fallback_rate_currency_id: string | null, ticker: string, is_base: boolean, owner: string, rate_to_base: string, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GetTxnsResponseFragment } from "./GetTxnsResponseFragment";
+
+export type GetTxnsResponseItem = { id: string, title: string, description: string, date: string, fragments: Array<GetTxnsResponseFragment>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RateSourceKind } from "./RateSourceKind";
+
+export type PatchCurrencyRateSourceRequest = { name?: string, kind?: RateSourceKind, refAmountCurrencyId?: string, hostname?: string, path?: string, jsonQueryString?: string, };
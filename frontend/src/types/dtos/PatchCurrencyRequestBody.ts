@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CurrencyKind } from "./CurrencyKind";
+import type { RateStrategy } from "./RateStrategy";
+
+export type PatchCurrencyRequestBody = { kind?: CurrencyKind, 
+/**
+ * An empty string removes the symbol.
+ */
+symbol?: string, 
+/**
+ * Archived currencies are left out of `GET /currencies`, their rates are still used.
+ */
+archived?: boolean, 
+/**
+ * Replaces `rateMaxGapDays` too, removing it unless given.
+ */
+rateStrategy?: RateStrategy, rateMaxGapDays?: number, 
+/**
+ * Only affects transactions created or edited afterwards, see the `derive-rates` command.
+ */
+deriveRates?: boolean, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConvertCurrencyResponse } from "./ConvertCurrencyResponse";
+
+export type PostConvertCurrencyResponse = { 
+/**
+ * One item per query, in the same order.
+ */
+items: Array<ConvertCurrencyResponse>, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RateSourceKind } from "./RateSourceKind";
+
+export type PostCurrencyRateSourceRequest = { name: string, 
+/**
+ * Defaults to `http`.
+ */
+kind?: RateSourceKind, refCurrencyId: string, refAmountCurrencyId: string, 
+/**
+ * Required by `http` sources, `https://` is assumed if it has no scheme.
+ */
+hostname?: string, 
+/**
+ * The path of the URL, or of the file relative to `rateSources.fileDirectory`.
+ */
+path: string, 
+/**
+ * Where the rate is in the JSON document, such as `$.rates.USD` or `$.data[0]["price"]`.
+ */
+jsonQueryString: string, };
@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CurrencyKind } from "./CurrencyKind";
+import type { RateStrategy } from "./RateStrategy";
+
+export type PostCurrencyRequestBody = { name: string, fallbackRateAmount: string | null, fallbackRateCurrencyId: string | null, ticker: string, 
+/**
+ * Decimal places of amounts in this currency, 2 if not given. Use 8 for BTC and 0 for JPY.
+ */
+decimals?: number, 
+/**
+ * `fiat` if not given.
+ */
+kind?: CurrencyKind, 
+/**
+ * Display symbol, such as "$" or "₿".
+ */
+symbol?: string, 
+/**
+ * `linear` if not given. Ignored for base currencies.
+ */
+rateStrategy?: RateStrategy, 
+/**
+ * Required by `linearWithMaxGap`, refused by the other strategies.
+ */
+rateMaxGapDays?: number, 
+/**
+ * Record the rates of transactions converting this currency as rate datums, `false` if
+ * not given. Ignored for base currencies.
+ */
+deriveRates?: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type PostImportUserQuery = { 
+/**
+ * Assign fresh ids to every imported row instead of keeping the ids in the archive.
+ */
+remapIds: boolean | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type PostImportUserResponseBody = { accounts: number, currencies: number, currencyRateDatums: number, txnTags: number, txns: number, currencyRateSources: number, wallets: number, walletTxns: number, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PostTxnRequestFragment } from "./PostTxnRequestFragment";
+
+export type PostTxnRequest = { description: string, title: string, dateUtc: string, fragments: Array<PostTxnRequestFragment>, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WalletChain } from "./WalletChain";
+
+export type PostWalletRequest = { name: string, chain: WalletChain, address: string, 
+/**
+ * The account receiving the synced transactions.
+ */
+accountId: string, 
+/**
+ * The currency of the synced amounts, which needs the decimal places of the chain.
+ */
+currencyId: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How the rate of a currency to the base currency was found.
+ */
+export type RateMethod = "base" | "interpolated" | "leftOnly" | "nearest" | "fallback";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where a currency rate source reads its JSON document from.
+ */
+export type RateSourceKind = "http" | "file";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How the rate of a currency is derived from its datums.
+ */
+export type RateStrategy = "linear" | "previousValue" | "nearest" | "linearWithMaxGap";
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UserArchiveAccount } from "./UserArchiveAccount";
+import type { UserArchiveCurrency } from "./UserArchiveCurrency";
+import type { UserArchiveCurrencyRateDatum } from "./UserArchiveCurrencyRateDatum";
+import type { UserArchiveCurrencyRateSource } from "./UserArchiveCurrencyRateSource";
+import type { UserArchiveTxn } from "./UserArchiveTxn";
+import type { UserArchiveTxnTag } from "./UserArchiveTxnTag";
+import type { UserArchiveWallet } from "./UserArchiveWallet";
+import type { UserArchiveWalletTxn } from "./UserArchiveWalletTxn";
+
+/**
+ * A self-contained snapshot of everything owned by a single user. 
+ */
+export type UserArchive = { version: number, exportedAt: string, owner: string, accounts: Array<UserArchiveAccount>, currencies: Array<UserArchiveCurrency>, currencyRateDatums: Array<UserArchiveCurrencyRateDatum>, txnTags: Array<UserArchiveTxnTag>, txns: Array<UserArchiveTxn>, 
+/**
+ * Missing from archives older than version 6, imported as none.
+ */
+currencyRateSources?: Array<UserArchiveCurrencyRateSource>, 
+/**
+ * Missing from archives older than version 6, imported as none.
+ */
+wallets?: Array<UserArchiveWallet>, 
+/**
+ * Missing from archives older than version 6, imported as none.
+ */
+walletTxns?: Array<UserArchiveWalletTxn>, };
@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CurrencyKind } from "./CurrencyKind";
+import type { RateStrategy } from "./RateStrategy";
+
+export type UserArchiveCurrency = { id: string, name: string, ticker: string, isBase: boolean, fallbackRateAmount: string | null, fallbackRateCurrencyId: string | null, 
+/**
+ * Missing from version 1 archives, imported as the default of 2.
+ */
+decimals?: number, 
+/**
+ * Missing from archives older than version 3, imported as `fiat`.
+ */
+kind?: CurrencyKind, symbol?: string, 
+/**
+ * Missing from archives older than version 3, imported as not archived.
+ */
+archived?: boolean, 
+/**
+ * Missing from archives older than version 4, imported as `linear`.
+ */
+rateStrategy?: RateStrategy, rateMaxGapDays?: number, 
+/**
+ * Missing from archives older than version 5, imported as not deriving rates.
+ */
+deriveRates?: boolean, };
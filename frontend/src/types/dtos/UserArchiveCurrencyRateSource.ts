@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RateSourceKind } from "./RateSourceKind";
+
+export type UserArchiveCurrencyRateSource = { id: string, name: string, kind: RateSourceKind, refCurrencyId: string, refAmountCurrencyId: string, hostname: string | null, path: string, jsonQueryString: string, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UserArchiveFragmentSide } from "./UserArchiveFragmentSide";
+
+export type UserArchiveFragment = { id: string, from: UserArchiveFragmentSide | null, to: UserArchiveFragmentSide | null, };
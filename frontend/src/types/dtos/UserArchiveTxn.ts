@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UserArchiveFragment } from "./UserArchiveFragment";
+
+export type UserArchiveTxn = { id: string, title: string, description: string, date: string, fragments: Array<UserArchiveFragment>, };
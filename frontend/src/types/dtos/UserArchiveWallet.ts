@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WalletChain } from "./WalletChain";
+
+/**
+ * The sync cursor is left out, transfers already synced are known from [`UserArchiveWalletTxn`].
+ */
+export type UserArchiveWallet = { id: string, name: string, chain: WalletChain, address: string, accountId: string, currencyId: string, };
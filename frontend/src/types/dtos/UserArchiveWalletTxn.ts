@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WalletChain } from "./WalletChain";
+
+/**
+ * A transfer synced from `address`, into `txnId` unless that txn was deleted since.
+ */
+export type UserArchiveWalletTxn = { chain: WalletChain, address: string, txHash: string, txnId: string | null, };
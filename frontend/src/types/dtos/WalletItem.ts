@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WalletChain } from "./WalletChain";
+
+/**
+ * A blockchain address whose transfers are synced as transactions of `accountId`.
+ */
+export type WalletItem = { id: string, name: string, chain: WalletChain, address: string, accountId: string, currencyId: string, 
+/**
+ * When the wallet was last synced successfully.
+ */
+lastSyncDate: string | null, 
+/**
+ * Why the last sync failed, cleared by the next successful one.
+ */
+lastError: string | null, };
@@ -0,0 +1,24 @@
+use std::process::Command;
+
+/// Bake the current git commit into the binary as `FM_GIT_HASH`, served by `GET /version`.
+/// Builds outside a git checkout may set `FM_GIT_HASH` themselves, otherwise "unknown" is used.
+fn main() {
+    println!("cargo:rerun-if-env-changed=FM_GIT_HASH");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
+
+    let git_hash = std::env::var("FM_GIT_HASH").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|hash| hash.trim().to_string())
+    });
+
+    println!(
+        "cargo:rustc-env=FM_GIT_HASH={}",
+        git_hash.unwrap_or_else(|| "unknown".to_string())
+    );
+}
@@ -1,4 +1,5 @@
 pub use finance_manager_migration::Migrator;
+pub use m20261019_000001_decimal_amounts::is_convertible_amount;
 pub use sea_orm::prelude::*;
 pub use sea_orm_migration::{MigrationStatus, MigrationTrait, MigratorTrait};
 mod m20220101_000002_create_user_table;
@@ -9,6 +10,13 @@ mod m20250208_000001_currency_rate_datum;
 mod m20250301_000001_create_txn_tag_table;
 mod m20250315_000001_create_fragment_table;
 mod m20250315_000002_create_txn_table;
+mod m20261019_000001_decimal_amounts;
+mod m20261019_000002_currency_decimals;
+mod m20261019_000003_currency_metadata;
+mod m20261019_000004_currency_rate_strategy;
+mod m20261019_000005_currency_rate_source;
+mod m20261019_000006_derived_rate_datums;
+mod m20261019_000007_wallet;
 
 pub mod finance_manager_migration {
     pub struct Migrator;
@@ -26,6 +34,13 @@ impl MigratorTrait for Migrator {
             Box::new(m20250301_000001_create_txn_tag_table::Migration),
             Box::new(m20250315_000002_create_txn_table::Migration),
             Box::new(m20250315_000001_create_fragment_table::Migration),
+            Box::new(m20261019_000001_decimal_amounts::Migration),
+            Box::new(m20261019_000002_currency_decimals::Migration),
+            Box::new(m20261019_000003_currency_metadata::Migration),
+            Box::new(m20261019_000004_currency_rate_strategy::Migration),
+            Box::new(m20261019_000005_currency_rate_source::Migration),
+            Box::new(m20261019_000006_derived_rate_datums::Migration),
+            Box::new(m20261019_000007_wallet::Migration),
         ]
     }
 }
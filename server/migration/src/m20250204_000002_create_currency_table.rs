@@ -135,4 +135,11 @@ pub enum Currency {
     IsBase,
     FallbackRateAmount,
     FallbackRateCurrencyId,
+    Decimals,
+    Kind,
+    Symbol,
+    Archived,
+    RateStrategy,
+    RateMaxGapDays,
+    DeriveRates,
 }
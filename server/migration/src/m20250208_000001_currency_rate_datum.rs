@@ -127,4 +127,5 @@ pub enum CurrencyRateDatum {
     RefAmountCurrencyId,
     OwnerId,
     Date,
+    SourceFragmentId,
 }
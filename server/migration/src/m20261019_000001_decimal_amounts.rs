@@ -0,0 +1,298 @@
+use crate::{
+    m20250204_000002_create_currency_table::Currency,
+    m20250208_000001_currency_rate_datum::CurrencyRateDatum,
+    m20250315_000001_create_fragment_table::Fragment,
+};
+use sea_orm::prelude::{Decimal, Uuid};
+use sea_orm::sqlx::{self, Row};
+use sea_orm::{DatabaseBackend, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::SchemaManagerConnection;
+use std::str::FromStr;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261019_000001_decimal_amounts"
+    }
+}
+
+/// Every amount column, by table.
+fn amount_columns() -> Vec<(String, Vec<String>)> {
+    vec![
+        (
+            Currency::Table.to_string(),
+            vec![Currency::FallbackRateAmount.to_string()],
+        ),
+        (
+            CurrencyRateDatum::Table.to_string(),
+            vec![CurrencyRateDatum::Amount.to_string()],
+        ),
+        (
+            Fragment::Table.to_string(),
+            vec![
+                Fragment::FromAmount.to_string(),
+                Fragment::ToAmount.to_string(),
+            ],
+        ),
+    ]
+}
+
+/// Declared types of the amount columns before and after the migration.
+struct ColumnTypes {
+    from: &'static str,
+    to: &'static str,
+    /// Type the existing values are cast to.
+    cast: &'static str,
+}
+
+const POSTGRES_UP: ColumnTypes = ColumnTypes {
+    from: "varchar",
+    to: "numeric",
+    cast: "numeric",
+};
+const POSTGRES_DOWN: ColumnTypes = ColumnTypes {
+    from: "numeric",
+    to: "varchar",
+    cast: "text",
+};
+// SQLite has no exact numeric type, and SeaORM reads decimals from it through `f64` only,
+// refusing text values, so the amounts are stored as `real`, like `ColumnType::Decimal` does on
+// SQLite. Amounts a `real` cannot hold exactly are refused instead of being rounded.
+const SQLITE_UP: ColumnTypes = ColumnTypes {
+    from: "varchar",
+    to: "real",
+    cast: "REAL",
+};
+const SQLITE_DOWN: ColumnTypes = ColumnTypes {
+    from: "real",
+    to: "varchar",
+    cast: "TEXT",
+};
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let invalid_amounts = find_invalid_amounts(manager).await?;
+        if !invalid_amounts.is_empty() {
+            return Err(DbErr::Migration(format!(
+                "{} amount(s) cannot be converted to decimals, fix them and migrate again \
+                 (`repair amounts` reports them with their owners): {}",
+                invalid_amounts.len(),
+                invalid_amounts.join("; ")
+            )));
+        }
+        match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => rebuild_sqlite_tables(manager, SQLITE_UP).await,
+            _ => alter_postgres_columns(manager, POSTGRES_UP).await,
+        }
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => rebuild_sqlite_tables(manager, SQLITE_DOWN).await,
+            _ => alter_postgres_columns(manager, POSTGRES_DOWN).await,
+        }
+    }
+}
+
+/// Whether `value` converts to a decimal without losing anything on `backend`.
+/// Postgres `numeric` keeps every digit, SQLite `real` only those surviving a round trip.
+pub fn is_convertible_amount(value: &str, backend: DatabaseBackend) -> bool {
+    let Ok(amount) = Decimal::from_str(value) else {
+        return false;
+    };
+    match backend {
+        DatabaseBackend::Sqlite => <f64 as TryFrom<Decimal>>::try_from(amount)
+            .ok()
+            .and_then(|x| <Decimal as TryFrom<f64>>::try_from(x).ok())
+            .is_some_and(|x| x == amount),
+        _ => true,
+    }
+}
+
+/// Every stored amount which cannot be converted, as `table.column "value" in row id`.
+/// Checked before touching any table, so that no value is ever coerced.
+async fn find_invalid_amounts(manager: &SchemaManager<'_>) -> Result<Vec<String>, DbErr> {
+    let db = manager.get_connection();
+    let backend = manager.get_database_backend();
+    let mut invalid_amounts = vec![];
+    for (table, columns) in amount_columns() {
+        for column in columns {
+            let rows = db
+                .query_all(Statement::from_string(
+                    backend,
+                    format!(
+                        "SELECT \"id\", CAST(\"{column}\" AS TEXT) AS \"value\" \
+                         FROM \"{table}\" WHERE \"{column}\" IS NOT NULL ORDER BY \"id\""
+                    ),
+                ))
+                .await?;
+            for row in rows {
+                let value: String = row.try_get("", "value")?;
+                if !is_convertible_amount(&value, backend) {
+                    let id: Uuid = row.try_get("", "id")?;
+                    invalid_amounts.push(format!("{table}.{column} \"{value}\" in row {id}"));
+                }
+            }
+        }
+    }
+    Ok(invalid_amounts)
+}
+
+/// Convert the columns in place, every value being checked beforehand.
+async fn alter_postgres_columns(
+    manager: &SchemaManager<'_>,
+    types: ColumnTypes,
+) -> Result<(), DbErr> {
+    for (table, columns) in amount_columns() {
+        let alterations = columns
+            .iter()
+            .map(|column| {
+                format!(
+                    "ALTER COLUMN \"{column}\" TYPE {} USING \"{column}\"::{}",
+                    types.to, types.cast
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        manager
+            .get_connection()
+            .execute_unprepared(&format!("ALTER TABLE \"{table}\" {alterations}"))
+            .await?;
+    }
+    Ok(())
+}
+
+/// SQLite cannot change the type of a column, so every table is rebuilt following
+/// https://www.sqlite.org/lang_altertable.html#otheralter: foreign keys are disabled on a single
+/// connection, and a copy of the table with the new column types replaces the original.
+async fn rebuild_sqlite_tables(
+    manager: &SchemaManager<'_>,
+    types: ColumnTypes,
+) -> Result<(), DbErr> {
+    let SchemaManagerConnection::Connection(db) = manager.get_connection() else {
+        return Err(DbErr::Migration(
+            "SQLite tables cannot be rebuilt inside a transaction.".to_string(),
+        ));
+    };
+    let mut conn = db
+        .get_sqlite_connection_pool()
+        .acquire()
+        .await
+        .map_err(to_db_err)?;
+
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .map_err(to_db_err)?;
+    let rebuilt = async {
+        sqlx::query("BEGIN").execute(&mut *conn).await?;
+        for (table, columns) in amount_columns() {
+            if let Err(err) = rebuild_sqlite_table(&mut conn, &table, &columns, &types).await {
+                sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+                return Err(err);
+            }
+        }
+        sqlx::query("COMMIT").execute(&mut *conn).await?;
+        Ok(())
+    }
+    .await;
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .map_err(to_db_err)?;
+    rebuilt.map_err(to_db_err)
+}
+
+async fn rebuild_sqlite_table(
+    conn: &mut sqlx::SqliteConnection,
+    table: &str,
+    columns: &[String],
+    types: &ColumnTypes,
+) -> Result<(), sqlx::Error> {
+    let rebuild_table = format!("{table}_rebuild");
+
+    let create_sql: String =
+        sqlx::query("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(table)
+            .fetch_one(&mut *conn)
+            .await?
+            .try_get(0)?;
+    let index_sqls = sqlx::query(
+        "SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ? AND sql IS NOT NULL",
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(|row| row.try_get::<String, _>(0))
+    .collect::<Result<Vec<_>, _>>()?;
+    let all_columns = sqlx::query("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|row| row.try_get::<String, _>(0))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut rebuild_sql =
+        create_sql.replacen(&format!("\"{table}\""), &format!("\"{rebuild_table}\""), 1);
+    for column in columns {
+        let from = format!("\"{column}\" {}", types.from);
+        if !rebuild_sql.contains(&from) {
+            return Err(sqlx::Error::Protocol(format!(
+                "Column {table}.{column} is not of type {}.",
+                types.from
+            )));
+        }
+        rebuild_sql = rebuild_sql.replacen(&from, &format!("\"{column}\" {}", types.to), 1);
+    }
+    let selected = all_columns
+        .iter()
+        .map(|column| match columns.contains(column) {
+            true => format!("CAST(\"{column}\" AS {})", types.cast),
+            false => format!("\"{column}\""),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let inserted = all_columns
+        .iter()
+        .map(|column| format!("\"{column}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    sqlx::query(&rebuild_sql).execute(&mut *conn).await?;
+    sqlx::query(&format!(
+        "INSERT INTO \"{rebuild_table}\" ({inserted}) SELECT {selected} FROM \"{table}\""
+    ))
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(&format!("DROP TABLE \"{table}\""))
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(&format!(
+        "ALTER TABLE \"{rebuild_table}\" RENAME TO \"{table}\""
+    ))
+    .execute(&mut *conn)
+    .await?;
+    for index_sql in index_sqls {
+        sqlx::query(&index_sql).execute(&mut *conn).await?;
+    }
+
+    let violations = sqlx::query(&format!("PRAGMA foreign_key_check(\"{table}\")"))
+        .fetch_all(&mut *conn)
+        .await?;
+    if !violations.is_empty() {
+        return Err(sqlx::Error::Protocol(format!(
+            "Rebuilding {table} broke {} foreign keys.",
+            violations.len()
+        )));
+    }
+    Ok(())
+}
+
+fn to_db_err(err: sqlx::Error) -> DbErr {
+    DbErr::Migration(err.to_string())
+}
@@ -0,0 +1,98 @@
+use crate::{
+    m20250204_000002_create_currency_table::Currency,
+    m20250315_000001_create_fragment_table::Fragment,
+};
+use sea_orm::prelude::{Decimal, Uuid};
+use sea_orm_migration::prelude::*;
+use std::collections::HashMap;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261019_000002_currency_decimals"
+    }
+}
+
+/// Decimal places of currencies created without any, enough for most fiat currencies.
+const DEFAULT_DECIMALS: u32 = 2;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Currency::Table)
+                    .add_column(
+                        ColumnDef::new(Currency::Decimals)
+                            .integer()
+                            .not_null()
+                            .default(DEFAULT_DECIMALS),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing amounts must stay valid, so currencies get at least as many decimal places as their most precise fragment.
+        for ((owner_id, currency_id), decimals) in fragment_decimals(manager).await? {
+            if decimals <= DEFAULT_DECIMALS {
+                continue;
+            }
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Currency::Table)
+                        .value(Currency::Decimals, decimals)
+                        .and_where(Expr::col(Currency::OwnerId).eq(owner_id))
+                        .and_where(Expr::col(Currency::Id).eq(currency_id))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Currency::Table)
+                    .drop_column(Currency::Decimals)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// The most decimal places used by the fragments of each currency, by owner and currency id.
+async fn fragment_decimals(
+    manager: &SchemaManager<'_>,
+) -> Result<HashMap<(Uuid, Uuid), u32>, DbErr> {
+    let db = manager.get_connection();
+    let mut output = HashMap::new();
+    for (currency_column, amount_column) in [
+        (Fragment::FromCurrencyId, Fragment::FromAmount),
+        (Fragment::ToCurrencyId, Fragment::ToAmount),
+    ] {
+        let (currency_name, amount_name) = (currency_column.to_string(), amount_column.to_string());
+        let select = Query::select()
+            .column(Fragment::OwnerId)
+            .column(currency_column)
+            .column(Alias::new(&amount_name))
+            .from(Fragment::Table)
+            .and_where(Expr::col(amount_column).is_not_null())
+            .to_owned();
+        for row in db
+            .query_all(db.get_database_backend().build(&select))
+            .await?
+        {
+            let owner_id: Uuid = row.try_get("", &Fragment::OwnerId.to_string())?;
+            let currency_id: Uuid = row.try_get("", &currency_name)?;
+            let amount: Decimal = row.try_get("", &amount_name)?;
+            let decimals = output.entry((owner_id, currency_id)).or_insert(0);
+            *decimals = amount.normalize().scale().max(*decimals);
+        }
+    }
+    Ok(output)
+}
@@ -0,0 +1,58 @@
+use crate::m20250204_000002_create_currency_table::Currency;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261019_000003_currency_metadata"
+    }
+}
+
+/// Kind of the currencies created before kinds existed.
+const DEFAULT_KIND: &str = "fiat";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only accepts a single change per `ALTER TABLE`
+        let columns = [
+            ColumnDef::new(Currency::Kind)
+                .string()
+                .not_null()
+                .default(DEFAULT_KIND)
+                .to_owned(),
+            ColumnDef::new(Currency::Symbol).string().to_owned(),
+            ColumnDef::new(Currency::Archived)
+                .boolean()
+                .not_null()
+                .default(false)
+                .to_owned(),
+        ];
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Currency::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [Currency::Archived, Currency::Symbol, Currency::Kind] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Currency::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
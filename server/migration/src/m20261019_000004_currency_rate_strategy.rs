@@ -0,0 +1,55 @@
+use crate::m20250204_000002_create_currency_table::Currency;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261019_000004_currency_rate_strategy"
+    }
+}
+
+/// Strategy of the currencies created before strategies existed, the only one used until then.
+const DEFAULT_RATE_STRATEGY: &str = "linear";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only accepts a single change per `ALTER TABLE`
+        let columns = [
+            ColumnDef::new(Currency::RateStrategy)
+                .string()
+                .not_null()
+                .default(DEFAULT_RATE_STRATEGY)
+                .to_owned(),
+            ColumnDef::new(Currency::RateMaxGapDays)
+                .integer()
+                .to_owned(),
+        ];
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Currency::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [Currency::RateMaxGapDays, Currency::RateStrategy] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Currency::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
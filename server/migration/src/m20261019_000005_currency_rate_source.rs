@@ -0,0 +1,121 @@
+use super::m20220101_000002_create_user_table::User;
+use crate::m20250204_000002_create_currency_table::Currency;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261019_000005_currency_rate_source"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let mut main_table = Table::create();
+        let mut table = main_table.table(CurrencyRateSource::Table);
+
+        {
+            table = table.col(ColumnDef::new(CurrencyRateSource::Id).uuid().not_null());
+            table = table.col(
+                ColumnDef::new(CurrencyRateSource::OwnerId)
+                    .uuid()
+                    .not_null(),
+            );
+            table = table.primary_key(
+                Index::create()
+                    .col(CurrencyRateSource::Id)
+                    .col(CurrencyRateSource::OwnerId),
+            );
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("currency_rate_source_owner")
+                    .take()
+                    .from(CurrencyRateSource::Table, CurrencyRateSource::OwnerId)
+                    .to(User::Table, User::Id),
+            );
+        }
+
+        {
+            table = table.col(
+                ColumnDef::new(CurrencyRateSource::RefCurrencyId)
+                    .uuid()
+                    .not_null(),
+            );
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("currency_rate_source_ref_currency_id")
+                    .take()
+                    .from(
+                        CurrencyRateSource::Table,
+                        (
+                            CurrencyRateSource::RefCurrencyId,
+                            CurrencyRateSource::OwnerId,
+                        ),
+                    )
+                    .to(Currency::Table, (Currency::Id, Currency::OwnerId)),
+            );
+        }
+
+        {
+            table = table.col(
+                ColumnDef::new(CurrencyRateSource::RefAmountCurrencyId)
+                    .uuid()
+                    .not_null(),
+            );
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("currency_rate_source_ref_amount_currency_id")
+                    .take()
+                    .from(
+                        CurrencyRateSource::Table,
+                        (
+                            CurrencyRateSource::RefAmountCurrencyId,
+                            CurrencyRateSource::OwnerId,
+                        ),
+                    )
+                    .to(Currency::Table, (Currency::Id, Currency::OwnerId)),
+            );
+        }
+
+        table = table
+            .col(ColumnDef::new(CurrencyRateSource::Name).string().not_null())
+            .col(ColumnDef::new(CurrencyRateSource::Kind).string().not_null())
+            .col(ColumnDef::new(CurrencyRateSource::Hostname).string())
+            .col(ColumnDef::new(CurrencyRateSource::Path).string().not_null())
+            .col(
+                ColumnDef::new(CurrencyRateSource::JsonQueryString)
+                    .string()
+                    .not_null(),
+            )
+            .col(ColumnDef::new(CurrencyRateSource::LastFetchDate).date_time())
+            .col(ColumnDef::new(CurrencyRateSource::LastError).string());
+
+        manager.create_table(table.to_owned()).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CurrencyRateSource::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum CurrencyRateSource {
+    Table,
+    Id,
+    OwnerId,
+    RefCurrencyId,
+    RefAmountCurrencyId,
+    Name,
+    Kind,
+    Hostname,
+    Path,
+    JsonQueryString,
+    LastFetchDate,
+    LastError,
+}
@@ -0,0 +1,61 @@
+use crate::m20250204_000002_create_currency_table::Currency;
+use crate::m20250208_000001_currency_rate_datum::CurrencyRateDatum;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261019_000006_derived_rate_datums"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Currency::Table)
+                    .add_column(
+                        ColumnDef::new(Currency::DeriveRates)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // Fragments are replaced on every transaction edit, so the link is kept without a
+        // foreign key and cleaned up by the transaction services instead
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CurrencyRateDatum::Table)
+                    .add_column(ColumnDef::new(CurrencyRateDatum::SourceFragmentId).uuid())
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CurrencyRateDatum::Table)
+                    .drop_column(CurrencyRateDatum::SourceFragmentId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Currency::Table)
+                    .drop_column(Currency::DeriveRates)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
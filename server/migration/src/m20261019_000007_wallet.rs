@@ -0,0 +1,143 @@
+use super::m20220101_000002_create_user_table::User;
+use crate::m20250204_000001_create_account_table::Account;
+use crate::m20250204_000002_create_currency_table::Currency;
+use crate::m20250315_000002_create_txn_table::Txn;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261019_000007_wallet"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let mut main_table = Table::create();
+        let mut table = main_table.table(Wallet::Table);
+
+        {
+            table = table.col(ColumnDef::new(Wallet::Id).uuid().not_null());
+            table = table.col(ColumnDef::new(Wallet::OwnerId).uuid().not_null());
+            table = table.primary_key(Index::create().col(Wallet::Id).col(Wallet::OwnerId));
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("wallet_owner")
+                    .take()
+                    .from(Wallet::Table, Wallet::OwnerId)
+                    .to(User::Table, User::Id),
+            );
+        }
+
+        {
+            table = table.col(ColumnDef::new(Wallet::AccountId).uuid().not_null());
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("wallet_account_id")
+                    .take()
+                    .from(Wallet::Table, (Wallet::AccountId, Wallet::OwnerId))
+                    .to(Account::Table, (Account::Id, Account::OwnerId)),
+            );
+        }
+
+        {
+            table = table.col(ColumnDef::new(Wallet::CurrencyId).uuid().not_null());
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("wallet_currency_id")
+                    .take()
+                    .from(Wallet::Table, (Wallet::CurrencyId, Wallet::OwnerId))
+                    .to(Currency::Table, (Currency::Id, Currency::OwnerId)),
+            );
+        }
+
+        table = table
+            .col(ColumnDef::new(Wallet::Name).string().not_null())
+            .col(ColumnDef::new(Wallet::Chain).string().not_null())
+            .col(ColumnDef::new(Wallet::Address).string().not_null())
+            .col(ColumnDef::new(Wallet::Cursor).string())
+            .col(ColumnDef::new(Wallet::LastSyncDate).date_time())
+            .col(ColumnDef::new(Wallet::LastError).string());
+
+        manager.create_table(table.to_owned()).await?;
+
+        // Remembers which on-chain transfers of an address were synced, so that syncing the same
+        // transfer again does nothing, even from another wallet of the same address.
+        // `txn_id` is cleared when the transaction is deleted, which keeps it from coming back
+        let mut main_table = Table::create();
+        let mut table = main_table.table(WalletTxn::Table);
+
+        {
+            table = table
+                .col(ColumnDef::new(WalletTxn::OwnerId).uuid().not_null())
+                .col(ColumnDef::new(WalletTxn::Chain).string().not_null())
+                .col(ColumnDef::new(WalletTxn::Address).string().not_null())
+                .col(ColumnDef::new(WalletTxn::TxHash).string().not_null());
+            table = table.primary_key(
+                Index::create()
+                    .col(WalletTxn::OwnerId)
+                    .col(WalletTxn::Chain)
+                    .col(WalletTxn::Address)
+                    .col(WalletTxn::TxHash),
+            );
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("wallet_txn_owner")
+                    .take()
+                    .from(WalletTxn::Table, WalletTxn::OwnerId)
+                    .to(User::Table, User::Id),
+            );
+        }
+
+        {
+            table = table.col(ColumnDef::new(WalletTxn::TxnId).uuid());
+            table = table.foreign_key(
+                ForeignKey::create()
+                    .name("wallet_txn_txn_id")
+                    .take()
+                    .from(WalletTxn::Table, (WalletTxn::TxnId, WalletTxn::OwnerId))
+                    .to(Txn::Table, (Txn::Id, Txn::OwnerId)),
+            );
+        }
+
+        manager.create_table(table.to_owned()).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WalletTxn::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Wallet::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Wallet {
+    Table,
+    Id,
+    OwnerId,
+    AccountId,
+    CurrencyId,
+    Name,
+    Chain,
+    Address,
+    Cursor,
+    LastSyncDate,
+    LastError,
+}
+
+#[derive(Iden)]
+pub enum WalletTxn {
+    Table,
+    OwnerId,
+    Chain,
+    Address,
+    TxHash,
+    TxnId,
+}
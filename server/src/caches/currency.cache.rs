@@ -1,4 +1,6 @@
-use crate::{extended_models::currency::Currency, extractors::auth_user::AuthUser};
+use crate::{
+    extended_models::currency::Currency, extractors::auth_user::AuthUser, metrics::METRICS,
+};
 
 // TODO: We might be able to use concurrency map for this. For now just use Mutex on the whole thing first.
 // TODO: Or we might be able to use VecDeque for this.
@@ -14,11 +16,38 @@ impl CurrencyCache {
             items: Vec::with_capacity(size),
         }
     }
-    pub fn register_item(&mut self, entry: Currency) {
+    /// Register the new state of a currency, dropping every stale copy of it.
+    pub fn replace_item(&mut self, entry: Currency) {
+        let (id, owner) = match &entry {
+            Currency::Normal { id, owner, .. } | Currency::Base { id, owner, .. } => (*id, owner.0),
+        };
+        self.items.retain(|item| match item {
+            Currency::Normal {
+                id: item_id,
+                owner: item_owner,
+                ..
+            }
+            | Currency::Base {
+                id: item_id,
+                owner: item_owner,
+                ..
+            } => *item_id != id || item_owner.0 != owner,
+        });
         self.items.push(entry);
     }
+    /// Remove every cached currency owned by the given user.
+    pub fn evict_owner(&mut self, owner: &AuthUser) {
+        self.items.retain(|item| match item {
+            Currency::Normal {
+                owner: item_owner, ..
+            }
+            | Currency::Base {
+                owner: item_owner, ..
+            } => item_owner.0 != owner.0,
+        });
+    }
     pub fn query_base_currency(&self, owner: &AuthUser) -> Option<&Currency> {
-        self.items.iter().find(|item| {
+        let result = self.items.iter().find(|item| {
             let cache_item_is_base = matches!(item, Currency::Base { .. });
             let cache_item_owner_id = match item {
                 Currency::Normal { owner, .. } | Currency::Base { owner, .. } => owner.0,
@@ -26,7 +55,9 @@ impl CurrencyCache {
             .to_string();
 
             cache_item_is_base && cache_item_owner_id == owner.0.to_string()
-        })
+        });
+        METRICS.record_cache_lookup("currency", result.is_some());
+        result
     }
     #[allow(unused)]
     pub fn query_item_by_currency_id(
@@ -34,7 +65,7 @@ impl CurrencyCache {
         owner: &AuthUser,
         currency_id: uuid::Uuid,
     ) -> Option<&Currency> {
-        self.items.iter().find(|item| {
+        let result = self.items.iter().find(|item| {
             let cache_item_currency_id = match item {
                 Currency::Normal { id, .. } | Currency::Base { id, .. } => id,
             };
@@ -46,6 +77,8 @@ impl CurrencyCache {
             .to_string();
 
             cache_item_currency_id.0 == currency_id && owner.0.to_string() == cache_item_owner_id
-        })
+        });
+        METRICS.record_cache_lookup("currency", result.is_some());
+        result
     }
 }
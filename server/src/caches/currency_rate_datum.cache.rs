@@ -1,4 +1,4 @@
-use crate::routes::currency_rate_datums::CurrencyRateDatum;
+use crate::{extractors::auth_user::AuthUser, routes::currency_rate_datums::CurrencyRateDatum};
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -24,4 +24,16 @@ impl CurrencyRateDatumCache {
             Some(existing_vec) => existing_vec.push(entry),
         }
     }
+
+    /// Remove the cached datums of the given user with one of the given ids.
+    pub fn remove_items(&mut self, owner: &AuthUser, ids: &[Uuid]) {
+        if let Some(existing_vec) = self.items.get_mut(&owner.0) {
+            existing_vec.retain(|datum| !ids.contains(&datum.id));
+        }
+    }
+
+    /// Remove every cached datum owned by the given user.
+    pub fn evict_owner(&mut self, owner: &AuthUser) {
+        self.items.remove(&owner.0);
+    }
 }
@@ -14,6 +14,10 @@ impl TxnTagsCache {
     pub fn register_item(&mut self, entry: TxnTag) {
         self.items.push(entry);
     }
+    /// Remove every cached tag owned by the given user.
+    pub fn evict_owner(&mut self, owner: &AuthUser) {
+        self.items.retain(|item| item.owner_id != owner.0);
+    }
     // TODO: currently do simple iter loop first, change this in the future
     pub fn query_txn_tag(&self, owner: &AuthUser) -> Vec<TxnTag> {
         self.items
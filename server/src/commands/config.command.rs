@@ -0,0 +1,219 @@
+use crate::env::{
+    AppEnv, EnvDb, EnvLogMode, EnvLogRotation, DEFAULT_POOL_MAX_CONNECTIONS,
+    DEFAULT_POOL_MIN_CONNECTIONS,
+};
+use crate::services::wallets::esplora_url;
+use clap::Subcommand;
+use sea_orm::Database;
+use serde_json::Value;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommands {
+    /// Validate the config merged with the `FM_` environment variables, including the database
+    /// connection, and print it with secrets redacted.
+    Check,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigCommandErrors {
+    #[error("Config has {0} problem(s).")]
+    InvalidConfig(usize),
+    #[error("Unable to print the config: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// Fields whose values are never printed.
+pub const SECRET_FIELDS: [&str; 1] = ["password"];
+
+pub const REDACTED: &str = "<redacted>";
+
+/// How long an unreachable database is waited for, much shorter than when serving.
+const CHECK_DATABASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Fields holding URLs, whose password is never printed.
+pub const URL_FIELDS: [&str; 1] = ["url"];
+
+/// Replace the value of every secret field and the password of every URL field, at any depth.
+pub fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if SECRET_FIELDS.contains(&name.as_str()) && !field.is_null() {
+                    *field = Value::String(REDACTED.to_string());
+                } else if let (true, Value::String(url)) =
+                    (URL_FIELDS.contains(&name.as_str()), &field)
+                {
+                    *field = Value::String(redact_url_password(url));
+                } else {
+                    redact_secrets(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+/// Unparsable URLs are redacted entirely, as the password cannot be told apart.
+fn redact_url_password(url: &str) -> String {
+    match url::Url::parse(url) {
+        Err(_) => REDACTED.to_string(),
+        Ok(mut url) => {
+            if url.password().is_some() {
+                let _ = url.set_password(Some(REDACTED));
+            }
+            url.to_string()
+        }
+    }
+}
+
+fn parent_dir_exists(path: &str) -> bool {
+    match Path::new(path).parent() {
+        None => false,
+        Some(parent) => parent.as_os_str().is_empty() || parent.is_dir(),
+    }
+}
+
+/// Check everything which can be checked without side effects, and return the problems found.
+pub fn validate_config(env: &AppEnv) -> Vec<String> {
+    let mut problems = vec![];
+
+    if let Some(server) = &env.server {
+        if server.port == Some(0) {
+            problems.push("server.port must be between 1 and 65535.".to_string());
+        }
+        if server.workers == Some(0) {
+            problems.push("server.workers must be at least 1.".to_string());
+        }
+        if !Path::new(&server.dist_folder_path).is_dir() {
+            problems.push(format!(
+                "server.distFolderPath \"{}\" is not a directory.",
+                server.dist_folder_path
+            ));
+        }
+        if let Some(ssl) = &server.ssl {
+            if let Err(err) = ssl.to_ssl_acceptor() {
+                problems.push(format!("server.ssl: {err}"));
+            }
+            match ssl.http_redirect_port {
+                Some(0) => problems
+                    .push("server.ssl.httpRedirectPort must be between 1 and 65535.".to_string()),
+                Some(redirect_port) if server.port == Some(redirect_port) => problems
+                    .push("server.ssl.httpRedirectPort must differ from server.port.".to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    if let EnvDb::Postgres { pool, .. } | EnvDb::Url { pool, .. } = &env.storage.db {
+        let pool = pool.clone().unwrap_or_default();
+        if pool.max_connections == Some(0) {
+            problems.push("storage.db.pool.maxConnections must be at least 1.".to_string());
+        }
+        let max_connections = pool.max_connections.unwrap_or(DEFAULT_POOL_MAX_CONNECTIONS);
+        let min_connections = pool.min_connections.unwrap_or(DEFAULT_POOL_MIN_CONNECTIONS);
+        if min_connections > max_connections {
+            problems.push(format!(
+                "storage.db.pool.minConnections ({min_connections}) must not exceed maxConnections ({max_connections})."
+            ));
+        }
+    }
+    if let EnvDb::SQLite { path } = &env.storage.db {
+        // Connection options such as `?mode=rwc` are not part of the path
+        let file_path = path.split('?').next().unwrap_or_default();
+        if !parent_dir_exists(file_path) {
+            problems.push(format!(
+                "storage.db.path \"{path}\" is not in an existing directory."
+            ));
+        }
+    }
+
+    if let EnvLogMode::File { path } | EnvLogMode::Both { path } = &env.logging.log_mode {
+        if !parent_dir_exists(path) {
+            problems.push(format!(
+                "logging.logMode.path \"{path}\" is not in an existing directory."
+            ));
+        }
+    }
+    if let Err(err) = env.logging.to_filter() {
+        problems.push(format!("logging: {err:?}"));
+    }
+    if let Some(EnvLogRotation::Size { max_bytes: 0, .. }) = env.logging.rotation {
+        problems.push("logging.rotation.maxBytes must be at least 1.".to_string());
+    }
+
+    if let Some(metrics) = &env.metrics {
+        if !metrics.path().starts_with('/') {
+            problems.push(format!(
+                "metrics.path \"{}\" must start with \"/\".",
+                metrics.path()
+            ));
+        }
+    }
+
+    if let Some(rate_sources) = &env.rate_sources {
+        problems.extend(rate_sources.problems());
+    }
+
+    if let Some(wallets) = &env.wallets {
+        problems.extend(wallets.problems());
+        for (field, url) in [
+            ("btcEsploraUrl", &wallets.btc_esplora_url),
+            ("ltcEsploraUrl", &wallets.ltc_esplora_url),
+        ] {
+            if let Some(Err(err)) = url.as_deref().map(esplora_url) {
+                problems.push(format!("wallets.{field}: {err}"));
+            }
+        }
+    }
+
+    problems
+}
+
+pub struct ConfigCheckReport {
+    /// The merged config with secrets redacted.
+    pub config: Value,
+    pub problems: Vec<String>,
+}
+
+impl ConfigCheckReport {
+    /// The lines to print, starting with the redacted config.
+    pub fn to_lines(&self) -> Result<Vec<String>, ConfigCommandErrors> {
+        let mut lines = vec![serde_json::to_string_pretty(&self.config)?];
+        match self.problems.is_empty() {
+            true => lines.push("Config is valid.".to_string()),
+            false => lines.extend(self.problems.iter().map(|x| format!("Problem: {x}"))),
+        }
+        Ok(lines)
+    }
+}
+
+/// Validate the config and try connecting to the database.
+pub async fn run_config_check(env: &AppEnv) -> Result<ConfigCheckReport, ConfigCommandErrors> {
+    let mut config = serde_json::to_value(env)?;
+    redact_secrets(&mut config);
+
+    let mut problems = validate_config(env);
+    match env.to_connection_options() {
+        Err(err) => problems.push(format!("storage.db: {err}")),
+        Ok(mut connect_options) => {
+            connect_options
+                .connect_timeout(CHECK_DATABASE_TIMEOUT)
+                .acquire_timeout(CHECK_DATABASE_TIMEOUT);
+            match Database::connect(connect_options).await {
+                Err(err) => problems.push(format!("Database is unreachable: {err}")),
+                Ok(db) => {
+                    if let Err(err) = db.ping().await {
+                        problems.push(format!("Database is unreachable: {err}"));
+                    }
+                    let _ = db.close().await;
+                }
+            }
+        }
+    }
+
+    Ok(ConfigCheckReport { config, problems })
+}
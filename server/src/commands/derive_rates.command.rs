@@ -0,0 +1,55 @@
+use crate::entities::user;
+use crate::extractors::auth_user::AuthUser;
+use crate::services::txns::{derive_rate_datums, get_txns};
+use crate::services::users::get_user_by_name;
+use crate::services::TransactionWithCallback;
+use crate::states::database_states::DatabaseStates;
+use sea_orm::EntityTrait;
+use std::error::Error;
+use tracing::info;
+
+/// Derive rate datums from the saved transactions of the user named `username`, or of every
+/// user, such as the ones saved before their currencies derived rates.
+/// Fragments which already have a datum at their date are skipped, so running it again is harmless.
+/// Returns the number of datums created.
+pub async fn run_derive_rates(
+    db_states: &DatabaseStates,
+    username: Option<&str>,
+) -> Result<usize, Box<dyn Error>> {
+    let users = match username {
+        Some(username) => vec![get_user_by_name(username, &db_states.db)
+            .await?
+            .ok_or_else(|| format!("User \"{username}\" does not exist."))?],
+        None => user::Entity::find().all(&db_states.db).await?,
+    };
+
+    let mut derived_count = 0;
+    for user in users {
+        let owner = AuthUser(user.id);
+        let db_txn = TransactionWithCallback::from_db_conn(&db_states.db, vec![]).await?;
+        let (txns, mut db_txn) = get_txns(&owner, db_txn).await?;
+
+        let mut user_derived_count = 0;
+        for (txn, fragments) in txns {
+            let count;
+            (count, db_txn) = derive_rate_datums(
+                &owner,
+                txn.date,
+                &fragments,
+                db_txn,
+                db_states.currency_rate_datums_cache.clone(),
+                db_states.currency_cache.clone(),
+            )
+            .await?;
+            user_derived_count += count;
+        }
+        db_txn.commit().await;
+
+        info!(
+            "Derived {user_derived_count} currency rate datums for user \"{}\".",
+            user.name
+        );
+        derived_count += user_derived_count;
+    }
+    Ok(derived_count)
+}
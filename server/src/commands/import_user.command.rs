@@ -0,0 +1,48 @@
+use crate::extended_models::user_archive::UserArchive;
+use crate::extractors::auth_user::AuthUser;
+use crate::routes::bootstrap::EndpointsErrors;
+use crate::services::user_archive::import_user_archive;
+use crate::services::users::get_user_by_name;
+use crate::services::TransactionWithCallback;
+use crate::states::database_states::DatabaseStates;
+use std::error::Error;
+use tracing::info;
+
+/// Import the archive at `archive_path` into the existing user named `username`.
+/// Everything is inserted in a single transaction, nothing is written if any row fails.
+pub async fn run_import_user(
+    db_states: &DatabaseStates,
+    username: &str,
+    archive_path: &str,
+    remap_ids: bool,
+) -> Result<(), Box<dyn Error>> {
+    let user = get_user_by_name(username, &db_states.db)
+        .await?
+        .ok_or_else(|| format!("User \"{username}\" does not exist."))?;
+
+    let archive: UserArchive = serde_json::from_str(&std::fs::read_to_string(archive_path)?)?;
+
+    let db_txn = TransactionWithCallback::from_db_conn(&db_states.db, vec![]).await?;
+    let db_txn = import_user_archive(
+        &AuthUser(user.id),
+        &archive,
+        remap_ids,
+        db_txn,
+        db_states.currency_cache.clone(),
+        db_states.currency_rate_datums_cache.clone(),
+        db_states.txn_tags_cache.clone(),
+    )
+    .await
+    .map_err(|err| EndpointsErrors::from(err).to_string())?;
+    db_txn.commit().await;
+
+    info!(
+        "Imported {} accounts, {} currencies, {} currency rate datums, {} txn tags and {} txns into user \"{username}\".",
+        archive.accounts.len(),
+        archive.currencies.len(),
+        archive.currency_rate_datums.len(),
+        archive.txn_tags.len(),
+        archive.txns.len(),
+    );
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use crate::env::AppMode;
+use clap::Subcommand;
+use finance_manager_migration::{MigrationStatus, Migrator, MigratorTrait};
+use sea_orm::{DatabaseConnection, DbErr};
+use thiserror::Error;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum MigrateCommands {
+    /// List every migration and whether it has been applied.
+    Status,
+
+    /// Apply every pending migration.
+    Up {
+        /// Only print the migrations that would be applied.
+        #[arg(long("dry-run"))]
+        dry_run: bool,
+    },
+
+    /// Roll back the last applied migrations.
+    Down {
+        /// Number of migrations to roll back, defaults to 1.
+        n: Option<u32>,
+
+        /// Only print the migrations that would be rolled back.
+        #[arg(long("dry-run"))]
+        dry_run: bool,
+    },
+
+    /// Drop every table and apply all migrations again. Only allowed in development mode.
+    Fresh {
+        /// Only print the migrations that would be applied.
+        #[arg(long("dry-run"))]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum MigrateCommandErrors {
+    #[error("`migrate fresh` drops every table, it is only allowed when envMode is Development.")]
+    FreshOutsideDevelopment,
+    #[error("Database error: {0}")]
+    DbErr(#[from] DbErr),
+}
+
+/// Run the given migration command, and return the report lines to print.
+pub async fn run_migrate(
+    db: &DatabaseConnection,
+    env_mode: &AppMode,
+    action: MigrateCommands,
+) -> Result<Vec<String>, MigrateCommandErrors> {
+    let report = |verb: &str, names: Vec<String>| match names.is_empty() {
+        true => vec!["No migrations affected.".to_string()],
+        false => names.into_iter().map(|x| format!("{verb} {x}")).collect(),
+    };
+
+    match action {
+        MigrateCommands::Status => Ok(Migrator::get_migration_with_status(db)
+            .await?
+            .iter()
+            .map(|x| {
+                let status = match x.status() {
+                    MigrationStatus::Applied => "Applied",
+                    MigrationStatus::Pending => "Pending",
+                };
+                format!("{status:<8} {}", x.name())
+            })
+            .collect()),
+        MigrateCommands::Up { dry_run } => {
+            let pending = Migrator::get_pending_migrations(db)
+                .await?
+                .iter()
+                .map(|x| x.name().to_string())
+                .collect();
+            if dry_run {
+                return Ok(report("Would apply", pending));
+            }
+            Migrator::up(db, None).await?;
+            Ok(report("Applied", pending))
+        }
+        MigrateCommands::Down { n, dry_run } => {
+            let n = n.unwrap_or(1);
+            let targets = Migrator::get_applied_migrations(db)
+                .await?
+                .iter()
+                .rev()
+                .take(n as usize)
+                .map(|x| x.name().to_string())
+                .collect();
+            if dry_run {
+                return Ok(report("Would roll back", targets));
+            }
+            Migrator::down(db, Some(n)).await?;
+            Ok(report("Rolled back", targets))
+        }
+        MigrateCommands::Fresh { dry_run } => {
+            if !matches!(env_mode, AppMode::Development) {
+                return Err(MigrateCommandErrors::FreshOutsideDevelopment);
+            }
+            let all = Migrator::migrations()
+                .iter()
+                .map(|x| x.name().to_string())
+                .collect();
+            if dry_run {
+                return Ok(report("Would apply", all));
+            }
+            Migrator::fresh(db).await?;
+            Ok(report("Applied", all))
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::env::AppEnv;
+use finance_manager_migration::{Migrator, MigratorTrait};
+use sea_orm::{Database, DatabaseConnection, DbErr};
+use tracing::info;
+
+#[path = "./config.command.rs"]
+pub mod config;
+
+#[path = "./derive_rates.command.rs"]
+pub mod derive_rates;
+
+#[path = "./import_user.command.rs"]
+pub mod import_user;
+
+#[path = "./migrate.command.rs"]
+pub mod migrate;
+
+#[path = "./repair.command.rs"]
+pub mod repair;
+
+#[path = "./serve.command.rs"]
+pub mod serve;
+
+#[path = "./sync_wallets.command.rs"]
+pub mod sync_wallets;
+
+#[cfg_attr(test, mutants::skip)]
+pub async fn connect_database(env: &AppEnv) -> Result<DatabaseConnection, DbErr> {
+    info!("Connecting to database...");
+    let options = env
+        .to_connection_options()
+        .map_err(|err| DbErr::Custom(err.to_string()))?;
+    let db = Database::connect(options).await?;
+    info!("Connected to database...");
+    Ok(db)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PendingMigrationsErrors {
+    #[error("Database is not fully migrated, {} migration(s) pending: {}. Run `migrate up` first.", .0.len(), .0.join(", "))]
+    NotFullyMigrated(Vec<String>),
+    #[error(transparent)]
+    DbErr(#[from] DbErr),
+}
+
+/// Apply the pending migrations before running a command against the database.
+/// If `exit_on_not_fully_migrated` is set, refuse with the pending names instead.
+pub async fn apply_pending_migrations(
+    db: &DatabaseConnection,
+    exit_on_not_fully_migrated: bool,
+) -> Result<(), PendingMigrationsErrors> {
+    let pending = Migrator::get_pending_migrations(db).await?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let pending_names = pending
+        .iter()
+        .map(|x| x.name().to_string())
+        .collect::<Vec<_>>();
+    if exit_on_not_fully_migrated {
+        return Err(PendingMigrationsErrors::NotFullyMigrated(pending_names));
+    }
+    info!("Applying pending migrations: {}.", pending_names.join(", "));
+    Migrator::up(db, None).await?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use crate::maths::is_valid_rate_amount;
+use clap::Subcommand;
+use finance_manager_migration::is_convertible_amount;
+use rust_decimal::Decimal;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr, Statement};
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RepairCommands {
+    /// Report every currency fallback rate and currency rate datum whose amount is not a finite
+    /// positive decimal, and every fragment whose amount is not a decimal. Nothing is changed.
+    /// Legacy text amounts are checked too, before the migration converting them refuses them.
+    Amounts,
+}
+
+#[derive(Debug, Error)]
+pub enum RepairCommandErrors {
+    #[error("Found {0} invalid row(s).")]
+    InvalidRows(usize),
+    #[error("Database error: {0}")]
+    DbErr(#[from] DbErr),
+}
+
+/// Tables and nullable columns holding amounts, and whether they must be positive.
+const AMOUNT_COLUMNS: [(&str, &str, bool); 4] = [
+    ("currency", "fallback_rate_amount", true),
+    ("currency_rate_datum", "amount", true),
+    ("fragment", "from_amount", false),
+    ("fragment", "to_amount", false),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidAmountRow {
+    pub table: String,
+    pub column: String,
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    /// The stored value, as text.
+    pub value: String,
+}
+
+/// Postgres prints `NaN` and infinities, SQLite prints large or tiny reals in scientific notation.
+/// Plain decimals are only accepted if the backend stores them exactly, like the migration to
+/// decimal amounts requires.
+fn parse_stored_amount(value: &str, backend: DatabaseBackend) -> Option<Decimal> {
+    match Decimal::from_str(value) {
+        Ok(amount) => is_convertible_amount(value, backend).then_some(amount),
+        Err(_) => Decimal::from_scientific(value).ok(),
+    }
+}
+
+/// Scan every stored amount, and return the rows whose amount is not valid anymore.
+/// Amounts are read as text, so this works before and after they were migrated to decimals.
+pub async fn find_invalid_amounts(db: &DatabaseConnection) -> Result<Vec<InvalidAmountRow>, DbErr> {
+    let mut invalid_rows = vec![];
+    for (table, column, is_rate) in AMOUNT_COLUMNS {
+        let rows = db
+            .query_all(Statement::from_string(
+                db.get_database_backend(),
+                format!(
+                    "SELECT \"id\", \"owner_id\", CAST(\"{column}\" AS TEXT) AS \"value\" FROM \"{table}\" WHERE \"{column}\" IS NOT NULL"
+                ),
+            ))
+            .await?;
+        for row in rows {
+            let value: String = row.try_get("", "value")?;
+            let is_valid = parse_stored_amount(&value, db.get_database_backend())
+                .is_some_and(|amount| !is_rate || is_valid_rate_amount(&amount));
+            if is_valid {
+                continue;
+            }
+            invalid_rows.push(InvalidAmountRow {
+                table: table.to_string(),
+                column: column.to_string(),
+                id: row.try_get("", "id")?,
+                owner_id: row.try_get("", "owner_id")?,
+                value,
+            });
+        }
+    }
+    Ok(invalid_rows)
+}
+
+pub struct RepairReport {
+    pub invalid_rows: Vec<InvalidAmountRow>,
+}
+
+impl RepairReport {
+    pub fn to_lines(&self) -> Vec<String> {
+        match self.invalid_rows.is_empty() {
+            true => vec!["Every amount is valid.".to_string()],
+            false => self
+                .invalid_rows
+                .iter()
+                .map(|row| {
+                    format!(
+                        "Invalid {}.{} \"{}\" in row {} owned by user {}.",
+                        row.table, row.column, row.value, row.id, row.owner_id
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Run the given repair command, and return what it found.
+pub async fn run_repair(
+    db: &DatabaseConnection,
+    action: RepairCommands,
+) -> Result<RepairReport, RepairCommandErrors> {
+    match action {
+        RepairCommands::Amounts => Ok(RepairReport {
+            invalid_rows: find_invalid_amounts(db).await?,
+        }),
+    }
+}
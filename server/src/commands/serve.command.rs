@@ -0,0 +1,198 @@
+use crate::commands::{apply_pending_migrations, connect_database};
+use crate::env::{self, AppEnv};
+use crate::metrics::{configure_metrics, metrics_middleware};
+use crate::request_tracing::request_tracing_middleware;
+use crate::routes::bootstrap::{apply_endpoints, json_config};
+use crate::routes::dist::configure_dist_files;
+use crate::services::currency_rate_sources::run_rate_sources_scheduler;
+use crate::shutdown::{wait_for_signal, SHUTDOWN};
+use crate::ssl;
+use crate::states::database_states::DatabaseStates;
+use actix_web::dev::ServerHandle;
+use actix_web::middleware::{from_fn, Condition};
+use actix_web::{web, App, HttpServer};
+use std::error::Error;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Run the HTTP(S) server until it is stopped.
+/// Pending migrations are applied before serving, unless `exit_on_not_fully_migrated` is set,
+/// in which case the server refuses to start instead.
+#[cfg_attr(test, mutants::skip)]
+pub async fn run_serve(
+    env: &AppEnv,
+    exit_on_not_fully_migrated: bool,
+) -> Result<(), Box<dyn Error>> {
+    let port = {
+        let port = match env.server {
+            None => None,
+            Some(ref server_section) => server_section.port,
+        };
+        if let Some(port_given) = port {
+            port_given
+        } else {
+            info!("No port given in env file, finding unused port starting from 1000.");
+            let unused_port = port_check::free_local_ipv4_port_in_range(1000..65535);
+            let unused_port = unused_port.expect("Unable to find an unused port.");
+            info!("Unused port {} on ipv4 is found.", unused_port);
+            unused_port
+        }
+    };
+
+    // Load certificates before touching the database, so bad SSL files are reported immediately.
+    let ssl_section = env.server.as_ref().and_then(|x| x.ssl.as_ref());
+    if let Some(ssl_section) = ssl_section {
+        ssl_section.to_ssl_acceptor()?;
+    }
+
+    let db = connect_database(env).await?;
+
+    apply_pending_migrations(&db, exit_on_not_fully_migrated).await?;
+
+    let app_data = web::Data::new(DatabaseStates::new(db.clone()));
+
+    if let Some(rate_sources) = env.rate_sources.as_ref().filter(|x| x.enabled) {
+        info!(
+            "Fetching currency rate sources every {} second(s).",
+            rate_sources.interval().as_secs()
+        );
+        actix_web::rt::spawn(run_rate_sources_scheduler(
+            rate_sources.clone(),
+            app_data.get_ref().clone(),
+        ));
+    }
+
+    let dist_folder_path = env.server.as_ref().and_then(|x| {
+        match std::path::Path::new(&x.dist_folder_path).is_dir() {
+            true => Some(x.dist_folder_path.clone()),
+            false => {
+                warn!(
+                    "distFolderPath \"{}\" is not a directory, the frontend will not be served.",
+                    x.dist_folder_path
+                );
+                None
+            }
+        }
+    });
+    let server_section = env.server.clone();
+    let max_body_size = server_section
+        .as_ref()
+        .map(|x| x.max_body_size())
+        .unwrap_or(env::DEFAULT_MAX_BODY_SIZE);
+    let bind_hosts = server_section
+        .as_ref()
+        .map(|x| x.bind_hosts())
+        .unwrap_or_else(|| env::DEFAULT_BIND_HOSTS.map(String::from).to_vec());
+    let workers = server_section.as_ref().and_then(|x| x.workers);
+    let shutdown_timeout = server_section
+        .as_ref()
+        .map(|x| x.shutdown_timeout())
+        .unwrap_or(Duration::from_secs(env::DEFAULT_SHUTDOWN_TIMEOUT));
+    let metrics_path = env.metrics.as_ref().filter(|x| x.enabled).map(|x| x.path());
+    if let Some(metrics_path) = &metrics_path {
+        info!("Serving metrics at {}.", metrics_path);
+    }
+
+    let mut server = HttpServer::new(move || {
+        let cors = server_section
+            .as_ref()
+            .map(|x| x.to_cors())
+            .unwrap_or_default();
+        let app = App::new()
+            .wrap(Condition::new(
+                metrics_path.is_some(),
+                from_fn(metrics_middleware),
+            ))
+            .wrap(cors)
+            .wrap(from_fn(request_tracing_middleware))
+            .app_data(app_data.clone())
+            .app_data(json_config(max_body_size))
+            .app_data(web::PayloadConfig::new(max_body_size));
+        apply_endpoints(app).configure(|cfg| {
+            if let Some(metrics_path) = &metrics_path {
+                configure_metrics(cfg, metrics_path);
+            }
+            if let Some(dist_folder_path) = &dist_folder_path {
+                configure_dist_files(cfg, dist_folder_path);
+            }
+        })
+    });
+    // Signals are handled below, so every server is stopped together and cut off transactions are reported
+    server = server
+        .disable_signals()
+        .shutdown_timeout(shutdown_timeout.as_secs());
+    if let Some(workers) = workers {
+        server = server.workers(workers);
+    }
+    for host in bind_hosts.iter() {
+        server = match ssl_section {
+            None => server.bind((host.as_str(), port))?,
+            Some(ssl_section) => {
+                server.bind_openssl((host.as_str(), port), ssl_section.to_ssl_acceptor()?)?
+            }
+        };
+        info!(
+            "Listening on {}:{} ({}).",
+            host,
+            port,
+            if ssl_section.is_some() {
+                "HTTPS"
+            } else {
+                "HTTP"
+            }
+        );
+    }
+
+    let server = server.run();
+    let mut server_handles = vec![server.handle()];
+    match ssl_section.and_then(|x| x.http_redirect_port) {
+        None => {
+            actix_web::rt::spawn(stop_on_signal(server_handles, shutdown_timeout));
+            server.await?
+        }
+        Some(redirect_port) => {
+            info!(
+                "Redirecting plain HTTP on port {} to HTTPS on port {}.",
+                redirect_port, port
+            );
+            let https_port = web::Data::new(ssl::HttpsPort(port));
+            let mut redirect_server = HttpServer::new(move || {
+                App::new()
+                    .app_data(https_port.clone())
+                    .default_service(web::to(ssl::redirect_to_https))
+            })
+            .disable_signals();
+            for host in bind_hosts.iter() {
+                redirect_server = redirect_server.bind((host.as_str(), redirect_port))?;
+            }
+            let redirect_server = redirect_server.run();
+            server_handles.push(redirect_server.handle());
+            actix_web::rt::spawn(stop_on_signal(server_handles, shutdown_timeout));
+            futures::try_join!(server, redirect_server)?;
+        }
+    }
+
+    match SHUTDOWN.cut_off() {
+        0 => info!("All in-flight transactions finished."),
+        cut_off => warn!(
+            "{} transaction(s) were cut off by the shutdown timeout and rolled back.",
+            cut_off
+        ),
+    }
+    db.close().await?;
+    info!("Database connections closed.");
+
+    Ok(())
+}
+
+/// Stop accepting connections on the first SIGINT or SIGTERM,
+/// then give in-flight requests `shutdown_timeout` to finish.
+#[cfg_attr(test, mutants::skip)]
+async fn stop_on_signal(server_handles: Vec<ServerHandle>, shutdown_timeout: Duration) {
+    if let Err(err) = wait_for_signal().await {
+        warn!("Unable to listen for shutdown signals: {}", err);
+        return;
+    }
+    SHUTDOWN.begin(shutdown_timeout);
+    futures::future::join_all(server_handles.iter().map(|x| x.stop(true))).await;
+}
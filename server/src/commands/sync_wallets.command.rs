@@ -0,0 +1,18 @@
+use crate::env::EnvWalletsSection;
+use crate::services::wallets::{chain_providers, sync_all_wallets};
+use crate::states::database_states::DatabaseStates;
+use std::error::Error;
+use tracing::info;
+
+/// Sync every wallet once with the chain providers configured in `settings`.
+/// Wallets which fail keep their cursor and get a `lastError`, the others are not affected.
+/// Returns the number of transactions saved.
+#[cfg_attr(test, mutants::skip)]
+pub async fn run_sync_wallets(
+    settings: Option<&EnvWalletsSection>,
+    db_states: &DatabaseStates,
+) -> Result<usize, Box<dyn Error>> {
+    let saved_count = sync_all_wallets(&chain_providers(settings)?, db_states).await?;
+    info!("Saved {saved_count} transactions from wallets.");
+    Ok(saved_count)
+}
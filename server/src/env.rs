@@ -1,30 +1,164 @@
+use actix_cors::Cors;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use sea_orm::ConnectOptions;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::LazyLock;
 use std::{fs, time::Duration};
+use utoipa::{PartialSchema, ToSchema};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub enum AppMode {
     Production,
     Development,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ServerSSLSection {
     #[serde(rename = "pemPath")]
     pub pem_path: String,
     #[serde(rename = "keyPath")]
     pub key_path: String,
+    /// If given, a plain HTTP listener is started on this port which redirects every request to HTTPS.
+    #[serde(rename = "httpRedirectPort")]
+    pub http_redirect_port: Option<u16>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Hosts the server binds to when `bindHosts` is not given.
+pub const DEFAULT_BIND_HOSTS: [&str; 1] = ["127.0.0.1"];
+
+/// Largest accepted request body in bytes when `maxBodySize` is not given.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Seconds in-flight requests are given to finish on shutdown when `shutdownTimeout` is not given.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct EnvServerSection {
     pub port: Option<u16>,
     #[serde(rename = "distFolderPath")]
     pub dist_folder_path: String,
     pub ssl: Option<ServerSSLSection>,
+    /// IPv4 or IPv6 addresses to listen on, for example `["0.0.0.0", "::"]`.
+    #[serde(rename = "bindHosts")]
+    pub bind_hosts: Option<Vec<String>>,
+    /// Origins allowed to make cross-origin requests. `"*"` allows any origin.
+    /// Cross-origin requests are rejected if this is not given.
+    #[serde(rename = "corsOrigins")]
+    pub cors_origins: Option<Vec<String>>,
+    #[serde(rename = "maxBodySize")]
+    pub max_body_size: Option<usize>,
+    /// Number of worker threads, defaults to the number of physical CPU cores.
+    pub workers: Option<usize>,
+    /// Seconds to wait on SIGINT or SIGTERM for in-flight requests and transactions before they are cut off.
+    #[serde(rename = "shutdownTimeout")]
+    pub shutdown_timeout: Option<u64>,
+}
+
+impl EnvServerSection {
+    pub fn bind_hosts(&self) -> Vec<String> {
+        match &self.bind_hosts {
+            Some(hosts) => hosts.clone(),
+            None => DEFAULT_BIND_HOSTS.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    pub fn max_body_size(&self) -> usize {
+        self.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE)
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT))
+    }
+
+    pub fn to_cors(&self) -> Cors {
+        let cors = Cors::default()
+            .allow_any_method()
+            .allow_any_header()
+            .max_age(3600);
+        let origins = self.cors_origins.as_deref().unwrap_or_default();
+        match origins.iter().any(|x| x == "*") {
+            true => cors.allow_any_origin(),
+            false => origins
+                .iter()
+                .fold(cors, |cors, origin| cors.allowed_origin(origin)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum EnvPostgresSslMode {
+    Disable,
+    Allow,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl EnvPostgresSslMode {
+    /// Value of the `sslmode` connection parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnvPostgresSslMode::Disable => "disable",
+            EnvPostgresSslMode::Allow => "allow",
+            EnvPostgresSslMode::Prefer => "prefer",
+            EnvPostgresSslMode::Require => "require",
+            EnvPostgresSslMode::VerifyCa => "verify-ca",
+            EnvPostgresSslMode::VerifyFull => "verify-full",
+        }
+    }
+}
+
+pub const DEFAULT_POOL_MAX_CONNECTIONS: u32 = 50;
+pub const DEFAULT_POOL_MIN_CONNECTIONS: u32 = 10;
+pub const DEFAULT_POOL_MAX_LIFETIME: u64 = 120;
+pub const DEFAULT_POOL_ACQUIRE_TIMEOUT: u64 = 60;
+pub const DEFAULT_POOL_CONNECT_TIMEOUT: u64 = 60;
+
+/// Connection pool tuning of a Postgres database, durations are in seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct EnvDbPoolSection {
+    #[serde(rename = "maxConnections")]
+    pub max_connections: Option<u32>,
+    #[serde(rename = "minConnections")]
+    pub min_connections: Option<u32>,
+    /// Connections older than this are closed once idle.
+    #[serde(rename = "maxLifetime")]
+    pub max_lifetime: Option<u64>,
+    /// Connections idle for this long are closed, kept open if not given.
+    #[serde(rename = "idleTimeout")]
+    pub idle_timeout: Option<u64>,
+    /// Longest wait for a free connection of the pool.
+    #[serde(rename = "acquireTimeout")]
+    pub acquire_timeout: Option<u64>,
+    /// Longest wait for a new connection to the database.
+    #[serde(rename = "connectTimeout")]
+    pub connect_timeout: Option<u64>,
+}
+
+impl EnvDbPoolSection {
+    pub fn apply(&self, options: &mut ConnectOptions) {
+        options
+            .max_connections(self.max_connections.unwrap_or(DEFAULT_POOL_MAX_CONNECTIONS))
+            .min_connections(self.min_connections.unwrap_or(DEFAULT_POOL_MIN_CONNECTIONS))
+            .max_lifetime(Duration::from_secs(
+                self.max_lifetime.unwrap_or(DEFAULT_POOL_MAX_LIFETIME),
+            ))
+            .acquire_timeout(Duration::from_secs(
+                self.acquire_timeout.unwrap_or(DEFAULT_POOL_ACQUIRE_TIMEOUT),
+            ))
+            .connect_timeout(Duration::from_secs(
+                self.connect_timeout.unwrap_or(DEFAULT_POOL_CONNECT_TIMEOUT),
+            ));
+        if let Some(idle_timeout) = self.idle_timeout {
+            options.idle_timeout(Duration::from_secs(idle_timeout));
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "type")]
 pub enum EnvDb {
     SQLite {
@@ -32,14 +166,49 @@ pub enum EnvDb {
     },
     SQLiteMemory,
     Postgres {
+        /// Host name or IP address, optionally followed by `:port`.
         hostname: String,
+        port: Option<u16>,
         password: String,
         username: String,
         database: String,
+        #[serde(rename = "sslMode")]
+        ssl_mode: Option<EnvPostgresSslMode>,
+        /// Certificate authority used to verify the server with `verify-ca` and `verify-full`.
+        #[serde(rename = "sslRootCert")]
+        ssl_root_cert: Option<String>,
+        /// Schema search path set on every connection, e.g. `"finance,public"`.
+        schema: Option<String>,
+        /// Reported to Postgres, shown in `pg_stat_activity`.
+        #[serde(rename = "applicationName")]
+        application_name: Option<String>,
+        pool: Option<EnvDbPoolSection>,
+    },
+    /// A raw Postgres connection URL for settings not covered by `Postgres`, it is used as is.
+    /// Special characters in the credentials must already be percent-encoded.
+    Url {
+        url: String,
+        schema: Option<String>,
+        pool: Option<EnvDbPoolSection>,
     },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Everything but unreserved characters is percent-encoded in the credentials and database name.
+const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnvDbErrors {
+    #[error("Invalid database URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+    #[error("\"{0}\" is not a valid database hostname.")]
+    InvalidHostname(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "type")]
 pub enum EnvLogMode {
     Console,
@@ -47,45 +216,235 @@ pub enum EnvLogMode {
     Both { path: String },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvLogFormat {
+    /// Human readable lines, span fields are printed before the message.
+    #[default]
+    Text,
+    /// One JSON object per line, including the fields of the current span.
+    Json,
+}
+
+/// When the log file is moved aside and a new one is started.
+/// At most `maxFiles` rotated files are kept next to the active one, older ones are deleted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type")]
+pub enum EnvLogRotation {
+    /// Keep appending to a single file.
+    #[default]
+    Never,
+    /// Start a new file on the first write of each UTC day, the old one gets a `.YYYY-MM-DD` suffix.
+    Daily {
+        #[serde(rename = "maxFiles")]
+        max_files: Option<usize>,
+    },
+    /// Start a new file once the active one would exceed `maxBytes`, old ones get `.1`, `.2`, ... suffixes.
+    Size {
+        #[serde(rename = "maxBytes")]
+        max_bytes: u64,
+        #[serde(rename = "maxFiles")]
+        max_files: Option<usize>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct EnvLoggingSection {
     #[serde(rename = "logMode")]
     pub log_mode: EnvLogMode,
+    /// Level of targets not listed in `targets`, defaults to `info`.
+    pub level: Option<String>,
+    /// Level per target, e.g. `{ "sqlx::query": "debug" }`. Overrides the built-in defaults.
+    pub targets: Option<BTreeMap<String, String>>,
+    pub format: Option<EnvLogFormat>,
+    /// Rotation of the log file, ignored when logging to console only.
+    pub rotation: Option<EnvLogRotation>,
+}
+
+/// Path of the metrics endpoint when `path` is not given.
+pub const DEFAULT_METRICS_PATH: &str = "/metrics";
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EnvMetricsSection {
+    /// Whether requests are measured and the metrics endpoint is served.
+    pub enabled: bool,
+    pub path: Option<String>,
+}
+
+impl EnvMetricsSection {
+    pub fn path(&self) -> String {
+        self.path
+            .clone()
+            .unwrap_or_else(|| DEFAULT_METRICS_PATH.to_string())
+    }
+}
+
+/// Seconds between two fetches of the currency rate sources when `interval` is not given.
+pub const DEFAULT_RATE_SOURCES_INTERVAL: u64 = 3600;
+
+/// Seconds a currency rate source is given to answer when `timeout` is not given.
+pub const DEFAULT_RATE_SOURCES_TIMEOUT: u64 = 30;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct EnvRateSourcesSection {
+    /// Whether the currency rate sources are fetched periodically while serving.
+    pub enabled: bool,
+    /// Seconds between two fetches of every source.
+    pub interval: Option<u64>,
+    /// Seconds a source is given to answer before its fetch fails.
+    pub timeout: Option<u64>,
+    /// Directory `file` sources are read from, they fail to fetch if this is not given.
+    #[serde(rename = "fileDirectory")]
+    pub file_directory: Option<String>,
+    /// Hosts `http` sources may reach, any host if not given. A host also matches its subdomains.
+    /// Private, loopback and link-local addresses are only reached when their host or address is listed.
+    #[serde(rename = "allowedHosts")]
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Hosts `http` sources never reach, even when listed in `allowedHosts`.
+    #[serde(rename = "deniedHosts")]
+    pub denied_hosts: Option<Vec<String>>,
+}
+
+impl EnvRateSourcesSection {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval.unwrap_or(DEFAULT_RATE_SOURCES_INTERVAL))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_RATE_SOURCES_TIMEOUT))
+    }
+
+    /// Settings the sources cannot be fetched with, a zero `interval` would not even let the server start.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if self.interval == Some(0) {
+            problems.push("rateSources.interval must be at least 1.".to_string());
+        }
+        if self.timeout == Some(0) {
+            problems.push("rateSources.timeout must be at least 1.".to_string());
+        }
+        problems
+    }
+}
+
+/// Seconds a chain provider is given to answer each request when `timeout` is not given.
+pub const DEFAULT_WALLETS_TIMEOUT: u64 = 30;
+
+/// Chain providers the wallets are synced with, wallets of a chain without one cannot be synced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct EnvWalletsSection {
+    /// Base URL of an Esplora API serving Bitcoin, e.g. `https://blockstream.info/api`.
+    #[serde(rename = "btcEsploraUrl")]
+    pub btc_esplora_url: Option<String>,
+    /// Base URL of an Esplora API serving Litecoin, e.g. `https://litecoinspace.org/api`.
+    #[serde(rename = "ltcEsploraUrl")]
+    pub ltc_esplora_url: Option<String>,
+    /// Seconds a chain provider is given to answer each request.
+    pub timeout: Option<u64>,
+}
+
+impl EnvWalletsSection {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_WALLETS_TIMEOUT))
+    }
+
+    /// Settings the wallets cannot be synced with.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if self.timeout == Some(0) {
+            problems.push("wallets.timeout must be at least 1.".to_string());
+        }
+        problems
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct EnvStorageSection {
     pub db: EnvDb,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AppEnv {
     #[serde(rename = "envMode")]
     pub env_mode: AppMode,
     pub server: Option<EnvServerSection>,
     pub storage: EnvStorageSection,
     pub logging: EnvLoggingSection,
+    pub metrics: Option<EnvMetricsSection>,
+    #[serde(rename = "rateSources")]
+    pub rate_sources: Option<EnvRateSourcesSection>,
+    pub wallets: Option<EnvWalletsSection>,
 }
 
 impl AppEnv {
-    pub fn to_connection_options(&self) -> ConnectOptions {
-        match &self.storage.db {
+    pub fn to_connection_options(&self) -> Result<ConnectOptions, EnvDbErrors> {
+        let mut options = ConnectOptions::new(self.storage.db.to_url()?);
+        if let EnvDb::Postgres { schema, pool, .. } | EnvDb::Url { schema, pool, .. } =
+            &self.storage.db
+        {
+            pool.clone().unwrap_or_default().apply(&mut options);
+            if let Some(schema) = schema {
+                options.set_schema_search_path(schema);
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl EnvDb {
+    /// The connection URL, credentials and database name of `Postgres` are percent-encoded.
+    pub fn to_url(&self) -> Result<String, EnvDbErrors> {
+        match self {
+            EnvDb::SQLite { path } => Ok(format!("sqlite://{path}")),
+            EnvDb::SQLiteMemory => Ok("sqlite::memory:".to_string()),
+            EnvDb::Url { url, .. } => {
+                url::Url::parse(url)?;
+                Ok(url.clone())
+            }
             EnvDb::Postgres {
                 hostname,
+                port,
                 password,
                 username,
                 database,
-            } => ConnectOptions::new(format!(
-                "postgres://{username}:{password}@{hostname}/{database}"
-            ))
-            .max_connections(50)
-            .min_connections(10)
-            .max_lifetime(Duration::from_secs_f64(120.0))
-            .acquire_timeout(Duration::from_secs(60))
-            .connect_timeout(Duration::from_secs(60))
-            .clone(),
-            EnvDb::SQLite { path } => ConnectOptions::new(format!("sqlite://{path}")),
-            EnvDb::SQLiteMemory => ConnectOptions::new("sqlite::memory:"),
+                ssl_mode,
+                ssl_root_cert,
+                application_name,
+                ..
+            } => {
+                let invalid_hostname = |_| EnvDbErrors::InvalidHostname(hostname.clone());
+                let mut url = url::Url::parse(&format!("postgres://{hostname}"))?;
+                // Anything but a host and a port would be silently dropped below
+                if !matches!(url.path(), "" | "/")
+                    || url.query().is_some()
+                    || !url.username().is_empty()
+                    || url.password().is_some()
+                {
+                    return Err(EnvDbErrors::InvalidHostname(hostname.clone()));
+                }
+                if port.is_some() {
+                    url.set_port(*port).map_err(invalid_hostname)?;
+                }
+                let encode = |x: &str| utf8_percent_encode(x, URL_COMPONENT).to_string();
+                url.set_username(&encode(username))
+                    .map_err(invalid_hostname)?;
+                url.set_password(Some(&encode(password)))
+                    .map_err(invalid_hostname)?;
+                url.set_path(&encode(database));
+
+                let params = [
+                    ("sslmode", ssl_mode.map(|x| x.as_str().to_string())),
+                    ("sslrootcert", ssl_root_cert.clone()),
+                    ("application_name", application_name.clone()),
+                ]
+                .into_iter()
+                .filter_map(|(name, value)| value.map(|value| (name, value)))
+                .collect::<Vec<_>>();
+                if !params.is_empty() {
+                    url.query_pairs_mut().extend_pairs(params);
+                }
+                Ok(url.to_string())
+            }
         }
     }
 }
@@ -95,7 +454,201 @@ pub fn read_json_as_str(path: &str) -> Result<String, Box<dyn std::error::Error>
     fs::read_to_string(path).map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
 }
 
-pub fn parse_env(json_str: &str) -> Result<AppEnv, serde_json::Error> {
-    let config: AppEnv = serde_json::from_str(json_str)?;
-    Ok(config)
+/// Prefix of the environment variables overriding fields of the config file.
+pub const ENV_OVERRIDE_PREFIX: &str = "FM_";
+
+/// Separator between the nested fields of an override, e.g. `FM_STORAGE__DB__PASSWORD`.
+pub const ENV_OVERRIDE_SEPARATOR: &str = "__";
+
+/// Suffix of an override whose value is read from the file at the given path, meant for secrets.
+pub const ENV_OVERRIDE_FILE_SUFFIX: &str = "_FILE";
+
+/// Whether every value accepted by the JSON `schema` is a string, or null.
+fn accepts_strings_only(schema: &serde_json::Value, schemas: &serde_json::Value) -> bool {
+    if let Some(name) = schema["$ref"].as_str().and_then(|x| x.rsplit('/').next()) {
+        return accepts_strings_only(&schemas[name], schemas);
+    }
+    for key in ["oneOf", "anyOf", "allOf"] {
+        if let Some(items) = schema[key].as_array() {
+            return items.iter().all(|x| accepts_strings_only(x, schemas));
+        }
+    }
+    match &schema["type"] {
+        serde_json::Value::String(x) => x == "string" || x == "null",
+        serde_json::Value::Array(types) => types.iter().all(|x| x == "string" || x == "null"),
+        _ => false,
+    }
+}
+
+/// Insert the dotted path of every field of the JSON `schema` under `prefix`, along with whether it accepts strings only.
+/// A field present in several variants of an enum only accepts strings if it does in all of them.
+fn collect_fields(
+    schema: &serde_json::Value,
+    schemas: &serde_json::Value,
+    prefix: &str,
+    fields: &mut BTreeMap<String, bool>,
+) {
+    if let Some(name) = schema["$ref"].as_str().and_then(|x| x.rsplit('/').next()) {
+        return collect_fields(&schemas[name], schemas, prefix, fields);
+    }
+    for key in ["oneOf", "anyOf", "allOf"] {
+        for item in schema[key].as_array().into_iter().flatten() {
+            collect_fields(item, schemas, prefix, fields);
+        }
+    }
+    for (name, field) in schema["properties"].as_object().into_iter().flatten() {
+        let path = match prefix {
+            "" => name.clone(),
+            _ => format!("{prefix}.{name}"),
+        };
+        let strings_only = accepts_strings_only(field, schemas);
+        *fields.entry(path.clone()).or_insert(true) &= strings_only;
+        collect_fields(field, schemas, &path, fields);
+    }
+}
+
+/// Dotted path of every field of [`AppEnv`], along with whether it accepts strings only, read from its schema.
+pub fn env_fields() -> BTreeMap<String, bool> {
+    let mut components = vec![];
+    AppEnv::schemas(&mut components);
+    let schemas = serde_json::to_value(components.into_iter().collect::<BTreeMap<_, _>>())
+        .expect("Unable to serialize the config schema.");
+    let schema =
+        serde_json::to_value(AppEnv::schema()).expect("Unable to serialize the config schema.");
+    let mut fields = BTreeMap::new();
+    collect_fields(&schema, &schemas, "", &mut fields);
+    fields
+}
+
+/// Fields whose overrides are parsed as JSON, every other field is set to the value as a string.
+/// Sections are included too, so a whole section can be given at once.
+pub static ENV_OVERRIDE_JSON_FIELDS: LazyLock<BTreeSet<String>> = LazyLock::new(|| {
+    env_fields()
+        .into_iter()
+        .filter(|(_, strings_only)| !strings_only)
+        .map(|(path, _)| path)
+        .collect()
+});
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseEnvErrors {
+    #[error("Invalid config: {0}")]
+    InvalidConfig(#[from] serde_json::Error),
+    #[error("Unable to read the file given by {var}: {source}")]
+    UnreadableSecretFile { var: String, source: std::io::Error },
+    #[error("{var} cannot be applied, \"{field}\" is not an object.")]
+    NotAnObject { var: String, field: String },
+    #[error("Invalid config: {0}")]
+    InvalidValue(String),
+}
+
+/// Turn a segment of an override name into the JSON field name, e.g. `DIST_FOLDER_PATH` into `distFolderPath`.
+fn override_field_name(segment: &str) -> String {
+    segment
+        .to_lowercase()
+        .split('_')
+        .enumerate()
+        .map(|(index, word)| match index {
+            0 => word.to_string(),
+            _ => {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        })
+        .collect()
+}
+
+/// Override fields of the parsed config file with `FM_` environment variables.
+/// Nested fields are separated by `__`, so `FM_SERVER__DIST_FOLDER_PATH` sets `server.distFolderPath`.
+/// Values of the [`ENV_OVERRIDE_JSON_FIELDS`] are parsed as JSON, falling back to plain strings,
+/// so a password like `123456` or `true` stays a string.
+/// `FM_X_FILE` sets `FM_X` to the content of the given file, and wins over `FM_X` when both are set.
+pub fn apply_env_overrides(
+    config: &mut serde_json::Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), ParseEnvErrors> {
+    let mut vars = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_OVERRIDE_PREFIX))
+        .collect::<Vec<_>>();
+    // `FM_X` sorts before `FM_X_FILE`, so the file variant is applied last
+    vars.sort();
+
+    for (var, value) in vars {
+        let name = &var[ENV_OVERRIDE_PREFIX.len()..];
+        let (name, from_file) = match name.strip_suffix(ENV_OVERRIDE_FILE_SUFFIX) {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+        let value = match from_file {
+            true => fs::read_to_string(&value)
+                .map_err(|source| ParseEnvErrors::UnreadableSecretFile {
+                    var: var.clone(),
+                    source,
+                })?
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+            false => value,
+        };
+
+        let fields = name
+            .split(ENV_OVERRIDE_SEPARATOR)
+            .map(override_field_name)
+            .collect::<Vec<_>>();
+        let Some((last, parents)) = fields.split_last() else {
+            continue;
+        };
+        let mut target = &mut *config;
+        for field in parents {
+            if target.is_null() {
+                *target = serde_json::Value::Object(Default::default());
+            }
+            target = target
+                .as_object_mut()
+                .ok_or_else(|| ParseEnvErrors::NotAnObject {
+                    var: var.clone(),
+                    field: field.clone(),
+                })?
+                .entry(field.clone())
+                .or_insert(serde_json::Value::Null);
+        }
+        if target.is_null() {
+            *target = serde_json::Value::Object(Default::default());
+        }
+        let target = target
+            .as_object_mut()
+            .ok_or_else(|| ParseEnvErrors::NotAnObject {
+                var: var.clone(),
+                field: parents.last().cloned().unwrap_or_default(),
+            })?;
+        let is_json = ENV_OVERRIDE_JSON_FIELDS.contains(&fields.join("."));
+        let value = match is_json && !from_file {
+            true => serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value)),
+            false => serde_json::Value::String(value),
+        };
+        target.insert(last.clone(), value);
+    }
+    Ok(())
+}
+
+/// Parse the config file, then apply the `FM_` overrides found in `vars`, see [`apply_env_overrides`].
+/// Settings which would make the server fail once started are refused.
+pub fn parse_env(
+    json_str: &str,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<AppEnv, ParseEnvErrors> {
+    let mut config: serde_json::Value = serde_json::from_str(json_str)?;
+    apply_env_overrides(&mut config, vars)?;
+    let env: AppEnv = serde_json::from_value(config)?;
+    let problems = env.rate_sources.iter().flat_map(|x| x.problems());
+    if let Some(problem) = problems
+        .chain(env.wallets.iter().flat_map(|x| x.problems()))
+        .next()
+    {
+        return Err(ParseEnvErrors::InvalidValue(problem));
+    }
+    Ok(env)
 }
@@ -2,14 +2,122 @@ use crate::{
     entities::currency::{self, Model},
     extractors::auth_user::AuthUser,
 };
+use rust_decimal::Decimal;
 use sea_orm::ActiveValue;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+/// Decimal places of currencies created without any, enough for most fiat currencies.
+pub const DEFAULT_CURRENCY_DECIMALS: u32 = 2;
+
+/// The most decimal places a `Decimal` can hold.
+pub const MAX_CURRENCY_DECIMALS: u32 = 28;
+
 #[derive(Serialize, Deserialize, TS, Clone, Debug, PartialEq, Copy)]
 pub struct CurrencyId(pub Uuid);
 
+/// What a currency is used to hold.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub enum CurrencyKind {
+    #[default]
+    Fiat,
+    Crypto,
+    Security,
+    Other,
+}
+
+impl CurrencyKind {
+    /// The value stored in the `kind` column, same as the serialized one.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CurrencyKind::Fiat => "fiat",
+            CurrencyKind::Crypto => "crypto",
+            CurrencyKind::Security => "security",
+            CurrencyKind::Other => "other",
+        }
+    }
+
+    /// Unknown values are read as `Other` rather than failing every query on the table.
+    pub fn from_stored(value: &str) -> CurrencyKind {
+        match value {
+            "fiat" => CurrencyKind::Fiat,
+            "crypto" => CurrencyKind::Crypto,
+            "security" => CurrencyKind::Security,
+            _ => CurrencyKind::Other,
+        }
+    }
+}
+
+/// How the rate of a currency to the base currency was found.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub enum RateMethod {
+    /// The base currency, whose rate is always 1.
+    Base,
+    /// Interpolated between the datums around the date.
+    Interpolated,
+    /// The last datum at or before the date, as is.
+    LeftOnly,
+    /// The datum closest to the date, as is.
+    Nearest,
+    /// No datum precedes the date, the fallback rate is used.
+    Fallback,
+}
+
+/// How the rate of a currency is derived from its datums.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub enum RateStrategy {
+    /// Interpolate between the datums around the date.
+    #[default]
+    Linear,
+    /// Keep the last datum until the next one, like prices of assets traded at discrete times.
+    PreviousValue,
+    /// Use the datum closest to the date.
+    Nearest,
+    /// Same as `linear`, ignoring datums more than `rateMaxGapDays` away from the date.
+    LinearWithMaxGap,
+}
+
+impl RateStrategy {
+    /// The value stored in the `rate_strategy` column, same as the serialized one.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateStrategy::Linear => "linear",
+            RateStrategy::PreviousValue => "previousValue",
+            RateStrategy::Nearest => "nearest",
+            RateStrategy::LinearWithMaxGap => "linearWithMaxGap",
+        }
+    }
+
+    /// Unknown values are read as `Linear`, the strategy used before any other existed.
+    pub fn from_stored(value: &str) -> RateStrategy {
+        match value {
+            "previousValue" => RateStrategy::PreviousValue,
+            "nearest" => RateStrategy::Nearest,
+            "linearWithMaxGap" => RateStrategy::LinearWithMaxGap,
+            _ => RateStrategy::Linear,
+        }
+    }
+
+    /// `LinearWithMaxGap` needs a gap of at least a day, the other strategies none.
+    pub fn accepts_max_gap_days(&self, max_gap_days: Option<u32>) -> bool {
+        match self {
+            RateStrategy::LinearWithMaxGap => max_gap_days.is_some_and(|days| days > 0),
+            _ => max_gap_days.is_none(),
+        }
+    }
+}
+
 /** This enum represent a currency that already exists in database (saved) */
 #[derive(Clone, Debug)]
 pub enum Currency {
@@ -18,17 +126,51 @@ pub enum Currency {
         name: String,
         owner: AuthUser,
         ticker: String,
+        decimals: u32,
+        kind: CurrencyKind,
+        symbol: Option<String>,
+        archived: bool,
     },
     Normal {
         id: CurrencyId,
         name: String,
         owner: AuthUser,
         ticker: String,
-        fallback_rate_amount: String,
+        decimals: u32,
+        kind: CurrencyKind,
+        symbol: Option<String>,
+        archived: bool,
+        rate_strategy: RateStrategy,
+        rate_max_gap_days: Option<u32>,
+        derive_rates: bool,
+        fallback_rate_amount: Decimal,
         fallback_rate_currency_id: CurrencyId,
     },
 }
 
+impl Currency {
+    pub fn id(&self) -> CurrencyId {
+        match self {
+            Currency::Base { id, .. } | Currency::Normal { id, .. } => *id,
+        }
+    }
+
+    /// Decimal places amounts in this currency are stored and reported with.
+    pub fn decimals(&self) -> u32 {
+        match self {
+            Currency::Base { decimals, .. } | Currency::Normal { decimals, .. } => *decimals,
+        }
+    }
+
+    /// Whether rates of this currency are derived from the transactions converting it.
+    pub fn derives_rates(&self) -> bool {
+        match self {
+            Currency::Base { .. } => false,
+            Currency::Normal { derive_rates, .. } => *derive_rates,
+        }
+    }
+}
+
 /** This enum represent the action to save a currency to a database, therefore the ID is not available in the enum. */
 #[derive(Clone, Debug)]
 pub enum CreateCurrencyAction {
@@ -36,12 +178,23 @@ pub enum CreateCurrencyAction {
         name: String,
         owner: AuthUser,
         ticker: String,
+        decimals: u32,
+        kind: CurrencyKind,
+        symbol: Option<String>,
+        archived: bool,
     },
     Normal {
         name: String,
         owner: AuthUser,
         ticker: String,
-        fallback_rate_amount: String,
+        decimals: u32,
+        kind: CurrencyKind,
+        symbol: Option<String>,
+        archived: bool,
+        rate_strategy: RateStrategy,
+        rate_max_gap_days: Option<u32>,
+        derive_rates: bool,
+        fallback_rate_amount: Decimal,
         fallback_rate_currency_id: CurrencyId,
     },
 }
@@ -53,11 +206,22 @@ impl From<CreateCurrencyAction> for currency::ActiveModel {
                 name,
                 owner,
                 ticker,
+                decimals,
+                kind,
+                symbol,
+                archived,
             } => currency::ActiveModel {
                 id: ActiveValue::Set(uuid::Uuid::new_v4()),
                 name: ActiveValue::Set(name.to_string()),
                 owner_id: ActiveValue::Set(owner.0),
                 ticker: ActiveValue::Set(ticker.to_string()),
+                decimals: ActiveValue::Set(decimals as i32),
+                kind: ActiveValue::Set(kind.as_str().to_string()),
+                symbol: ActiveValue::Set(symbol),
+                archived: ActiveValue::Set(archived),
+                rate_strategy: ActiveValue::Set(RateStrategy::default().as_str().to_string()),
+                rate_max_gap_days: ActiveValue::Set(None),
+                derive_rates: ActiveValue::Set(false),
                 fallback_rate_amount: ActiveValue::Set(None),
                 fallback_rate_currency_id: ActiveValue::Set(None),
                 is_base: ActiveValue::Set(true),
@@ -66,6 +230,13 @@ impl From<CreateCurrencyAction> for currency::ActiveModel {
                 name,
                 owner,
                 ticker,
+                decimals,
+                kind,
+                symbol,
+                archived,
+                rate_strategy,
+                rate_max_gap_days,
+                derive_rates,
                 fallback_rate_amount,
                 fallback_rate_currency_id,
             } => currency::ActiveModel {
@@ -73,6 +244,13 @@ impl From<CreateCurrencyAction> for currency::ActiveModel {
                 name: ActiveValue::Set(name.to_string()),
                 owner_id: ActiveValue::Set(owner.0),
                 ticker: ActiveValue::Set(ticker.to_string()),
+                decimals: ActiveValue::Set(decimals as i32),
+                kind: ActiveValue::Set(kind.as_str().to_string()),
+                symbol: ActiveValue::Set(symbol),
+                archived: ActiveValue::Set(archived),
+                rate_strategy: ActiveValue::Set(rate_strategy.as_str().to_string()),
+                rate_max_gap_days: ActiveValue::Set(rate_max_gap_days.map(|x| x as i32)),
+                derive_rates: ActiveValue::Set(derive_rates),
                 fallback_rate_amount: ActiveValue::Set(Some(fallback_rate_amount)),
                 fallback_rate_currency_id: ActiveValue::Set(Some(fallback_rate_currency_id.0)),
                 is_base: ActiveValue::Set(false),
@@ -88,6 +266,12 @@ impl CreateCurrencyAction {
             CreateCurrencyAction::Normal { .. } => false,
         }
     }
+    pub fn get_decimals(&self) -> u32 {
+        match self {
+            CreateCurrencyAction::Normal { decimals, .. }
+            | CreateCurrencyAction::Base { decimals, .. } => *decimals,
+        }
+    }
     pub fn get_owner(&self) -> &AuthUser {
         match self {
             CreateCurrencyAction::Normal { owner, .. }
@@ -100,16 +284,31 @@ impl CreateCurrencyAction {
                 name,
                 owner,
                 ticker,
+                decimals,
+                kind,
+                symbol,
+                archived,
             } => Currency::Base {
                 id: CurrencyId(db_id),
                 name,
                 owner,
                 ticker,
+                decimals,
+                kind,
+                symbol,
+                archived,
             },
             CreateCurrencyAction::Normal {
                 name,
                 owner,
                 ticker,
+                decimals,
+                kind,
+                symbol,
+                archived,
+                rate_strategy,
+                rate_max_gap_days,
+                derive_rates,
                 fallback_rate_amount,
                 fallback_rate_currency_id,
             } => Currency::Normal {
@@ -117,6 +316,13 @@ impl CreateCurrencyAction {
                 name,
                 owner,
                 ticker,
+                decimals,
+                kind,
+                symbol,
+                archived,
+                rate_strategy,
+                rate_max_gap_days,
+                derive_rates,
                 fallback_rate_amount,
                 fallback_rate_currency_id,
             },
@@ -132,15 +338,25 @@ impl From<Model> for Currency {
                 name: value.name.to_string(),
                 owner: AuthUser(value.owner_id),
                 ticker: value.ticker.to_string(),
+                decimals: value.decimals as u32,
+                kind: CurrencyKind::from_stored(&value.kind),
+                symbol: value.symbol.clone(),
+                archived: value.archived,
             },
             false => Currency::Normal {
                 id: CurrencyId(value.id),
                 name: value.name.to_string(),
                 owner: AuthUser(value.owner_id),
                 ticker: value.ticker.to_string(),
+                decimals: value.decimals as u32,
+                kind: CurrencyKind::from_stored(&value.kind),
+                symbol: value.symbol.clone(),
+                archived: value.archived,
+                rate_strategy: RateStrategy::from_stored(&value.rate_strategy),
+                rate_max_gap_days: value.rate_max_gap_days.map(|x| x as u32),
+                derive_rates: value.derive_rates,
                 fallback_rate_amount: value
                     .fallback_rate_amount
-                    .clone()
                     .expect("Currency Domain Enum failure 1"),
                 fallback_rate_currency_id: CurrencyId(
                     value
@@ -0,0 +1,74 @@
+use crate::{
+    entities::currency_rate_source::Model, extended_models::currency::CurrencyId,
+    extractors::auth_user::AuthUser,
+};
+use sea_orm::prelude::DateTime;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Where a currency rate source reads its JSON document from.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub enum RateSourceKind {
+    /// `GET` the `path` of `hostname`.
+    #[default]
+    Http,
+    /// Read the file at `path`, relative to the `rateSources.fileDirectory` of the config.
+    File,
+}
+
+impl RateSourceKind {
+    /// The value stored in the `kind` column, same as the serialized one.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateSourceKind::Http => "http",
+            RateSourceKind::File => "file",
+        }
+    }
+
+    /// Unknown values are read as `Http`, the kind every source had before files were supported.
+    pub fn from_stored(value: &str) -> RateSourceKind {
+        match value {
+            "file" => RateSourceKind::File,
+            _ => RateSourceKind::Http,
+        }
+    }
+}
+
+/// A saved source of the rate of `ref_currency_id`, expressed in `ref_amount_currency_id`.
+#[derive(Clone, Debug)]
+pub struct CurrencyRateSource {
+    pub id: Uuid,
+    pub owner: AuthUser,
+    pub name: String,
+    pub kind: RateSourceKind,
+    pub ref_currency_id: CurrencyId,
+    pub ref_amount_currency_id: CurrencyId,
+    pub hostname: Option<String>,
+    pub path: String,
+    pub json_query_string: String,
+    pub last_fetch_date: Option<DateTime>,
+    pub last_error: Option<String>,
+}
+
+impl From<Model> for CurrencyRateSource {
+    fn from(value: Model) -> Self {
+        CurrencyRateSource {
+            id: value.id,
+            owner: AuthUser(value.owner_id),
+            name: value.name,
+            kind: RateSourceKind::from_stored(&value.kind),
+            ref_currency_id: CurrencyId(value.ref_currency_id),
+            ref_amount_currency_id: CurrencyId(value.ref_amount_currency_id),
+            hostname: value.hostname,
+            path: value.path,
+            json_query_string: value.json_query_string,
+            last_fetch_date: value.last_fetch_date,
+            last_error: value.last_error,
+        }
+    }
+}
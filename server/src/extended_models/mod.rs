@@ -1,2 +1,5 @@
 pub mod account;
 pub mod currency;
+pub mod currency_rate_source;
+pub mod user_archive;
+pub mod wallet;
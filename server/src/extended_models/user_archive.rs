@@ -0,0 +1,337 @@
+use crate::{
+    date::iso8601_to_js_iso,
+    entities::{
+        account, currency, currency_rate_datum, currency_rate_source, fragment, txn, txn_tag,
+        wallet, wallet_txn,
+    },
+    extended_models::{
+        currency::{CurrencyKind, RateStrategy},
+        currency_rate_source::RateSourceKind,
+        wallet::WalletChain,
+    },
+};
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The version of the archive format produced by `GET /users/export`.
+/// Bump this whenever the shape of [`UserArchive`] changes.
+/// 2: currencies carry their `decimals`.
+/// 3: currencies carry their `kind`, `symbol` and `archived` flag.
+/// 4: currencies carry their `rateStrategy` and `rateMaxGapDays`.
+/// 5: currencies carry their `deriveRates` flag, derived rate datums are left out.
+/// 6: rate sources, wallets and the transfers synced from them.
+pub const USER_ARCHIVE_VERSION: u32 = 6;
+
+/// The oldest archive format still accepted by `POST /users/import`.
+pub const OLDEST_USER_ARCHIVE_VERSION: u32 = 1;
+
+/** A self-contained snapshot of everything owned by a single user. */
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchive {
+    pub version: u32,
+    pub exported_at: String,
+    pub owner: Uuid,
+    pub accounts: Vec<UserArchiveAccount>,
+    pub currencies: Vec<UserArchiveCurrency>,
+    pub currency_rate_datums: Vec<UserArchiveCurrencyRateDatum>,
+    pub txn_tags: Vec<UserArchiveTxnTag>,
+    pub txns: Vec<UserArchiveTxn>,
+    /// Missing from archives older than version 6, imported as none.
+    #[serde(default)]
+    #[ts(optional)]
+    pub currency_rate_sources: Option<Vec<UserArchiveCurrencyRateSource>>,
+    /// Missing from archives older than version 6, imported as none.
+    #[serde(default)]
+    #[ts(optional)]
+    pub wallets: Option<Vec<UserArchiveWallet>>,
+    /// Missing from archives older than version 6, imported as none.
+    #[serde(default)]
+    #[ts(optional)]
+    pub wallet_txns: Option<Vec<UserArchiveWalletTxn>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveAccount {
+    pub id: Uuid,
+    pub name: String,
+    pub creation_date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveCurrency {
+    pub id: Uuid,
+    pub name: String,
+    pub ticker: String,
+    pub is_base: bool,
+    pub fallback_rate_amount: Option<String>,
+    pub fallback_rate_currency_id: Option<Uuid>,
+    /// Missing from version 1 archives, imported as the default of 2.
+    #[serde(default)]
+    #[ts(optional)]
+    pub decimals: Option<u32>,
+    /// Missing from archives older than version 3, imported as `fiat`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub kind: Option<CurrencyKind>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub symbol: Option<String>,
+    /// Missing from archives older than version 3, imported as not archived.
+    #[serde(default)]
+    #[ts(optional)]
+    pub archived: Option<bool>,
+    /// Missing from archives older than version 4, imported as `linear`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub rate_strategy: Option<RateStrategy>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub rate_max_gap_days: Option<u32>,
+    /// Missing from archives older than version 5, imported as not deriving rates.
+    #[serde(default)]
+    #[ts(optional)]
+    pub derive_rates: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveCurrencyRateDatum {
+    pub id: Uuid,
+    pub amount: String,
+    pub ref_currency_id: Uuid,
+    pub ref_amount_currency_id: Uuid,
+    pub date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveTxnTag {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveTxn {
+    pub id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub date: String,
+    pub fragments: Vec<UserArchiveFragment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveFragment {
+    pub id: Uuid,
+    pub from: Option<UserArchiveFragmentSide>,
+    pub to: Option<UserArchiveFragmentSide>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveFragmentSide {
+    pub account: Uuid,
+    pub amount: String,
+    pub currency: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveCurrencyRateSource {
+    pub id: Uuid,
+    pub name: String,
+    pub kind: RateSourceKind,
+    pub ref_currency_id: Uuid,
+    pub ref_amount_currency_id: Uuid,
+    pub hostname: Option<String>,
+    pub path: String,
+    pub json_query_string: String,
+}
+
+/// The sync cursor is left out, transfers already synced are known from [`UserArchiveWalletTxn`].
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveWallet {
+    pub id: Uuid,
+    pub name: String,
+    pub chain: WalletChain,
+    pub address: String,
+    pub account_id: Uuid,
+    pub currency_id: Uuid,
+}
+
+/// A transfer synced from `address`, into `txnId` unless that txn was deleted since.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct UserArchiveWalletTxn {
+    pub chain: WalletChain,
+    pub address: String,
+    pub tx_hash: String,
+    pub txn_id: Option<Uuid>,
+}
+
+impl From<account::Model> for UserArchiveAccount {
+    fn from(value: account::Model) -> Self {
+        UserArchiveAccount {
+            id: value.id,
+            name: value.name,
+            creation_date: iso8601_to_js_iso(value.creation_date.and_utc()),
+        }
+    }
+}
+
+impl From<currency::Model> for UserArchiveCurrency {
+    fn from(value: currency::Model) -> Self {
+        UserArchiveCurrency {
+            id: value.id,
+            name: value.name,
+            ticker: value.ticker,
+            is_base: value.is_base,
+            fallback_rate_amount: value.fallback_rate_amount.map(|x| x.to_string()),
+            fallback_rate_currency_id: value.fallback_rate_currency_id,
+            decimals: Some(value.decimals as u32),
+            kind: Some(CurrencyKind::from_stored(&value.kind)),
+            symbol: value.symbol,
+            archived: Some(value.archived),
+            rate_strategy: Some(RateStrategy::from_stored(&value.rate_strategy)),
+            rate_max_gap_days: value.rate_max_gap_days.map(|x| x as u32),
+            derive_rates: Some(value.derive_rates),
+        }
+    }
+}
+
+impl From<currency_rate_datum::Model> for UserArchiveCurrencyRateDatum {
+    fn from(value: currency_rate_datum::Model) -> Self {
+        UserArchiveCurrencyRateDatum {
+            id: value.id,
+            amount: value.amount.to_string(),
+            ref_currency_id: value.ref_currency_id,
+            ref_amount_currency_id: value.ref_amount_currency_id,
+            date: iso8601_to_js_iso(value.date.and_utc()),
+        }
+    }
+}
+
+impl From<txn_tag::Model> for UserArchiveTxnTag {
+    fn from(value: txn_tag::Model) -> Self {
+        UserArchiveTxnTag {
+            id: value.id,
+            name: value.name,
+        }
+    }
+}
+
+impl From<fragment::Model> for UserArchiveFragment {
+    fn from(value: fragment::Model) -> Self {
+        let from = match (
+            value.from_account,
+            value.from_amount,
+            value.from_currency_id,
+        ) {
+            (Some(account), Some(amount), Some(currency)) => Some(UserArchiveFragmentSide {
+                account,
+                amount: amount.to_string(),
+                currency,
+            }),
+            _ => None,
+        };
+        let to = match (value.to_account, value.to_amount, value.to_currency_id) {
+            (Some(account), Some(amount), Some(currency)) => Some(UserArchiveFragmentSide {
+                account,
+                amount: amount.to_string(),
+                currency,
+            }),
+            _ => None,
+        };
+        UserArchiveFragment {
+            id: value.id,
+            from,
+            to,
+        }
+    }
+}
+
+impl From<(txn::Model, Vec<fragment::Model>)> for UserArchiveTxn {
+    fn from(value: (txn::Model, Vec<fragment::Model>)) -> Self {
+        let (txn, fragments) = value;
+        UserArchiveTxn {
+            id: txn.id,
+            title: txn.title,
+            description: txn.description,
+            date: iso8601_to_js_iso(txn.date.and_utc()),
+            fragments: fragments.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<currency_rate_source::Model> for UserArchiveCurrencyRateSource {
+    fn from(value: currency_rate_source::Model) -> Self {
+        UserArchiveCurrencyRateSource {
+            id: value.id,
+            name: value.name,
+            kind: RateSourceKind::from_stored(&value.kind),
+            ref_currency_id: value.ref_currency_id,
+            ref_amount_currency_id: value.ref_amount_currency_id,
+            hostname: value.hostname,
+            path: value.path,
+            json_query_string: value.json_query_string,
+        }
+    }
+}
+
+impl TryFrom<wallet::Model> for UserArchiveWallet {
+    type Error = DbErr;
+
+    fn try_from(value: wallet::Model) -> Result<Self, Self::Error> {
+        Ok(UserArchiveWallet {
+            id: value.id,
+            name: value.name,
+            chain: WalletChain::from_stored(&value.chain)?,
+            address: value.address,
+            account_id: value.account_id,
+            currency_id: value.currency_id,
+        })
+    }
+}
+
+impl TryFrom<wallet_txn::Model> for UserArchiveWalletTxn {
+    type Error = DbErr;
+
+    fn try_from(value: wallet_txn::Model) -> Result<Self, Self::Error> {
+        Ok(UserArchiveWalletTxn {
+            chain: WalletChain::from_stored(&value.chain)?,
+            address: value.address,
+            tx_hash: value.tx_hash,
+            txn_id: value.txn_id,
+        })
+    }
+}
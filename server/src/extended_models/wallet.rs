@@ -0,0 +1,74 @@
+use crate::{
+    entities::wallet::Model, extended_models::account::AccountId,
+    extended_models::currency::CurrencyId, extractors::auth_user::AuthUser,
+};
+use sea_orm::prelude::DateTime;
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The blockchain a wallet address lives on.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub enum WalletChain {
+    Btc,
+    Ltc,
+}
+
+impl WalletChain {
+    /// The value stored in the `chain` column, same as the serialized one.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletChain::Btc => "btc",
+            WalletChain::Ltc => "ltc",
+        }
+    }
+
+    /// Unknown values are refused, a wallet is never synced against the API of another chain.
+    pub fn from_stored(value: &str) -> Result<WalletChain, DbErr> {
+        match value {
+            "btc" => Ok(WalletChain::Btc),
+            "ltc" => Ok(WalletChain::Ltc),
+            _ => Err(DbErr::Type(format!("Unknown wallet chain \"{value}\"."))),
+        }
+    }
+}
+
+/// A blockchain address whose transfers are synced into `account_id`, in `currency_id`.
+#[derive(Clone, Debug)]
+pub struct Wallet {
+    pub id: Uuid,
+    pub owner: AuthUser,
+    pub name: String,
+    pub chain: WalletChain,
+    pub address: String,
+    pub account_id: AccountId,
+    pub currency_id: CurrencyId,
+    /// Where the next sync resumes, as given by the chain provider.
+    pub cursor: Option<String>,
+    pub last_sync_date: Option<DateTime>,
+    pub last_error: Option<String>,
+}
+
+impl TryFrom<Model> for Wallet {
+    type Error = DbErr;
+
+    fn try_from(value: Model) -> Result<Self, Self::Error> {
+        Ok(Wallet {
+            id: value.id,
+            owner: AuthUser(value.owner_id),
+            name: value.name,
+            chain: WalletChain::from_stored(&value.chain)?,
+            address: value.address,
+            account_id: AccountId(value.account_id),
+            currency_id: CurrencyId(value.currency_id),
+            cursor: value.cursor,
+            last_sync_date: value.last_sync_date,
+            last_error: value.last_error,
+        })
+    }
+}
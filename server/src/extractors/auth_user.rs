@@ -1,8 +1,4 @@
-use actix_web::{
-    error::{ErrorInternalServerError, ErrorUnauthorized},
-    web::Data,
-    Error, FromRequest,
-};
+use actix_web::{web::Data, Error, FromRequest};
 use futures::Future;
 use sea_orm::{prelude::Expr, sea_query::IntoCondition, EntityTrait, JoinType, QuerySelect};
 use serde::{Deserialize, Serialize};
@@ -10,6 +6,8 @@ use std::{pin::Pin, str::FromStr};
 
 use crate::{
     entities::{access_token, user},
+    request_tracing::record_request_user,
+    routes::bootstrap::EndpointsErrors,
     DatabaseStates,
 };
 
@@ -29,18 +27,17 @@ impl FromRequest for AuthUser {
             .db
             .clone();
         let req = req.clone();
-        let unauthorized_msg = "Authorization header not provided or user is not found.";
         Box::pin(async move {
             let token_header_utf8 = req
                 .headers()
                 .get("authorization")
-                .ok_or(ErrorUnauthorized(unauthorized_msg))?
+                .ok_or(EndpointsErrors::Unauthorized)?
                 .to_str()
-                .map_err(|_| ErrorUnauthorized(unauthorized_msg))?;
+                .map_err(|_| EndpointsErrors::Unauthorized)?;
 
             let token_header_parsed = String::from(token_header_utf8).clone();
             let uuid_header_parsed = uuid::Uuid::from_str(token_header_parsed.as_str())
-                .map_err(|_| ErrorUnauthorized(unauthorized_msg))?;
+                .map_err(|_| EndpointsErrors::Unauthorized)?;
 
             let token_owner_query = user::Entity::find()
                 .join(
@@ -57,11 +54,14 @@ impl FromRequest for AuthUser {
                 )
                 .one(&db_connection)
                 .await
-                .map_err(|_db_err| ErrorInternalServerError("Error querying database."))?;
+                .map_err(EndpointsErrors::DbErr)?;
 
             match token_owner_query {
-                None => Err(ErrorUnauthorized(unauthorized_msg)),
-                Some(token_owner_query) => Ok(AuthUser(token_owner_query.id)),
+                None => Err(EndpointsErrors::Unauthorized.into()),
+                Some(token_owner_query) => {
+                    record_request_user(&token_owner_query.id);
+                    Ok(AuthUser(token_owner_query.id))
+                }
             }
         })
     }
@@ -0,0 +1,112 @@
+use serde_json::Value;
+
+/// One step of a [`JsonQuery`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonQueryStep {
+    Key(String),
+    Index(usize),
+}
+
+/// A small subset of JSONPath selecting a single value of a JSON document,
+/// such as `$.rates.USD`, `$.data[0]["price usd"]` or `[2].close`.
+/// The leading `$` is optional, wildcards, slices and filters are not supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonQuery {
+    pub steps: Vec<JsonQueryStep>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ParseJsonQueryErrors {
+    #[error("Unexpected \"{found}\" at position {position}.")]
+    UnexpectedChar { found: char, position: usize },
+    #[error("Expected a key at position {position}.")]
+    MissingKey { position: usize },
+    #[error("Unclosed bracket at position {position}.")]
+    UnclosedBracket { position: usize },
+    #[error("\"{0}\" is not a valid array index.")]
+    InvalidIndex(String),
+}
+
+impl JsonQuery {
+    pub fn parse(query: &str) -> Result<JsonQuery, ParseJsonQueryErrors> {
+        type E = ParseJsonQueryErrors;
+        let chars = query.trim().chars().collect::<Vec<_>>();
+        let mut steps = vec![];
+        let mut position = match chars.first() {
+            Some('$') => 1,
+            _ => 0,
+        };
+        // A query may start with a bare key, as in `rates.USD`
+        if let Some(first) = chars.get(position) {
+            if *first != '.' && *first != '[' {
+                let (key, next) = read_key(&chars, position)?;
+                steps.push(JsonQueryStep::Key(key));
+                position = next;
+            }
+        }
+
+        while let Some(current) = chars.get(position) {
+            match current {
+                '.' => {
+                    let (key, next) = read_key(&chars, position + 1)?;
+                    steps.push(JsonQueryStep::Key(key));
+                    position = next;
+                }
+                '[' => {
+                    let close = chars[position..]
+                        .iter()
+                        .position(|x| *x == ']')
+                        .map(|offset| position + offset)
+                        .ok_or(E::UnclosedBracket { position })?;
+                    let inner = chars[position + 1..close]
+                        .iter()
+                        .collect::<String>()
+                        .trim()
+                        .to_string();
+                    let quoted = inner.len() >= 2
+                        && ((inner.starts_with('"') && inner.ends_with('"'))
+                            || (inner.starts_with('\'') && inner.ends_with('\'')));
+                    steps.push(match quoted {
+                        true => JsonQueryStep::Key(inner[1..inner.len() - 1].to_string()),
+                        false => JsonQueryStep::Index(
+                            inner
+                                .parse::<usize>()
+                                .map_err(|_| E::InvalidIndex(inner.clone()))?,
+                        ),
+                    });
+                    position = close + 1;
+                }
+                found => {
+                    return Err(E::UnexpectedChar {
+                        found: *found,
+                        position,
+                    })
+                }
+            }
+        }
+        Ok(JsonQuery { steps })
+    }
+
+    /// The value at the end of the query, `None` if any step is missing from the document.
+    pub fn select<'a>(&self, document: &'a Value) -> Option<&'a Value> {
+        self.steps
+            .iter()
+            .try_fold(document, |value, step| match step {
+                JsonQueryStep::Key(key) => value.as_object()?.get(key),
+                JsonQueryStep::Index(index) => value.as_array()?.get(*index),
+            })
+    }
+}
+
+/// Read an unquoted key starting at `start`, up to the next `.` or `[`.
+fn read_key(chars: &[char], start: usize) -> Result<(String, usize), ParseJsonQueryErrors> {
+    let end = chars[start.min(chars.len())..]
+        .iter()
+        .position(|x| *x == '.' || *x == '[')
+        .map(|offset| start + offset)
+        .unwrap_or(chars.len());
+    match end > start {
+        true => Ok((chars[start..end].iter().collect(), end)),
+        false => Err(ParseJsonQueryErrors::MissingKey { position: start }),
+    }
+}
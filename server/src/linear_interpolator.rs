@@ -1,4 +1,5 @@
-use chrono::TimeDelta;
+use crate::extended_models::currency::RateStrategy;
+use chrono::{NaiveDateTime, TimeDelta};
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 
 /// Convert a given `TimeDelta` into its total mills.
@@ -34,3 +35,49 @@ pub fn try_linear_interpolate(
         (_, _) => None,
     }
 }
+
+/// The datums a [`RateStrategy`] derives a rate from.
+#[derive(Debug, PartialEq)]
+pub enum DatumSelection<T> {
+    /// Interpolate between both datums.
+    Interpolate(T, T),
+    /// Use the last datum before the date as is.
+    Previous(T),
+    /// Use the datum closest to the date as is.
+    Nearest(T),
+    /// No datum applies, the fallback rate is used.
+    Fallback,
+}
+
+/// Pick the datums `strategy` uses at `target`, given the datums at or before (`left`) and at or after (`right`) it.
+/// Without a left datum, `Nearest` uses the right one and every other strategy falls back.
+/// `max_gap` is only used by `LinearWithMaxGap`, datums further than it from `target` are ignored.
+pub fn select_datums<T>(
+    strategy: RateStrategy,
+    max_gap: Option<TimeDelta>,
+    target: &NaiveDateTime,
+    left: Option<(NaiveDateTime, T)>,
+    right: Option<(NaiveDateTime, T)>,
+) -> DatumSelection<T> {
+    let within_gap = |datum: &(NaiveDateTime, T)| match (strategy, max_gap) {
+        (RateStrategy::LinearWithMaxGap, Some(max_gap)) => (datum.0 - *target).abs() <= max_gap,
+        (_, _) => true,
+    };
+    let left = left.filter(within_gap);
+    let right = right.filter(within_gap);
+
+    match (strategy, left, right) {
+        (RateStrategy::Nearest, None, Some((_, right))) => DatumSelection::Nearest(right),
+        (_, None, _) => DatumSelection::Fallback,
+        (RateStrategy::PreviousValue, Some((_, left)), _) => DatumSelection::Previous(left),
+        (RateStrategy::Nearest, Some((left_x, left)), Some((right_x, right))) => {
+            match right_x - *target < *target - left_x {
+                true => DatumSelection::Nearest(right),
+                false => DatumSelection::Nearest(left),
+            }
+        }
+        (RateStrategy::Nearest, Some((_, left)), None) => DatumSelection::Nearest(left),
+        (_, Some((_, left)), Some((_, right))) => DatumSelection::Interpolate(left, right),
+        (_, Some((_, left)), None) => DatumSelection::Previous(left),
+    }
+}
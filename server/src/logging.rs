@@ -1,7 +1,25 @@
-use crate::env::AppEnv;
 use crate::env::EnvLogMode;
-use tracing::{level_filters::LevelFilter, Level};
-use tracing_subscriber::{filter, fmt::Layer, layer::SubscriberExt, util::SubscriberInitExt};
+use crate::env::{AppEnv, EnvLogFormat, EnvLogRotation, EnvLoggingSection};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{
+    filter::Targets,
+    fmt::{self, MakeWriter},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    Layer, Registry,
+};
+
+/// Targets which are too noisy to be logged unless they are explicitly configured.
+const DEFAULT_LOG_TARGETS: [(&str, LevelFilter); 2] = [
+    ("sqlx::postgres::notice", LevelFilter::OFF),
+    ("sqlx::query", LevelFilter::OFF),
+];
 
 #[derive(Debug)]
 pub enum EnvInitLoggerErrors {
@@ -9,6 +27,8 @@ pub enum EnvInitLoggerErrors {
     IOError(std::io::Error),
     #[allow(unused)]
     TryInitError(tracing_subscriber::util::TryInitError),
+    #[allow(unused)]
+    InvalidLevel { target: String, level: String },
 }
 
 impl From<std::io::Error> for EnvInitLoggerErrors {
@@ -23,37 +43,208 @@ impl From<tracing_subscriber::util::TryInitError> for EnvInitLoggerErrors {
     }
 }
 
+impl EnvLoggingSection {
+    /// Build the level filter, configured targets take precedence over the built-in defaults.
+    pub fn to_filter(&self) -> Result<Targets, EnvInitLoggerErrors> {
+        let parse_level = |target: &str, level: &str| {
+            LevelFilter::from_str(level).map_err(|_| EnvInitLoggerErrors::InvalidLevel {
+                target: target.to_string(),
+                level: level.to_string(),
+            })
+        };
+        let default_level = match &self.level {
+            None => LevelFilter::INFO,
+            Some(level) => parse_level("default", level)?,
+        };
+
+        let mut filter = Targets::new()
+            .with_default(default_level)
+            .with_targets(DEFAULT_LOG_TARGETS);
+        for (target, level) in self.targets.iter().flatten() {
+            filter = filter.with_target(target, parse_level(target, level)?);
+        }
+        Ok(filter)
+    }
+}
+
+pub fn fmt_layer<W>(
+    format: EnvLogFormat,
+    writer: W,
+    ansi: bool,
+) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::Layer::new().with_ansi(ansi).with_writer(writer);
+    match format {
+        EnvLogFormat::Text => layer.boxed(),
+        EnvLogFormat::Json => layer
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    }
+}
+
 impl AppEnv {
     pub fn init_logger(&self) -> Result<(), EnvInitLoggerErrors> {
         let file_appender_path = match &self.logging.log_mode {
             EnvLogMode::File { path } | EnvLogMode::Both { path } => Some(path),
             EnvLogMode::Console => None,
         };
+        let format = self.logging.format.unwrap_or_default();
+
+        let mut layers = vec![fmt_layer(format, io::stdout, true)];
 
         // Create a file logger if needed
-        let file_appender = file_appender_path.map(|path| {
-            Layer::new().with_ansi(false).with_writer(
-                std::fs::OpenOptions::new()
-                    .append(true)
-                    .create(true)
-                    .open(path)
-                    .unwrap(),
-            )
-        });
-
-        let stdout_layer = Layer::new();
-
-        let filter = filter::Targets::new()
-            .with_default(Level::INFO)
-            .with_target("sqlx::postgres::notice", LevelFilter::OFF)
-            .with_target("sqlx::query", LevelFilter::OFF);
-
-        let subscriber = tracing_subscriber::registry()
-            .with(filter)
-            .with(stdout_layer)
-            .with(file_appender);
+        if let Some(path) = file_appender_path {
+            let rotation = self.logging.rotation.clone().unwrap_or_default();
+            let writer = RollingFileWriter::open(path, rotation)?;
+            layers.push(fmt_layer(format, Mutex::new(writer), false));
+        }
+
+        let subscriber =
+            tracing_subscriber::registry().with(layers.with_filter(self.logging.to_filter()?));
 
         // Initialize the subscriber
         Ok(subscriber.try_init()?)
     }
 }
+
+/// Append-only log file which is moved aside according to an [`EnvLogRotation`].
+pub struct RollingFileWriter {
+    path: PathBuf,
+    rotation: EnvLogRotation,
+    file: File,
+    size: u64,
+    opened_on: NaiveDate,
+}
+
+impl RollingFileWriter {
+    pub fn open(path: impl Into<PathBuf>, rotation: EnvLogRotation) -> io::Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new().append(true).create(true).open(&path)?;
+        let metadata = file.metadata()?;
+        // An existing file is attributed to the day it was last written
+        let opened_on = metadata
+            .modified()
+            .map(|x| DateTime::<Utc>::from(x).date_naive())
+            .unwrap_or_else(|_| Utc::now().date_naive());
+        Ok(RollingFileWriter {
+            path,
+            rotation,
+            file,
+            size: metadata.len(),
+            opened_on,
+        })
+    }
+
+    /// Same as [`Write::write`], as if today were `today`.
+    pub fn write_dated(&mut self, buf: &[u8], today: NaiveDate) -> io::Result<usize> {
+        // Nothing to move aside yet, the file belongs to the day of its first line
+        if self.size == 0 {
+            self.opened_on = today;
+        }
+        if self.should_rotate(buf.len(), today) {
+            self.rotate(today)?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn should_rotate(&self, incoming: usize, today: NaiveDate) -> bool {
+        match self.rotation {
+            EnvLogRotation::Never => false,
+            EnvLogRotation::Daily { .. } => today != self.opened_on,
+            EnvLogRotation::Size { max_bytes, .. } => {
+                self.size > 0 && self.size + incoming as u64 > max_bytes
+            }
+        }
+    }
+
+    fn rotated_path(&self, suffix: &str) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".");
+        path.push(suffix);
+        path.into()
+    }
+
+    fn rotate(&mut self, today: NaiveDate) -> io::Result<()> {
+        self.file.flush()?;
+        match self.rotation {
+            EnvLogRotation::Never => {}
+            EnvLogRotation::Daily { max_files } => {
+                let suffix = self.opened_on.format("%Y-%m-%d").to_string();
+                fs::rename(&self.path, self.rotated_path(&suffix))?;
+                if let Some(max_files) = max_files {
+                    self.prune_daily(max_files)?;
+                }
+            }
+            EnvLogRotation::Size { max_files, .. } => {
+                // Index of the first free slot, or of the slot to overwrite when full
+                let mut last = 1;
+                while self.rotated_path(&last.to_string()).exists() {
+                    last += 1;
+                }
+                let last = max_files.map_or(last, |max_files| last.min(max_files));
+                if last == 0 {
+                    fs::remove_file(&self.path)?;
+                } else {
+                    for index in (1..last).rev() {
+                        fs::rename(
+                            self.rotated_path(&index.to_string()),
+                            self.rotated_path(&(index + 1).to_string()),
+                        )?;
+                    }
+                    fs::rename(&self.path, self.rotated_path("1"))?;
+                }
+            }
+        }
+        self.file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?;
+        self.size = 0;
+        self.opened_on = today;
+        Ok(())
+    }
+
+    /// Delete the oldest daily files until at most `max_files` are left.
+    fn prune_daily(&self, max_files: usize) -> io::Result<()> {
+        let prefix = match self.path.file_name() {
+            None => return Ok(()),
+            Some(file_name) => format!("{}.", file_name.to_string_lossy()),
+        };
+        let dir = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let mut rotated = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let date = file_name.strip_prefix(&prefix)?;
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+                Some(entry.path())
+            })
+            .collect::<Vec<_>>();
+        // Dates are zero padded, so names sort chronologically
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(max_files);
+        for path in rotated.into_iter().take(excess) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_dated(buf, Utc::now().date_naive())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
@@ -1,26 +1,30 @@
 mod caches;
+mod commands;
 mod date;
 #[allow(unused)]
 mod entities;
 mod env;
 mod extended_models;
 mod extractors;
+mod json_query;
 mod linear_interpolator;
 mod logging;
 mod maths;
+mod metrics;
+mod request_tracing;
 mod routes;
 mod services;
+mod shutdown;
+mod ssl;
 mod states;
 mod tests;
 
-use actix_web::{web, App, HttpServer};
-use clap::{command, Parser, ValueHint};
-use finance_manager_migration::{Migrator, MigratorTrait};
-use routes::bootstrap::apply_endpoints;
-use sea_orm::Database;
+use clap::{Parser, Subcommand, ValueHint};
+use commands::config::{ConfigCommandErrors, ConfigCommands};
+use commands::migrate::MigrateCommands;
+use commands::repair::{RepairCommandErrors, RepairCommands};
 use states::database_states::DatabaseStates;
 use std::error::Error;
-use tracing::info;
 
 const RESTFUL_DIGITS: u32 = 20;
 
@@ -32,9 +36,67 @@ struct Args {
     #[arg(short('e'), long("env-file"), value_name = "PATH", value_hint=ValueHint::FilePath)]
     env_path: Option<String>,
 
-    /// Exit the program when database is not migrated to the latest schema.
-    #[arg(long("exit-on-not-fully-migrated"), value_name = "BOOL")]
+    /// Exit the program when database is not migrated to the latest schema,
+    /// instead of applying the pending migrations.
+    /// Honoured by `serve`, `import-user`, `derive-rates` and `sync-wallets`.
+    #[arg(long("exit-on-not-fully-migrated"), value_name = "BOOL", global = true)]
     exit_on_not_fully_migrated: Option<bool>,
+
+    /// Defaults to `serve` if not given.
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Run the server.
+    Serve,
+
+    /// Inspect the configuration, then exit.
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+
+    /// Inspect or change the database schema, then exit.
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateCommands,
+    },
+
+    /// Check the stored data for problems, then exit.
+    /// Pending migrations are not applied, so the data they would refuse can be found first.
+    Repair {
+        #[command(subcommand)]
+        action: RepairCommands,
+    },
+
+    /// Import an archive produced by `GET /users/export` into an existing user, then exit.
+    ImportUser {
+        /// Name of the user receiving the imported data.
+        #[arg(long("username"))]
+        username: String,
+
+        /// Relative or absolute path to the archive JSON file.
+        #[arg(long("archive"), value_name = "PATH", value_hint=ValueHint::FilePath)]
+        archive_path: String,
+
+        /// Assign fresh ids to every imported row instead of keeping the ids in the archive.
+        #[arg(long("remap-ids"))]
+        remap_ids: bool,
+    },
+
+    /// Derive currency rate datums from the saved transactions, for the currencies deriving
+    /// their rates, then exit.
+    DeriveRates {
+        /// Only derive the rates of this user, every user if not given.
+        #[arg(long("username"))]
+        username: Option<String>,
+    },
+
+    /// Save the new transfers of every wallet as transactions, with the chain providers of the
+    /// `wallets` config, then exit.
+    SyncWallets,
 }
 
 #[cfg_attr(test, mutants::skip)]
@@ -42,47 +104,84 @@ struct Args {
 pub async fn main() -> Result<(), Box<dyn Error>> {
     let cli = Args::parse();
 
+    // Variables from a `.env` file are picked up as `FM_` overrides, without replacing the real ones
+    dotenv::dotenv().ok();
     let env = {
-        let env_file_path = cli.env_path.as_deref().unwrap_or("./env.json");
-        let json_str = env::read_json_as_str(env_file_path)?;
-        env::parse_env(json_str.as_str())?
-    };
-
-    env.init_logger().expect("Unable to init logger.");
-
-    let port = {
-        let port = match env.server {
-            None => None,
-            Some(ref server_section) => server_section.port,
+        let json_str = match cli.env_path.as_deref() {
+            Some(env_file_path) => env::read_json_as_str(env_file_path)?,
+            // Without a file, the whole config may come from `FM_` variables
+            None => env::read_json_as_str("./env.json").unwrap_or_else(|_| "{}".to_string()),
         };
-        if let Some(port_given) = port {
-            port_given
-        } else {
-            info!("No port given in env file, finding unused port starting from 1000.");
-            let unused_port = port_check::free_local_ipv4_port_in_range(1000..65535);
-            let unused_port = unused_port.expect("Unable to find an unused port.");
-            info!("Unused port {} on ipv4 is found.", unused_port);
-            unused_port
-        }
+        env::parse_env(json_str.as_str(), std::env::vars())?
     };
 
-    let db = {
-        info!("Connecting to database...");
-        let connect_options = env.to_connection_options().clone();
-        let db = Database::connect(connect_options).await?;
-        info!("Connected to database...");
-        db
-    };
-
-    Migrator::up(&db, None).await?;
-
-    let app_data = web::Data::new(DatabaseStates::new(db.clone()));
-    HttpServer::new(move || {
-        apply_endpoints(App::new().app_data(app_data.clone()))
-    })
-    .bind(("127.0.0.1", port))?
-    .run()
-    .await?;
+    let command = cli.command.unwrap_or(Commands::Serve);
+    let exit_on_not_fully_migrated = cli.exit_on_not_fully_migrated.unwrap_or(false);
+    // Logging problems are reported by `config check` instead of aborting it
+    if !matches!(command, Commands::Config { .. }) {
+        env.init_logger().expect("Unable to init logger.");
+    }
 
-    Ok(())
+    match command {
+        Commands::Config {
+            action: ConfigCommands::Check,
+        } => {
+            let report = commands::config::run_config_check(&env).await?;
+            for line in report.to_lines()? {
+                println!("{line}");
+            }
+            match report.problems.len() {
+                0 => Ok(()),
+                count => Err(ConfigCommandErrors::InvalidConfig(count).into()),
+            }
+        }
+        Commands::Serve => commands::serve::run_serve(&env, exit_on_not_fully_migrated).await,
+        Commands::Migrate { action } => {
+            let db = commands::connect_database(&env).await?;
+            for line in commands::migrate::run_migrate(&db, &env.env_mode, action).await? {
+                println!("{line}");
+            }
+            Ok(())
+        }
+        Commands::Repair { action } => {
+            let db = commands::connect_database(&env).await?;
+            let report = commands::repair::run_repair(&db, action).await?;
+            for line in report.to_lines() {
+                println!("{line}");
+            }
+            match report.invalid_rows.len() {
+                0 => Ok(()),
+                count => Err(RepairCommandErrors::InvalidRows(count).into()),
+            }
+        }
+        Commands::ImportUser {
+            username,
+            archive_path,
+            remap_ids,
+        } => {
+            let db = commands::connect_database(&env).await?;
+            commands::apply_pending_migrations(&db, exit_on_not_fully_migrated).await?;
+            commands::import_user::run_import_user(
+                &DatabaseStates::new(db),
+                &username,
+                &archive_path,
+                remap_ids,
+            )
+            .await
+        }
+        Commands::DeriveRates { username } => {
+            let db = commands::connect_database(&env).await?;
+            commands::apply_pending_migrations(&db, exit_on_not_fully_migrated).await?;
+            commands::derive_rates::run_derive_rates(&DatabaseStates::new(db), username.as_deref())
+                .await
+                .map(|_| ())
+        }
+        Commands::SyncWallets => {
+            let db = commands::connect_database(&env).await?;
+            commands::apply_pending_migrations(&db, exit_on_not_fully_migrated).await?;
+            commands::sync_wallets::run_sync_wallets(env.wallets.as_ref(), &DatabaseStates::new(db))
+                .await
+                .map(|_| ())
+        }
+    }
 }
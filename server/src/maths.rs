@@ -1,14 +1,8 @@
-use std::str::FromStr;
-
 use crate::services::currencies::CalculateCurrencyRateErrors;
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 
 #[allow(unused)]
 pub trait ForgivingDecimal {
-    fn forgiving_decimal_mul_str(
-        &self,
-        another: &str,
-    ) -> Result<Decimal, CalculateCurrencyRateErrors>;
     fn forgiving_decimal_mul(
         &self,
         another: &Decimal,
@@ -16,15 +10,6 @@ pub trait ForgivingDecimal {
 }
 
 impl ForgivingDecimal for Decimal {
-    fn forgiving_decimal_mul_str(
-        &self,
-        another: &str,
-    ) -> Result<Decimal, CalculateCurrencyRateErrors> {
-        self.checked_mul(Decimal::from_str(another).map_err(|_err| {
-            CalculateCurrencyRateErrors::InvalidDecimalValue(another.to_string())
-        })?)
-        .ok_or(CalculateCurrencyRateErrors::OverflowOrUnderflow)
-    }
     fn forgiving_decimal_mul(
         &self,
         another: &Decimal,
@@ -33,3 +18,23 @@ impl ForgivingDecimal for Decimal {
             .ok_or(CalculateCurrencyRateErrors::OverflowOrUnderflow)
     }
 }
+
+/// Rates and fallback rates scale other amounts, so only positive ones make sense.
+/// `Decimal` cannot hold NaN or infinities, every parsed value is already finite.
+pub fn is_valid_rate_amount(amount: &Decimal) -> bool {
+    amount.is_sign_positive() && !amount.is_zero()
+}
+
+/// Amounts are rounded half to even ("banker's rounding"), so that rounding many amounts
+/// does not drift their total up or down: 0.125 becomes 0.12 and 0.135 becomes 0.14.
+pub const AMOUNT_ROUNDING: RoundingStrategy = RoundingStrategy::MidpointNearestEven;
+
+/// Round an amount to the decimal places of its currency, following [`AMOUNT_ROUNDING`].
+pub fn round_amount(amount: &Decimal, decimals: u32) -> Decimal {
+    amount.round_dp_with_strategy(decimals, AMOUNT_ROUNDING)
+}
+
+/// Whether `amount` needs at most `decimals` decimal places, ignoring trailing zeros.
+pub fn fits_decimals(amount: &Decimal, decimals: u32) -> bool {
+    amount.normalize().scale() <= decimals
+}
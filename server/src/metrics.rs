@@ -0,0 +1,186 @@
+use crate::DatabaseStates;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+pub const METRICS_NAMESPACE: &str = "fm";
+
+/// Every cache reporting hits and misses, used as the `cache` label.
+/// Only the currency cache is looked up while serving requests, rates and tags are read from the database.
+pub const CACHE_NAMES: [&str; 1] = ["currency"];
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    db_pool_connections: IntGaugeVec,
+    cache_lookups: IntCounterVec,
+    currency_rate_recursion_depth: Histogram,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some(METRICS_NAMESPACE.to_string()), None)
+            .expect("Unable to create metrics registry.");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Number of HTTP requests handled."),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to handle HTTP requests.",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Connections of the database pool, by state.",
+            ),
+            &["state"],
+        )
+        .unwrap();
+        let cache_lookups = IntCounterVec::new(
+            Opts::new("cache_lookups_total", "Number of cache lookups, by result."),
+            &["cache", "result"],
+        )
+        .unwrap();
+        let currency_rate_recursion_depth = Histogram::with_opts(
+            HistogramOpts::new(
+                "currency_rate_recursion_depth",
+                "Depth of the currency chain walked to resolve a rate to the base currency.",
+            )
+            .buckets(vec![0.0, 1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 32.0]),
+        )
+        .unwrap();
+
+        registry.register(Box::new(http_requests.clone())).unwrap();
+        registry
+            .register(Box::new(http_request_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_connections.clone()))
+            .unwrap();
+        registry.register(Box::new(cache_lookups.clone())).unwrap();
+        registry
+            .register(Box::new(currency_rate_recursion_depth.clone()))
+            .unwrap();
+
+        // Export every cache series from the start, so dashboards do not see gaps.
+        for cache in CACHE_NAMES {
+            for result in ["hit", "miss"] {
+                cache_lookups.with_label_values(&[cache, result]);
+            }
+        }
+
+        Metrics {
+            registry,
+            http_requests,
+            http_request_duration,
+            db_pool_connections,
+            cache_lookups,
+            currency_rate_recursion_depth,
+        }
+    }
+
+    pub fn record_cache_lookup(&self, cache: &str, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+        self.cache_lookups.with_label_values(&[cache, result]).inc();
+    }
+
+    pub fn observe_currency_rate_recursion_depth(&self, depth: usize) {
+        self.currency_rate_recursion_depth.observe(depth as f64);
+    }
+
+    fn observe_db_pool(&self, db: &DatabaseConnection) {
+        let (size, idle, max) = match db.get_database_backend() {
+            DatabaseBackend::Postgres => {
+                let pool = db.get_postgres_connection_pool();
+                (
+                    pool.size(),
+                    pool.num_idle(),
+                    pool.options().get_max_connections(),
+                )
+            }
+            DatabaseBackend::Sqlite => {
+                let pool = db.get_sqlite_connection_pool();
+                (
+                    pool.size(),
+                    pool.num_idle(),
+                    pool.options().get_max_connections(),
+                )
+            }
+            DatabaseBackend::MySql => return,
+        };
+        let gauge = |state: &str, value: i64| {
+            self.db_pool_connections
+                .with_label_values(&[state])
+                .set(value)
+        };
+        gauge("open", size as i64);
+        gauge("idle", idle as i64);
+        gauge("active", size as i64 - idle as i64);
+        gauge("max", max as i64);
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn render(&self, db: &DatabaseConnection) -> String {
+        self.observe_db_pool(db);
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("Unable to encode metrics.");
+        String::from_utf8(buffer).expect("Metrics are not valid UTF-8.")
+    }
+}
+
+/// Count and time every request, labelled by the matched route pattern rather than the raw path,
+/// so ids in paths and queries do not explode the number of series.
+pub async fn metrics_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let started_at = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .match_pattern()
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let res = next.call(req).await?;
+
+    let status = res.status().as_u16().to_string();
+    METRICS
+        .http_requests
+        .with_label_values(&[&method, &route, &status])
+        .inc();
+    METRICS
+        .http_request_duration
+        .with_label_values(&[&method, &route])
+        .observe(started_at.elapsed().as_secs_f64());
+    Ok(res)
+}
+
+async fn get_metrics(data: web::Data<DatabaseStates>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(TextEncoder::new().format_type())
+        .body(METRICS.render(&data.db))
+}
+
+/// Register the metrics endpoint at `path`, it is not versioned and requires no authentication.
+pub fn configure_metrics(cfg: &mut web::ServiceConfig, path: &str) {
+    cfg.route(path, web::get().to(get_metrics));
+}
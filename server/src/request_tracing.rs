@@ -0,0 +1,70 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use std::time::Instant;
+use tracing::{field, info, info_span, Instrument, Span};
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest incoming request id which is propagated instead of being replaced.
+pub const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Id of the current request, available in the request extensions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+/// Incoming ids are only kept if they are short printable ASCII, so they are safe to log and echo.
+fn parse_request_id(value: &HeaderValue) -> Option<String> {
+    let value = value.to_str().ok()?;
+    let is_valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LENGTH
+        && value.bytes().all(|x| x.is_ascii_graphic());
+    is_valid.then(|| value.to_string())
+}
+
+/// Record the authenticated user on the span of the current request.
+pub fn record_request_user(user_id: &uuid::Uuid) {
+    Span::current().record("user_id", field::display(user_id));
+}
+
+/// Assign an `X-Request-Id` to every request, reusing the one sent by the client if any,
+/// and run the request inside a span carrying the id, route, user and status.
+pub async fn request_tracing_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let started_at = Instant::now();
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(parse_request_id)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let route = req
+        .match_pattern()
+        .unwrap_or_else(|| "unmatched".to_string());
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        route = %route,
+        user_id = field::Empty,
+        status = field::Empty,
+    );
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let mut res = next.call(req).instrument(span.clone()).await?;
+
+    span.record("status", res.status().as_u16());
+    span.in_scope(|| {
+        info!(
+            elapsed_ms = started_at.elapsed().as_millis() as u64,
+            "Request completed."
+        )
+    });
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        res.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    Ok(res)
+}
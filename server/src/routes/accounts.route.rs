@@ -1,3 +1,6 @@
+use crate::routes::openapi::{
+    BadRequestResponse, InternalServerErrorResponse, UnauthorizedResponse,
+};
 use crate::services::accounts::{get_account, get_accounts};
 use crate::{extractors::auth_user::AuthUser, services::accounts::create_account, DatabaseStates};
 use actix_web::get;
@@ -5,6 +8,7 @@ use actix_web::{post, web};
 use serde::Deserialize;
 use serde::Serialize;
 use ts_rs::TS;
+use utoipa::{IntoParams, ToSchema};
 
 pub mod get_account {
 
@@ -18,14 +22,16 @@ pub mod get_account {
 
     use super::*;
 
+    #[derive(Serialize, Deserialize, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
-    #[derive(Serialize, Deserialize)]
     pub struct GetAccountQuery {
         pub id: Option<String>,
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -35,11 +41,27 @@ pub mod get_account {
         pub creation_date: i64,
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
     pub struct GetAccountResponse {
         pub items: Vec<GetAccountResponseItem>,
     }
 
+    /// List the accounts of the user, or the one with the given id.
+    #[utoipa::path(
+        operation_id = "getAccount",
+        tag = "accounts",
+        params(GetAccountQuery),
+        responses(
+            (status = 200, body = GetAccountResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[get("/accounts")]
     async fn handler(
         user: AuthUser,
@@ -81,7 +103,7 @@ pub mod post_account {
     use crate::{routes::bootstrap::EndpointsErrors, services::TransactionWithCallback};
     use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc, TransactionTrait};
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -89,7 +111,7 @@ pub mod post_account {
         pub account_name: String,
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -97,6 +119,19 @@ pub mod post_account {
         pub id: String,
     }
 
+    /// Create an account.
+    #[utoipa::path(
+        operation_id = "postAccount",
+        tag = "accounts",
+        request_body = PostAccountRequestBody,
+        responses(
+            (status = 200, body = PostAccountResponseBody),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[post("/accounts")]
     async fn handler(
         user: AuthUser,
@@ -107,6 +142,7 @@ pub mod post_account {
             &user,
             info.account_name.as_str(),
             DateTime::new(Utc::now().date_naive(), Utc::now().time()),
+            None,
             TransactionWithCallback::new(data.db.begin().await?, vec![]),
         )
         .await?;
@@ -1,16 +1,26 @@
 use crate::{
     date::ParseISO8601Errors,
-    extended_models::{account::AccountId, currency::CurrencyId},
+    extended_models::{
+        account::AccountId,
+        currency::{CurrencyId, RateStrategy, MAX_CURRENCY_DECIMALS},
+    },
     routes,
 };
 use actix_http::StatusCode;
 use actix_web::{
     body::{BoxBody, MessageBody},
     dev::{ServiceFactory, ServiceRequest, ServiceResponse},
-    App, Error, HttpResponse,
+    error::JsonPayloadError,
+    web, App, Error, HttpResponse, Scope,
 };
+use rust_decimal::Decimal;
 use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
+use tracing::error;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 #[derive(Error, Debug)]
 pub enum EndpointsErrors {
@@ -20,8 +30,25 @@ pub enum EndpointsErrors {
     DbErr(#[from] DbErr),
     #[error("Cannot find currency {}", .0.0)]
     CurrencyNotFound(CurrencyId),
+    #[error("Cannot find currency rate source {0}")]
+    CurrencyRateSourceNotFound(uuid::Uuid),
+    #[error("Invalid {field} of currency rate source: {reason}")]
+    InvalidRateSource { field: String, reason: String },
     #[error("{0} is not a valid decimal value.")]
     InvalidDecimalValue(String),
+    #[error("Currencies allow at most {MAX_CURRENCY_DECIMALS} decimal places, got {0}.")]
+    InvalidCurrencyDecimals(u32),
+    #[error("{amount} has more decimal places than the {decimals} allowed by currency {}.", .currency_id.0)]
+    AmountPrecisionExceeded {
+        amount: String,
+        currency_id: CurrencyId,
+        decimals: u32,
+    },
+    #[error("{} does not take a rateMaxGapDays of {max_gap_days:?}, only linearWithMaxGap takes one, of at least 1.", .strategy.as_str())]
+    InvalidRateMaxGap {
+        strategy: RateStrategy,
+        max_gap_days: Option<u32>,
+    },
     #[error("Decimal encountered overflow or underflow.")]
     OverflowOrUnderflow,
     #[error("Invalid uuid: {0}")]
@@ -45,6 +72,187 @@ pub enum EndpointsErrors {
     MissingPassword,
     #[error("The given account: {} is not found.", .0.0)]
     AccountNotFound(AccountId),
+    #[error("Cannot find transaction {0}")]
+    TxnNotFound(uuid::Uuid),
+    #[error("Cannot find wallet {0}")]
+    WalletNotFound(uuid::Uuid),
+    #[error("Invalid {field} of wallet: {reason}")]
+    InvalidWallet { field: String, reason: String },
+    #[error("Unsupported archive version: {0}")]
+    UnsupportedArchiveVersion(u32),
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(String),
+    #[error("Invalid JSON body: {0}")]
+    InvalidJsonBody(String),
+    #[error("Request body must be JSON.")]
+    UnsupportedContentType,
+    #[error("Request body exceeds the limit of {limit} bytes.")]
+    PayloadTooLarge { limit: usize },
+    #[error("Invalid query string: {0}")]
+    InvalidQuery(String),
+    #[error("The requested route does not exist.")]
+    RouteNotFound,
+}
+
+/// Machine-readable identifier of an [`EndpointsErrors`] variant.
+/// These are part of the API contract, existing codes must never be renamed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(TS)]
+#[ts(export)]
+pub enum ErrorCode {
+    Unauthorized,
+    DatabaseError,
+    CurrencyNotFound,
+    CurrencyRateSourceNotFound,
+    InvalidRateSource,
+    InvalidDecimalValue,
+    InvalidCurrencyDecimals,
+    AmountPrecisionExceeded,
+    InvalidRateMaxGap,
+    DecimalOverflow,
+    InvalidUuid,
+    InvalidDate,
+    CyclicRefAmountCurrency,
+    MissingArgPair,
+    RepeatedBaseCurrency,
+    InternalServerError,
+    MissingUsername,
+    MissingPassword,
+    AccountNotFound,
+    TxnNotFound,
+    WalletNotFound,
+    InvalidWallet,
+    UnsupportedArchiveVersion,
+    InvalidArchive,
+    InvalidJsonBody,
+    UnsupportedContentType,
+    PayloadTooLarge,
+    InvalidQuery,
+    RouteNotFound,
+}
+
+/// The body of every error response returned by the API.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct ErrorResponseBody {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Structured context of the error, such as the id of the missing entity.
+    #[ts(type = "Record<string, unknown> | null")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl EndpointsErrors {
+    pub fn code(&self) -> ErrorCode {
+        type E = EndpointsErrors;
+        match self {
+            E::Unauthorized => ErrorCode::Unauthorized,
+            E::DbErr(_) => ErrorCode::DatabaseError,
+            E::CurrencyNotFound(_) => ErrorCode::CurrencyNotFound,
+            E::CurrencyRateSourceNotFound(_) => ErrorCode::CurrencyRateSourceNotFound,
+            E::InvalidRateSource { .. } => ErrorCode::InvalidRateSource,
+            E::InvalidDecimalValue(_) => ErrorCode::InvalidDecimalValue,
+            E::InvalidCurrencyDecimals(_) => ErrorCode::InvalidCurrencyDecimals,
+            E::AmountPrecisionExceeded { .. } => ErrorCode::AmountPrecisionExceeded,
+            E::InvalidRateMaxGap { .. } => ErrorCode::InvalidRateMaxGap,
+            E::OverflowOrUnderflow => ErrorCode::DecimalOverflow,
+            E::InvalidUUID(_) => ErrorCode::InvalidUuid,
+            E::ParseISO8601Errors(_) => ErrorCode::InvalidDate,
+            E::CyclicRefAmountCurrency(_) => ErrorCode::CyclicRefAmountCurrency,
+            E::MissingArgPair { .. } => ErrorCode::MissingArgPair,
+            E::RepeatedBaseCurrency => ErrorCode::RepeatedBaseCurrency,
+            E::InternalServerError { .. } => ErrorCode::InternalServerError,
+            E::MissingUsername => ErrorCode::MissingUsername,
+            E::MissingPassword => ErrorCode::MissingPassword,
+            E::AccountNotFound(_) => ErrorCode::AccountNotFound,
+            E::TxnNotFound(_) => ErrorCode::TxnNotFound,
+            E::WalletNotFound(_) => ErrorCode::WalletNotFound,
+            E::InvalidWallet { .. } => ErrorCode::InvalidWallet,
+            E::UnsupportedArchiveVersion(_) => ErrorCode::UnsupportedArchiveVersion,
+            E::InvalidArchive(_) => ErrorCode::InvalidArchive,
+            E::InvalidJsonBody(_) => ErrorCode::InvalidJsonBody,
+            E::UnsupportedContentType => ErrorCode::UnsupportedContentType,
+            E::PayloadTooLarge { .. } => ErrorCode::PayloadTooLarge,
+            E::InvalidQuery(_) => ErrorCode::InvalidQuery,
+            E::RouteNotFound => ErrorCode::RouteNotFound,
+        }
+    }
+
+    pub fn details(&self) -> Option<serde_json::Value> {
+        type E = EndpointsErrors;
+        match self {
+            E::CurrencyNotFound(currency_id) => Some(json!({ "id": currency_id.0 })),
+            E::CurrencyRateSourceNotFound(source_id) => Some(json!({ "id": source_id })),
+            E::InvalidRateSource { field, .. } => Some(json!({ "field": field })),
+            E::AccountNotFound(account_id) => Some(json!({ "id": account_id.0 })),
+            E::TxnNotFound(txn_id) => Some(json!({ "id": txn_id })),
+            E::WalletNotFound(wallet_id) => Some(json!({ "id": wallet_id })),
+            E::InvalidWallet { field, .. } => Some(json!({ "field": field })),
+            E::CyclicRefAmountCurrency(currency_id) => Some(json!({ "id": currency_id })),
+            E::InvalidDecimalValue(value) | E::InvalidUUID(value) => {
+                Some(json!({ "value": value }))
+            }
+            E::InvalidCurrencyDecimals(decimals) => Some(json!({ "value": decimals })),
+            E::AmountPrecisionExceeded {
+                amount,
+                currency_id,
+                decimals,
+            } => Some(json!({ "value": amount, "id": currency_id.0, "decimals": decimals })),
+            E::InvalidRateMaxGap {
+                strategy,
+                max_gap_days,
+            } => Some(json!({ "strategy": strategy, "value": max_gap_days })),
+            E::MissingArgPair {
+                left_prop_name,
+                right_prop_name,
+            } => Some(json!({ "left": left_prop_name, "right": right_prop_name })),
+            E::UnsupportedArchiveVersion(version) => Some(json!({ "version": version })),
+            E::PayloadTooLarge { limit } => Some(json!({ "limit": limit })),
+            _ => None,
+        }
+    }
+
+    pub fn to_response_body(&self) -> ErrorResponseBody {
+        ErrorResponseBody {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        }
+    }
+}
+
+impl From<JsonPayloadError> for EndpointsErrors {
+    fn from(value: JsonPayloadError) -> Self {
+        match value {
+            JsonPayloadError::Overflow { limit }
+            | JsonPayloadError::OverflowKnownLength { limit, .. } => {
+                EndpointsErrors::PayloadTooLarge { limit }
+            }
+            JsonPayloadError::ContentType => EndpointsErrors::UnsupportedContentType,
+            other => EndpointsErrors::InvalidJsonBody(other.to_string()),
+        }
+    }
+}
+
+/// JSON extractor config shared by every endpoint,
+/// rejecting bodies larger than `max_body_size` and reporting malformed bodies as [`EndpointsErrors`].
+pub fn json_config(max_body_size: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(max_body_size)
+        .error_handler(|err, _req| EndpointsErrors::from(err).into())
+}
+
+/// Query extractor config shared by every endpoint, reporting malformed query strings as [`EndpointsErrors`].
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default()
+        .error_handler(|err, _req| EndpointsErrors::InvalidQuery(err.to_string()).into())
+}
+
+async fn route_not_found() -> Result<HttpResponse, EndpointsErrors> {
+    Err(EndpointsErrors::RouteNotFound)
 }
 
 pub fn parse_uuid(value: &str) -> Result<uuid::Uuid, EndpointsErrors> {
@@ -52,9 +260,21 @@ pub fn parse_uuid(value: &str) -> Result<uuid::Uuid, EndpointsErrors> {
         .map_err(|_| EndpointsErrors::InvalidUUID(value.to_string()))
 }
 
+/// Parse an amount given by the client, rejecting anything but a plain decimal number.
+pub fn parse_decimal(value: &str) -> Result<Decimal, EndpointsErrors> {
+    Decimal::from_str_exact(value)
+        .map_err(|_| EndpointsErrors::InvalidDecimalValue(value.to_string()))
+}
+
 impl actix_web::ResponseError for EndpointsErrors {
     fn error_response(&self) -> HttpResponse<BoxBody> {
-        HttpResponse::build(self.status_code()).body(self.to_string())
+        // Internals are only logged, clients only ever see the generic message.
+        match self {
+            EndpointsErrors::DbErr(db_err) => error!("Database error: {db_err}"),
+            EndpointsErrors::InternalServerError { msg } => error!("Internal server error: {msg}"),
+            _ => {}
+        }
+        HttpResponse::build(self.status_code()).json(self.to_response_body())
     }
 
     fn status_code(&self) -> StatusCode {
@@ -63,21 +283,62 @@ impl actix_web::ResponseError for EndpointsErrors {
             E::DbErr(_db_err) => StatusCode::INTERNAL_SERVER_ERROR,
             E::InternalServerError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             E::CurrencyNotFound(_currency_id) => StatusCode::NOT_FOUND,
+            E::CurrencyRateSourceNotFound(_source_id) => StatusCode::NOT_FOUND,
+            E::InvalidRateSource { .. } => StatusCode::BAD_REQUEST,
             E::AccountNotFound(_account_id) => StatusCode::NOT_FOUND,
+            E::TxnNotFound(_txn_id) => StatusCode::NOT_FOUND,
+            E::WalletNotFound(_wallet_id) => StatusCode::NOT_FOUND,
+            E::InvalidWallet { .. } => StatusCode::BAD_REQUEST,
             E::Unauthorized => StatusCode::UNAUTHORIZED,
             E::ParseISO8601Errors(_parse_iso8601_errors) => StatusCode::BAD_REQUEST,
             E::CyclicRefAmountCurrency(_uuid) => StatusCode::BAD_REQUEST,
             E::RepeatedBaseCurrency => StatusCode::BAD_REQUEST,
             E::MissingArgPair { .. } => StatusCode::BAD_REQUEST,
             E::InvalidDecimalValue(_) => StatusCode::BAD_REQUEST,
+            E::InvalidCurrencyDecimals(_) => StatusCode::BAD_REQUEST,
+            E::AmountPrecisionExceeded { .. } => StatusCode::BAD_REQUEST,
+            E::InvalidRateMaxGap { .. } => StatusCode::BAD_REQUEST,
             E::OverflowOrUnderflow => StatusCode::BAD_REQUEST,
             E::InvalidUUID(_error) => StatusCode::BAD_REQUEST,
             E::MissingUsername => StatusCode::BAD_REQUEST,
             E::MissingPassword => StatusCode::BAD_REQUEST,
+            E::UnsupportedArchiveVersion(_version) => StatusCode::BAD_REQUEST,
+            E::InvalidArchive(_msg) => StatusCode::BAD_REQUEST,
+            E::InvalidJsonBody(_msg) => StatusCode::BAD_REQUEST,
+            E::UnsupportedContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            E::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            E::InvalidQuery(_msg) => StatusCode::BAD_REQUEST,
+            E::RouteNotFound => StatusCode::NOT_FOUND,
         }
     }
 }
 
+/// Every API handler is mounted under this prefix, matching `frontend/src/apiPaths.ts`.
+/// Superseded by `API_V2_PREFIX`, it keeps serving the legacy snake_case `GET /currencies` until the frontend moves over.
+pub const API_PREFIX: &str = "/api/v1";
+
+/// Same handlers as `API_PREFIX`, with every DTO in camelCase.
+pub const API_V2_PREFIX: &str = "/api/v2";
+
+/// Every handler shared by all API versions.
+fn api_scope(prefix: &str) -> Scope {
+    macro_rules! mount {
+        ($($($segment:ident)::+),* $(,)?) => {
+            web::scope(prefix)
+                .app_data(query_config())
+                .default_service(web::to(route_not_found))
+                $(.service(routes::$($segment)::+))*
+        };
+    }
+    let mut api = with_api_handlers!(mount);
+
+    #[cfg(debug_assertions)]
+    {
+        api = api.service(routes::dev::dev_test::handler);
+    }
+    api
+}
+
 pub fn apply_endpoints(
     app: App<
         impl ServiceFactory<
@@ -97,23 +358,14 @@ pub fn apply_endpoints(
         Error = Error,
     >,
 > {
-    let mut app = app
-        .service(routes::users::login::handler)
-        .service(routes::users::register::handler)
-        .service(routes::accounts::post_account::handler)
-        .service(routes::currencies::post_currency::handler)
-        .service(routes::currencies::get_currency::handler)
-        .service(routes::currency_rate_datums::post_currency_rate_datum::handler)
-        .service(routes::txn_tags::get_tags::handler)
-        .service(routes::txn_tags::create_tag::handler)
-        .service(routes::accounts::get_account::handler)
-        .service(routes::txns::post_txns::handler)
-        .service(routes::txns::get_txns::handler);
-
-    #[cfg(debug_assertions)]
-    {
-        app = app.service(routes::dev::dev_test::handler);
-    }
+    let api_v1 = api_scope(API_PREFIX).service(routes::currencies::get_currency::legacy_handler);
+    let api_v2 = api_scope(API_V2_PREFIX).service(routes::currencies::get_currency::handler);
 
-    app
+    // Probes live outside the versioned API and never require authentication.
+    app.service(routes::probes::health::handler)
+        .service(routes::probes::ready::handler)
+        .service(routes::probes::version::handler)
+        .service(routes::openapi::spec::handler)
+        .service(api_v1)
+        .service(api_v2)
 }
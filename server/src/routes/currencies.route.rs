@@ -1,20 +1,26 @@
+use crate::routes::openapi::{
+    BadRequestResponse, InternalServerErrorResponse, NotFoundResponse, UnauthorizedResponse,
+};
 use crate::{extractors::auth_user::AuthUser, states::database_states::DatabaseStates};
-use actix_web::{get, post, web};
+use actix_web::{get, patch, post, web};
 use sea_orm::TransactionTrait;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use utoipa::{IntoParams, ToSchema};
 
 pub mod post_currency {
 
     use crate::{
-        extended_models::currency::{CreateCurrencyAction, CurrencyId},
-        routes::bootstrap::{parse_uuid, EndpointsErrors},
+        extended_models::currency::{
+            CreateCurrencyAction, CurrencyId, CurrencyKind, RateStrategy, DEFAULT_CURRENCY_DECIMALS,
+        },
+        routes::bootstrap::{parse_decimal, parse_uuid, EndpointsErrors},
         services::{currencies::create_currency, TransactionWithCallback},
     };
 
     use super::*;
 
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -23,9 +29,34 @@ pub mod post_currency {
         pub fallback_rate_amount: Option<String>,
         pub fallback_rate_currency_id: Option<String>,
         pub ticker: String,
+        /// Decimal places of amounts in this currency, 2 if not given. Use 8 for BTC and 0 for JPY.
+        #[serde(default)]
+        #[ts(optional)]
+        pub decimals: Option<u32>,
+        /// `fiat` if not given.
+        #[serde(default)]
+        #[ts(optional)]
+        pub kind: Option<CurrencyKind>,
+        /// Display symbol, such as "$" or "₿".
+        #[serde(default)]
+        #[ts(optional)]
+        pub symbol: Option<String>,
+        /// `linear` if not given. Ignored for base currencies.
+        #[serde(default)]
+        #[ts(optional)]
+        pub rate_strategy: Option<RateStrategy>,
+        /// Required by `linearWithMaxGap`, refused by the other strategies.
+        #[serde(default)]
+        #[ts(optional)]
+        pub rate_max_gap_days: Option<u32>,
+        /// Record the rates of transactions converting this currency as rate datums, `false` if
+        /// not given. Ignored for base currencies.
+        #[serde(default)]
+        #[ts(optional)]
+        pub derive_rates: Option<bool>,
     }
 
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -33,12 +64,30 @@ pub mod post_currency {
         pub id: String,
     }
 
+    /// Create a currency, a base currency if no fallback rate is given.
+    #[utoipa::path(
+        operation_id = "postCurrency",
+        tag = "currencies",
+        request_body = PostCurrencyRequestBody,
+        responses(
+            (status = 200, body = PostCurrencyResponseBody),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[post("/currencies")]
     async fn handler(
         user: AuthUser,
         info: web::Json<PostCurrencyRequestBody>,
         data: web::Data<DatabaseStates>,
     ) -> Result<web::Json<PostCurrencyResponseBody>, EndpointsErrors> {
+        let decimals = info.decimals.unwrap_or(DEFAULT_CURRENCY_DECIMALS);
+        let kind = info.kind.unwrap_or_default();
+        let symbol = info.symbol.clone().filter(|x| !x.is_empty());
+
         // Convert request to create domain enum
         let domain_enum_to_be_saved = match (
             info.fallback_rate_amount.clone(),
@@ -48,13 +97,24 @@ pub mod post_currency {
                 name: info.name.clone(),
                 owner: user,
                 ticker: info.ticker.clone(),
+                decimals,
+                kind,
+                symbol,
+                archived: false,
             },
             (Some(ref fallback_rate_amount), Some(ref fallback_rate_currency_id)) => {
                 CreateCurrencyAction::Normal {
                     name: info.name.clone(),
                     owner: user,
                     ticker: info.ticker.clone(),
-                    fallback_rate_amount: fallback_rate_amount.clone(),
+                    decimals,
+                    kind,
+                    symbol,
+                    archived: false,
+                    rate_strategy: info.rate_strategy.unwrap_or_default(),
+                    rate_max_gap_days: info.rate_max_gap_days,
+                    derive_rates: info.derive_rates.unwrap_or(false),
+                    fallback_rate_amount: parse_decimal(fallback_rate_amount)?,
                     fallback_rate_currency_id: CurrencyId(parse_uuid(fallback_rate_currency_id)?),
                 }
             }
@@ -67,25 +127,139 @@ pub mod post_currency {
         };
 
         let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
-        let (id, db_txn) =
-            create_currency(domain_enum_to_be_saved, db_txn, data.currency_cache.clone()).await?;
+        let (id, db_txn) = create_currency(
+            domain_enum_to_be_saved,
+            None,
+            db_txn,
+            data.currency_cache.clone(),
+        )
+        .await?;
 
         db_txn.commit().await;
         Ok(web::Json(PostCurrencyResponseBody { id: id.to_string() }))
     }
 }
 
+pub mod patch_currency {
+
+    use crate::{
+        extended_models::currency::{CurrencyId, CurrencyKind, RateStrategy},
+        routes::bootstrap::{parse_uuid, EndpointsErrors},
+        services::{
+            currencies::{update_currency_metadata, UpdateCurrencyMetadataAction},
+            TransactionWithCallback,
+        },
+    };
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PatchCurrencyRequestBody {
+        #[serde(default)]
+        #[ts(optional)]
+        pub kind: Option<CurrencyKind>,
+        /// An empty string removes the symbol.
+        #[serde(default)]
+        #[ts(optional)]
+        pub symbol: Option<String>,
+        /// Archived currencies are left out of `GET /currencies`, their rates are still used.
+        #[serde(default)]
+        #[ts(optional)]
+        pub archived: Option<bool>,
+        /// Replaces `rateMaxGapDays` too, removing it unless given.
+        #[serde(default)]
+        #[ts(optional)]
+        pub rate_strategy: Option<RateStrategy>,
+        #[serde(default)]
+        #[ts(optional)]
+        pub rate_max_gap_days: Option<u32>,
+        /// Only affects transactions created or edited afterwards, see the `derive-rates` command.
+        #[serde(default)]
+        #[ts(optional)]
+        pub derive_rates: Option<bool>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PatchCurrencyResponseBody {
+        pub id: String,
+    }
+
+    /// Change the kind, symbol or archived flag of a currency. Omitted fields are left as they are.
+    #[utoipa::path(
+        operation_id = "patchCurrency",
+        tag = "currencies",
+        params(("id" = String, Path, description = "Id of the currency.")),
+        request_body = PatchCurrencyRequestBody,
+        responses(
+            (status = 200, body = PatchCurrencyResponseBody),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[patch("/currencies/{id}")]
+    async fn handler(
+        user: AuthUser,
+        id: web::Path<String>,
+        info: web::Json<PatchCurrencyRequestBody>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<PatchCurrencyResponseBody>, EndpointsErrors> {
+        let currency_id = CurrencyId(parse_uuid(&id)?);
+        let action = UpdateCurrencyMetadataAction {
+            kind: info.kind,
+            symbol: info
+                .symbol
+                .clone()
+                .map(|symbol| Some(symbol).filter(|x| !x.is_empty())),
+            archived: info.archived,
+            rate_strategy: info.rate_strategy,
+            rate_max_gap_days: match info.rate_strategy {
+                Some(_) => Some(info.rate_max_gap_days),
+                None => info.rate_max_gap_days.map(Some),
+            },
+            derive_rates: info.derive_rates,
+        };
+
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (currency, db_txn) = update_currency_metadata(
+            &user,
+            &currency_id,
+            action,
+            db_txn,
+            data.currency_cache.clone(),
+        )
+        .await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(PatchCurrencyResponseBody {
+            id: currency.id().0.to_string(),
+        }))
+    }
+}
+
 pub mod get_currency {
 
+    use actix_web::{http::header, CustomizeResponder, Responder};
     use futures::TryFutureExt;
     use rust_decimal::Decimal;
 
     use crate::{
-        date::js_iso_to_iso8601,
-        extended_models::currency::{Currency, CurrencyId},
-        routes::bootstrap::{parse_uuid, EndpointsErrors},
+        date::{iso8601_to_js_iso, js_iso_to_iso8601},
+        extended_models::currency::{Currency, CurrencyId, CurrencyKind, RateMethod, RateStrategy},
+        routes::bootstrap::{parse_uuid, EndpointsErrors, API_V2_PREFIX},
         services::{
-            currencies::{calculate_currency_rate, get_currencies, get_currency_by_id},
+            currencies::{
+                explain_currency_rate, get_currencies, get_currency_by_id, CurrencyFilter,
+                RateExplanation,
+            },
             TransactionWithCallback,
         },
         RESTFUL_DIGITS,
@@ -93,13 +267,26 @@ pub mod get_currency {
 
     use super::*;
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, Default, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
     pub struct GetCurrencyQuery {
         pub id: Option<String>,
         pub date: Option<String>,
+        /// Only list the currencies of this kind. Ignored when `id` is given.
+        pub kind: Option<CurrencyKind>,
+        /// Also list archived currencies. Ignored when `id` is given, which finds archived currencies too.
+        pub include_archived: Option<bool>,
+        /// Tell how every rate was found.
+        pub explain: Option<bool>,
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
     pub struct GetCurrencyResponseItem {
         pub id: String,
         pub name: String,
@@ -108,20 +295,178 @@ pub mod get_currency {
         pub ticker: String,
         pub is_base: bool,
         pub owner: String,
+        /// Rates are ratios between currencies rather than amounts, they keep 20 decimal places.
         pub rate_to_base: String,
+        pub decimals: u32,
+        pub kind: CurrencyKind,
+        pub symbol: Option<String>,
+        pub archived: bool,
+        /// Not given for the base currency.
+        pub rate_strategy: Option<RateStrategy>,
+        pub rate_max_gap_days: Option<u32>,
+        pub derive_rates: bool,
+        /// Only given with `explain=true`.
+        pub explanation: Option<GetCurrencyRateExplanation>,
+    }
+
+    /// How the rate of a currency was found, with the rates it was computed from.
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[schema(no_recursion)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetCurrencyRateExplanation {
+        pub currency_id: String,
+        pub method: RateMethod,
+        pub rate_to_base: String,
+        /// The datums of the currency used, left one first.
+        pub datums: Vec<GetCurrencyRateExplanationDatum>,
+        /// Given when the fallback rate of the currency was used.
+        pub fallback_rate_amount: Option<String>,
+        /// How the rates of the currencies the datums or the fallback rate refer to were found.
+        pub sources: Vec<GetCurrencyRateExplanation>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetCurrencyRateExplanationDatum {
+        pub id: String,
+        pub date: String,
+        pub amount: String,
+        pub ref_amount_currency_id: String,
+    }
+
+    impl From<RateExplanation> for GetCurrencyRateExplanation {
+        fn from(value: RateExplanation) -> Self {
+            GetCurrencyRateExplanation {
+                currency_id: value.currency_id.0.to_string(),
+                method: value.method,
+                rate_to_base: value.rate.round_dp(RESTFUL_DIGITS).normalize().to_string(),
+                datums: value
+                    .datums
+                    .into_iter()
+                    .map(|datum| GetCurrencyRateExplanationDatum {
+                        id: datum.id.to_string(),
+                        date: iso8601_to_js_iso(datum.date.and_utc()),
+                        amount: datum.amount.to_string(),
+                        ref_amount_currency_id: datum.ref_amount_currency_id.to_string(),
+                    })
+                    .collect(),
+                fallback_rate_amount: value.fallback_rate_amount.map(|x| x.to_string()),
+                sources: value.sources.into_iter().map(Into::into).collect(),
+            }
+        }
     }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
     pub struct GetCurrencyResponse {
         pub items: Vec<GetCurrencyResponseItem>,
     }
 
+    /// `GetCurrencyResponseItem` with the snake_case field names still served under `/api/v1`.
+    #[derive(Serialize, Deserialize, ToSchema, TS)]
+    #[ts(export)]
+    pub struct GetCurrencyResponseItemV1 {
+        pub id: String,
+        pub name: String,
+        pub fallback_rate_amount: Option<String>,
+        pub fallback_rate_currency_id: Option<String>,
+        pub ticker: String,
+        pub is_base: bool,
+        pub owner: String,
+        pub rate_to_base: String,
+    }
+
+    #[derive(Serialize, Deserialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetCurrencyResponseV1 {
+        pub items: Vec<GetCurrencyResponseItemV1>,
+    }
+
+    impl From<GetCurrencyResponseItem> for GetCurrencyResponseItemV1 {
+        fn from(value: GetCurrencyResponseItem) -> Self {
+            GetCurrencyResponseItemV1 {
+                id: value.id,
+                name: value.name,
+                fallback_rate_amount: value.fallback_rate_amount,
+                fallback_rate_currency_id: value.fallback_rate_currency_id,
+                ticker: value.ticker,
+                is_base: value.is_base,
+                owner: value.owner,
+                rate_to_base: value.rate_to_base,
+            }
+        }
+    }
+
+    /// List the currencies of the user, or the one with the given id, with their rate to the base currency at `date`.
+    #[utoipa::path(
+        operation_id = "getCurrency",
+        tag = "currencies",
+        params(GetCurrencyQuery),
+        responses(
+            (status = 200, body = GetCurrencyResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[get("/currencies")]
     async fn handler(
         user: AuthUser,
         query: web::Query<GetCurrencyQuery>,
         data: web::Data<DatabaseStates>,
     ) -> Result<web::Json<GetCurrencyResponse>, EndpointsErrors> {
+        let items = get_currency_items(&user, &query, &data).await?;
+        Ok(web::Json(GetCurrencyResponse { items }))
+    }
+
+    /// Same as `getCurrency`, with snake_case fields. Superseded by `GET /api/v2/currencies`.
+    #[utoipa::path(
+        operation_id = "getCurrency",
+        tag = "currencies",
+        params(GetCurrencyQuery),
+        responses(
+            (status = 200, body = GetCurrencyResponseV1),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[get("/currencies")]
+    async fn legacy_handler(
+        user: AuthUser,
+        query: web::Query<GetCurrencyQuery>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<CustomizeResponder<web::Json<GetCurrencyResponseV1>>, EndpointsErrors> {
+        let items = get_currency_items(&user, &query, &data).await?;
+        let body = GetCurrencyResponseV1 {
+            items: items.into_iter().map(Into::into).collect(),
+        };
+        Ok(web::Json(body)
+            .customize()
+            .insert_header(("Deprecation", "true"))
+            .insert_header((
+                header::LINK,
+                format!("<{API_V2_PREFIX}/currencies>; rel=\"successor-version\""),
+            )))
+    }
+
+    async fn get_currency_items(
+        user: &AuthUser,
+        query: &GetCurrencyQuery,
+        data: &DatabaseStates,
+    ) -> Result<Vec<GetCurrencyResponseItem>, EndpointsErrors> {
         let db_txn_raw = data
             .db
             .begin()
@@ -133,10 +478,16 @@ pub mod get_currency {
 
         // Get all currencies if no params are given
         let (currencies_found, db_txn) = match parsed_uuid {
-            None => get_currencies(&user, db_txn).await,
+            None => {
+                let filter = CurrencyFilter {
+                    kind: query.kind,
+                    include_archived: query.include_archived.unwrap_or(false),
+                };
+                get_currencies(user, &filter, db_txn).await
+            }
             Some(parsed_uuid) => {
                 get_currency_by_id(
-                    &user,
+                    user,
                     &CurrencyId(*parsed_uuid),
                     db_txn,
                     data.currency_cache.clone(),
@@ -154,6 +505,7 @@ pub mod get_currency {
         };
 
         let currencies_len = currencies_found.len();
+        let explain = query.explain.unwrap_or(false);
 
         let mut db_txn = db_txn;
         let mut output: Vec<GetCurrencyResponseItem> = Vec::with_capacity(currencies_len);
@@ -164,6 +516,10 @@ pub mod get_currency {
                     name,
                     owner,
                     ticker,
+                    decimals,
+                    kind,
+                    symbol,
+                    archived,
                 } => {
                     output.push(GetCurrencyResponseItem {
                         fallback_rate_amount: None,
@@ -174,6 +530,21 @@ pub mod get_currency {
                         ticker: ticker.to_string(),
                         is_base: true,
                         rate_to_base: Decimal::ONE.to_string(),
+                        decimals: *decimals,
+                        kind: *kind,
+                        symbol: symbol.clone(),
+                        archived: *archived,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: false,
+                        explanation: explain.then(|| GetCurrencyRateExplanation {
+                            currency_id: id.0.to_string(),
+                            method: RateMethod::Base,
+                            rate_to_base: Decimal::ONE.to_string(),
+                            datums: vec![],
+                            fallback_rate_amount: None,
+                            sources: vec![],
+                        }),
                     });
                     db_txn
                 }
@@ -182,10 +553,17 @@ pub mod get_currency {
                     name,
                     owner,
                     ticker,
+                    decimals,
+                    kind,
+                    symbol,
+                    archived,
+                    rate_strategy,
+                    rate_max_gap_days,
+                    derive_rates,
                     fallback_rate_amount,
                     fallback_rate_currency_id,
                 } => {
-                    let (rate, db_txn) = calculate_currency_rate(
+                    let (explanation, db_txn) = explain_currency_rate(
                         owner,
                         *id,
                         db_txn,
@@ -203,7 +581,19 @@ pub mod get_currency {
                         owner: owner.0.to_string(),
                         ticker: ticker.to_string(),
                         is_base: false,
-                        rate_to_base: rate.round_dp(RESTFUL_DIGITS).normalize().to_string(),
+                        rate_to_base: explanation
+                            .rate
+                            .round_dp(RESTFUL_DIGITS)
+                            .normalize()
+                            .to_string(),
+                        decimals: *decimals,
+                        kind: *kind,
+                        symbol: symbol.clone(),
+                        archived: *archived,
+                        rate_strategy: Some(*rate_strategy),
+                        rate_max_gap_days: *rate_max_gap_days,
+                        derive_rates: *derive_rates,
+                        explanation: explain.then(|| explanation.into()),
                     });
 
                     db_txn
@@ -211,6 +601,167 @@ pub mod get_currency {
             }
         }
 
-        Ok(web::Json(GetCurrencyResponse { items: output }))
+        Ok(output)
+    }
+}
+
+pub mod convert_currency {
+
+    use crate::{
+        date::{iso8601_to_js_iso, js_iso_to_iso8601},
+        extended_models::currency::CurrencyId,
+        routes::bootstrap::{parse_decimal, parse_uuid, EndpointsErrors},
+        services::{currencies::convert_currency_amount, TransactionWithCallback},
+        RESTFUL_DIGITS,
+    };
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, IntoParams, ToSchema)]
+    #[into_params(parameter_in = Query)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct ConvertCurrencyQuery {
+        /// Id of the currency `amount` is in.
+        pub from: String,
+        /// Id of the currency to convert `amount` into.
+        pub to: String,
+        pub amount: String,
+        /// The date of the rates, now if not given.
+        #[serde(default)]
+        #[ts(optional)]
+        pub date: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct ConvertCurrencyResponse {
+        pub from: String,
+        pub to: String,
+        pub date: String,
+        pub amount: String,
+        /// `amount` in `to`, rounded to the decimal places of `to`.
+        pub converted_amount: String,
+        pub from_rate_to_base: String,
+        pub to_rate_to_base: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostConvertCurrencyRequest {
+        pub queries: Vec<ConvertCurrencyQuery>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostConvertCurrencyResponse {
+        /// One item per query, in the same order.
+        pub items: Vec<ConvertCurrencyResponse>,
+    }
+
+    /// Convert an amount between two currencies, with the rates of both to the base currency at `date`.
+    #[utoipa::path(
+        operation_id = "convertCurrency",
+        tag = "currencies",
+        params(ConvertCurrencyQuery),
+        responses(
+            (status = 200, body = ConvertCurrencyResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[get("/currencies/convert")]
+    async fn handler(
+        user: AuthUser,
+        query: web::Query<ConvertCurrencyQuery>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<ConvertCurrencyResponse>, EndpointsErrors> {
+        let mut items = convert_all(&user, std::slice::from_ref(&query), &data).await?;
+        Ok(web::Json(items.remove(0)))
+    }
+
+    /// Same as `convertCurrency` for many conversions at once. Fails as a whole if any query does.
+    #[utoipa::path(
+        operation_id = "convertCurrencyBatch",
+        tag = "currencies",
+        request_body = PostConvertCurrencyRequest,
+        responses(
+            (status = 200, body = PostConvertCurrencyResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[post("/currencies/convert")]
+    async fn batch_handler(
+        user: AuthUser,
+        info: web::Json<PostConvertCurrencyRequest>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<PostConvertCurrencyResponse>, EndpointsErrors> {
+        let items = convert_all(&user, &info.queries, &data).await?;
+        Ok(web::Json(PostConvertCurrencyResponse { items }))
+    }
+
+    /// Run every conversion in a single database transaction.
+    async fn convert_all(
+        user: &AuthUser,
+        queries: &[ConvertCurrencyQuery],
+        data: &DatabaseStates,
+    ) -> Result<Vec<ConvertCurrencyResponse>, EndpointsErrors> {
+        let mut db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let mut output = Vec::with_capacity(queries.len());
+        for query in queries {
+            let from = CurrencyId(parse_uuid(&query.from)?);
+            let to = CurrencyId(parse_uuid(&query.to)?);
+            let amount = parse_decimal(&query.amount)?;
+            let date = match &query.date {
+                Some(date_str) => js_iso_to_iso8601(date_str)?,
+                None => chrono::Utc::now(),
+            };
+
+            let conversion;
+            (conversion, db_txn) = convert_currency_amount(
+                user,
+                from,
+                to,
+                amount,
+                date,
+                db_txn,
+                data.currency_cache.clone(),
+            )
+            .await?;
+            output.push(ConvertCurrencyResponse {
+                from: from.0.to_string(),
+                to: to.0.to_string(),
+                date: iso8601_to_js_iso(date),
+                amount: amount.to_string(),
+                converted_amount: conversion.amount.to_string(),
+                from_rate_to_base: conversion
+                    .from_rate_to_base
+                    .round_dp(RESTFUL_DIGITS)
+                    .normalize()
+                    .to_string(),
+                to_rate_to_base: conversion
+                    .to_rate_to_base
+                    .round_dp(RESTFUL_DIGITS)
+                    .normalize()
+                    .to_string(),
+            });
+        }
+
+        db_txn.commit().await;
+        Ok(output)
     }
 }
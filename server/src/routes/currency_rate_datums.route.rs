@@ -1,22 +1,30 @@
 use crate::entities::currency_rate_datum;
 use crate::extended_models::currency::CurrencyId;
+use crate::routes::openapi::{
+    BadRequestResponse, InternalServerErrorResponse, NotFoundResponse, UnauthorizedResponse,
+};
 use crate::services::{currency_rate_datum::create_currency_rate_datum, TransactionWithCallback};
 use crate::{extractors::auth_user::AuthUser, states::database_states::DatabaseStates};
 use actix_web::{post, web};
+use rust_decimal::Decimal;
 use sea_orm::prelude::DateTime;
 use sea_orm::ActiveValue;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use ts_rs::TS;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 pub mod post_currency_rate_datum {
 
-    use crate::{date::js_iso_to_iso8601, routes::bootstrap::EndpointsErrors};
+    use crate::{
+        date::js_iso_to_iso8601,
+        routes::bootstrap::{parse_decimal, EndpointsErrors},
+    };
 
     use super::*;
 
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -27,7 +35,7 @@ pub mod post_currency_rate_datum {
         pub date_utc: String,
     }
 
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -35,6 +43,20 @@ pub mod post_currency_rate_datum {
         pub id: String,
     }
 
+    /// Record the rate of a currency at a point in time.
+    #[utoipa::path(
+        operation_id = "postCurrencyRateDatum",
+        tag = "currencyRateDatums",
+        request_body = PostCurrencyRateDatumRequest,
+        responses(
+            (status = 200, body = PostCurrencyRateDatumResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[post("/currency_rate_datums")]
     async fn handler(
         user: AuthUser,
@@ -62,16 +84,18 @@ pub mod post_currency_rate_datum {
 
         // Convert request to create domain enum
         let domain_to_be_saved = CreateCurrencyRateDatumAction {
-            amount: info.amount.clone(),
+            amount: parse_decimal(&info.amount)?,
             date: date.naive_utc(),
             owner: user.clone(),
             ref_currency_id: CurrencyId(uuids.0),
             ref_amount_currency_id: CurrencyId(uuids.1),
+            source_fragment_id: None,
         };
 
         let (row_id, db_txn) = create_currency_rate_datum(
             &user,
             domain_to_be_saved,
+            None,
             TransactionWithCallback::from_db_conn(&data.db, vec![]).await?,
             data.currency_rate_datums_cache.clone(),
             data.currency_cache.clone(),
@@ -89,31 +113,35 @@ pub mod post_currency_rate_datum {
 #[derive(Clone, Debug)]
 pub struct CurrencyRateDatum {
     pub id: Uuid,
-    pub amount: String,
+    pub amount: Decimal,
     pub ref_currency_id: CurrencyId,
     pub ref_amount_currency_id: CurrencyId,
     pub owner: AuthUser,
     pub date: DateTime,
+    /// The transaction fragment a derived datum was computed from.
+    pub source_fragment_id: Option<Uuid>,
 }
 
 #[derive(Clone, Debug)]
 pub struct CreateCurrencyRateDatumAction {
-    pub amount: String,
+    pub amount: Decimal,
     pub ref_currency_id: CurrencyId,
     pub ref_amount_currency_id: CurrencyId,
     pub owner: AuthUser,
     pub date: DateTime,
+    pub source_fragment_id: Option<Uuid>,
 }
 
 impl CreateCurrencyRateDatumAction {
     pub fn into_domain(self, db_id: uuid::Uuid) -> CurrencyRateDatum {
         CurrencyRateDatum {
             id: db_id,
-            amount: self.amount.to_string(),
+            amount: self.amount,
             owner: self.owner,
             ref_amount_currency_id: self.ref_amount_currency_id,
             ref_currency_id: self.ref_currency_id,
             date: self.date,
+            source_fragment_id: self.source_fragment_id,
         }
     }
 }
@@ -127,6 +155,7 @@ impl From<CreateCurrencyRateDatumAction> for currency_rate_datum::ActiveModel {
             ref_currency_id: ActiveValue::Set(value.ref_currency_id.0),
             ref_amount_currency_id: ActiveValue::Set(value.ref_amount_currency_id.0),
             date: ActiveValue::Set(value.date),
+            source_fragment_id: ActiveValue::Set(value.source_fragment_id),
         }
     }
 }
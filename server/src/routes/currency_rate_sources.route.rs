@@ -0,0 +1,336 @@
+use crate::date::iso8601_to_js_iso;
+use crate::extended_models::currency::CurrencyId;
+use crate::extended_models::currency_rate_source::{CurrencyRateSource, RateSourceKind};
+use crate::routes::bootstrap::{parse_uuid, EndpointsErrors};
+use crate::routes::openapi::{
+    BadRequestResponse, InternalServerErrorResponse, NotFoundResponse, UnauthorizedResponse,
+};
+use crate::services::TransactionWithCallback;
+use crate::{extractors::auth_user::AuthUser, states::database_states::DatabaseStates};
+use actix_web::{delete, get, patch, post, web};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::{IntoParams, ToSchema};
+
+/// A source the rate of `refCurrencyId` is fetched from periodically,
+/// saved as datums expressed in `refAmountCurrencyId`.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct CurrencyRateSourceItem {
+    pub id: String,
+    pub name: String,
+    pub kind: RateSourceKind,
+    pub ref_currency_id: String,
+    pub ref_amount_currency_id: String,
+    /// Only given for `http` sources.
+    pub hostname: Option<String>,
+    pub path: String,
+    pub json_query_string: String,
+    /// When a rate was last fetched and saved successfully.
+    pub last_fetch_date: Option<String>,
+    /// Why the last fetch failed, cleared by the next successful one.
+    pub last_error: Option<String>,
+}
+
+impl From<CurrencyRateSource> for CurrencyRateSourceItem {
+    fn from(value: CurrencyRateSource) -> Self {
+        CurrencyRateSourceItem {
+            id: value.id.to_string(),
+            name: value.name,
+            kind: value.kind,
+            ref_currency_id: value.ref_currency_id.0.to_string(),
+            ref_amount_currency_id: value.ref_amount_currency_id.0.to_string(),
+            hostname: value.hostname,
+            path: value.path,
+            json_query_string: value.json_query_string,
+            last_fetch_date: value
+                .last_fetch_date
+                .map(|x| iso8601_to_js_iso(x.and_utc())),
+            last_error: value.last_error,
+        }
+    }
+}
+
+pub mod post_currency_rate_source {
+    use super::*;
+    use crate::services::currency_rate_sources::{
+        create_currency_rate_source, CreateCurrencyRateSourceAction,
+    };
+
+    #[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostCurrencyRateSourceRequest {
+        pub name: String,
+        /// Defaults to `http`.
+        #[serde(default)]
+        #[ts(optional)]
+        pub kind: Option<RateSourceKind>,
+        pub ref_currency_id: String,
+        pub ref_amount_currency_id: String,
+        /// Required by `http` sources, `https://` is assumed if it has no scheme.
+        #[serde(default)]
+        #[ts(optional)]
+        pub hostname: Option<String>,
+        /// The path of the URL, or of the file relative to `rateSources.fileDirectory`.
+        pub path: String,
+        /// Where the rate is in the JSON document, such as `$.rates.USD` or `$.data[0]["price"]`.
+        pub json_query_string: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostCurrencyRateSourceResponse {
+        pub id: String,
+    }
+
+    /// Add a source the rate of a currency is fetched from.
+    #[utoipa::path(
+        operation_id = "postCurrencyRateSource",
+        tag = "currencyRateSources",
+        request_body = PostCurrencyRateSourceRequest,
+        responses(
+            (status = 200, body = PostCurrencyRateSourceResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[post("/currencyRateSources")]
+    async fn handler(
+        user: AuthUser,
+        info: web::Json<PostCurrencyRateSourceRequest>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<PostCurrencyRateSourceResponse>, EndpointsErrors> {
+        let info = info.into_inner();
+        let action = CreateCurrencyRateSourceAction {
+            ref_currency_id: CurrencyId(parse_uuid(&info.ref_currency_id)?),
+            ref_amount_currency_id: CurrencyId(parse_uuid(&info.ref_amount_currency_id)?),
+            name: info.name,
+            kind: info.kind.unwrap_or_default(),
+            hostname: info.hostname,
+            path: info.path,
+            json_query_string: info.json_query_string,
+        };
+
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (source, db_txn) =
+            create_currency_rate_source(&user, action, None, db_txn, data.currency_cache.clone())
+                .await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(PostCurrencyRateSourceResponse {
+            id: source.id.to_string(),
+        }))
+    }
+}
+
+pub mod get_currency_rate_sources {
+    use super::*;
+    use crate::services::currency_rate_sources::{
+        get_currency_rate_source_by_id, get_currency_rate_sources,
+    };
+
+    #[derive(Serialize, Deserialize, Debug, Default, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetCurrencyRateSourcesQuery {
+        /// Only list the sources of this currency.
+        pub currency_id: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetCurrencyRateSourcesResponse {
+        pub items: Vec<CurrencyRateSourceItem>,
+    }
+
+    /// List the currency rate sources of the user, ordered by name.
+    #[utoipa::path(
+        operation_id = "getCurrencyRateSources",
+        tag = "currencyRateSources",
+        params(GetCurrencyRateSourcesQuery),
+        responses(
+            (status = 200, body = GetCurrencyRateSourcesResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[get("/currencyRateSources")]
+    async fn handler(
+        user: AuthUser,
+        query: web::Query<GetCurrencyRateSourcesQuery>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<GetCurrencyRateSourcesResponse>, EndpointsErrors> {
+        let currency_id = match &query.currency_id {
+            None => None,
+            Some(id) => Some(CurrencyId(parse_uuid(id)?)),
+        };
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (sources, _db_txn) =
+            get_currency_rate_sources(&user, currency_id.as_ref(), db_txn).await?;
+        Ok(web::Json(GetCurrencyRateSourcesResponse {
+            items: sources.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    /// Get a single currency rate source.
+    #[utoipa::path(
+        operation_id = "getCurrencyRateSource",
+        tag = "currencyRateSources",
+        params(("id" = String, Path, description = "Id of the currency rate source.")),
+        responses(
+            (status = 200, body = CurrencyRateSourceItem),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[get("/currencyRateSources/{id}")]
+    async fn single_handler(
+        user: AuthUser,
+        id: web::Path<String>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<CurrencyRateSourceItem>, EndpointsErrors> {
+        let id = parse_uuid(&id)?;
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (source, _db_txn) = get_currency_rate_source_by_id(&user, &id, db_txn).await?;
+        Ok(web::Json(source.into()))
+    }
+}
+
+pub mod patch_currency_rate_source {
+    use super::*;
+    use crate::services::currency_rate_sources::{
+        update_currency_rate_source, UpdateCurrencyRateSourceAction,
+    };
+
+    #[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PatchCurrencyRateSourceRequest {
+        #[serde(default)]
+        #[ts(optional)]
+        pub name: Option<String>,
+        #[serde(default)]
+        #[ts(optional)]
+        pub kind: Option<RateSourceKind>,
+        #[serde(default)]
+        #[ts(optional)]
+        pub ref_amount_currency_id: Option<String>,
+        #[serde(default)]
+        #[ts(optional)]
+        pub hostname: Option<String>,
+        #[serde(default)]
+        #[ts(optional)]
+        pub path: Option<String>,
+        #[serde(default)]
+        #[ts(optional)]
+        pub json_query_string: Option<String>,
+    }
+
+    /// Change a currency rate source. Omitted fields are left as they are,
+    /// the currency whose rate it gives cannot be changed.
+    #[utoipa::path(
+        operation_id = "patchCurrencyRateSource",
+        tag = "currencyRateSources",
+        params(("id" = String, Path, description = "Id of the currency rate source.")),
+        request_body = PatchCurrencyRateSourceRequest,
+        responses(
+            (status = 200, body = CurrencyRateSourceItem),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[patch("/currencyRateSources/{id}")]
+    async fn handler(
+        user: AuthUser,
+        id: web::Path<String>,
+        info: web::Json<PatchCurrencyRateSourceRequest>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<CurrencyRateSourceItem>, EndpointsErrors> {
+        let id = parse_uuid(&id)?;
+        let info = info.into_inner();
+        let action = UpdateCurrencyRateSourceAction {
+            ref_amount_currency_id: match &info.ref_amount_currency_id {
+                None => None,
+                Some(currency_id) => Some(CurrencyId(parse_uuid(currency_id)?)),
+            },
+            name: info.name,
+            kind: info.kind,
+            hostname: info.hostname,
+            path: info.path,
+            json_query_string: info.json_query_string,
+        };
+
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (source, db_txn) =
+            update_currency_rate_source(&user, &id, action, db_txn, data.currency_cache.clone())
+                .await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(source.into()))
+    }
+}
+
+pub mod delete_currency_rate_source {
+    use super::*;
+    use crate::services::currency_rate_sources::delete_currency_rate_source;
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct DeleteCurrencyRateSourceResponse {
+        pub id: String,
+    }
+
+    /// Delete a currency rate source, the datums it saved are kept.
+    #[utoipa::path(
+        operation_id = "deleteCurrencyRateSource",
+        tag = "currencyRateSources",
+        params(("id" = String, Path, description = "Id of the currency rate source.")),
+        responses(
+            (status = 200, body = DeleteCurrencyRateSourceResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[delete("/currencyRateSources/{id}")]
+    async fn handler(
+        user: AuthUser,
+        id: web::Path<String>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<DeleteCurrencyRateSourceResponse>, EndpointsErrors> {
+        let id = parse_uuid(&id)?;
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let db_txn = delete_currency_rate_source(&user, &id, db_txn).await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(DeleteCurrencyRateSourceResponse {
+            id: id.to_string(),
+        }))
+    }
+}
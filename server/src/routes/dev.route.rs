@@ -5,6 +5,7 @@ pub mod dev_test {
 
     use crate::extended_models::currency::CurrencyId;
     use crate::extractors::auth_user::AuthUser;
+    use crate::routes::bootstrap::EndpointsErrors;
     use crate::services::currencies::{
         calculate_currency_rate, create_currency, get_currency_by_id,
     };
@@ -22,14 +23,8 @@ pub mod dev_test {
     use uuid::Uuid;
 
     #[get("/dev-test")]
-    async fn handler(data: web::Data<DatabaseStates>) -> HttpResponse {
-        let db_txn = match TransactionWithCallback::from_db_conn(&data.db, vec![]).await {
-            Err(_db_err) => {
-                return HttpResponse::InternalServerError()
-                    .body("Unable to start database transaction.")
-            }
-            Ok(db_txn) => db_txn,
-        };
+    async fn handler(data: web::Data<DatabaseStates>) -> Result<HttpResponse, EndpointsErrors> {
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
 
         // let db_txn = sea_orm::TransactionTrait::begin(&data.db).await.unwrap();
 
@@ -73,11 +68,19 @@ pub mod dev_test {
                     name: time.clone(),
                     owner: user,
                     ticker: time,
-                    fallback_rate_amount: "1".to_string(),
+                    decimals: crate::extended_models::currency::DEFAULT_CURRENCY_DECIMALS,
+                    kind: Default::default(),
+                    symbol: None,
+                    archived: false,
+                    rate_strategy: Default::default(),
+                    rate_max_gap_days: None,
+                    derive_rates: false,
+                    fallback_rate_amount: rust_decimal::Decimal::ONE,
                     fallback_rate_currency_id: CurrencyId(
                         Uuid::from_str("887900f0-a8f0-43d7-8c8c-258cd2111055").unwrap(),
                     ),
                 },
+                None,
                 db_txn,
                 data.currency_cache.clone(),
             )
@@ -87,6 +90,6 @@ pub mod dev_test {
         };
 
         // db_txn.commit().await;
-        HttpResponse::Ok().body("123")
+        Ok(HttpResponse::Ok().body("123"))
     }
 }
@@ -0,0 +1,90 @@
+use actix_files::{Files, NamedFile};
+use actix_http::{header, Method, StatusCode};
+use actix_web::dev::{fn_service, Service, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{CacheControl, CacheDirective};
+use actix_web::{web, HttpResponse};
+use futures::FutureExt;
+use std::path::{Path, PathBuf};
+
+/// The frontend build emits content-hashed file names under this folder,
+/// so anything served from here never changes and can be cached forever.
+const HASHED_ASSETS_PREFIX: &str = "/assets/";
+
+fn cache_control_for(path: &str, res: &ServiceResponse) -> Option<CacheControl> {
+    if !res.status().is_success() {
+        return None;
+    }
+    if path.starts_with(HASHED_ASSETS_PREFIX) {
+        return Some(CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(31_536_000),
+            CacheDirective::Extension("immutable".to_string(), None),
+        ]));
+    }
+    let is_html = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|x| x.to_str().ok())
+        .is_some_and(|x| x.starts_with("text/html"));
+    match is_html {
+        // index.html references the hashed assets, so it must be revalidated on every load.
+        true => Some(CacheControl(vec![CacheDirective::NoCache])),
+        false => Some(CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(3600),
+        ])),
+    }
+}
+
+/// Serve `index.html` for client-side routes such as `/transactions/123`.
+/// Paths that look like files (having an extension) still return 404 so missing assets are not masked.
+async fn spa_fallback(
+    req: ServiceRequest,
+    index_path: PathBuf,
+) -> Result<ServiceResponse, actix_web::Error> {
+    let (req, _payload) = req.into_parts();
+    let looks_like_file = req
+        .path()
+        .rsplit('/')
+        .next()
+        .is_some_and(|segment| segment.contains('.'));
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD);
+
+    if looks_like_file || !is_read {
+        let res = HttpResponse::new(StatusCode::NOT_FOUND);
+        return Ok(ServiceResponse::new(req, res));
+    }
+
+    let res = NamedFile::open(index_path)?.into_response(&req);
+    Ok(ServiceResponse::new(req, res))
+}
+
+/// Register the built frontend at `dist_folder_path` as a catch-all service.
+/// This must be configured after every API scope, since it matches any path.
+pub fn configure_dist_files(cfg: &mut web::ServiceConfig, dist_folder_path: &str) {
+    let index_path = Path::new(dist_folder_path).join("index.html");
+    let files = Files::new("/", dist_folder_path)
+        .index_file("index.html")
+        .use_etag(true)
+        .use_last_modified(true)
+        .default_handler(fn_service(move |req| spa_fallback(req, index_path.clone())));
+
+    cfg.service(
+        web::scope("")
+            .wrap_fn(|req, srv| {
+                let path = req.path().to_string();
+                srv.call(req).map(move |res| {
+                    res.map(|mut res| {
+                        if let Some(cache_control) = cache_control_for(&path, &res) {
+                            res.headers_mut().insert(
+                                header::CACHE_CONTROL,
+                                cache_control.to_string().parse().unwrap(),
+                            );
+                        }
+                        res
+                    })
+                })
+            })
+            .service(files),
+    );
+}
@@ -1,3 +1,38 @@
+/// Pass every handler shared by all API versions, relative to this module, to the `$apply` macro.
+/// Both `bootstrap::api_scope` and the OpenAPI documents are built from this list.
+macro_rules! with_api_handlers {
+    ($apply:ident) => {
+        $apply! {
+            users::login::handler,
+            users::register::handler,
+            users::export_user::handler,
+            users::delete_user::handler,
+            users::import_user::handler,
+            accounts::get_account::handler,
+            accounts::post_account::handler,
+            currencies::post_currency::handler,
+            currencies::convert_currency::handler,
+            currencies::convert_currency::batch_handler,
+            currencies::patch_currency::handler,
+            currency_rate_datums::post_currency_rate_datum::handler,
+            currency_rate_sources::post_currency_rate_source::handler,
+            currency_rate_sources::get_currency_rate_sources::handler,
+            currency_rate_sources::get_currency_rate_sources::single_handler,
+            currency_rate_sources::patch_currency_rate_source::handler,
+            currency_rate_sources::delete_currency_rate_source::handler,
+            wallets::post_wallet::handler,
+            wallets::get_wallets::handler,
+            wallets::delete_wallet::handler,
+            txn_tags::get_tags::handler,
+            txn_tags::create_tag::handler,
+            txns::post_txns::handler,
+            txns::get_txns::handler,
+            txns::put_txn::handler,
+            txns::delete_txn::handler,
+        }
+    };
+}
+
 #[path = "./users.route.rs"]
 pub mod users;
 
@@ -16,8 +51,23 @@ pub mod currencies;
 #[path = "./currency_rate_datums.route.rs"]
 pub mod currency_rate_datums;
 
+#[path = "./currency_rate_sources.route.rs"]
+pub mod currency_rate_sources;
+
+#[path = "./wallets.route.rs"]
+pub mod wallets;
+
 #[path = "./txn_tags.route.rs"]
 pub mod txn_tags;
 
 #[path = "./txns.route.rs"]
 pub mod txns;
+
+#[path = "./dist.route.rs"]
+pub mod dist;
+
+#[path = "./probes.route.rs"]
+pub mod probes;
+
+#[path = "./openapi.route.rs"]
+pub mod openapi;
@@ -0,0 +1,142 @@
+use crate::routes::bootstrap::{ErrorCode, ErrorResponseBody};
+use crate::routes::{
+    accounts, currencies, currency_rate_datums, currency_rate_sources, probes, txn_tags, txns,
+    users, wallets,
+};
+use actix_web::{get, web};
+use std::sync::LazyLock;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::openapi::Deprecated;
+use utoipa::{Modify, OpenApi, ToResponse};
+
+/// Name of the security scheme of every route requiring an access token.
+pub const ACCESS_TOKEN_SCHEME: &str = "accessToken";
+
+// The error responses below only describe `EndpointsErrors` in the document, they are never built.
+
+#[allow(dead_code)]
+#[derive(ToResponse)]
+#[response(description = "The request is malformed or violates a constraint, see `code`.")]
+pub struct BadRequestResponse(#[to_schema] ErrorResponseBody);
+
+#[allow(dead_code)]
+#[derive(ToResponse)]
+#[response(description = "The access token is missing, invalid or expired.")]
+pub struct UnauthorizedResponse(#[to_schema] ErrorResponseBody);
+
+#[allow(dead_code)]
+#[derive(ToResponse)]
+#[response(description = "An entity referenced by the request does not exist.")]
+pub struct NotFoundResponse(#[to_schema] ErrorResponseBody);
+
+#[allow(dead_code)]
+#[derive(ToResponse)]
+#[response(description = "The server failed to handle the request.")]
+pub struct InternalServerErrorResponse(#[to_schema] ErrorResponseBody);
+
+struct AccessTokenSecurity;
+
+impl Modify for AccessTokenSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            ACCESS_TOKEN_SCHEME,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "authorization",
+                "Token returned by `POST /api/v2/login`.",
+            ))),
+        );
+    }
+}
+
+macro_rules! api_docs {
+    ($($($segment:ident)::+),* $(,)?) => {
+        /// Every route mounted under `API_V2_PREFIX` by `apply_endpoints`.
+        /// The development-only `/dev-test` route is not part of the contract.
+        #[derive(OpenApi)]
+        #[openapi(paths($($($segment)::+,)* currencies::get_currency::handler))]
+        struct ApiV2Doc;
+
+        /// Every route mounted under `API_PREFIX`, the same as `ApiV2Doc` but for the legacy `GET /currencies`.
+        #[derive(OpenApi)]
+        #[openapi(
+            paths($($($segment)::+,)* currencies::get_currency::legacy_handler),
+            modifiers(&DEPRECATED_V1)
+        )]
+        struct ApiV1Doc;
+    };
+}
+
+with_api_handlers!(api_docs);
+
+/// Mark every operation of a superseded API version as deprecated, suffixing its operation id
+/// with the version so it does not clash with the current one.
+struct DeprecatedVersion(&'static str);
+
+const DEPRECATED_V1: DeprecatedVersion = DeprecatedVersion("V1");
+
+impl Modify for DeprecatedVersion {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.patch,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation.deprecated = Some(Deprecated::True);
+                if let Some(operation_id) = &mut operation.operation_id {
+                    operation_id.push_str(self.0);
+                }
+            }
+        }
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Finance Manager API"),
+    paths(
+        probes::health::handler,
+        probes::ready::handler,
+        probes::version::handler,
+        spec::handler,
+    ),
+    nest(
+        (path = "/api/v1", api = ApiV1Doc),
+        (path = "/api/v2", api = ApiV2Doc),
+    ),
+    components(
+        schemas(ErrorResponseBody, ErrorCode),
+        responses(
+            BadRequestResponse,
+            UnauthorizedResponse,
+            NotFoundResponse,
+            InternalServerErrorResponse
+        )
+    ),
+    modifiers(&AccessTokenSecurity)
+)]
+pub struct ApiDoc;
+
+/// The OpenAPI document, built once.
+pub static OPENAPI: LazyLock<utoipa::openapi::OpenApi> = LazyLock::new(ApiDoc::openapi);
+
+pub mod spec {
+    use super::*;
+
+    /// This document.
+    #[utoipa::path(
+        operation_id = "getOpenapi",
+        tag = "probes",
+        responses(
+            (status = 200, description = "OpenAPI 3 document of the API.", body = serde_json::Value),
+        ),
+    )]
+    #[get("/openapi.json")]
+    async fn handler() -> web::Json<&'static utoipa::openapi::OpenApi> {
+        web::Json(&OPENAPI)
+    }
+}
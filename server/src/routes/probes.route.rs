@@ -0,0 +1,122 @@
+use crate::routes::openapi::InternalServerErrorResponse;
+use crate::DatabaseStates;
+use actix_web::{get, web, HttpResponse};
+use finance_manager_migration::{Migrator, MigratorTrait};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+pub mod health {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetHealthResponseBody {
+        pub status: String,
+    }
+
+    /// Liveness probe, succeeds as long as the process is able to serve requests.
+    #[utoipa::path(
+        operation_id = "health",
+        tag = "probes",
+        responses(
+            (status = 200, body = GetHealthResponseBody),
+        ),
+    )]
+    #[get("/health")]
+    async fn handler() -> web::Json<GetHealthResponseBody> {
+        web::Json(GetHealthResponseBody {
+            status: "ok".to_string(),
+        })
+    }
+}
+
+pub mod ready {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetReadyResponseBody {
+        pub ready: bool,
+        pub database_reachable: bool,
+        pub pending_migrations: Vec<String>,
+    }
+
+    /// Readiness probe, responds `503` unless the database is reachable and fully migrated.
+    /// Migrations are recorded one by one, so this also fails while they are being applied.
+    #[utoipa::path(
+        operation_id = "ready",
+        tag = "probes",
+        responses(
+            (status = 200, body = GetReadyResponseBody),
+            (status = 503, description = "The database is unreachable or not fully migrated.", body = GetReadyResponseBody),
+        ),
+    )]
+    #[get("/ready")]
+    async fn handler(data: web::Data<DatabaseStates>) -> HttpResponse {
+        let database_reachable = data.db.ping().await.is_ok();
+        let pending_migrations = match database_reachable {
+            false => None,
+            true => Migrator::get_pending_migrations(&data.db)
+                .await
+                .ok()
+                .map(|x| x.iter().map(|x| x.name().to_string()).collect::<Vec<_>>()),
+        };
+        let ready = pending_migrations.as_ref().is_some_and(|x| x.is_empty());
+        let body = GetReadyResponseBody {
+            ready,
+            database_reachable,
+            pending_migrations: pending_migrations.unwrap_or_default(),
+        };
+
+        match ready {
+            true => HttpResponse::Ok().json(body),
+            false => HttpResponse::ServiceUnavailable().json(body),
+        }
+    }
+}
+
+pub mod version {
+    use super::*;
+    use crate::routes::bootstrap::EndpointsErrors;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetVersionResponseBody {
+        pub version: String,
+        pub git_hash: String,
+        pub applied_migrations: Vec<String>,
+    }
+
+    /// Version of the server and the migrations applied to its database.
+    #[utoipa::path(
+        operation_id = "version",
+        tag = "probes",
+        responses(
+            (status = 200, body = GetVersionResponseBody),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+    )]
+    #[get("/version")]
+    async fn handler(
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<GetVersionResponseBody>, EndpointsErrors> {
+        let applied_migrations = Migrator::get_applied_migrations(&data.db)
+            .await?
+            .iter()
+            .map(|x| x.name().to_string())
+            .collect();
+
+        Ok(web::Json(GetVersionResponseBody {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("FM_GIT_HASH").to_string(),
+            applied_migrations,
+        }))
+    }
+}
@@ -1,3 +1,5 @@
+use crate::routes::bootstrap::EndpointsErrors;
+use crate::routes::openapi::{InternalServerErrorResponse, UnauthorizedResponse};
 use crate::DatabaseStates;
 use crate::{
     extractors::auth_user::AuthUser,
@@ -6,13 +8,14 @@ use crate::{
 use ::serde::{Deserialize, Serialize};
 use actix_web::get;
 use actix_web::post;
-use actix_web::{http::header::ContentType, web, HttpResponse};
+use actix_web::web;
 use ts_rs::TS;
+use utoipa::ToSchema;
 
 pub mod create_tag {
     use super::*;
 
-    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -20,7 +23,7 @@ pub mod create_tag {
         pub name: String,
     }
 
-    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -28,44 +31,39 @@ pub mod create_tag {
         pub id: String,
     }
 
+    /// Create a transaction tag.
+    #[utoipa::path(
+        operation_id = "createTag",
+        tag = "txnTags",
+        request_body = PostTxnTagRequestBody,
+        responses(
+            (status = 200, body = PostTxnTagResponseBody),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[post("/txnTags")]
     async fn handler(
         user: AuthUser,
         info: web::Json<PostTxnTagRequestBody>,
         data: web::Data<DatabaseStates>,
-    ) -> HttpResponse {
-        let db_txn = match TransactionWithCallback::from_db_conn(&data.db, vec![]).await {
-            Err(_db_err) => {
-                return HttpResponse::InternalServerError()
-                    .body("Unable to start database transaction.")
-            }
-            Ok(db_txn) => db_txn,
-        };
-
-        let tags_cache = &mut data.txn_tags_cache.lock().await;
+    ) -> Result<web::Json<PostTxnTagResponseBody>, EndpointsErrors> {
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (new_id, db_txn) =
+            create_txn_tag(&user, &info.name, None, db_txn, data.txn_tags_cache.clone()).await?;
 
-        match create_txn_tag(&user, &info.name, db_txn, tags_cache).await {
-            Err(db_err) => HttpResponse::InternalServerError()
-                .content_type(ContentType::json())
-                .body(format!("Error querying database: {db_err}")),
-            Ok((new_id, db_txn)) => {
-                let response = PostTxnTagResponseBody { id: new_id.into() };
-                db_txn.commit().await;
-                HttpResponse::Ok().content_type(ContentType::json()).body(
-                    serde_json::to_string(&response)
-                        .expect("Unable to serde json to string in posting txn tags."),
-                )
-            }
-        }
+        db_txn.commit().await;
+        Ok(web::Json(PostTxnTagResponseBody { id: new_id.into() }))
     }
 }
 
 pub mod get_tags {
-    use crate::{routes::bootstrap::EndpointsErrors, services::txn_tags::get_txn_tags};
+    use crate::services::txn_tags::get_txn_tags;
 
     use super::*;
 
-    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -74,7 +72,7 @@ pub mod get_tags {
         pub id: String,
     }
 
-    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -82,6 +80,17 @@ pub mod get_tags {
         pub tags: Vec<GetTxnTagsResponseBodyItem>,
     }
 
+    /// List the transaction tags of the user.
+    #[utoipa::path(
+        operation_id = "getTags",
+        tag = "txnTags",
+        responses(
+            (status = 200, body = GetTxnTagsResponseBody),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[get("/txnTags")]
     async fn handler(
         user: AuthUser,
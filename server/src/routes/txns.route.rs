@@ -1,30 +1,42 @@
 use crate::date::iso8601_to_js_iso;
 use crate::extractors::auth_user::AuthUser;
 use crate::routes::bootstrap::EndpointsErrors;
+use crate::routes::openapi::{
+    BadRequestResponse, InternalServerErrorResponse, NotFoundResponse, UnauthorizedResponse,
+};
 use crate::services::txns::create_txn;
+use crate::services::txns::delete_txn;
 use crate::services::txns::get_txns;
+use crate::services::txns::update_txn;
 use crate::services::txns::CreateTxnAction;
 use crate::services::txns::CreateTxnActionFragment;
 use crate::services::txns::CreateTxnActionFragmentSide;
 use crate::services::TransactionWithCallback;
 use crate::states::database_states::DatabaseStates;
+use actix_web::delete;
 use actix_web::get;
 use actix_web::post;
+use actix_web::put;
 use actix_web::web;
-use rust_decimal::Decimal;
 use sea_orm::TransactionTrait;
 use serde::Deserialize;
 use serde::Serialize;
 use std::str::FromStr;
 use ts_rs::TS;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 /// Get all transactions as a user.
 pub mod get_txns {
 
+    use crate::maths::round_amount;
+    use crate::services::currencies::{get_currencies, CurrencyFilter};
+    use rust_decimal::Decimal;
+    use std::collections::HashMap;
+
     use super::*;
 
-    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -34,7 +46,7 @@ pub mod get_txns {
         pub currency: Uuid,
     }
 
-    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -43,7 +55,7 @@ pub mod get_txns {
         pub to: Option<GetTxnsResponseFragmentSide>,
     }
 
-    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -55,7 +67,7 @@ pub mod get_txns {
         pub fragments: Vec<GetTxnsResponseFragment>,
     }
 
-    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -63,6 +75,17 @@ pub mod get_txns {
         pub items: Vec<GetTxnsResponseItem>,
     }
 
+    /// List the transactions of the user with their fragments.
+    #[utoipa::path(
+        operation_id = "getTxns",
+        tag = "txns",
+        responses(
+            (status = 200, body = GetTxnsResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
     #[get("/txns")]
     async fn handler(
         user: AuthUser,
@@ -70,8 +93,20 @@ pub mod get_txns {
     ) -> Result<web::Json<GetTxnsResponse>, EndpointsErrors> {
         let db_txn = TransactionWithCallback::new(data.db.begin().await?, vec![]);
         let (txns, db_txn) = get_txns(&user, db_txn).await?;
+        let (currencies, db_txn) = get_currencies(&user, &CurrencyFilter::all(), db_txn).await?;
 
         db_txn.commit().await;
+
+        // Amounts are reported with at most the decimal places of their currency
+        let decimals = currencies
+            .iter()
+            .map(|currency| (currency.id().0, currency.decimals()))
+            .collect::<HashMap<_, _>>();
+        let report_amount = |amount: Decimal, currency: Uuid| match decimals.get(&currency) {
+            Some(decimals) => round_amount(&amount, *decimals).to_string(),
+            None => amount.to_string(),
+        };
+
         Ok(web::Json(GetTxnsResponse {
             items: txns
                 .iter()
@@ -86,12 +121,18 @@ pub mod get_txns {
                             from: fragment.from_account.map(|_| GetTxnsResponseFragmentSide {
                                 account: fragment.from_account.unwrap(),
                                 currency: fragment.from_currency_id.unwrap(),
-                                amount: fragment.from_amount.clone().unwrap().to_string(),
+                                amount: report_amount(
+                                    fragment.from_amount.unwrap(),
+                                    fragment.from_currency_id.unwrap(),
+                                ),
                             }),
                             to: fragment.to_account.map(|_| GetTxnsResponseFragmentSide {
                                 account: fragment.to_account.unwrap(),
                                 currency: fragment.to_currency_id.unwrap(),
-                                amount: fragment.to_amount.clone().unwrap().to_string(),
+                                amount: report_amount(
+                                    fragment.to_amount.unwrap(),
+                                    fragment.to_currency_id.unwrap(),
+                                ),
                             }),
                         })
                         .collect::<Vec<_>>(),
@@ -104,10 +145,11 @@ pub mod get_txns {
 pub mod post_txns {
 
     use crate::date::js_iso_to_iso8601;
+    use crate::routes::bootstrap::parse_decimal;
 
     use super::*;
 
-    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -118,7 +160,7 @@ pub mod post_txns {
         pub fragments: Vec<PostTxnRequestFragment>,
     }
 
-    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -128,7 +170,7 @@ pub mod post_txns {
         pub currency: String,
     }
 
-    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -137,7 +179,7 @@ pub mod post_txns {
         pub to: Option<PostTxnRequestFragmentSide>,
     }
 
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -145,16 +187,13 @@ pub mod post_txns {
         pub id: String,
     }
 
-    #[post("/txns")]
-    async fn handler(
-        user: AuthUser,
-        info: web::Json<PostTxnRequest>,
-        data: web::Data<DatabaseStates>,
-    ) -> Result<web::Json<PostTxnResponse>, EndpointsErrors> {
-        let db_txn = TransactionWithCallback::new(data.db.begin().await?, vec![]);
-        let mut fragments: Vec<CreateTxnActionFragment> = Vec::with_capacity(info.fragments.len());
-        let map_to_err = |_| EndpointsErrors::OverflowOrUnderflow;
-        for frag in info.fragments.iter() {
+    /// Parse the fragments of a request into the fragments to save.
+    pub fn to_create_fragments(
+        request_fragments: &[PostTxnRequestFragment],
+    ) -> Result<Vec<CreateTxnActionFragment>, EndpointsErrors> {
+        let mut fragments: Vec<CreateTxnActionFragment> =
+            Vec::with_capacity(request_fragments.len());
+        for frag in request_fragments.iter() {
             let map_side_checked = |side: Option<PostTxnRequestFragmentSide>| {
                 side.map(|side| {
                     let account_uuid = Uuid::from_str(&side.account)
@@ -165,8 +204,7 @@ pub mod post_txns {
                     match (account_uuid, currency_uuid) {
                         (Err(err), _) | (_, Err(err)) => Err(err),
                         (Ok(account_uuid), Ok(currency_uuid)) => {
-                            let amount = Decimal::from_str_exact(&side.amount).map_err(map_to_err);
-                            amount.map(|amount| CreateTxnActionFragmentSide {
+                            parse_decimal(&side.amount).map(|amount| CreateTxnActionFragmentSide {
                                 amount,
                                 account: account_uuid,
                                 currency: currency_uuid,
@@ -182,6 +220,31 @@ pub mod post_txns {
 
             fragments.push(CreateTxnActionFragment { from, to });
         }
+        Ok(fragments)
+    }
+
+    /// Create a transaction with its fragments.
+    #[utoipa::path(
+        operation_id = "postTxns",
+        tag = "txns",
+        request_body = PostTxnRequest,
+        responses(
+            (status = 200, body = PostTxnResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[post("/txns")]
+    async fn handler(
+        user: AuthUser,
+        info: web::Json<PostTxnRequest>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<PostTxnResponse>, EndpointsErrors> {
+        let db_txn = TransactionWithCallback::new(data.db.begin().await?, vec![]);
+        let fragments = to_create_fragments(&info.fragments)?;
 
         let (id, db_txn) = create_txn(
             CreateTxnAction {
@@ -190,9 +253,11 @@ pub mod post_txns {
                 description: info.description.clone(),
             },
             &fragments,
+            None,
             db_txn,
             &user,
             data.currency_cache.clone(),
+            data.currency_rate_datums_cache.clone(),
         )
         .await?;
 
@@ -201,3 +266,117 @@ pub mod post_txns {
         Ok(web::Json(PostTxnResponse { id: id.to_string() }))
     }
 }
+
+pub mod put_txn {
+
+    use crate::date::js_iso_to_iso8601;
+    use crate::routes::bootstrap::parse_uuid;
+    use crate::routes::txns::post_txns::{to_create_fragments, PostTxnRequest};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PutTxnResponse {
+        pub id: String,
+    }
+
+    /// Replace a transaction and its fragments, with the rates derived from them.
+    #[utoipa::path(
+        operation_id = "putTxn",
+        tag = "txns",
+        params(("id" = String, Path, description = "Id of the transaction.")),
+        request_body = PostTxnRequest,
+        responses(
+            (status = 200, body = PutTxnResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[put("/txns/{id}")]
+    async fn handler(
+        user: AuthUser,
+        id: web::Path<String>,
+        info: web::Json<PostTxnRequest>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<PutTxnResponse>, EndpointsErrors> {
+        let id = parse_uuid(&id)?;
+        let fragments = to_create_fragments(&info.fragments)?;
+
+        let db_txn = TransactionWithCallback::new(data.db.begin().await?, vec![]);
+        let db_txn = update_txn(
+            id,
+            CreateTxnAction {
+                date: js_iso_to_iso8601(&info.date_utc)?.naive_utc(),
+                title: info.title.clone(),
+                description: info.description.clone(),
+            },
+            &fragments,
+            db_txn,
+            &user,
+            data.currency_cache.clone(),
+            data.currency_rate_datums_cache.clone(),
+        )
+        .await?;
+
+        db_txn.commit().await;
+
+        Ok(web::Json(PutTxnResponse { id: id.to_string() }))
+    }
+}
+
+pub mod delete_txn {
+
+    use crate::routes::bootstrap::parse_uuid;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct DeleteTxnResponse {
+        pub id: String,
+    }
+
+    /// Delete a transaction with its fragments, and the rates derived from them.
+    #[utoipa::path(
+        operation_id = "deleteTxn",
+        tag = "txns",
+        params(("id" = String, Path, description = "Id of the transaction.")),
+        responses(
+            (status = 200, body = DeleteTxnResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[delete("/txns/{id}")]
+    async fn handler(
+        user: AuthUser,
+        id: web::Path<String>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<DeleteTxnResponse>, EndpointsErrors> {
+        let id = parse_uuid(&id)?;
+        let db_txn = TransactionWithCallback::new(data.db.begin().await?, vec![]);
+        let db_txn = delete_txn(
+            id,
+            db_txn,
+            &user,
+            data.currency_cache.clone(),
+            data.currency_rate_datums_cache.clone(),
+        )
+        .await?;
+
+        db_txn.commit().await;
+
+        Ok(web::Json(DeleteTxnResponse { id: id.to_string() }))
+    }
+}
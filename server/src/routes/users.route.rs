@@ -1,9 +1,14 @@
+use crate::routes::openapi::{
+    BadRequestResponse, InternalServerErrorResponse, NotFoundResponse, UnauthorizedResponse,
+};
 use crate::services::users::{generate_token_unverified, verify_creds};
 use crate::DatabaseStates;
-use actix_web::post;
+use crate::{extractors::auth_user::AuthUser, services::TransactionWithCallback};
 use actix_web::web;
+use actix_web::{delete, get, post};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use utoipa::{IntoParams, ToSchema};
 
 pub mod login {
 
@@ -11,7 +16,7 @@ pub mod login {
 
     use super::*;
 
-    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -20,7 +25,7 @@ pub mod login {
         pub password: String,
     }
 
-    #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     #[serde(rename_all = "camelCase")]
     #[derive(TS)]
     #[ts(export)]
@@ -29,6 +34,18 @@ pub mod login {
         pub owner: String,
     }
 
+    /// Exchange a username and password for an access token.
+    #[utoipa::path(
+        operation_id = "login",
+        tag = "users",
+        request_body = LoginRequestBody,
+        responses(
+            (status = 200, body = LoginResponseBody),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+    )]
     #[post("/login")]
     async fn handler(
         info: web::Json<LoginRequestBody>,
@@ -57,17 +74,34 @@ pub mod register {
     use super::*;
     use crate::{routes::bootstrap::EndpointsErrors, services::users::register_user};
 
-    #[derive(Serialize, Deserialize, Clone)]
+    #[derive(Serialize, Deserialize, Clone, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
     pub struct PostUserResponseBody {
         pub id: String,
     }
 
-    #[derive(Serialize, Deserialize, Clone)]
+    #[derive(Serialize, Deserialize, Clone, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
     pub struct PostUserRequestBody {
         pub username: String,
         pub password: String,
     }
 
+    /// Register a new user.
+    #[utoipa::path(
+        operation_id = "register",
+        tag = "users",
+        request_body = PostUserRequestBody,
+        responses(
+            (status = 200, body = PostUserResponseBody),
+            (status = 400, response = BadRequestResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+    )]
     #[post("/users")]
     async fn handler(
         info: web::Json<PostUserRequestBody>,
@@ -80,3 +114,160 @@ pub mod register {
         Ok(web::Json(PostUserResponseBody { id: user.into() }))
     }
 }
+
+pub mod export_user {
+    use super::*;
+    use crate::{
+        extended_models::user_archive::UserArchive, routes::bootstrap::EndpointsErrors,
+        services::user_archive::export_user_archive,
+    };
+
+    /// Export every record owned by the user as an archive.
+    #[utoipa::path(
+        operation_id = "exportUser",
+        tag = "users",
+        responses(
+            (status = 200, body = UserArchive),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[get("/users/export")]
+    async fn handler(
+        user: AuthUser,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<UserArchive>, EndpointsErrors> {
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (archive, db_txn) = export_user_archive(&user, db_txn).await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(archive))
+    }
+}
+
+pub mod delete_user {
+    use super::*;
+    use crate::{routes::bootstrap::EndpointsErrors, services::users::delete_user};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct DeleteUserResponseBody {
+        pub id: String,
+    }
+
+    /// Delete the user and every record they own.
+    #[utoipa::path(
+        operation_id = "deleteUser",
+        tag = "users",
+        responses(
+            (status = 200, body = DeleteUserResponseBody),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[delete("/users")]
+    async fn handler(
+        user: AuthUser,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<DeleteUserResponseBody>, EndpointsErrors> {
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let db_txn = delete_user(
+            &user,
+            db_txn,
+            data.currency_cache.clone(),
+            data.currency_rate_datums_cache.clone(),
+            data.txn_tags_cache.clone(),
+        )
+        .await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(DeleteUserResponseBody {
+            id: user.0.to_string(),
+        }))
+    }
+}
+
+pub mod import_user {
+    use super::*;
+    use crate::{
+        extended_models::user_archive::UserArchive, routes::bootstrap::EndpointsErrors,
+        services::user_archive::import_user_archive,
+    };
+
+    #[derive(Serialize, Deserialize, Clone, Debug, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostImportUserQuery {
+        /// Assign fresh ids to every imported row instead of keeping the ids in the archive.
+        pub remap_ids: Option<bool>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostImportUserResponseBody {
+        pub accounts: usize,
+        pub currencies: usize,
+        pub currency_rate_datums: usize,
+        pub txn_tags: usize,
+        pub txns: usize,
+        pub currency_rate_sources: usize,
+        pub wallets: usize,
+        pub wallet_txns: usize,
+    }
+
+    /// Import an archive produced by `GET /users/export` into the user.
+    #[utoipa::path(
+        operation_id = "importUser",
+        tag = "users",
+        params(PostImportUserQuery),
+        request_body = UserArchive,
+        responses(
+            (status = 200, body = PostImportUserResponseBody),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[post("/users/import")]
+    async fn handler(
+        user: AuthUser,
+        query: web::Query<PostImportUserQuery>,
+        info: web::Json<UserArchive>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<PostImportUserResponseBody>, EndpointsErrors> {
+        let archive = info.into_inner();
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let db_txn = import_user_archive(
+            &user,
+            &archive,
+            query.remap_ids.unwrap_or(false),
+            db_txn,
+            data.currency_cache.clone(),
+            data.currency_rate_datums_cache.clone(),
+            data.txn_tags_cache.clone(),
+        )
+        .await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(PostImportUserResponseBody {
+            accounts: archive.accounts.len(),
+            currencies: archive.currencies.len(),
+            currency_rate_datums: archive.currency_rate_datums.len(),
+            txn_tags: archive.txn_tags.len(),
+            txns: archive.txns.len(),
+            currency_rate_sources: archive.currency_rate_sources.as_ref().map_or(0, Vec::len),
+            wallets: archive.wallets.as_ref().map_or(0, Vec::len),
+            wallet_txns: archive.wallet_txns.as_ref().map_or(0, Vec::len),
+        }))
+    }
+}
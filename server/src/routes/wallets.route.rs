@@ -0,0 +1,190 @@
+use crate::date::iso8601_to_js_iso;
+use crate::extended_models::wallet::{Wallet, WalletChain};
+use crate::routes::bootstrap::{parse_uuid, EndpointsErrors};
+use crate::routes::openapi::{
+    BadRequestResponse, InternalServerErrorResponse, NotFoundResponse, UnauthorizedResponse,
+};
+use crate::services::TransactionWithCallback;
+use crate::{extractors::auth_user::AuthUser, states::database_states::DatabaseStates};
+use actix_web::{delete, get, post, web};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// A blockchain address whose transfers are synced as transactions of `accountId`.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[derive(TS)]
+#[ts(export)]
+pub struct WalletItem {
+    pub id: String,
+    pub name: String,
+    pub chain: WalletChain,
+    pub address: String,
+    pub account_id: String,
+    pub currency_id: String,
+    /// When the wallet was last synced successfully.
+    pub last_sync_date: Option<String>,
+    /// Why the last sync failed, cleared by the next successful one.
+    pub last_error: Option<String>,
+}
+
+impl From<Wallet> for WalletItem {
+    fn from(value: Wallet) -> Self {
+        WalletItem {
+            id: value.id.to_string(),
+            name: value.name,
+            chain: value.chain,
+            address: value.address,
+            account_id: value.account_id.0.to_string(),
+            currency_id: value.currency_id.0.to_string(),
+            last_sync_date: value.last_sync_date.map(|x| iso8601_to_js_iso(x.and_utc())),
+            last_error: value.last_error,
+        }
+    }
+}
+
+pub mod post_wallet {
+    use super::*;
+    use crate::extended_models::account::AccountId;
+    use crate::extended_models::currency::CurrencyId;
+    use crate::services::wallets::{create_wallet, CreateWalletAction};
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostWalletRequest {
+        pub name: String,
+        pub chain: WalletChain,
+        pub address: String,
+        /// The account receiving the synced transactions.
+        pub account_id: String,
+        /// The currency of the synced amounts, which needs the decimal places of the chain.
+        pub currency_id: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct PostWalletResponse {
+        pub id: String,
+    }
+
+    /// Add a wallet whose transfers are synced into an account.
+    #[utoipa::path(
+        operation_id = "postWallet",
+        tag = "wallets",
+        request_body = PostWalletRequest,
+        responses(
+            (status = 200, body = PostWalletResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[post("/wallets")]
+    async fn handler(
+        user: AuthUser,
+        info: web::Json<PostWalletRequest>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<PostWalletResponse>, EndpointsErrors> {
+        let info = info.into_inner();
+        let action = CreateWalletAction {
+            account_id: AccountId(parse_uuid(&info.account_id)?),
+            currency_id: CurrencyId(parse_uuid(&info.currency_id)?),
+            name: info.name,
+            chain: info.chain,
+            address: info.address,
+        };
+
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (wallet, db_txn) =
+            create_wallet(&user, action, None, db_txn, data.currency_cache.clone()).await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(PostWalletResponse {
+            id: wallet.id.to_string(),
+        }))
+    }
+}
+
+pub mod get_wallets {
+    use super::*;
+    use crate::services::wallets::get_wallets;
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct GetWalletsResponse {
+        pub items: Vec<WalletItem>,
+    }
+
+    /// List the wallets of the user, ordered by name.
+    #[utoipa::path(
+        operation_id = "getWallets",
+        tag = "wallets",
+        responses(
+            (status = 200, body = GetWalletsResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[get("/wallets")]
+    async fn handler(
+        user: AuthUser,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<GetWalletsResponse>, EndpointsErrors> {
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let (wallets, _db_txn) = get_wallets(&user, db_txn).await?;
+        Ok(web::Json(GetWalletsResponse {
+            items: wallets.into_iter().map(Into::into).collect(),
+        }))
+    }
+}
+
+pub mod delete_wallet {
+    use super::*;
+    use crate::services::wallets::delete_wallet;
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[derive(TS)]
+    #[ts(export)]
+    pub struct DeleteWalletResponse {
+        pub id: String,
+    }
+
+    /// Delete a wallet, the transactions synced from it are kept.
+    #[utoipa::path(
+        operation_id = "deleteWallet",
+        tag = "wallets",
+        params(("id" = String, Path, description = "Id of the wallet.")),
+        responses(
+            (status = 200, body = DeleteWalletResponse),
+            (status = 400, response = BadRequestResponse),
+            (status = 401, response = UnauthorizedResponse),
+            (status = 404, response = NotFoundResponse),
+            (status = 500, response = InternalServerErrorResponse),
+        ),
+        security(("accessToken" = [])),
+    )]
+    #[delete("/wallets/{id}")]
+    async fn handler(
+        user: AuthUser,
+        id: web::Path<String>,
+        data: web::Data<DatabaseStates>,
+    ) -> Result<web::Json<DeleteWalletResponse>, EndpointsErrors> {
+        let id = parse_uuid(&id)?;
+        let db_txn = TransactionWithCallback::from_db_conn(&data.db, vec![]).await?;
+        let db_txn = delete_wallet(&user, &id, db_txn).await?;
+
+        db_txn.commit().await;
+        Ok(web::Json(DeleteWalletResponse { id: id.to_string() }))
+    }
+}
@@ -5,14 +5,16 @@ use crate::services::TransactionWithCallback;
 use sea_orm::prelude::DateTime;
 use sea_orm::{ActiveValue, ColumnTrait, DbErr, EntityTrait, QueryFilter};
 
+/// Create an account. A new id is generated unless `preset_id` is given.
 pub async fn create_account(
     auth_user: &AuthUser,
     name: &str,
     creation_date: DateTime,
+    preset_id: Option<AccountId>,
     db_txn: TransactionWithCallback,
 ) -> Result<(uuid::Uuid, TransactionWithCallback), DbErr> {
     let new_account = account::ActiveModel {
-        id: ActiveValue::Set(uuid::Uuid::new_v4()),
+        id: ActiveValue::Set(preset_id.map_or_else(uuid::Uuid::new_v4, |id| id.0)),
         name: ActiveValue::Set(name.to_string()),
         owner_id: ActiveValue::Set(auth_user.0),
         creation_date: ActiveValue::Set(creation_date),
@@ -2,16 +2,21 @@ use std::sync::Arc;
 
 use crate::caches::currency_cache::CurrencyCache;
 use crate::entities::currency_rate_datum::Model;
-use crate::extended_models::currency::{Currency, CurrencyId};
+use crate::extended_models::currency::{
+    Currency, CurrencyId, CurrencyKind, RateMethod, RateStrategy, MAX_CURRENCY_DECIMALS,
+};
 use crate::extractors::auth_user::AuthUser;
-use crate::linear_interpolator::{force_time_delta_to_mills_decimal, try_linear_interpolate};
-use crate::maths::ForgivingDecimal;
+use crate::linear_interpolator::{
+    force_time_delta_to_mills_decimal, select_datums, try_linear_interpolate, DatumSelection,
+};
+use crate::maths::{is_valid_rate_amount, round_amount, ForgivingDecimal};
+use crate::metrics::METRICS;
 use crate::routes::bootstrap::EndpointsErrors;
 use crate::services::TransactionWithCallback;
 use crate::{entities::currency, extended_models::currency::CreateCurrencyAction};
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
-use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, DbErr, EntityTrait, QueryFilter};
 use tokio::sync::Mutex;
 
 use super::currency_rate_datum::get_datum_left_right;
@@ -20,7 +25,6 @@ use super::currency_rate_datum::get_datum_left_right;
 pub enum CalculateCurrencyRateErrors {
     DbErr(DbErr),
     CurrencyNotFound(CurrencyId),
-    InvalidDecimalValue(String),
     OverflowOrUnderflow,
 }
 
@@ -31,9 +35,6 @@ impl From<CalculateCurrencyRateErrors> for EndpointsErrors {
             CalculateCurrencyRateErrors::CurrencyNotFound(currency_id) => {
                 EndpointsErrors::CurrencyNotFound(currency_id)
             }
-            CalculateCurrencyRateErrors::InvalidDecimalValue(value) => {
-                EndpointsErrors::InvalidDecimalValue(value)
-            }
             CalculateCurrencyRateErrors::OverflowOrUnderflow => {
                 EndpointsErrors::OverflowOrUnderflow
             }
@@ -41,35 +42,62 @@ impl From<CalculateCurrencyRateErrors> for EndpointsErrors {
     }
 }
 
-/// A shorthand method to get 2 datums rates, given the left and right datums, in the same database transaction.
-async fn get_left_right_datum_rate(
+/// One hop of a rate calculation, with the hops it was computed from.
+#[derive(Clone, Debug)]
+pub struct RateExplanation {
+    pub currency_id: CurrencyId,
+    pub method: RateMethod,
+    pub rate: Decimal,
+    /// Datums of the currency the rate was computed from, left one first.
+    pub datums: Vec<Model>,
+    /// Set when the fallback rate amount of the currency scaled the rate.
+    pub fallback_rate_amount: Option<Decimal>,
+    /// Rates of the currencies the datums or the fallback rate are expressed in.
+    pub sources: Vec<RateExplanation>,
+}
+
+impl RateExplanation {
+    fn new(currency_id: CurrencyId, method: RateMethod, rate: Decimal) -> RateExplanation {
+        RateExplanation {
+            currency_id,
+            method,
+            rate,
+            datums: vec![],
+            fallback_rate_amount: None,
+            sources: vec![],
+        }
+    }
+}
+
+/// A shorthand method to explain 2 datums rates, given the left and right datums, in the same database transaction.
+async fn explain_left_right_datum_rate(
     owner: &AuthUser,
     left_datum: &Model,
     right_datum: &Model,
     db_txn: TransactionWithCallback,
     cache: Arc<Mutex<CurrencyCache>>,
-) -> Result<(Decimal, Decimal, TransactionWithCallback), CalculateCurrencyRateErrors> {
-    let (left_rate, db_txn) = calculate_currency_rate(
+    depth: usize,
+) -> Result<(RateExplanation, RateExplanation, TransactionWithCallback), CalculateCurrencyRateErrors>
+{
+    let (left, db_txn) = explain_currency_rate_at_depth(
         owner,
         CurrencyId(left_datum.ref_amount_currency_id),
         db_txn,
         left_datum.date.and_utc(),
         cache.clone(),
+        depth,
     )
     .await?;
-    let (right_rate, db_txn) = calculate_currency_rate(
+    let (right, db_txn) = explain_currency_rate_at_depth(
         owner,
         CurrencyId(right_datum.ref_amount_currency_id),
         db_txn,
         right_datum.date.and_utc(),
         cache.clone(),
+        depth,
     )
     .await?;
-    Ok((
-        left_rate.forgiving_decimal_mul_str(&left_datum.amount)?,
-        right_rate.forgiving_decimal_mul_str(&right_datum.amount)?,
-        db_txn,
-    ))
+    Ok((left, right, db_txn))
 }
 
 /// Calculate the exchange rate of the given currency at a given date.
@@ -80,37 +108,115 @@ pub async fn calculate_currency_rate(
     date: chrono::DateTime<chrono::Utc>,
     cache: Arc<Mutex<CurrencyCache>>,
 ) -> Result<(Decimal, TransactionWithCallback), CalculateCurrencyRateErrors> {
+    let (explanation, db_txn) =
+        explain_currency_rate_at_depth(owner, currency_id, db_txn, date, cache, 0).await?;
+    Ok((explanation.rate, db_txn))
+}
+
+/// Same as [`calculate_currency_rate`], also telling how every currency of the chain got its rate.
+pub async fn explain_currency_rate(
+    owner: &AuthUser,
+    currency_id: CurrencyId,
+    db_txn: TransactionWithCallback,
+    date: chrono::DateTime<chrono::Utc>,
+    cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<(RateExplanation, TransactionWithCallback), CalculateCurrencyRateErrors> {
+    explain_currency_rate_at_depth(owner, currency_id, db_txn, date, cache, 0).await
+}
+
+/// The rate of a currency given by a single datum, scaled by the rate of the currency the datum refers to.
+#[allow(clippy::too_many_arguments)]
+async fn explain_single_datum_rate(
+    owner: &AuthUser,
+    currency_id: CurrencyId,
+    method: RateMethod,
+    datum: Model,
+    db_txn: TransactionWithCallback,
+    date: chrono::DateTime<chrono::Utc>,
+    cache: Arc<Mutex<CurrencyCache>>,
+    depth: usize,
+) -> Result<(RateExplanation, TransactionWithCallback), CalculateCurrencyRateErrors> {
+    let (source, db_txn) = explain_currency_rate_at_depth(
+        owner,
+        CurrencyId(datum.ref_amount_currency_id),
+        db_txn,
+        date,
+        cache,
+        depth,
+    )
+    .await?;
+    let mut explanation = RateExplanation::new(
+        currency_id,
+        method,
+        source.rate.forgiving_decimal_mul(&datum.amount)?,
+    );
+    explanation.datums = vec![datum];
+    explanation.sources = vec![source];
+    Ok((explanation, db_txn))
+}
+
+/// `depth` is the number of currencies walked so far, and is reported once the chain reaches the base currency.
+async fn explain_currency_rate_at_depth(
+    owner: &AuthUser,
+    currency_id: CurrencyId,
+    db_txn: TransactionWithCallback,
+    date: chrono::DateTime<chrono::Utc>,
+    cache: Arc<Mutex<CurrencyCache>>,
+    depth: usize,
+) -> Result<(RateExplanation, TransactionWithCallback), CalculateCurrencyRateErrors> {
     let (curr, db_txn) = get_currency_by_id(owner, &currency_id, db_txn, cache.clone())
         .await
         .map_err(CalculateCurrencyRateErrors::DbErr)?;
 
     match curr {
-        Some(Currency::Base { .. }) => Ok((Decimal::ONE, db_txn)),
+        Some(Currency::Base { .. }) => {
+            METRICS.observe_currency_rate_recursion_depth(depth);
+            Ok((
+                RateExplanation::new(currency_id, RateMethod::Base, Decimal::ONE),
+                db_txn,
+            ))
+        }
         Some(Currency::Normal {
             fallback_rate_amount,
             fallback_rate_currency_id,
+            rate_strategy,
+            rate_max_gap_days,
             ..
         }) => {
             let (left_d, right_d, db_txn) = get_datum_left_right(owner, date, currency_id, db_txn)
                 .await
                 .map_err(CalculateCurrencyRateErrors::DbErr)?;
+            let selection = select_datums(
+                rate_strategy,
+                rate_max_gap_days.map(|days| chrono::TimeDelta::days(days.into())),
+                &date.naive_utc(),
+                left_d.map(|datum| (datum.date, datum)),
+                right_d.map(|datum| (datum.date, datum)),
+            );
 
-            match (left_d, right_d) {
-                // If left and right datums are found, get their rates, and interpolate.
-                (Some(left_d), Some(right_d)) => {
+            match selection {
+                // Get the rates of both datums, and interpolate.
+                DatumSelection::Interpolate(left_d, right_d) => {
                     let left_delta = date.signed_duration_since(left_d.date.and_utc());
                     let full_range = right_d.date.signed_duration_since(left_d.date);
-                    let (left_rate, right_rate, db_txn) = Box::pin(get_left_right_datum_rate(
+                    let (left, right, db_txn) = Box::pin(explain_left_right_datum_rate(
                         owner,
                         &left_d,
                         &right_d,
                         db_txn,
                         cache.clone(),
+                        depth + 1,
                     ))
                     .await?;
                     let interpolate_result = try_linear_interpolate(
-                        Some((Decimal::ZERO, left_rate)),
-                        Some((force_time_delta_to_mills_decimal(&full_range), right_rate)),
+                        Some((
+                            Decimal::ZERO,
+                            left.rate.forgiving_decimal_mul(&left_d.amount)?,
+                        )),
+                        Some((
+                            force_time_delta_to_mills_decimal(&full_range),
+                            right.rate.forgiving_decimal_mul(&right_d.amount)?,
+                        )),
                         Decimal::from_i64(left_delta.num_milliseconds())
                             .expect("Unable to convert left_delta to Decimal."),
                     );
@@ -118,49 +224,81 @@ pub async fn calculate_currency_rate(
                     match interpolate_result {
                         // If interpolation returns None, use fallback rate.
                         None => {
-                            let (fallback_rate, db_txn) = Box::pin(calculate_currency_rate(
+                            let (fallback, db_txn) = Box::pin(explain_currency_rate_at_depth(
                                 owner,
                                 fallback_rate_currency_id,
                                 db_txn,
                                 date,
                                 cache.clone(),
+                                depth + 1,
                             ))
                             .await?;
-                            Ok((fallback_rate, db_txn))
+                            let mut explanation = RateExplanation::new(
+                                currency_id,
+                                RateMethod::Fallback,
+                                fallback.rate,
+                            );
+                            explanation.sources.push(fallback);
+                            Ok((explanation, db_txn))
+                        }
+                        Some(interpolate_result) => {
+                            let mut explanation = RateExplanation::new(
+                                currency_id,
+                                RateMethod::Interpolated,
+                                interpolate_result,
+                            );
+                            explanation.datums = vec![left_d, right_d];
+                            explanation.sources = vec![left, right];
+                            Ok((explanation, db_txn))
                         }
-                        Some(interpolate_result) => Ok((interpolate_result, db_txn)),
                     }
                 }
-                // If only the left datum is found, return the left datum's rate.
-                (Some(left_d), None) => {
-                    let (left_d_rate, db_txn) = Box::pin(calculate_currency_rate(
+                // Use the rate of a single datum as is.
+                DatumSelection::Previous(datum) => {
+                    Box::pin(explain_single_datum_rate(
                         owner,
-                        CurrencyId(left_d.ref_amount_currency_id),
+                        currency_id,
+                        RateMethod::LeftOnly,
+                        datum,
                         db_txn,
                         date,
                         cache,
+                        depth + 1,
                     ))
-                    .await?;
-                    Ok((
-                        left_d_rate.forgiving_decimal_mul_str(&left_d.amount)?,
+                    .await
+                }
+                DatumSelection::Nearest(datum) => {
+                    Box::pin(explain_single_datum_rate(
+                        owner,
+                        currency_id,
+                        RateMethod::Nearest,
+                        datum,
                         db_txn,
+                        date,
+                        cache,
+                        depth + 1,
                     ))
+                    .await
                 }
-                // If only the right datum is found / not found at all, return the currency fallback rate
-                (None, _) => {
-                    let (fallback_rate, db_txn) = Box::pin(calculate_currency_rate(
+                // Without usable datums, return the currency fallback rate
+                DatumSelection::Fallback => {
+                    let (fallback, db_txn) = Box::pin(explain_currency_rate_at_depth(
                         owner,
                         fallback_rate_currency_id,
                         db_txn,
                         date,
                         cache,
+                        depth + 1,
                     ))
                     .await?;
-
-                    Ok((
-                        fallback_rate.forgiving_decimal_mul_str(&fallback_rate_amount)?,
-                        db_txn,
-                    ))
+                    let mut explanation = RateExplanation::new(
+                        currency_id,
+                        RateMethod::Fallback,
+                        fallback.rate.forgiving_decimal_mul(&fallback_rate_amount)?,
+                    );
+                    explanation.fallback_rate_amount = Some(fallback_rate_amount);
+                    explanation.sources = vec![fallback];
+                    Ok((explanation, db_txn))
                 }
             }
         }
@@ -168,14 +306,82 @@ pub async fn calculate_currency_rate(
     }
 }
 
+/// An amount converted from one currency to another, through the rates of both to the base currency.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurrencyConversion {
+    /// Rounded to the decimal places of the target currency.
+    pub amount: Decimal,
+    pub from_rate_to_base: Decimal,
+    pub to_rate_to_base: Decimal,
+}
+
+/// Convert `amount` of `from` into `to`, with the rates of both currencies at `date`.
+pub async fn convert_currency_amount(
+    owner: &AuthUser,
+    from: CurrencyId,
+    to: CurrencyId,
+    amount: Decimal,
+    date: chrono::DateTime<chrono::Utc>,
+    db_txn: TransactionWithCallback,
+    cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<(CurrencyConversion, TransactionWithCallback), CalculateCurrencyRateErrors> {
+    let (from_rate_to_base, db_txn) =
+        calculate_currency_rate(owner, from, db_txn, date, cache.clone()).await?;
+    let (to_rate_to_base, db_txn) =
+        calculate_currency_rate(owner, to, db_txn, date, cache.clone()).await?;
+    let (to_currency, db_txn) = get_currency_by_id(owner, &to, db_txn, cache)
+        .await
+        .map_err(CalculateCurrencyRateErrors::DbErr)?;
+    let decimals = to_currency
+        .ok_or(CalculateCurrencyRateErrors::CurrencyNotFound(to))?
+        .decimals();
+
+    let converted = amount
+        .forgiving_decimal_mul(&from_rate_to_base)?
+        .checked_div(to_rate_to_base)
+        .ok_or(CalculateCurrencyRateErrors::OverflowOrUnderflow)?;
+    Ok((
+        CurrencyConversion {
+            amount: round_amount(&converted, decimals),
+            from_rate_to_base,
+            to_rate_to_base,
+        },
+        db_txn,
+    ))
+}
+
+/// Which currencies `get_currencies` lists.
+#[derive(Clone, Debug, Default)]
+pub struct CurrencyFilter {
+    /// Only list the currencies of this kind.
+    pub kind: Option<CurrencyKind>,
+    /// Archived currencies are left out unless this is set.
+    pub include_archived: bool,
+}
+
+impl CurrencyFilter {
+    /// Every currency, archived ones included.
+    pub fn all() -> CurrencyFilter {
+        CurrencyFilter {
+            kind: None,
+            include_archived: true,
+        }
+    }
+}
+
 pub async fn get_currencies(
     owner: &AuthUser,
+    filter: &CurrencyFilter,
     db_txn: TransactionWithCallback,
 ) -> Result<(Vec<Currency>, TransactionWithCallback), DbErr> {
-    let db_result = currency::Entity::find()
-        .filter(currency::Column::OwnerId.eq(owner.0))
-        .all(db_txn.get_db_txn())
-        .await?;
+    let mut query = currency::Entity::find().filter(currency::Column::OwnerId.eq(owner.0));
+    if let Some(kind) = filter.kind {
+        query = query.filter(currency::Column::Kind.eq(kind.as_str()));
+    }
+    if !filter.include_archived {
+        query = query.filter(currency::Column::Archived.eq(false));
+    }
+    let db_result = query.all(db_txn.get_db_txn()).await?;
     Ok((
         db_result
             .iter()
@@ -188,9 +394,11 @@ pub async fn get_currencies(
     ))
 }
 
+/// The found currency is only cached once the transaction is committed, as it may have been
+/// created by that transaction.
 pub async fn get_base_currency(
     owner: &AuthUser,
-    db_txn: TransactionWithCallback,
+    mut db_txn: TransactionWithCallback,
     cache: Arc<Mutex<CurrencyCache>>,
 ) -> Result<(Option<Currency>, TransactionWithCallback), DbErr> {
     let db_result = currency::Entity::find()
@@ -202,9 +410,10 @@ pub async fn get_base_currency(
         None => Ok((None, db_txn)),
         Some(model) => {
             let cache_entry: Currency = model.into();
-            {
-                cache.lock().await.register_item(cache_entry.clone());
-            }
+            let cached = cache_entry.clone();
+            db_txn.add_callback(async move {
+                cache.lock().await.replace_item(cached);
+            });
             Ok((Some(cache_entry), db_txn))
         }
     }
@@ -229,10 +438,11 @@ pub async fn find_first_unknown_currencies(
     Ok((None, db_txn))
 }
 
+/// Like [get_base_currency], the found currency is only cached once the transaction is committed.
 pub async fn get_currency_by_id(
     owner: &AuthUser,
     currency_id: &CurrencyId,
-    db_txn: TransactionWithCallback,
+    mut db_txn: TransactionWithCallback,
     cache: Arc<Mutex<CurrencyCache>>,
 ) -> Result<(Option<Currency>, TransactionWithCallback), DbErr> {
     let db_result = currency::Entity::find()
@@ -242,9 +452,10 @@ pub async fn get_currency_by_id(
         .await?;
 
     if let Some(ref model) = db_result {
-        {
-            cache.lock().await.register_item(model.clone().into());
-        }
+        let cached: Currency = model.clone().into();
+        db_txn.add_callback(async move {
+            cache.lock().await.replace_item(cached);
+        });
     }
 
     Ok((db_result.map(|f| f.into()), db_txn))
@@ -255,6 +466,9 @@ pub enum CreateCurrencyErrors {
     DbErr(DbErr),
     ReferencedCurrencyNotExist(CurrencyId),
     RepeatedBaseCurrency,
+    InvalidFallbackRateAmount(Decimal),
+    InvalidDecimals(u32),
+    InvalidRateMaxGap(RateStrategy, Option<u32>),
 }
 
 impl From<CreateCurrencyErrors> for EndpointsErrors {
@@ -263,15 +477,54 @@ impl From<CreateCurrencyErrors> for EndpointsErrors {
             CreateCurrencyErrors::DbErr(db_err) => Self::DbErr(db_err),
             CreateCurrencyErrors::RepeatedBaseCurrency => Self::RepeatedBaseCurrency,
             CreateCurrencyErrors::ReferencedCurrencyNotExist(cid) => Self::CurrencyNotFound(cid),
+            CreateCurrencyErrors::InvalidFallbackRateAmount(amount) => {
+                Self::InvalidDecimalValue(amount.to_string())
+            }
+            CreateCurrencyErrors::InvalidDecimals(decimals) => {
+                Self::InvalidCurrencyDecimals(decimals)
+            }
+            CreateCurrencyErrors::InvalidRateMaxGap(strategy, max_gap_days) => {
+                Self::InvalidRateMaxGap {
+                    strategy,
+                    max_gap_days,
+                }
+            }
         }
     }
 }
 
+/// Create a currency. A new id is generated unless `preset_id` is given.
 pub async fn create_currency(
     currency: CreateCurrencyAction,
+    preset_id: Option<CurrencyId>,
     db_txn: TransactionWithCallback,
     cache: Arc<Mutex<CurrencyCache>>,
 ) -> Result<(uuid::Uuid, TransactionWithCallback), CreateCurrencyErrors> {
+    if currency.get_decimals() > MAX_CURRENCY_DECIMALS {
+        return Err(CreateCurrencyErrors::InvalidDecimals(
+            currency.get_decimals(),
+        ));
+    }
+    if let CreateCurrencyAction::Normal {
+        fallback_rate_amount,
+        rate_strategy,
+        rate_max_gap_days,
+        ..
+    } = currency
+    {
+        if !is_valid_rate_amount(&fallback_rate_amount) {
+            return Err(CreateCurrencyErrors::InvalidFallbackRateAmount(
+                fallback_rate_amount,
+            ));
+        }
+        if !rate_strategy.accepts_max_gap_days(rate_max_gap_days) {
+            return Err(CreateCurrencyErrors::InvalidRateMaxGap(
+                rate_strategy,
+                rate_max_gap_days,
+            ));
+        }
+    }
+
     // Ensure another base currency doesnt exist
     let db_txn = match currency.is_base() {
         true => {
@@ -318,16 +571,114 @@ pub async fn create_currency(
         },
     };
 
-    let create_currency_active_record: currency::ActiveModel = currency.clone().into();
+    let mut create_currency_active_record: currency::ActiveModel = currency.clone().into();
+    if let Some(preset_id) = preset_id {
+        create_currency_active_record.id = sea_orm::ActiveValue::Set(preset_id.0);
+    }
     let model = currency::Entity::insert(create_currency_active_record)
         .exec(db_txn.get_db_txn())
         .await
         .map_err(CreateCurrencyErrors::DbErr)?;
 
-    cache
-        .clone()
-        .lock()
-        .await
-        .register_item(currency.into_domain(model.last_insert_id.0));
+    // Cached after the commit, so a rolled back currency is never seen by later requests
+    let mut db_txn = db_txn;
+    let cached = currency.into_domain(model.last_insert_id.0);
+    db_txn.add_callback(async move {
+        cache.lock().await.replace_item(cached);
+    });
     Ok((model.last_insert_id.0, db_txn))
 }
+
+/// Changes to the metadata of a currency, `None` leaves the field as is.
+#[derive(Clone, Debug, Default)]
+pub struct UpdateCurrencyMetadataAction {
+    pub kind: Option<CurrencyKind>,
+    /// `Some(None)` removes the symbol.
+    pub symbol: Option<Option<String>>,
+    pub archived: Option<bool>,
+    pub rate_strategy: Option<RateStrategy>,
+    /// `Some(None)` removes the max gap.
+    pub rate_max_gap_days: Option<Option<u32>>,
+    pub derive_rates: Option<bool>,
+}
+
+#[derive(Debug)]
+pub enum UpdateCurrencyErrors {
+    DbErr(DbErr),
+    CurrencyNotFound(CurrencyId),
+    InvalidRateMaxGap(RateStrategy, Option<u32>),
+}
+
+impl From<UpdateCurrencyErrors> for EndpointsErrors {
+    fn from(value: UpdateCurrencyErrors) -> Self {
+        match value {
+            UpdateCurrencyErrors::DbErr(db_err) => Self::DbErr(db_err),
+            UpdateCurrencyErrors::CurrencyNotFound(cid) => Self::CurrencyNotFound(cid),
+            UpdateCurrencyErrors::InvalidRateMaxGap(strategy, max_gap_days) => {
+                Self::InvalidRateMaxGap {
+                    strategy,
+                    max_gap_days,
+                }
+            }
+        }
+    }
+}
+
+/// Change the kind, symbol, archived state or rate strategy of a currency.
+pub async fn update_currency_metadata(
+    owner: &AuthUser,
+    currency_id: &CurrencyId,
+    action: UpdateCurrencyMetadataAction,
+    mut db_txn: TransactionWithCallback,
+    cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<(Currency, TransactionWithCallback), UpdateCurrencyErrors> {
+    let model = currency::Entity::find()
+        .filter(currency::Column::OwnerId.eq(owner.0))
+        .filter(currency::Column::Id.eq(currency_id.0))
+        .one(db_txn.get_db_txn())
+        .await
+        .map_err(UpdateCurrencyErrors::DbErr)?
+        .ok_or(UpdateCurrencyErrors::CurrencyNotFound(*currency_id))?;
+
+    let rate_strategy = action
+        .rate_strategy
+        .unwrap_or(RateStrategy::from_stored(&model.rate_strategy));
+    let rate_max_gap_days = action
+        .rate_max_gap_days
+        .unwrap_or(model.rate_max_gap_days.map(|x| x as u32));
+    if !rate_strategy.accepts_max_gap_days(rate_max_gap_days) {
+        return Err(UpdateCurrencyErrors::InvalidRateMaxGap(
+            rate_strategy,
+            rate_max_gap_days,
+        ));
+    }
+
+    let mut active_model: currency::ActiveModel = model.into();
+    if let Some(kind) = action.kind {
+        active_model.kind = ActiveValue::Set(kind.as_str().to_string());
+    }
+    if let Some(symbol) = action.symbol {
+        active_model.symbol = ActiveValue::Set(symbol);
+    }
+    if let Some(archived) = action.archived {
+        active_model.archived = ActiveValue::Set(archived);
+    }
+    if let Some(derive_rates) = action.derive_rates {
+        active_model.derive_rates = ActiveValue::Set(derive_rates);
+    }
+    if action.rate_strategy.is_some() || action.rate_max_gap_days.is_some() {
+        active_model.rate_strategy = ActiveValue::Set(rate_strategy.as_str().to_string());
+        active_model.rate_max_gap_days = ActiveValue::Set(rate_max_gap_days.map(|x| x as i32));
+    }
+    let updated: Currency = active_model
+        .update(db_txn.get_db_txn())
+        .await
+        .map_err(UpdateCurrencyErrors::DbErr)?
+        .into();
+
+    let cached = updated.clone();
+    db_txn.add_callback(async move {
+        cache.lock().await.replace_item(cached);
+    });
+    Ok((updated, db_txn))
+}
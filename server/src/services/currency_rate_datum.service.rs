@@ -1,6 +1,7 @@
 use super::currencies::{find_first_unknown_currencies, get_currency_by_id};
 use crate::entities::currency_rate_datum;
 use crate::extended_models::currency::CurrencyId;
+use crate::maths::is_valid_rate_amount;
 use crate::routes::bootstrap::EndpointsErrors;
 use crate::routes::currency_rate_datums::CreateCurrencyRateDatumAction;
 use crate::services::TransactionWithCallback;
@@ -8,9 +9,9 @@ use crate::{
     caches::{currency_cache::CurrencyCache, currency_rate_datum::CurrencyRateDatumCache},
     extractors::auth_user::AuthUser,
 };
-use sea_orm::prelude::Expr;
+use rust_decimal::Decimal;
 use sea_orm::sqlx::types::chrono::{self, Utc};
-use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, Value};
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -20,6 +21,7 @@ pub enum CreateCurrencyRateDatumErrors {
     DbErr(DbErr),
     CyclicRefAmountCurrency(Uuid),
     CurrencyNotFound(CurrencyId),
+    InvalidAmount(Decimal),
 }
 
 impl From<CreateCurrencyRateDatumErrors> for EndpointsErrors {
@@ -32,49 +34,16 @@ impl From<CreateCurrencyRateDatumErrors> for EndpointsErrors {
                 EndpointsErrors::CyclicRefAmountCurrency(uuid)
             }
             CreateCurrencyRateDatumErrors::DbErr(db_err) => EndpointsErrors::DbErr(db_err),
-        }
-    }
-}
-
-pub fn find_neighbors_left_biased<T: Ord + Clone, R>(
-    target: &T,
-    pair_1: Option<(T, R)>,
-    pair_2: Option<(T, R)>,
-) -> (Option<R>, Option<R>) {
-    match (pair_1, pair_2) {
-        (Some(first), Some(second)) => {
-            // Simple swap if not sorted
-            let (first, second) = match first.0 > second.0 {
-                true => (second, first),
-                false => (first, second),
-            };
-
-            match (target >= &first.0, target <= &second.0) {
-                (true, true) => (Some(first.1), Some(second.1)), // A T B
-                (true, false) => (Some(second.1), None),         // A B T
-                (false, true) => (None, None),                   // T A B
-                (false, false) => panic!("impossible case encountered."), // T A B T
-            }
-        }
-        (Some(first), None) => {
-            match target >= &first.0 {
-                true => (Some(first.1), None), // A T
-                false => (None, None),         // T A
+            CreateCurrencyRateDatumErrors::InvalidAmount(amount) => {
+                EndpointsErrors::InvalidDecimalValue(amount.to_string())
             }
         }
-        (None, Some(second)) => {
-            match target >= &second.0 {
-                true => (Some(second.1), None), // B T
-                false => (None, None),          // T B
-            }
-        }
-        (None, None) => (None, None),
     }
 }
 
 // TODO: Don't call db every time
-/// Get the nearest 2 datums of a currency given a date.
-/// If the given date is exactly the same as one of the 2 nearest datums, the returned pair of datums will be the same.
+/// Get the last datum at or before, and the first datum at or after a given date.
+/// If a datum is exactly at the given date, the returned pair of datums will be the same.
 pub async fn get_datum_left_right(
     owner: &AuthUser,
     date: chrono::DateTime<Utc>,
@@ -88,70 +57,47 @@ pub async fn get_datum_left_right(
     ),
     DbErr,
 > {
-    // TODO: See if this can be done via builder instead of raw SQL
-    let results_iter = currency_rate_datum::Entity::find()
-        .filter(
+    let date = date.naive_utc();
+    let currency_datums = || {
+        currency_rate_datum::Entity::find().filter(
             currency_rate_datum::Column::OwnerId
                 .eq(owner.0)
                 .and(currency_rate_datum::Column::RefCurrencyId.eq(currency_id.0)),
         )
-        .order_by(
-            Expr::cust_with_values(
-                r#"abs(extract (epoch from ($1::timestamp - "date")))"#,
-                vec![Value::ChronoDateTimeUtc(Some(Box::new(date)))],
-            ),
-            sea_orm::Order::Asc,
-        )
-        .all(db_txn.get_db_txn())
-        .await?;
-
-    let first_item = results_iter.first().cloned();
-    let second_item = results_iter.get(1).cloned();
-    let date = &date.naive_utc();
-
-    // TODO: Dont use clone
-    let nearest = find_neighbors_left_biased(
-        date,
-        match first_item {
-            Some(model) => Some((model.date, model.clone())),
-            None => None,
-        },
-        match second_item {
-            Some(model) => Some((model.date, model.clone())),
-            None => None,
-        },
-    );
-
-    // TODO: can inline bool
-    let is_left_same = match nearest.0.is_some() {
-        true => nearest.0.as_ref().unwrap().date == *date,
-        false => false,
-    };
-    let is_right_same = match nearest.1.is_some() {
-        true => nearest.1.as_ref().unwrap().date == *date,
-        false => false,
     };
 
-    if is_left_same {
-        let left_side = nearest.0;
-        let left_side_copy = left_side.clone();
-        Ok((left_side, left_side_copy, db_txn))
-    } else if is_right_same {
-        let right_side = nearest.1;
-        let right_side_copy = right_side.clone();
-        Ok((right_side, right_side_copy, db_txn))
-    } else {
-        Ok((nearest.0, nearest.1, db_txn))
+    let left = currency_datums()
+        .filter(currency_rate_datum::Column::Date.lte(date))
+        .order_by_desc(currency_rate_datum::Column::Date)
+        .one(db_txn.get_db_txn())
+        .await?;
+
+    if let Some(left) = left.as_ref().filter(|left| left.date == date) {
+        return Ok((Some(left.clone()), Some(left.clone()), db_txn));
     }
+
+    let right = currency_datums()
+        .filter(currency_rate_datum::Column::Date.gte(date))
+        .order_by_asc(currency_rate_datum::Column::Date)
+        .one(db_txn.get_db_txn())
+        .await?;
+
+    Ok((left, right, db_txn))
 }
 
+/// Create a currency rate datum. A new id is generated unless `preset_id` is given.
 pub async fn create_currency_rate_datum(
     owner: &AuthUser,
     datum: CreateCurrencyRateDatumAction,
+    preset_id: Option<Uuid>,
     db_txn: TransactionWithCallback,
     rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
     currency_cache: Arc<Mutex<CurrencyCache>>,
 ) -> Result<(uuid::Uuid, TransactionWithCallback), CreateCurrencyRateDatumErrors> {
+    if !is_valid_rate_amount(&datum.amount) {
+        return Err(CreateCurrencyRateDatumErrors::InvalidAmount(datum.amount));
+    }
+
     // Check if all currencies referenced exist
     let db_txn = {
         let currencies_to_check = [datum.ref_currency_id, datum.ref_amount_currency_id];
@@ -199,17 +145,19 @@ pub async fn create_currency_rate_datum(
         ));
     }
 
-    let create_datum_active_record: currency_rate_datum::ActiveModel = datum.clone().into();
+    let mut create_datum_active_record: currency_rate_datum::ActiveModel = datum.clone().into();
+    if let Some(preset_id) = preset_id {
+        create_datum_active_record.id = sea_orm::ActiveValue::Set(preset_id);
+    }
     let model = currency_rate_datum::Entity::insert(create_datum_active_record)
         .exec(db_txn.get_db_txn())
         .await
         .map_err(CreateCurrencyRateDatumErrors::DbErr)?;
 
-    {
-        rates_cache
-            .lock()
-            .await
-            .register_item(datum.into_domain(model.last_insert_id.0));
-    }
+    let mut db_txn = db_txn;
+    let cached = datum.into_domain(model.last_insert_id.0);
+    db_txn.add_callback(async move {
+        rates_cache.lock().await.register_item(cached);
+    });
     Ok((model.last_insert_id.0, db_txn))
 }
@@ -0,0 +1,713 @@
+use super::currencies::find_first_unknown_currencies;
+use super::currency_rate_datum::create_currency_rate_datum;
+use crate::caches::currency_cache::CurrencyCache;
+use crate::entities::currency_rate_source;
+use crate::env::EnvRateSourcesSection;
+use crate::extended_models::currency::CurrencyId;
+use crate::extended_models::currency_rate_source::{CurrencyRateSource, RateSourceKind};
+use crate::extractors::auth_user::AuthUser;
+use crate::json_query::{JsonQuery, ParseJsonQueryErrors};
+use crate::maths::is_valid_rate_amount;
+use crate::routes::bootstrap::EndpointsErrors;
+use crate::routes::currency_rate_datums::CreateCurrencyRateDatumAction;
+use crate::services::TransactionWithCallback;
+use crate::states::database_states::DatabaseStates;
+use actix_tls::connect::{Connector, Resolve, Resolver};
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use futures::future::LocalBoxFuture;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde_json::Value;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use url::{Host, Url};
+use uuid::Uuid;
+
+/// Largest JSON document read from a source, in bytes.
+pub const MAX_RATE_DOCUMENT_SIZE: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchRateErrors {
+    #[error("Request failed: {0}")]
+    Request(String),
+    #[error("{0} is not allowed, see rateSources.allowedHosts and rateSources.deniedHosts.")]
+    ForbiddenHost(String),
+    #[error("Responded with status {0}.")]
+    Status(u16),
+    #[error("File sources are disabled, rateSources.fileDirectory is not configured.")]
+    FileSourcesDisabled,
+    #[error("Unable to read {path}: {reason}")]
+    File { path: String, reason: String },
+    #[error("The document is not valid JSON: {0}")]
+    InvalidJson(String),
+    #[error("Invalid JSON query: {0}")]
+    InvalidQuery(#[from] ParseJsonQueryErrors),
+    #[error("Nothing is found at {0}.")]
+    NothingAtQuery(String),
+    #[error("{0} is not a valid rate.")]
+    InvalidRate(String),
+    #[error("Unable to save the rate: {0}")]
+    Save(String),
+}
+
+/// Somewhere the current rate of a currency can be read from, as a JSON document.
+pub trait RateSource {
+    fn fetch_document(&self) -> LocalBoxFuture<'_, Result<Value, FetchRateErrors>>;
+}
+
+/// Whether `host` is `entry` or one of its subdomains. Addresses only match themselves.
+fn is_matching_host(host: &str, entry: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let entry = entry.trim_end_matches('.').to_ascii_lowercase();
+    host == entry || host.ends_with(&format!(".{entry}"))
+}
+
+/// Addresses anyone on the internet could reach. The server's own, or those of its network, are not.
+pub fn is_public_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let octets = address.octets();
+            !(address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_unspecified()
+                || address.is_broadcast()
+                || address.is_multicast()
+                // "This network", 0.0.0.0/8
+                || octets[0] == 0
+                // Shared address space of carrier-grade NAT, 100.64.0.0/10
+                || (octets[0] == 100 && octets[1] & 0b1100_0000 == 64)
+                // Protocol assignments, 192.0.0.0/24
+                || octets[..3] == [192, 0, 0]
+                // Benchmarking, 198.18.0.0/15
+                || (octets[0] == 198 && octets[1] & 0b1111_1110 == 18)
+                // Reserved, 240.0.0.0/4
+                || octets[0] >= 240)
+        }
+        IpAddr::V6(address) => {
+            let segments = address.segments();
+            // NAT64, 64:ff9b::/96, reaches the IPv4 address embedded in the last 32 bits
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., a, b, c, d] = address.octets();
+                return is_public_address(IpAddr::V4(Ipv4Addr::new(a, b, c, d)));
+            }
+            if address.is_loopback() || address.is_unspecified() {
+                return false;
+            }
+            // IPv4-mapped ::ffff:a.b.c.d, and the deprecated IPv4-compatible ::a.b.c.d
+            if let Some(address) = address.to_ipv4() {
+                return is_public_address(IpAddr::V4(address));
+            }
+            !(address.is_multicast()
+                // Unique local, fc00::/7
+                || segments[0] & 0xfe00 == 0xfc00
+                // Link-local, fe80::/10
+                || segments[0] & 0xffc0 == 0xfe80)
+        }
+    }
+}
+
+/// The hosts `http` sources may reach, see `allowedHosts` and `deniedHosts` of [`EnvRateSourcesSection`].
+#[derive(Clone, Debug, Default)]
+pub struct RateSourceHosts {
+    pub allowed: Option<Vec<String>>,
+    pub denied: Vec<String>,
+}
+
+impl RateSourceHosts {
+    pub fn from_settings(settings: &EnvRateSourcesSection) -> Self {
+        RateSourceHosts {
+            allowed: settings.allowed_hosts.clone(),
+            denied: settings.denied_hosts.clone().unwrap_or_default(),
+        }
+    }
+
+    fn is_listed(&self, host: &str) -> bool {
+        self.allowed
+            .iter()
+            .flatten()
+            .any(|entry| is_matching_host(host, entry))
+    }
+
+    fn is_denied(&self, host: &str) -> bool {
+        self.denied
+            .iter()
+            .any(|entry| is_matching_host(host, entry))
+    }
+
+    /// Whether a URL naming `host` may be requested, before it is resolved.
+    pub fn is_allowed_host(&self, host: &str) -> bool {
+        !self.is_denied(host) && (self.allowed.is_none() || self.is_listed(host))
+    }
+
+    /// Whether `host` may be reached at `address`. Non-public addresses must be listed in `allowedHosts`,
+    /// by name or by address.
+    pub fn is_allowed_address(&self, host: &str, address: IpAddr) -> bool {
+        let address_text = address.to_string();
+        self.is_allowed_host(host)
+            && !self.is_denied(&address_text)
+            && (is_public_address(address) || self.is_listed(host) || self.is_listed(&address_text))
+    }
+}
+
+/// Resolves host names like the default resolver, but refuses addresses the hosts do not allow.
+/// Checking once resolved prevents a public name from leading to a private address.
+struct RateSourceResolver {
+    hosts: RateSourceHosts,
+}
+
+impl Resolve for RateSourceResolver {
+    fn lookup<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> LocalBoxFuture<'a, Result<Vec<SocketAddr>, Box<dyn std::error::Error>>> {
+        Box::pin(async move {
+            let target = (host.to_string(), port);
+            let addresses = web::block(move || target.to_socket_addrs().map(Vec::from_iter))
+                .await
+                .map_err(|err| err.to_string())??;
+            match addresses
+                .iter()
+                .find(|x| !self.hosts.is_allowed_address(host, x.ip()))
+            {
+                Some(address) => Err(Box::new(FetchRateErrors::ForbiddenHost(format!(
+                    "{host} ({})",
+                    address.ip()
+                ))) as Box<dyn std::error::Error>),
+                None => Ok(addresses),
+            }
+        })
+    }
+}
+
+/// `GET` a URL answering with JSON. Redirects are not followed.
+pub struct HttpRateSource {
+    pub url: Url,
+    pub timeout: Duration,
+    pub hosts: RateSourceHosts,
+}
+
+impl HttpRateSource {
+    /// Refuse URLs naming a forbidden host, or a forbidden address which is never resolved.
+    fn check_host(&self) -> Result<(), FetchRateErrors> {
+        let allowed = match self.url.host() {
+            None => false,
+            Some(Host::Domain(domain)) => self.hosts.is_allowed_host(domain),
+            Some(Host::Ipv4(address)) => self
+                .hosts
+                .is_allowed_address(&address.to_string(), IpAddr::V4(address)),
+            Some(Host::Ipv6(address)) => self
+                .hosts
+                .is_allowed_address(&address.to_string(), IpAddr::V6(address)),
+        };
+        match allowed {
+            true => Ok(()),
+            false => Err(FetchRateErrors::ForbiddenHost(
+                self.url.host_str().unwrap_or_default().to_string(),
+            )),
+        }
+    }
+}
+
+impl RateSource for HttpRateSource {
+    fn fetch_document(&self) -> LocalBoxFuture<'_, Result<Value, FetchRateErrors>> {
+        Box::pin(async move {
+            self.check_host()?;
+            let resolver = Resolver::custom(RateSourceResolver {
+                hosts: self.hosts.clone(),
+            });
+            let client = awc::Client::builder()
+                .connector(awc::Connector::new().connector(Connector::new(resolver).service()))
+                .disable_redirects()
+                .timeout(self.timeout)
+                .finish();
+            let mut response = client
+                .get(self.url.as_str())
+                .insert_header(("accept", "application/json"))
+                .send()
+                .await
+                .map_err(|err| FetchRateErrors::Request(err.to_string()))?;
+            if !response.status().is_success() {
+                return Err(FetchRateErrors::Status(response.status().as_u16()));
+            }
+            let body = response
+                .body()
+                .limit(MAX_RATE_DOCUMENT_SIZE)
+                .await
+                .map_err(|err| FetchRateErrors::Request(err.to_string()))?;
+            serde_json::from_slice(&body)
+                .map_err(|err| FetchRateErrors::InvalidJson(err.to_string()))
+        })
+    }
+}
+
+/// A JSON file on the local disk, kept up to date by another program.
+pub struct FileRateSource {
+    pub path: PathBuf,
+}
+
+impl RateSource for FileRateSource {
+    fn fetch_document(&self) -> LocalBoxFuture<'_, Result<Value, FetchRateErrors>> {
+        Box::pin(async move {
+            let path = self.path.clone();
+            let file_error = |reason: String| FetchRateErrors::File {
+                path: self.path.display().to_string(),
+                reason,
+            };
+            let content = web::block(move || std::fs::read(path))
+                .await
+                .map_err(|err| file_error(err.to_string()))?
+                .map_err(|err| file_error(err.to_string()))?;
+            if content.len() > MAX_RATE_DOCUMENT_SIZE {
+                return Err(file_error(format!(
+                    "larger than {MAX_RATE_DOCUMENT_SIZE} bytes"
+                )));
+            }
+            serde_json::from_slice(&content)
+                .map_err(|err| FetchRateErrors::InvalidJson(err.to_string()))
+        })
+    }
+}
+
+/// The URL of an HTTP source. `https://` is assumed if `hostname` has no scheme.
+pub fn http_source_url(hostname: &str, path: &str) -> Result<Url, String> {
+    let base = match hostname.contains("://") {
+        true => hostname.to_string(),
+        false => format!("https://{hostname}"),
+    };
+    let base = Url::parse(&base).map_err(|err| err.to_string())?;
+    if !matches!(base.scheme(), "http" | "https") {
+        return Err(format!(
+            "{} is not supported, use http or https.",
+            base.scheme()
+        ));
+    }
+    base.join(path).map_err(|err| err.to_string())
+}
+
+/// File sources may only name files inside the configured directory.
+fn is_contained_path(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().next().is_some()
+        && path.components().all(|x| matches!(x, Component::Normal(_)))
+}
+
+/// Build the [`RateSource`] a saved source reads from.
+pub fn to_rate_source(
+    source: &CurrencyRateSource,
+    settings: &EnvRateSourcesSection,
+) -> Result<Box<dyn RateSource>, FetchRateErrors> {
+    match source.kind {
+        RateSourceKind::Http => {
+            let url = http_source_url(source.hostname.as_deref().unwrap_or_default(), &source.path)
+                .map_err(FetchRateErrors::Request)?;
+            Ok(Box::new(HttpRateSource {
+                url,
+                timeout: settings.timeout(),
+                hosts: RateSourceHosts::from_settings(settings),
+            }))
+        }
+        RateSourceKind::File => {
+            let directory = settings
+                .file_directory
+                .as_ref()
+                .ok_or(FetchRateErrors::FileSourcesDisabled)?;
+            if !is_contained_path(&source.path) {
+                return Err(FetchRateErrors::File {
+                    path: source.path.clone(),
+                    reason: "not inside the file directory".to_string(),
+                });
+            }
+            Ok(Box::new(FileRateSource {
+                path: Path::new(directory).join(&source.path),
+            }))
+        }
+    }
+}
+
+/// Read the rate at `json_query_string` of a document. Both JSON numbers and numeric strings are accepted.
+pub fn extract_rate(document: &Value, json_query_string: &str) -> Result<Decimal, FetchRateErrors> {
+    let value = JsonQuery::parse(json_query_string)?
+        .select(document)
+        .ok_or_else(|| FetchRateErrors::NothingAtQuery(json_query_string.to_string()))?;
+    let text = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.trim().to_string(),
+        other => return Err(FetchRateErrors::InvalidRate(other.to_string())),
+    };
+    let rate = Decimal::from_str_exact(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .map_err(|_| FetchRateErrors::InvalidRate(text.clone()))?;
+    match is_valid_rate_amount(&rate) {
+        true => Ok(rate),
+        false => Err(FetchRateErrors::InvalidRate(text)),
+    }
+}
+
+#[derive(Debug)]
+pub enum CurrencyRateSourceErrors {
+    DbErr(DbErr),
+    NotFound(Uuid),
+    CurrencyNotFound(CurrencyId),
+    CyclicRefAmountCurrency(Uuid),
+    InvalidSource { field: String, reason: String },
+}
+
+impl From<CurrencyRateSourceErrors> for EndpointsErrors {
+    fn from(value: CurrencyRateSourceErrors) -> Self {
+        match value {
+            CurrencyRateSourceErrors::DbErr(db_err) => EndpointsErrors::DbErr(db_err),
+            CurrencyRateSourceErrors::NotFound(id) => {
+                EndpointsErrors::CurrencyRateSourceNotFound(id)
+            }
+            CurrencyRateSourceErrors::CurrencyNotFound(currency_id) => {
+                EndpointsErrors::CurrencyNotFound(currency_id)
+            }
+            CurrencyRateSourceErrors::CyclicRefAmountCurrency(id) => {
+                EndpointsErrors::CyclicRefAmountCurrency(id)
+            }
+            CurrencyRateSourceErrors::InvalidSource { field, reason } => {
+                EndpointsErrors::InvalidRateSource { field, reason }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateCurrencyRateSourceAction {
+    pub name: String,
+    pub kind: RateSourceKind,
+    pub ref_currency_id: CurrencyId,
+    pub ref_amount_currency_id: CurrencyId,
+    pub hostname: Option<String>,
+    pub path: String,
+    pub json_query_string: String,
+}
+
+/// Fields left as `None` are not changed.
+#[derive(Clone, Debug, Default)]
+pub struct UpdateCurrencyRateSourceAction {
+    pub name: Option<String>,
+    pub kind: Option<RateSourceKind>,
+    pub ref_amount_currency_id: Option<CurrencyId>,
+    pub hostname: Option<String>,
+    pub path: Option<String>,
+    pub json_query_string: Option<String>,
+}
+
+/// Check a source could be fetched, without fetching it.
+/// The hostname of file sources is dropped, since they have none.
+fn validate_source(
+    action: CreateCurrencyRateSourceAction,
+) -> Result<CreateCurrencyRateSourceAction, CurrencyRateSourceErrors> {
+    let invalid = |field: &str, reason: String| CurrencyRateSourceErrors::InvalidSource {
+        field: field.to_string(),
+        reason,
+    };
+    if action.ref_currency_id == action.ref_amount_currency_id {
+        return Err(CurrencyRateSourceErrors::CyclicRefAmountCurrency(
+            action.ref_currency_id.0,
+        ));
+    }
+    if action.name.trim().is_empty() {
+        return Err(invalid("name", "must not be empty.".to_string()));
+    }
+    JsonQuery::parse(&action.json_query_string)
+        .map_err(|err| invalid("jsonQueryString", err.to_string()))?;
+    match action.kind {
+        RateSourceKind::Http => {
+            let hostname = action
+                .hostname
+                .as_deref()
+                .filter(|x| !x.trim().is_empty())
+                .ok_or_else(|| invalid("hostname", "is required by http sources.".to_string()))?;
+            http_source_url(hostname, &action.path).map_err(|reason| invalid("path", reason))?;
+            Ok(action)
+        }
+        RateSourceKind::File => match is_contained_path(&action.path) {
+            true => Ok(CreateCurrencyRateSourceAction {
+                hostname: None,
+                ..action
+            }),
+            false => Err(invalid(
+                "path",
+                "must be a relative path without \"..\".".to_string(),
+            )),
+        },
+    }
+}
+
+async fn check_currencies_exist(
+    owner: &AuthUser,
+    ids: &[CurrencyId],
+    db_txn: TransactionWithCallback,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<TransactionWithCallback, CurrencyRateSourceErrors> {
+    match find_first_unknown_currencies(owner, ids, db_txn, currency_cache)
+        .await
+        .map_err(CurrencyRateSourceErrors::DbErr)?
+    {
+        (None, db_txn) => Ok(db_txn),
+        (Some(unknown), _db_txn) => Err(CurrencyRateSourceErrors::CurrencyNotFound(unknown)),
+    }
+}
+
+pub async fn create_currency_rate_source(
+    owner: &AuthUser,
+    action: CreateCurrencyRateSourceAction,
+    preset_id: Option<Uuid>,
+    db_txn: TransactionWithCallback,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<(CurrencyRateSource, TransactionWithCallback), CurrencyRateSourceErrors> {
+    let action = validate_source(action)?;
+    let db_txn = check_currencies_exist(
+        owner,
+        &[action.ref_currency_id, action.ref_amount_currency_id],
+        db_txn,
+        currency_cache,
+    )
+    .await?;
+
+    let model = currency_rate_source::ActiveModel {
+        id: ActiveValue::Set(preset_id.unwrap_or_else(Uuid::new_v4)),
+        owner_id: ActiveValue::Set(owner.0),
+        ref_currency_id: ActiveValue::Set(action.ref_currency_id.0),
+        ref_amount_currency_id: ActiveValue::Set(action.ref_amount_currency_id.0),
+        name: ActiveValue::Set(action.name),
+        kind: ActiveValue::Set(action.kind.as_str().to_string()),
+        hostname: ActiveValue::Set(action.hostname),
+        path: ActiveValue::Set(action.path),
+        json_query_string: ActiveValue::Set(action.json_query_string),
+        last_fetch_date: ActiveValue::Set(None),
+        last_error: ActiveValue::Set(None),
+    }
+    .insert(db_txn.get_db_txn())
+    .await
+    .map_err(CurrencyRateSourceErrors::DbErr)?;
+    Ok((model.into(), db_txn))
+}
+
+/// List the sources of the user, only those of `ref_currency_id` if given.
+pub async fn get_currency_rate_sources(
+    owner: &AuthUser,
+    ref_currency_id: Option<&CurrencyId>,
+    db_txn: TransactionWithCallback,
+) -> Result<(Vec<CurrencyRateSource>, TransactionWithCallback), DbErr> {
+    let mut query = currency_rate_source::Entity::find()
+        .filter(currency_rate_source::Column::OwnerId.eq(owner.0));
+    if let Some(ref_currency_id) = ref_currency_id {
+        query = query.filter(currency_rate_source::Column::RefCurrencyId.eq(ref_currency_id.0));
+    }
+    let models = query
+        .order_by_asc(currency_rate_source::Column::Name)
+        .all(db_txn.get_db_txn())
+        .await?;
+    Ok((models.into_iter().map(Into::into).collect(), db_txn))
+}
+
+async fn find_source_model(
+    owner: &AuthUser,
+    id: &Uuid,
+    db_txn: &TransactionWithCallback,
+) -> Result<currency_rate_source::Model, CurrencyRateSourceErrors> {
+    currency_rate_source::Entity::find()
+        .filter(currency_rate_source::Column::OwnerId.eq(owner.0))
+        .filter(currency_rate_source::Column::Id.eq(*id))
+        .one(db_txn.get_db_txn())
+        .await
+        .map_err(CurrencyRateSourceErrors::DbErr)?
+        .ok_or(CurrencyRateSourceErrors::NotFound(*id))
+}
+
+pub async fn get_currency_rate_source_by_id(
+    owner: &AuthUser,
+    id: &Uuid,
+    db_txn: TransactionWithCallback,
+) -> Result<(CurrencyRateSource, TransactionWithCallback), CurrencyRateSourceErrors> {
+    let model = find_source_model(owner, id, &db_txn).await?;
+    Ok((model.into(), db_txn))
+}
+
+/// Change a source, the currency whose rate it gives stays the same.
+pub async fn update_currency_rate_source(
+    owner: &AuthUser,
+    id: &Uuid,
+    action: UpdateCurrencyRateSourceAction,
+    db_txn: TransactionWithCallback,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<(CurrencyRateSource, TransactionWithCallback), CurrencyRateSourceErrors> {
+    let model = find_source_model(owner, id, &db_txn).await?;
+    let current: CurrencyRateSource = model.clone().into();
+    let merged = validate_source(CreateCurrencyRateSourceAction {
+        name: action.name.unwrap_or(current.name),
+        kind: action.kind.unwrap_or(current.kind),
+        ref_currency_id: current.ref_currency_id,
+        ref_amount_currency_id: action
+            .ref_amount_currency_id
+            .unwrap_or(current.ref_amount_currency_id),
+        hostname: action.hostname.or(current.hostname),
+        path: action.path.unwrap_or(current.path),
+        json_query_string: action
+            .json_query_string
+            .unwrap_or(current.json_query_string),
+    })?;
+    let db_txn = check_currencies_exist(
+        owner,
+        &[merged.ref_amount_currency_id],
+        db_txn,
+        currency_cache,
+    )
+    .await?;
+
+    let mut active_model: currency_rate_source::ActiveModel = model.into();
+    active_model.name = ActiveValue::Set(merged.name);
+    active_model.kind = ActiveValue::Set(merged.kind.as_str().to_string());
+    active_model.ref_amount_currency_id = ActiveValue::Set(merged.ref_amount_currency_id.0);
+    active_model.hostname = ActiveValue::Set(merged.hostname);
+    active_model.path = ActiveValue::Set(merged.path);
+    active_model.json_query_string = ActiveValue::Set(merged.json_query_string);
+    let updated = active_model
+        .update(db_txn.get_db_txn())
+        .await
+        .map_err(CurrencyRateSourceErrors::DbErr)?;
+    Ok((updated.into(), db_txn))
+}
+
+/// Delete a source. The datums it saved are kept.
+pub async fn delete_currency_rate_source(
+    owner: &AuthUser,
+    id: &Uuid,
+    db_txn: TransactionWithCallback,
+) -> Result<TransactionWithCallback, CurrencyRateSourceErrors> {
+    let result = currency_rate_source::Entity::delete_many()
+        .filter(currency_rate_source::Column::OwnerId.eq(owner.0))
+        .filter(currency_rate_source::Column::Id.eq(*id))
+        .exec(db_txn.get_db_txn())
+        .await
+        .map_err(CurrencyRateSourceErrors::DbErr)?;
+    match result.rows_affected {
+        0 => Err(CurrencyRateSourceErrors::NotFound(*id)),
+        _ => Ok(db_txn),
+    }
+}
+
+/// Record the outcome of a fetch on the source. `last_fetch_date` only moves on success.
+async fn record_fetch(
+    source: &CurrencyRateSource,
+    date: Option<DateTime<Utc>>,
+    error: Option<String>,
+    db_txn: &TransactionWithCallback,
+) -> Result<(), DbErr> {
+    let mut update = currency_rate_source::Entity::update_many()
+        .col_expr(
+            currency_rate_source::Column::LastError,
+            sea_orm::sea_query::Expr::value(error),
+        )
+        .filter(currency_rate_source::Column::OwnerId.eq(source.owner.0))
+        .filter(currency_rate_source::Column::Id.eq(source.id));
+    if let Some(date) = date {
+        update = update.col_expr(
+            currency_rate_source::Column::LastFetchDate,
+            sea_orm::sea_query::Expr::value(date.naive_utc()),
+        );
+    }
+    update.exec(db_txn.get_db_txn()).await?;
+    Ok(())
+}
+
+/// Fetch the current rate of a source and save it as a datum dated `date`.
+/// Returns the id of the new datum, or the reason the fetch failed, which is also saved as `lastError`.
+pub async fn fetch_currency_rate_source(
+    source: &CurrencyRateSource,
+    settings: &EnvRateSourcesSection,
+    date: DateTime<Utc>,
+    states: &DatabaseStates,
+) -> Result<Result<Uuid, FetchRateErrors>, DbErr> {
+    let rate = async {
+        let document = to_rate_source(source, settings)?.fetch_document().await?;
+        extract_rate(&document, &source.json_query_string)
+    }
+    .await;
+
+    let saved = match rate {
+        Err(err) => Err(err),
+        Ok(amount) => create_currency_rate_datum(
+            &source.owner,
+            CreateCurrencyRateDatumAction {
+                amount,
+                ref_currency_id: source.ref_currency_id,
+                ref_amount_currency_id: source.ref_amount_currency_id,
+                owner: source.owner.clone(),
+                date: date.naive_utc(),
+                source_fragment_id: None,
+            },
+            None,
+            TransactionWithCallback::from_db_conn(&states.db, vec![]).await?,
+            states.currency_rate_datums_cache.clone(),
+            states.currency_cache.clone(),
+        )
+        .await
+        .map_err(|err| FetchRateErrors::Save(EndpointsErrors::from(err).to_string())),
+    };
+
+    match saved {
+        Ok((datum_id, db_txn)) => {
+            record_fetch(source, Some(date), None, &db_txn).await?;
+            db_txn.commit().await;
+            Ok(Ok(datum_id))
+        }
+        Err(err) => {
+            warn!(
+                "Unable to fetch currency rate source {} ({}): {}",
+                source.id, source.name, err
+            );
+            let db_txn = TransactionWithCallback::from_db_conn(&states.db, vec![]).await?;
+            record_fetch(source, None, Some(err.to_string()), &db_txn).await?;
+            db_txn.commit().await;
+            Ok(Err(err))
+        }
+    }
+}
+
+/// Fetch every source of every user once, one after another.
+/// Returns the number of datums saved.
+pub async fn fetch_all_currency_rate_sources(
+    settings: &EnvRateSourcesSection,
+    states: &DatabaseStates,
+) -> Result<usize, DbErr> {
+    let sources = currency_rate_source::Entity::find()
+        .order_by_asc(currency_rate_source::Column::OwnerId)
+        .all(&states.db)
+        .await?;
+    let mut saved = 0;
+    for source in sources.into_iter().map(CurrencyRateSource::from) {
+        if fetch_currency_rate_source(&source, settings, Utc::now(), states)
+            .await?
+            .is_ok()
+        {
+            saved += 1;
+        }
+    }
+    Ok(saved)
+}
+
+/// Fetch every source each `interval` of the settings, for as long as the server runs.
+#[cfg_attr(test, mutants::skip)]
+pub async fn run_rate_sources_scheduler(settings: EnvRateSourcesSection, states: DatabaseStates) {
+    let mut interval = actix_web::rt::time::interval(settings.interval());
+    loop {
+        interval.tick().await;
+        match fetch_all_currency_rate_sources(&settings, &states).await {
+            Ok(saved) => info!("Saved {} currency rate(s) from the rate sources.", saved),
+            Err(db_err) => warn!("Unable to fetch the currency rate sources: {}", db_err),
+        }
+    }
+}
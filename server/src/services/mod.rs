@@ -1,3 +1,4 @@
+use crate::shutdown::{InFlightTransaction, TransactionStage, SHUTDOWN};
 use sea_orm::{DatabaseConnection, DatabaseTransaction};
 
 #[path = "users.service.rs"]
@@ -12,12 +13,21 @@ pub mod currencies;
 #[path = "currency_rate_datum.service.rs"]
 pub mod currency_rate_datum;
 
+#[path = "currency_rate_sources.service.rs"]
+pub mod currency_rate_sources;
+
 #[path = "txn_tags.service.rs"]
 pub mod txn_tags;
 
 #[path = "txns.service.rs"]
 pub mod txns;
 
+#[path = "user_archive.service.rs"]
+pub mod user_archive;
+
+#[path = "wallets.service.rs"]
+pub mod wallets;
+
 type AsyncCallbackBox =
     Box<dyn FnOnce() -> std::pin::Pin<Box<dyn std::future::Future<Output = ()>>>>;
 
@@ -26,10 +36,12 @@ A wrapped version of the ``DatabaseTransaction`` from seaorm.
 This struct follows the RAII pattern. This transaction will default to rollback when out of scope.
 It is recommended when being used as parameters of a function, the function consumes this transaction.
 The transaction should be returned to the caller upon success, and be consumed when failed.
+Open transactions are tracked so a graceful shutdown can wait for them to commit.
 */
 pub struct TransactionWithCallback {
     db_txn: DatabaseTransaction,
     callbacks: Vec<AsyncCallbackBox>,
+    in_flight: InFlightTransaction,
 }
 
 impl TransactionWithCallback {
@@ -37,7 +49,11 @@ impl TransactionWithCallback {
         db_txn: DatabaseTransaction,
         callbacks: Vec<AsyncCallbackBox>,
     ) -> TransactionWithCallback {
-        TransactionWithCallback { db_txn, callbacks }
+        TransactionWithCallback {
+            db_txn,
+            callbacks,
+            in_flight: SHUTDOWN.track(),
+        }
     }
     pub async fn from_db_conn(
         db_conn: &DatabaseConnection,
@@ -49,7 +65,6 @@ impl TransactionWithCallback {
     pub fn get_db_txn(&self) -> &DatabaseTransaction {
         &self.db_txn
     }
-    #[allow(unused)]
     pub fn add_callback(&mut self, callback: impl std::future::Future<Output = ()> + 'static) {
         self.callbacks.push(Box::new(|| Box::pin(callback)));
     }
@@ -59,14 +74,19 @@ impl TransactionWithCallback {
             .rollback()
             .await
             .expect("Error while rolling back database transaction.");
+        self.in_flight.finish();
     }
     pub async fn commit(self) {
+        let mut in_flight = self.in_flight;
+        in_flight.set_stage(TransactionStage::Committing);
         self.db_txn
             .commit()
             .await
             .expect("Database transaction commit failure.");
+        in_flight.set_stage(TransactionStage::RunningCallbacks);
         for f in self.callbacks {
             f().await;
         }
+        in_flight.finish();
     }
 }
@@ -2,26 +2,34 @@ use crate::caches::txn_tag::TxnTagsCache;
 use crate::services::TransactionWithCallback;
 use crate::{entities::txn_tag, extractors::auth_user::AuthUser};
 use sea_orm::{ActiveValue, ColumnTrait, DbErr, EntityTrait, QueryFilter};
+use std::sync::Arc;
+use tokio::sync::Mutex;
 use uuid::Uuid;
 
+/// Create a transaction tag. A new id is generated unless `preset_id` is given.
+/// The tag is only cached once the transaction is committed.
 pub async fn create_txn_tag(
     owner: &AuthUser,
     name: &str,
-    db_txn: TransactionWithCallback,
-    txn_tags_cache: &mut TxnTagsCache,
+    preset_id: Option<Uuid>,
+    mut db_txn: TransactionWithCallback,
+    txn_tags_cache: Arc<Mutex<TxnTagsCache>>,
 ) -> Result<(Uuid, TransactionWithCallback), DbErr> {
     let new_tag = txn_tag::ActiveModel {
-        id: ActiveValue::Set(uuid::Uuid::new_v4()),
+        id: ActiveValue::Set(preset_id.unwrap_or_else(uuid::Uuid::new_v4)),
         name: ActiveValue::Set(name.to_string()),
         owner_id: ActiveValue::Set(owner.0),
     };
     let model = txn_tag::Entity::insert(new_tag)
         .exec(db_txn.get_db_txn())
         .await?;
-    txn_tags_cache.register_item(txn_tag::Model {
+    let cached = txn_tag::Model {
         id: model.last_insert_id.0,
         name: name.to_string(),
         owner_id: owner.0,
+    };
+    db_txn.add_callback(async move {
+        txn_tags_cache.lock().await.register_item(cached);
     });
     Ok((model.last_insert_id.0, db_txn))
 }
@@ -1,15 +1,22 @@
 use crate::caches::currency_cache::CurrencyCache;
-use crate::entities::{fragment, txn};
+use crate::caches::currency_rate_datum::CurrencyRateDatumCache;
+use crate::entities::{currency_rate_datum, fragment, txn, wallet_txn};
 use crate::extended_models::account::AccountId;
-use crate::extended_models::currency::CurrencyId;
+use crate::extended_models::currency::{Currency, CurrencyId};
 use crate::extractors::auth_user::AuthUser;
+use crate::maths::{fits_decimals, is_valid_rate_amount};
 use crate::routes::bootstrap::EndpointsErrors;
+use crate::routes::currency_rate_datums::CreateCurrencyRateDatumAction;
+use crate::services::currency_rate_datum::{
+    create_currency_rate_datum, CreateCurrencyRateDatumErrors,
+};
 use crate::services::TransactionWithCallback;
+use crate::RESTFUL_DIGITS;
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use sea_orm::{
     ActiveModelBehavior, ActiveModelTrait, ActiveValue, ColumnTrait, DbErr, EntityTrait,
-    QueryFilter,
+    ModelTrait, QueryFilter, QueryOrder,
 };
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -17,13 +24,18 @@ use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use super::accounts::find_first_unknown_account;
-use super::currencies::find_first_unknown_currencies;
+use super::currencies::{find_first_unknown_currencies, get_currency_by_id};
 
 #[derive(Debug)]
 pub enum CreateTxnErrors {
     DbErr(DbErr),
     CurrencyNotFound(CurrencyId),
     AccountNotFound(AccountId),
+    AmountPrecisionExceeded {
+        amount: Decimal,
+        currency_id: CurrencyId,
+        decimals: u32,
+    },
 }
 
 impl From<CreateTxnErrors> for EndpointsErrors {
@@ -32,6 +44,45 @@ impl From<CreateTxnErrors> for EndpointsErrors {
             CreateTxnErrors::CurrencyNotFound(uuid) => EndpointsErrors::CurrencyNotFound(uuid),
             CreateTxnErrors::DbErr(db_err) => EndpointsErrors::DbErr(db_err),
             CreateTxnErrors::AccountNotFound(uuid) => EndpointsErrors::AccountNotFound(uuid),
+            CreateTxnErrors::AmountPrecisionExceeded {
+                amount,
+                currency_id,
+                decimals,
+            } => EndpointsErrors::AmountPrecisionExceeded {
+                amount: amount.to_string(),
+                currency_id,
+                decimals,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum UpdateTxnErrors {
+    TxnNotFound(Uuid),
+    InvalidTxn(CreateTxnErrors),
+}
+
+impl From<UpdateTxnErrors> for EndpointsErrors {
+    fn from(value: UpdateTxnErrors) -> Self {
+        match value {
+            UpdateTxnErrors::TxnNotFound(uuid) => EndpointsErrors::TxnNotFound(uuid),
+            UpdateTxnErrors::InvalidTxn(err) => err.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DeleteTxnErrors {
+    DbErr(DbErr),
+    TxnNotFound(Uuid),
+}
+
+impl From<DeleteTxnErrors> for EndpointsErrors {
+    fn from(value: DeleteTxnErrors) -> Self {
+        match value {
+            DeleteTxnErrors::DbErr(db_err) => EndpointsErrors::DbErr(db_err),
+            DeleteTxnErrors::TxnNotFound(uuid) => EndpointsErrors::TxnNotFound(uuid),
         }
     }
 }
@@ -85,7 +136,7 @@ pub fn fragments_to_curr_ids(fragments: &[CreateTxnActionFragment]) -> Vec<Curre
         .collect::<Vec<_>>()
 }
 
-/// Get all transactions of a given user.
+/// Get all transactions of a given user, oldest first.
 pub async fn get_txns(
     owner: &AuthUser,
     db_txn: TransactionWithCallback,
@@ -98,6 +149,7 @@ pub async fn get_txns(
 > {
     let models = txn::Entity::find()
         .filter(txn::Column::OwnerId.eq(owner.0))
+        .order_by_asc(txn::Column::Date)
         .find_with_related(fragment::Entity)
         .all(db_txn.get_db_txn())
         .await?;
@@ -105,7 +157,6 @@ pub async fn get_txns(
     Ok((models, db_txn))
 }
 
-#[allow(unused)]
 /// Get a transaction of a given user given ID.
 pub async fn get_txn_by_id(
     owner: &AuthUser,
@@ -121,13 +172,13 @@ pub async fn get_txn_by_id(
     Ok((model, db_txn))
 }
 
-pub async fn create_txn(
-    txn: CreateTxnAction,
+/// Ensure the accounts and currencies of the fragments exist, and that amounts fit their currency.
+async fn validate_fragments(
     fragments: &[CreateTxnActionFragment],
     db_txn: TransactionWithCallback,
     owner: &AuthUser,
     currency_cache: Arc<Mutex<CurrencyCache>>,
-) -> Result<(Uuid, TransactionWithCallback), CreateTxnErrors> {
+) -> Result<TransactionWithCallback, CreateTxnErrors> {
     // Ensure accounts exist
     let db_txn = {
         let (unknown_account, db_txn) =
@@ -160,7 +211,277 @@ pub async fn create_txn(
         db_txn
     };
 
-    let generated_txn_uuid = uuid::Uuid::new_v4();
+    // Ensure amounts have no more decimal places than their currency allows
+    let mut db_txn = db_txn;
+    for side in fragments
+        .iter()
+        .flat_map(|frag| [&frag.from, &frag.to])
+        .flatten()
+    {
+        let (currency, next_db_txn) = get_currency_by_id(
+            owner,
+            &CurrencyId(side.currency),
+            db_txn,
+            currency_cache.clone(),
+        )
+        .await
+        .map_err(CreateTxnErrors::DbErr)?;
+        db_txn = next_db_txn;
+
+        if let Some(currency) = currency {
+            if !fits_decimals(&side.amount, currency.decimals()) {
+                return Err(CreateTxnErrors::AmountPrecisionExceeded {
+                    amount: side.amount,
+                    currency_id: currency.id(),
+                    decimals: currency.decimals(),
+                });
+            }
+        }
+    }
+
+    Ok(db_txn)
+}
+
+/// Insert the fragments of a transaction, returning the saved rows.
+async fn insert_fragments(
+    txn_id: Uuid,
+    fragments: &[CreateTxnActionFragment],
+    db_txn: TransactionWithCallback,
+    owner: &AuthUser,
+) -> Result<(Vec<fragment::Model>, TransactionWithCallback), DbErr> {
+    let mut inserted = Vec::with_capacity(fragments.len());
+    for frag in fragments {
+        let fragment_to_save = fragment::ActiveModel {
+            from_account: ActiveValue::Set(frag.from.clone().map(|x| x.account)),
+            from_amount: ActiveValue::Set(frag.from.clone().map(|x| x.amount)),
+            from_currency_id: ActiveValue::Set(frag.from.clone().map(|x| x.currency)),
+            id: ActiveValue::Set(uuid::Uuid::new_v4()),
+            owner_id: ActiveValue::Set(owner.0),
+            to_account: ActiveValue::Set(frag.to.clone().map(|x| x.account)),
+            to_amount: ActiveValue::Set(frag.to.clone().map(|x| x.amount)),
+            to_currency_id: ActiveValue::Set(frag.to.clone().map(|x| x.currency)),
+            parent_txn: ActiveValue::Set(txn_id),
+        };
+        inserted.push(fragment_to_save.insert(db_txn.get_db_txn()).await?);
+    }
+
+    Ok((inserted, db_txn))
+}
+
+/// Record the rate implied by every fragment converting a currency deriving its rates into
+/// another currency, as a datum at the date of the transaction linked to the fragment.
+///
+/// The other currency must not derive its rates itself, so derived datums never reference each
+/// other in a loop. Dates which already have a datum for the currency are left alone, which
+/// also makes the first fragment win within a transaction, and calling this again harmless.
+pub async fn derive_rate_datums(
+    owner: &AuthUser,
+    date: NaiveDateTime,
+    fragments: &[fragment::Model],
+    mut db_txn: TransactionWithCallback,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<(usize, TransactionWithCallback), DbErr> {
+    let mut derived_count = 0;
+    for frag in fragments {
+        let (Some(from_currency), Some(from_amount), Some(to_currency), Some(to_amount)) = (
+            frag.from_currency_id,
+            frag.from_amount,
+            frag.to_currency_id,
+            frag.to_amount,
+        ) else {
+            continue;
+        };
+        if from_currency == to_currency
+            || !is_valid_rate_amount(&from_amount)
+            || !is_valid_rate_amount(&to_amount)
+        {
+            continue;
+        }
+
+        let (from, next_db_txn) = get_currency_by_id(
+            owner,
+            &CurrencyId(from_currency),
+            db_txn,
+            currency_cache.clone(),
+        )
+        .await?;
+        let (to, next_db_txn) = get_currency_by_id(
+            owner,
+            &CurrencyId(to_currency),
+            next_db_txn,
+            currency_cache.clone(),
+        )
+        .await?;
+        db_txn = next_db_txn;
+
+        let derives =
+            |currency: &Option<Currency>| currency.as_ref().is_some_and(Currency::derives_rates);
+        let (ref_currency, ref_amount_currency, amount) = match (derives(&from), derives(&to)) {
+            (true, false) => (
+                from_currency,
+                to_currency,
+                to_amount.checked_div(from_amount),
+            ),
+            (false, true) => (
+                to_currency,
+                from_currency,
+                from_amount.checked_div(to_amount),
+            ),
+            (_, _) => continue,
+        };
+        let Some(amount) = amount
+            .map(|amount| amount.round_dp(RESTFUL_DIGITS).normalize())
+            .filter(is_valid_rate_amount)
+        else {
+            continue;
+        };
+
+        let existing = currency_rate_datum::Entity::find()
+            .filter(currency_rate_datum::Column::OwnerId.eq(owner.0))
+            .filter(currency_rate_datum::Column::RefCurrencyId.eq(ref_currency))
+            .filter(currency_rate_datum::Column::Date.eq(date))
+            .one(db_txn.get_db_txn())
+            .await?;
+        if existing.is_some() {
+            continue;
+        }
+
+        db_txn = match create_currency_rate_datum(
+            owner,
+            CreateCurrencyRateDatumAction {
+                amount,
+                ref_currency_id: CurrencyId(ref_currency),
+                ref_amount_currency_id: CurrencyId(ref_amount_currency),
+                owner: owner.clone(),
+                date,
+                source_fragment_id: Some(frag.id),
+            },
+            None,
+            db_txn,
+            rates_cache.clone(),
+            currency_cache.clone(),
+        )
+        .await
+        {
+            Ok((_, db_txn)) => db_txn,
+            Err(CreateCurrencyRateDatumErrors::DbErr(db_err)) => return Err(db_err),
+            Err(err) => {
+                return Err(DbErr::Custom(format!(
+                    "Unable to derive a rate datum from fragment {}: {}",
+                    frag.id,
+                    EndpointsErrors::from(err)
+                )))
+            }
+        };
+        derived_count += 1;
+    }
+
+    Ok((derived_count, db_txn))
+}
+
+/// Delete the datums derived from the given fragments, returning the dates they were at.
+async fn delete_derived_rate_datums(
+    owner: &AuthUser,
+    fragment_ids: Vec<Uuid>,
+    mut db_txn: TransactionWithCallback,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+) -> Result<(Vec<NaiveDateTime>, TransactionWithCallback), DbErr> {
+    let derived = currency_rate_datum::Entity::find()
+        .filter(currency_rate_datum::Column::OwnerId.eq(owner.0))
+        .filter(currency_rate_datum::Column::SourceFragmentId.is_in(fragment_ids))
+        .all(db_txn.get_db_txn())
+        .await?;
+    if derived.is_empty() {
+        return Ok((vec![], db_txn));
+    }
+    let derived_ids = derived.iter().map(|datum| datum.id).collect::<Vec<_>>();
+    let mut dates = derived
+        .into_iter()
+        .map(|datum| datum.date)
+        .collect::<Vec<_>>();
+    dates.sort();
+    dates.dedup();
+
+    currency_rate_datum::Entity::delete_many()
+        .filter(currency_rate_datum::Column::OwnerId.eq(owner.0))
+        .filter(currency_rate_datum::Column::Id.is_in(derived_ids.clone()))
+        .exec(db_txn.get_db_txn())
+        .await?;
+
+    let owner = owner.clone();
+    db_txn.add_callback(async move {
+        rates_cache.lock().await.remove_items(&owner, &derived_ids);
+    });
+    Ok((dates, db_txn))
+}
+
+/// Delete the fragments of a transaction, with the datums derived from them.
+/// The rates of those dates are then derived again from the fragments of the other transactions.
+async fn delete_fragments(
+    owner: &AuthUser,
+    txn: &txn::Model,
+    db_txn: TransactionWithCallback,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+) -> Result<TransactionWithCallback, DbErr> {
+    let fragment_ids = txn
+        .find_related(fragment::Entity)
+        .all(db_txn.get_db_txn())
+        .await?
+        .into_iter()
+        .map(|fragment| fragment.id)
+        .collect::<Vec<_>>();
+    let (dates, db_txn) =
+        delete_derived_rate_datums(owner, fragment_ids, db_txn, rates_cache.clone()).await?;
+
+    fragment::Entity::delete_many()
+        .filter(fragment::Column::OwnerId.eq(owner.0))
+        .filter(fragment::Column::ParentTxn.eq(txn.id))
+        .exec(db_txn.get_db_txn())
+        .await?;
+    if dates.is_empty() {
+        return Ok(db_txn);
+    }
+
+    let others = txn::Entity::find()
+        .filter(txn::Column::OwnerId.eq(owner.0))
+        .filter(txn::Column::Date.is_in(dates))
+        .filter(txn::Column::Id.ne(txn.id))
+        .order_by_asc(txn::Column::Date)
+        .order_by_asc(txn::Column::Id)
+        .find_with_related(fragment::Entity)
+        .all(db_txn.get_db_txn())
+        .await?;
+    let mut db_txn = db_txn;
+    for (other, fragments) in others {
+        (_, db_txn) = derive_rate_datums(
+            owner,
+            other.date,
+            &fragments,
+            db_txn,
+            rates_cache.clone(),
+            currency_cache.clone(),
+        )
+        .await?;
+    }
+    Ok(db_txn)
+}
+
+/// Create a transaction with its fragments. A new id is generated unless `preset_id` is given.
+/// Rates are derived from the fragments, see [`derive_rate_datums`].
+pub async fn create_txn(
+    txn: CreateTxnAction,
+    fragments: &[CreateTxnActionFragment],
+    preset_id: Option<Uuid>,
+    db_txn: TransactionWithCallback,
+    owner: &AuthUser,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+) -> Result<(Uuid, TransactionWithCallback), CreateTxnErrors> {
+    let db_txn = validate_fragments(fragments, db_txn, owner, currency_cache.clone()).await?;
+
+    let generated_txn_uuid = preset_id.unwrap_or_else(uuid::Uuid::new_v4);
     let active_model = {
         let mut model = txn::ActiveModel::new();
         model.id = ActiveValue::Set(generated_txn_uuid);
@@ -178,30 +499,110 @@ pub async fn create_txn(
 
     let _inserted_txn = _inserted_txn?;
 
-    let fragment_models = {
-        let mut models: Vec<fragment::ActiveModel> = vec![];
-        for frag in fragments {
-            models.push(fragment::ActiveModel {
-                from_account: ActiveValue::Set(frag.from.clone().map(|x| x.account)),
-                from_amount: ActiveValue::Set(frag.from.clone().map(|x| x.amount.to_string())),
-                from_currency_id: ActiveValue::Set(frag.from.clone().map(|x| x.currency)),
-                id: ActiveValue::Set(uuid::Uuid::new_v4()),
-                owner_id: ActiveValue::Set(owner.0),
-                to_account: ActiveValue::Set(frag.to.clone().map(|x| x.account)),
-                to_amount: ActiveValue::Set(frag.to.clone().map(|x| x.amount.to_string())),
-                to_currency_id: ActiveValue::Set(frag.to.clone().map(|x| x.currency)),
-                parent_txn: ActiveValue::Set(generated_txn_uuid),
-            });
-        }
-        models
-    };
-
-    for fragment_to_save in fragment_models {
-        fragment_to_save
-            .insert(db_txn.get_db_txn())
+    let (inserted_fragments, db_txn) =
+        insert_fragments(generated_txn_uuid, fragments, db_txn, owner)
             .await
             .map_err(CreateTxnErrors::DbErr)?;
-    }
+
+    let (_, db_txn) = derive_rate_datums(
+        owner,
+        txn.date,
+        &inserted_fragments,
+        db_txn,
+        rates_cache,
+        currency_cache,
+    )
+    .await
+    .map_err(CreateTxnErrors::DbErr)?;
 
     Ok((generated_txn_uuid, db_txn))
 }
+
+/// Replace a transaction and its fragments. The datums derived from the old fragments are
+/// deleted, and derived again from the new ones.
+pub async fn update_txn(
+    id: Uuid,
+    txn: CreateTxnAction,
+    fragments: &[CreateTxnActionFragment],
+    db_txn: TransactionWithCallback,
+    owner: &AuthUser,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+) -> Result<TransactionWithCallback, UpdateTxnErrors> {
+    let (existing, db_txn) = get_txn_by_id(owner, id, db_txn)
+        .await
+        .map_err(|err| UpdateTxnErrors::InvalidTxn(CreateTxnErrors::DbErr(err)))?;
+    let existing = existing.ok_or(UpdateTxnErrors::TxnNotFound(id))?;
+
+    let db_txn = validate_fragments(fragments, db_txn, owner, currency_cache.clone())
+        .await
+        .map_err(UpdateTxnErrors::InvalidTxn)?;
+
+    let result = async {
+        let db_txn = delete_fragments(
+            owner,
+            &existing,
+            db_txn,
+            currency_cache.clone(),
+            rates_cache.clone(),
+        )
+        .await?;
+
+        let mut active_model: txn::ActiveModel = existing.into();
+        active_model.date = ActiveValue::Set(txn.date);
+        active_model.title = ActiveValue::Set(txn.title);
+        active_model.description = ActiveValue::Set(txn.description);
+        active_model.update(db_txn.get_db_txn()).await?;
+
+        let (inserted_fragments, db_txn) = insert_fragments(id, fragments, db_txn, owner).await?;
+        derive_rate_datums(
+            owner,
+            txn.date,
+            &inserted_fragments,
+            db_txn,
+            rates_cache,
+            currency_cache,
+        )
+        .await
+    }
+    .await;
+
+    result
+        .map(|(_, db_txn)| db_txn)
+        .map_err(|err| UpdateTxnErrors::InvalidTxn(CreateTxnErrors::DbErr(err)))
+}
+
+/// Delete a transaction with its fragments, and the datums derived from them.
+/// The hash of a transfer it was synced from is kept, so that the transfer is not synced again.
+pub async fn delete_txn(
+    id: Uuid,
+    db_txn: TransactionWithCallback,
+    owner: &AuthUser,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+) -> Result<TransactionWithCallback, DeleteTxnErrors> {
+    let (existing, db_txn) = get_txn_by_id(owner, id, db_txn)
+        .await
+        .map_err(DeleteTxnErrors::DbErr)?;
+    let existing = existing.ok_or(DeleteTxnErrors::TxnNotFound(id))?;
+
+    let db_txn = delete_fragments(owner, &existing, db_txn, currency_cache, rates_cache)
+        .await
+        .map_err(DeleteTxnErrors::DbErr)?;
+    wallet_txn::Entity::update_many()
+        .col_expr(
+            wallet_txn::Column::TxnId,
+            sea_orm::sea_query::Expr::value(Option::<Uuid>::None),
+        )
+        .filter(wallet_txn::Column::OwnerId.eq(owner.0))
+        .filter(wallet_txn::Column::TxnId.eq(id))
+        .exec(db_txn.get_db_txn())
+        .await
+        .map_err(DeleteTxnErrors::DbErr)?;
+    existing
+        .delete(db_txn.get_db_txn())
+        .await
+        .map_err(DeleteTxnErrors::DbErr)?;
+
+    Ok(db_txn)
+}
@@ -0,0 +1,576 @@
+use crate::caches::currency_cache::CurrencyCache;
+use crate::caches::currency_rate_datum::CurrencyRateDatumCache;
+use crate::caches::txn_tag::TxnTagsCache;
+use crate::date::{iso8601_to_js_iso, js_iso_to_iso8601, ParseISO8601Errors};
+use crate::entities::{
+    currency, currency_rate_datum, currency_rate_source, txn_tag, wallet, wallet_txn,
+};
+use crate::extended_models::account::AccountId;
+use crate::extended_models::currency::{
+    CreateCurrencyAction, CurrencyId, DEFAULT_CURRENCY_DECIMALS,
+};
+use crate::extended_models::user_archive::{
+    UserArchive, UserArchiveCurrency, UserArchiveFragmentSide, OLDEST_USER_ARCHIVE_VERSION,
+    USER_ARCHIVE_VERSION,
+};
+use crate::extractors::auth_user::AuthUser;
+use crate::routes::bootstrap::EndpointsErrors;
+use crate::routes::currency_rate_datums::CreateCurrencyRateDatumAction;
+use crate::services::accounts::{create_account, get_accounts};
+use crate::services::currencies::{create_currency, CreateCurrencyErrors};
+use crate::services::currency_rate_datum::{
+    create_currency_rate_datum, CreateCurrencyRateDatumErrors,
+};
+use crate::services::currency_rate_sources::{
+    create_currency_rate_source, CreateCurrencyRateSourceAction, CurrencyRateSourceErrors,
+};
+use crate::services::txn_tags::create_txn_tag;
+use crate::services::txns::{
+    create_txn, get_txns, CreateTxnAction, CreateTxnActionFragment, CreateTxnActionFragmentSide,
+    CreateTxnErrors,
+};
+use crate::services::wallets::{
+    create_wallet, create_wallet_txn, CreateWalletAction, WalletErrors,
+};
+use crate::services::TransactionWithCallback;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Collect every row owned by the given user into a [`UserArchive`].
+pub async fn export_user_archive(
+    owner: &AuthUser,
+    db_txn: TransactionWithCallback,
+) -> Result<(UserArchive, TransactionWithCallback), DbErr> {
+    let (accounts, db_txn) = get_accounts(owner, db_txn).await?;
+
+    let currencies = currency::Entity::find()
+        .filter(currency::Column::OwnerId.eq(owner.0))
+        .all(db_txn.get_db_txn())
+        .await?;
+
+    let currency_rate_datums = currency_rate_datum::Entity::find()
+        .filter(currency_rate_datum::Column::OwnerId.eq(owner.0))
+        // Derived datums are derived again when the transactions are imported
+        .filter(currency_rate_datum::Column::SourceFragmentId.is_null())
+        .order_by_asc(currency_rate_datum::Column::Date)
+        .all(db_txn.get_db_txn())
+        .await?;
+
+    let txn_tags = txn_tag::Entity::find()
+        .filter(txn_tag::Column::OwnerId.eq(owner.0))
+        .all(db_txn.get_db_txn())
+        .await?;
+
+    let (txns, db_txn) = get_txns(owner, db_txn).await?;
+
+    let currency_rate_sources = currency_rate_source::Entity::find()
+        .filter(currency_rate_source::Column::OwnerId.eq(owner.0))
+        .order_by_asc(currency_rate_source::Column::Name)
+        .all(db_txn.get_db_txn())
+        .await?;
+
+    let wallets = wallet::Entity::find()
+        .filter(wallet::Column::OwnerId.eq(owner.0))
+        .order_by_asc(wallet::Column::Name)
+        .all(db_txn.get_db_txn())
+        .await?;
+
+    let wallet_txns = wallet_txn::Entity::find()
+        .filter(wallet_txn::Column::OwnerId.eq(owner.0))
+        .all(db_txn.get_db_txn())
+        .await?;
+
+    Ok((
+        UserArchive {
+            version: USER_ARCHIVE_VERSION,
+            exported_at: iso8601_to_js_iso(chrono::Utc::now()),
+            owner: owner.0,
+            accounts: accounts.into_iter().map(Into::into).collect(),
+            currencies: currencies.into_iter().map(Into::into).collect(),
+            currency_rate_datums: currency_rate_datums.into_iter().map(Into::into).collect(),
+            txn_tags: txn_tags.into_iter().map(Into::into).collect(),
+            txns: txns.into_iter().map(Into::into).collect(),
+            currency_rate_sources: Some(
+                currency_rate_sources.into_iter().map(Into::into).collect(),
+            ),
+            wallets: Some(
+                wallets
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            wallet_txns: Some(
+                wallet_txns
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+        },
+        db_txn,
+    ))
+}
+
+#[derive(Debug)]
+pub enum ImportUserArchiveErrors {
+    UnsupportedVersion(u32),
+    InvalidArchive(String),
+    InvalidDate(ParseISO8601Errors),
+    InvalidDecimalValue(String),
+    DbErr(DbErr),
+    CreateCurrency(CreateCurrencyErrors),
+    CreateCurrencyRateDatum(CreateCurrencyRateDatumErrors),
+    CreateTxn(CreateTxnErrors),
+    CreateCurrencyRateSource(CurrencyRateSourceErrors),
+    CreateWallet(WalletErrors),
+}
+
+impl From<ImportUserArchiveErrors> for EndpointsErrors {
+    fn from(value: ImportUserArchiveErrors) -> Self {
+        match value {
+            ImportUserArchiveErrors::UnsupportedVersion(version) => {
+                EndpointsErrors::UnsupportedArchiveVersion(version)
+            }
+            ImportUserArchiveErrors::InvalidArchive(msg) => EndpointsErrors::InvalidArchive(msg),
+            ImportUserArchiveErrors::InvalidDate(err) => EndpointsErrors::ParseISO8601Errors(err),
+            ImportUserArchiveErrors::InvalidDecimalValue(value) => {
+                EndpointsErrors::InvalidDecimalValue(value)
+            }
+            ImportUserArchiveErrors::DbErr(db_err) => EndpointsErrors::DbErr(db_err),
+            ImportUserArchiveErrors::CreateCurrency(err) => err.into(),
+            ImportUserArchiveErrors::CreateCurrencyRateDatum(err) => err.into(),
+            ImportUserArchiveErrors::CreateTxn(err) => err.into(),
+            ImportUserArchiveErrors::CreateCurrencyRateSource(err) => err.into(),
+            ImportUserArchiveErrors::CreateWallet(err) => err.into(),
+        }
+    }
+}
+
+fn parse_archive_date(date: &str) -> Result<NaiveDateTime, ImportUserArchiveErrors> {
+    js_iso_to_iso8601(date)
+        .map(|date| date.naive_utc())
+        .map_err(ImportUserArchiveErrors::InvalidDate)
+}
+
+fn parse_archive_amount(amount: &str) -> Result<Decimal, ImportUserArchiveErrors> {
+    Decimal::from_str_exact(amount)
+        .map_err(|_| ImportUserArchiveErrors::InvalidDecimalValue(amount.to_string()))
+}
+
+fn collect_unique_ids(
+    kind: &str,
+    ids: impl Iterator<Item = Uuid>,
+) -> Result<HashSet<Uuid>, ImportUserArchiveErrors> {
+    let mut output = HashSet::new();
+    for id in ids {
+        if !output.insert(id) {
+            return Err(ImportUserArchiveErrors::InvalidArchive(format!(
+                "Repeated {kind} id {id}."
+            )));
+        }
+    }
+    Ok(output)
+}
+
+/// Check that the given archive is self-consistent before anything is written.
+/// Every id referenced inside the archive must be defined inside the same archive,
+/// and every currency fallback chain must end at the base currency.
+pub fn validate_user_archive(archive: &UserArchive) -> Result<(), ImportUserArchiveErrors> {
+    type E = ImportUserArchiveErrors;
+    if !(OLDEST_USER_ARCHIVE_VERSION..=USER_ARCHIVE_VERSION).contains(&archive.version) {
+        return Err(E::UnsupportedVersion(archive.version));
+    }
+
+    let account_ids = collect_unique_ids("account", archive.accounts.iter().map(|x| x.id))?;
+    let currency_ids = collect_unique_ids("currency", archive.currencies.iter().map(|x| x.id))?;
+    collect_unique_ids(
+        "currency rate datum",
+        archive.currency_rate_datums.iter().map(|x| x.id),
+    )?;
+    collect_unique_ids("txn tag", archive.txn_tags.iter().map(|x| x.id))?;
+    let txn_ids = collect_unique_ids("txn", archive.txns.iter().map(|x| x.id))?;
+    let currency_rate_sources = archive.currency_rate_sources.as_deref().unwrap_or_default();
+    collect_unique_ids(
+        "currency rate source",
+        currency_rate_sources.iter().map(|x| x.id),
+    )?;
+    let wallets = archive.wallets.as_deref().unwrap_or_default();
+    collect_unique_ids("wallet", wallets.iter().map(|x| x.id))?;
+
+    for account in archive.accounts.iter() {
+        parse_archive_date(&account.creation_date)?;
+    }
+
+    // Currencies
+    {
+        let currencies: HashMap<Uuid, &UserArchiveCurrency> =
+            archive.currencies.iter().map(|x| (x.id, x)).collect();
+
+        if archive.currencies.iter().filter(|x| x.is_base).count() > 1 {
+            return Err(E::InvalidArchive(
+                "At most 1 base currency is allowed.".to_string(),
+            ));
+        }
+
+        for currency in archive.currencies.iter() {
+            match (
+                currency.is_base,
+                &currency.fallback_rate_amount,
+                currency.fallback_rate_currency_id,
+            ) {
+                (true, None, None) => {}
+                (false, Some(fallback_rate_amount), Some(_)) => {
+                    parse_archive_amount(fallback_rate_amount)?;
+                }
+                _ => {
+                    return Err(E::InvalidArchive(format!(
+                        "Currency {} must either be base, or have both fallback amount and fallback currency.",
+                        currency.id
+                    )))
+                }
+            }
+
+            // Walk the fallback chain, it must end at the base currency without revisiting any currency.
+            let mut visited = HashSet::new();
+            let mut current = currency;
+            while let Some(next_id) = current.fallback_rate_currency_id {
+                if !visited.insert(current.id) {
+                    return Err(E::InvalidArchive(format!(
+                        "Currency {} has a cyclic fallback chain.",
+                        currency.id
+                    )));
+                }
+                current = currencies.get(&next_id).ok_or_else(|| {
+                    E::InvalidArchive(format!(
+                        "Currency {} falls back to unknown currency {next_id}.",
+                        current.id
+                    ))
+                })?;
+            }
+        }
+    }
+
+    for datum in archive.currency_rate_datums.iter() {
+        for ref_id in [datum.ref_currency_id, datum.ref_amount_currency_id] {
+            if !currency_ids.contains(&ref_id) {
+                return Err(E::InvalidArchive(format!(
+                    "Currency rate datum {} references unknown currency {ref_id}.",
+                    datum.id
+                )));
+            }
+        }
+        if datum.ref_currency_id == datum.ref_amount_currency_id {
+            return Err(E::InvalidArchive(format!(
+                "Currency rate datum {} references the same currency on both sides.",
+                datum.id
+            )));
+        }
+        parse_archive_amount(&datum.amount)?;
+        parse_archive_date(&datum.date)?;
+    }
+
+    for source in currency_rate_sources.iter() {
+        for ref_id in [source.ref_currency_id, source.ref_amount_currency_id] {
+            if !currency_ids.contains(&ref_id) {
+                return Err(E::InvalidArchive(format!(
+                    "Currency rate source {} references unknown currency {ref_id}.",
+                    source.id
+                )));
+            }
+        }
+    }
+
+    for wallet in wallets.iter() {
+        if !account_ids.contains(&wallet.account_id) {
+            return Err(E::InvalidArchive(format!(
+                "Wallet {} references unknown account {}.",
+                wallet.id, wallet.account_id
+            )));
+        }
+        if !currency_ids.contains(&wallet.currency_id) {
+            return Err(E::InvalidArchive(format!(
+                "Wallet {} references unknown currency {}.",
+                wallet.id, wallet.currency_id
+            )));
+        }
+    }
+
+    let mut wallet_txn_keys = HashSet::new();
+    for wallet_txn in archive.wallet_txns.as_deref().unwrap_or_default() {
+        if !wallet_txn_keys.insert((wallet_txn.chain, &wallet_txn.address, &wallet_txn.tx_hash)) {
+            return Err(E::InvalidArchive(format!(
+                "Repeated wallet txn {} of {}.",
+                wallet_txn.tx_hash, wallet_txn.address
+            )));
+        }
+        if let Some(txn_id) = wallet_txn.txn_id.filter(|id| !txn_ids.contains(id)) {
+            return Err(E::InvalidArchive(format!(
+                "Wallet txn {} references unknown txn {txn_id}.",
+                wallet_txn.tx_hash
+            )));
+        }
+    }
+
+    for txn in archive.txns.iter() {
+        parse_archive_date(&txn.date)?;
+        for side in txn
+            .fragments
+            .iter()
+            .flat_map(|frag| [frag.from.as_ref(), frag.to.as_ref()])
+            .flatten()
+        {
+            if !account_ids.contains(&side.account) {
+                return Err(E::InvalidArchive(format!(
+                    "Txn {} references unknown account {}.",
+                    txn.id, side.account
+                )));
+            }
+            if !currency_ids.contains(&side.currency) {
+                return Err(E::InvalidArchive(format!(
+                    "Txn {} references unknown currency {}.",
+                    txn.id, side.currency
+                )));
+            }
+            parse_archive_amount(&side.amount)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Insert everything in the given archive under `owner`, in dependency order.
+/// If `remap_ids` is set, every row receives a freshly generated id and all references are rewritten,
+/// otherwise the ids in the archive are kept as-is.
+pub async fn import_user_archive(
+    owner: &AuthUser,
+    archive: &UserArchive,
+    remap_ids: bool,
+    db_txn: TransactionWithCallback,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+    txn_tags_cache: Arc<Mutex<TxnTagsCache>>,
+) -> Result<TransactionWithCallback, ImportUserArchiveErrors> {
+    type E = ImportUserArchiveErrors;
+    validate_user_archive(archive)?;
+
+    let id_map: HashMap<Uuid, Uuid> = match remap_ids {
+        false => HashMap::new(),
+        true => archive
+            .accounts
+            .iter()
+            .map(|x| x.id)
+            .chain(archive.currencies.iter().map(|x| x.id))
+            .chain(archive.currency_rate_datums.iter().map(|x| x.id))
+            .chain(archive.txn_tags.iter().map(|x| x.id))
+            .chain(archive.txns.iter().map(|x| x.id))
+            .chain(archive.currency_rate_sources.iter().flatten().map(|x| x.id))
+            .chain(archive.wallets.iter().flatten().map(|x| x.id))
+            .map(|id| (id, Uuid::new_v4()))
+            .collect(),
+    };
+    let map_id = |id: Uuid| id_map.get(&id).copied().unwrap_or(id);
+
+    let mut db_txn = db_txn;
+
+    for account in archive.accounts.iter() {
+        (_, db_txn) = create_account(
+            owner,
+            &account.name,
+            parse_archive_date(&account.creation_date)?,
+            Some(AccountId(map_id(account.id))),
+            db_txn,
+        )
+        .await
+        .map_err(E::DbErr)?;
+    }
+
+    for tag in archive.txn_tags.iter() {
+        (_, db_txn) = create_txn_tag(
+            owner,
+            &tag.name,
+            Some(map_id(tag.id)),
+            db_txn,
+            txn_tags_cache.clone(),
+        )
+        .await
+        .map_err(E::DbErr)?;
+    }
+
+    // Currencies must be created after the currency they fall back to.
+    {
+        let mut inserted = HashSet::new();
+        let mut pending = archive.currencies.iter().collect::<Vec<_>>();
+        while !pending.is_empty() {
+            let (ready, rest): (Vec<_>, Vec<_>) = pending.into_iter().partition(|x| {
+                x.fallback_rate_currency_id
+                    .is_none_or(|fallback_id| inserted.contains(&fallback_id))
+            });
+            if ready.is_empty() {
+                return Err(E::InvalidArchive(
+                    "Currencies have cyclic fallback chains.".to_string(),
+                ));
+            }
+            for currency in ready {
+                let decimals = currency.decimals.unwrap_or(DEFAULT_CURRENCY_DECIMALS);
+                let kind = currency.kind.unwrap_or_default();
+                let archived = currency.archived.unwrap_or(false);
+                let action = match (
+                    &currency.fallback_rate_amount,
+                    currency.fallback_rate_currency_id,
+                ) {
+                    (Some(fallback_rate_amount), Some(fallback_rate_currency_id)) => {
+                        CreateCurrencyAction::Normal {
+                            name: currency.name.clone(),
+                            owner: owner.clone(),
+                            ticker: currency.ticker.clone(),
+                            decimals,
+                            kind,
+                            symbol: currency.symbol.clone(),
+                            archived,
+                            rate_strategy: currency.rate_strategy.unwrap_or_default(),
+                            rate_max_gap_days: currency.rate_max_gap_days,
+                            derive_rates: currency.derive_rates.unwrap_or(false),
+                            fallback_rate_amount: parse_archive_amount(fallback_rate_amount)?,
+                            fallback_rate_currency_id: CurrencyId(map_id(
+                                fallback_rate_currency_id,
+                            )),
+                        }
+                    }
+                    (_, _) => CreateCurrencyAction::Base {
+                        name: currency.name.clone(),
+                        owner: owner.clone(),
+                        ticker: currency.ticker.clone(),
+                        decimals,
+                        kind,
+                        symbol: currency.symbol.clone(),
+                        archived,
+                    },
+                };
+                (_, db_txn) = create_currency(
+                    action,
+                    Some(CurrencyId(map_id(currency.id))),
+                    db_txn,
+                    currency_cache.clone(),
+                )
+                .await
+                .map_err(E::CreateCurrency)?;
+                inserted.insert(currency.id);
+            }
+            pending = rest;
+        }
+    }
+
+    for datum in archive.currency_rate_datums.iter() {
+        (_, db_txn) = create_currency_rate_datum(
+            owner,
+            CreateCurrencyRateDatumAction {
+                amount: parse_archive_amount(&datum.amount)?,
+                ref_currency_id: CurrencyId(map_id(datum.ref_currency_id)),
+                ref_amount_currency_id: CurrencyId(map_id(datum.ref_amount_currency_id)),
+                owner: owner.clone(),
+                date: parse_archive_date(&datum.date)?,
+                source_fragment_id: None,
+            },
+            Some(map_id(datum.id)),
+            db_txn,
+            rates_cache.clone(),
+            currency_cache.clone(),
+        )
+        .await
+        .map_err(E::CreateCurrencyRateDatum)?;
+    }
+
+    for txn in archive.txns.iter() {
+        let map_side = |side: &Option<UserArchiveFragmentSide>| {
+            side.as_ref()
+                .map(|side| {
+                    parse_archive_amount(&side.amount).map(|amount| CreateTxnActionFragmentSide {
+                        account: map_id(side.account),
+                        amount,
+                        currency: map_id(side.currency),
+                    })
+                })
+                .transpose()
+        };
+        let mut fragments = Vec::with_capacity(txn.fragments.len());
+        for frag in txn.fragments.iter() {
+            fragments.push(CreateTxnActionFragment {
+                from: map_side(&frag.from)?,
+                to: map_side(&frag.to)?,
+            });
+        }
+
+        (_, db_txn) = create_txn(
+            CreateTxnAction {
+                date: parse_archive_date(&txn.date)?,
+                title: txn.title.clone(),
+                description: txn.description.clone(),
+            },
+            &fragments,
+            Some(map_id(txn.id)),
+            db_txn,
+            owner,
+            currency_cache.clone(),
+            rates_cache.clone(),
+        )
+        .await
+        .map_err(E::CreateTxn)?;
+    }
+
+    for source in archive.currency_rate_sources.iter().flatten() {
+        (_, db_txn) = create_currency_rate_source(
+            owner,
+            CreateCurrencyRateSourceAction {
+                name: source.name.clone(),
+                kind: source.kind,
+                ref_currency_id: CurrencyId(map_id(source.ref_currency_id)),
+                ref_amount_currency_id: CurrencyId(map_id(source.ref_amount_currency_id)),
+                hostname: source.hostname.clone(),
+                path: source.path.clone(),
+                json_query_string: source.json_query_string.clone(),
+            },
+            Some(map_id(source.id)),
+            db_txn,
+            currency_cache.clone(),
+        )
+        .await
+        .map_err(E::CreateCurrencyRateSource)?;
+    }
+
+    for wallet in archive.wallets.iter().flatten() {
+        (_, db_txn) = create_wallet(
+            owner,
+            CreateWalletAction {
+                name: wallet.name.clone(),
+                chain: wallet.chain,
+                address: wallet.address.clone(),
+                account_id: AccountId(map_id(wallet.account_id)),
+                currency_id: CurrencyId(map_id(wallet.currency_id)),
+            },
+            Some(map_id(wallet.id)),
+            db_txn,
+            currency_cache.clone(),
+        )
+        .await
+        .map_err(E::CreateWallet)?;
+    }
+
+    for wallet_txn in archive.wallet_txns.iter().flatten() {
+        db_txn = create_wallet_txn(
+            owner,
+            wallet_txn.chain,
+            &wallet_txn.address,
+            &wallet_txn.tx_hash,
+            wallet_txn.txn_id.map(map_id),
+            db_txn,
+        )
+        .await
+        .map_err(E::DbErr)?;
+    }
+
+    Ok(db_txn)
+}
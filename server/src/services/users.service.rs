@@ -1,5 +1,13 @@
-use crate::entities::{access_token, user};
+use crate::caches::currency_cache::CurrencyCache;
+use crate::caches::currency_rate_datum::CurrencyRateDatumCache;
+use crate::caches::txn_tag::TxnTagsCache;
+use crate::entities::{
+    access_token, account, currency, currency_rate_datum, currency_rate_source, fragment, txn,
+    txn_tag, user, wallet, wallet_txn,
+};
+use crate::extractors::auth_user::AuthUser;
 use crate::routes::bootstrap::EndpointsErrors;
+use crate::services::TransactionWithCallback;
 use argon2::password_hash::Error;
 use argon2::PasswordHasher;
 use argon2::{
@@ -7,6 +15,8 @@ use argon2::{
     Argon2, PasswordHash, PasswordVerifier,
 };
 use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 use sea_orm::ActiveValue;
 
@@ -55,6 +65,16 @@ impl From<VerifyCredsErr> for EndpointsErrors {
     }
 }
 
+pub async fn get_user_by_name(
+    username: &str,
+    db: &DatabaseConnection,
+) -> Result<Option<user::Model>, DbErr> {
+    user::Entity::find()
+        .filter(user::Column::Name.eq(username))
+        .one(db)
+        .await
+}
+
 pub async fn verify_creds(
     username: &str,
     password: &str,
@@ -126,3 +146,71 @@ pub async fn register_user(
         .await
         .map_err(RegisterUserErrors::DbErr)
 }
+
+/// Delete the given user together with every row they own.
+/// Rows are removed children-first so that no foreign key is violated midway.
+/// The caches are only evicted after the transaction is committed.
+pub async fn delete_user(
+    owner: &AuthUser,
+    db_txn: TransactionWithCallback,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+    rates_cache: Arc<Mutex<CurrencyRateDatumCache>>,
+    txn_tags_cache: Arc<Mutex<TxnTagsCache>>,
+) -> Result<TransactionWithCallback, DbErr> {
+    let mut db_txn = db_txn;
+    let conn = db_txn.get_db_txn();
+
+    wallet_txn::Entity::delete_many()
+        .filter(wallet_txn::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    wallet::Entity::delete_many()
+        .filter(wallet::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    fragment::Entity::delete_many()
+        .filter(fragment::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    txn::Entity::delete_many()
+        .filter(txn::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    currency_rate_datum::Entity::delete_many()
+        .filter(currency_rate_datum::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    currency_rate_source::Entity::delete_many()
+        .filter(currency_rate_source::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    currency::Entity::delete_many()
+        .filter(currency::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    account::Entity::delete_many()
+        .filter(account::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    txn_tag::Entity::delete_many()
+        .filter(txn_tag::Column::OwnerId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    access_token::Entity::delete_many()
+        .filter(access_token::Column::UserId.eq(owner.0))
+        .exec(conn)
+        .await?;
+    user::Entity::delete_many()
+        .filter(user::Column::Id.eq(owner.0))
+        .exec(conn)
+        .await?;
+
+    let owner = owner.clone();
+    db_txn.add_callback(async move {
+        currency_cache.lock().await.evict_owner(&owner);
+        rates_cache.lock().await.evict_owner(&owner);
+        txn_tags_cache.lock().await.evict_owner(&owner);
+    });
+
+    Ok(db_txn)
+}
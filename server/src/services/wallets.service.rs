@@ -0,0 +1,662 @@
+use super::accounts::find_first_unknown_account;
+use super::currencies::find_first_unknown_currencies;
+use super::txns::{
+    create_txn, CreateTxnAction, CreateTxnActionFragment, CreateTxnActionFragmentSide,
+};
+use crate::caches::currency_cache::CurrencyCache;
+use crate::entities::{wallet, wallet_txn};
+use crate::env::EnvWalletsSection;
+use crate::extended_models::account::AccountId;
+use crate::extended_models::currency::CurrencyId;
+use crate::extended_models::wallet::{Wallet, WalletChain};
+use crate::extractors::auth_user::AuthUser;
+use crate::routes::bootstrap::EndpointsErrors;
+use crate::services::TransactionWithCallback;
+use crate::states::database_states::DatabaseStates;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures::future::LocalBoxFuture;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::warn;
+use url::Url;
+use uuid::Uuid;
+
+/// Most pages of transfers read from a provider by a single sync of a wallet.
+/// The next sync carries on from the saved cursor.
+pub const MAX_SYNC_PAGES: usize = 50;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SyncWalletErrors {
+    #[error("No chain provider is configured for {}.", .0.as_str())]
+    NoProvider(WalletChain),
+    #[error("Request failed: {0}")]
+    Request(String),
+    #[error("Invalid response from the chain provider: {0}")]
+    InvalidResponse(String),
+    #[error("Unable to save transfer {hash}: {reason}")]
+    Save { hash: String, reason: String },
+}
+
+/// A confirmed transfer changing the balance of an address.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainTransfer {
+    /// Hash of the on-chain transaction, unique within a chain.
+    pub hash: String,
+    pub date: NaiveDateTime,
+    /// Change of the balance of the address, fees included. Negative when funds left the address.
+    pub amount: Decimal,
+}
+
+/// Transfers read from a provider, oldest first within the page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChainTransfersPage {
+    pub transfers: Vec<ChainTransfer>,
+    /// Where to resume after this page, `None` keeps the cursor given.
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+/// Somewhere the transfers of the addresses of a chain can be read from.
+pub trait ChainProvider {
+    /// Transfers of `address` after `cursor`, every transfer from the first one if it is `None`.
+    fn fetch_transfers<'a>(
+        &'a self,
+        address: &'a str,
+        cursor: Option<&'a str>,
+    ) -> LocalBoxFuture<'a, Result<ChainTransfersPage, SyncWalletErrors>>;
+}
+
+/// The provider used for the wallets of each chain.
+pub type ChainProviders = HashMap<WalletChain, Box<dyn ChainProvider>>;
+
+/// Transactions in a page of the Esplora API, a shorter page is the last one.
+pub const ESPLORA_PAGE_SIZE: usize = 25;
+
+/// Largest response read from a chain provider, in bytes.
+pub const MAX_PROVIDER_RESPONSE_SIZE: usize = 8 * 1024 * 1024;
+
+/// Amounts of the Esplora API are in satoshis, or litoshis, 10^-8 of a coin.
+const ESPLORA_AMOUNT_SCALE: u32 = 8;
+
+#[derive(Deserialize)]
+struct EsploraOutput {
+    scriptpubkey_address: Option<String>,
+    value: i64,
+}
+
+#[derive(Deserialize)]
+struct EsploraInput {
+    /// The output spent, missing for coinbase inputs.
+    prevout: Option<EsploraOutput>,
+}
+
+#[derive(Deserialize)]
+struct EsploraStatus {
+    block_time: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct EsploraTransaction {
+    txid: String,
+    vin: Vec<EsploraInput>,
+    vout: Vec<EsploraOutput>,
+    status: EsploraStatus,
+}
+
+/// Confirmed transfers of a Bitcoin-like chain, read from an [Esplora](https://github.com/Blockstream/esplora) API.
+/// The API lists transactions newest first, so each sync walks back a page at a time from the
+/// newest transaction to the newest one of the previous walk, see [`EsploraCursor`].
+pub struct EsploraProvider {
+    pub base_url: Url,
+    pub timeout: Duration,
+}
+
+/// The base URL of an Esplora API, to which `/address/...` is appended.
+pub fn esplora_url(value: &str) -> Result<Url, String> {
+    let url = Url::parse(value).map_err(|err| err.to_string())?;
+    match matches!(url.scheme(), "http" | "https") && !url.cannot_be_a_base() {
+        true => Ok(url),
+        false => Err(format!("{value} is not an http or https URL.")),
+    }
+}
+
+/// Where the walk back over the transactions of an address stands.
+/// Saved as `newest` once the walk is done, and as `newest:lastSeen:stop` while it is not.
+#[derive(Debug, Default, PartialEq)]
+struct EsploraCursor {
+    /// The newest transaction when the walk started, where the next walk stops once this one is done.
+    newest: Option<String>,
+    /// The oldest transaction read by the walk, the next page starts after it.
+    last_seen: Option<String>,
+    /// The newest transaction of the previous walk, where this one stops.
+    stop: Option<String>,
+}
+
+impl EsploraCursor {
+    fn parse(cursor: Option<&str>) -> EsploraCursor {
+        let non_empty = |x: &str| Some(x.to_string()).filter(|x| !x.is_empty());
+        match cursor
+            .map(|x| x.splitn(3, ':').collect::<Vec<_>>())
+            .as_deref()
+        {
+            Some([newest, last_seen, stop]) => EsploraCursor {
+                newest: non_empty(newest),
+                last_seen: non_empty(last_seen),
+                stop: non_empty(stop),
+            },
+            Some([stop, ..]) => EsploraCursor {
+                stop: non_empty(stop),
+                ..Default::default()
+            },
+            _ => EsploraCursor::default(),
+        }
+    }
+}
+
+impl EsploraProvider {
+    /// Confirmed transactions of `address`, newest first, older than `last_seen` if given.
+    async fn fetch_page(
+        &self,
+        address: &str,
+        last_seen: Option<&str>,
+    ) -> Result<Vec<EsploraTransaction>, SyncWalletErrors> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| SyncWalletErrors::Request(format!("{} is not a base URL", self.base_url)))?
+            .pop_if_empty()
+            .extend(["address", address, "txs", "chain"])
+            .extend(last_seen);
+        let client = awc::Client::builder().timeout(self.timeout).finish();
+        let mut response = client
+            .get(url.as_str())
+            .insert_header(("accept", "application/json"))
+            .send()
+            .await
+            .map_err(|err| SyncWalletErrors::Request(err.to_string()))?;
+        if !response.status().is_success() {
+            return Err(SyncWalletErrors::Request(format!(
+                "responded with status {}",
+                response.status().as_u16()
+            )));
+        }
+        let body = response
+            .body()
+            .limit(MAX_PROVIDER_RESPONSE_SIZE)
+            .await
+            .map_err(|err| SyncWalletErrors::Request(err.to_string()))?;
+        serde_json::from_slice(&body)
+            .map_err(|err| SyncWalletErrors::InvalidResponse(err.to_string()))
+    }
+}
+
+/// The change of the balance of `address` made by a transaction, its inputs spending from it.
+fn to_chain_transfer(
+    address: &str,
+    transaction: EsploraTransaction,
+) -> Result<ChainTransfer, SyncWalletErrors> {
+    let total = |outputs: Vec<&EsploraOutput>| -> i128 {
+        outputs
+            .into_iter()
+            .filter(|x| x.scriptpubkey_address.as_deref() == Some(address))
+            .map(|x| i128::from(x.value))
+            .sum()
+    };
+    let received = total(transaction.vout.iter().collect());
+    let spent = total(
+        transaction
+            .vin
+            .iter()
+            .filter_map(|x| x.prevout.as_ref())
+            .collect(),
+    );
+    let date = transaction
+        .status
+        .block_time
+        .and_then(|x| DateTime::from_timestamp(x, 0))
+        .ok_or_else(|| {
+            SyncWalletErrors::InvalidResponse(format!(
+                "transaction {} has no block time",
+                transaction.txid
+            ))
+        })?;
+    Ok(ChainTransfer {
+        hash: transaction.txid,
+        date: date.naive_utc(),
+        amount: Decimal::from_i128_with_scale(received - spent, ESPLORA_AMOUNT_SCALE).normalize(),
+    })
+}
+
+impl ChainProvider for EsploraProvider {
+    fn fetch_transfers<'a>(
+        &'a self,
+        address: &'a str,
+        cursor: Option<&'a str>,
+    ) -> LocalBoxFuture<'a, Result<ChainTransfersPage, SyncWalletErrors>> {
+        Box::pin(async move {
+            let cursor = EsploraCursor::parse(cursor);
+            let page = self
+                .fetch_page(address, cursor.last_seen.as_deref())
+                .await?;
+            let newest = cursor
+                .newest
+                .or_else(|| page.first().map(|x| x.txid.clone()));
+            let last_seen = page.last().map(|x| x.txid.clone());
+            let is_last_page = page.len() < ESPLORA_PAGE_SIZE;
+
+            let mut transfers = vec![];
+            let mut reached_stop = false;
+            for transaction in page {
+                if Some(&transaction.txid) == cursor.stop.as_ref() {
+                    reached_stop = true;
+                    break;
+                }
+                transfers.push(to_chain_transfer(address, transaction)?);
+            }
+            transfers.reverse();
+
+            let has_more = !reached_stop && !is_last_page;
+            let next_cursor = match (has_more, newest, last_seen) {
+                (true, Some(newest), Some(last_seen)) => Some(format!(
+                    "{newest}:{last_seen}:{}",
+                    cursor.stop.unwrap_or_default()
+                )),
+                (_, newest, _) => newest,
+            };
+            Ok(ChainTransfersPage {
+                transfers,
+                next_cursor,
+                has_more,
+            })
+        })
+    }
+}
+
+/// Providers of the chains configured in `settings`. Wallets of other chains record
+/// [SyncWalletErrors::NoProvider] when synced.
+pub fn chain_providers(settings: Option<&EnvWalletsSection>) -> Result<ChainProviders, String> {
+    let settings = settings.cloned().unwrap_or_default();
+    let timeout = settings.timeout();
+    let mut providers = ChainProviders::new();
+    for (chain, base_url) in [
+        (WalletChain::Btc, settings.btc_esplora_url),
+        (WalletChain::Ltc, settings.ltc_esplora_url),
+    ] {
+        if let Some(base_url) = base_url {
+            let provider = EsploraProvider {
+                base_url: esplora_url(&base_url)?,
+                timeout,
+            };
+            providers.insert(chain, Box::new(provider) as Box<dyn ChainProvider>);
+        }
+    }
+    Ok(providers)
+}
+
+#[derive(Debug)]
+pub enum WalletErrors {
+    DbErr(DbErr),
+    NotFound(Uuid),
+    AccountNotFound(AccountId),
+    CurrencyNotFound(CurrencyId),
+    InvalidWallet { field: String, reason: String },
+}
+
+impl From<WalletErrors> for EndpointsErrors {
+    fn from(value: WalletErrors) -> Self {
+        match value {
+            WalletErrors::DbErr(db_err) => EndpointsErrors::DbErr(db_err),
+            WalletErrors::NotFound(id) => EndpointsErrors::WalletNotFound(id),
+            WalletErrors::AccountNotFound(account_id) => {
+                EndpointsErrors::AccountNotFound(account_id)
+            }
+            WalletErrors::CurrencyNotFound(currency_id) => {
+                EndpointsErrors::CurrencyNotFound(currency_id)
+            }
+            WalletErrors::InvalidWallet { field, reason } => {
+                EndpointsErrors::InvalidWallet { field, reason }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateWalletAction {
+    pub name: String,
+    pub chain: WalletChain,
+    pub address: String,
+    pub account_id: AccountId,
+    pub currency_id: CurrencyId,
+}
+
+pub async fn create_wallet(
+    owner: &AuthUser,
+    action: CreateWalletAction,
+    preset_id: Option<Uuid>,
+    db_txn: TransactionWithCallback,
+    currency_cache: Arc<Mutex<CurrencyCache>>,
+) -> Result<(Wallet, TransactionWithCallback), WalletErrors> {
+    let invalid = |field: &str| WalletErrors::InvalidWallet {
+        field: field.to_string(),
+        reason: "must not be empty.".to_string(),
+    };
+    if action.name.trim().is_empty() {
+        return Err(invalid("name"));
+    }
+    let address = action.address.trim().to_string();
+    if address.is_empty() {
+        return Err(invalid("address"));
+    }
+
+    let (unknown_account, db_txn) = find_first_unknown_account(owner, &[action.account_id], db_txn)
+        .await
+        .map_err(WalletErrors::DbErr)?;
+    if let Some(unknown_account) = unknown_account {
+        return Err(WalletErrors::AccountNotFound(unknown_account));
+    }
+    let (unknown_currency, db_txn) =
+        find_first_unknown_currencies(owner, &[action.currency_id], db_txn, currency_cache)
+            .await
+            .map_err(WalletErrors::DbErr)?;
+    if let Some(unknown_currency) = unknown_currency {
+        return Err(WalletErrors::CurrencyNotFound(unknown_currency));
+    }
+
+    let model = wallet::ActiveModel {
+        id: ActiveValue::Set(preset_id.unwrap_or_else(Uuid::new_v4)),
+        owner_id: ActiveValue::Set(owner.0),
+        account_id: ActiveValue::Set(action.account_id.0),
+        currency_id: ActiveValue::Set(action.currency_id.0),
+        name: ActiveValue::Set(action.name),
+        chain: ActiveValue::Set(action.chain.as_str().to_string()),
+        address: ActiveValue::Set(address),
+        cursor: ActiveValue::Set(None),
+        last_sync_date: ActiveValue::Set(None),
+        last_error: ActiveValue::Set(None),
+    }
+    .insert(db_txn.get_db_txn())
+    .await
+    .map_err(WalletErrors::DbErr)?;
+    Ok((model.try_into().map_err(WalletErrors::DbErr)?, db_txn))
+}
+
+pub async fn get_wallets(
+    owner: &AuthUser,
+    db_txn: TransactionWithCallback,
+) -> Result<(Vec<Wallet>, TransactionWithCallback), DbErr> {
+    let models = wallet::Entity::find()
+        .filter(wallet::Column::OwnerId.eq(owner.0))
+        .order_by_asc(wallet::Column::Name)
+        .all(db_txn.get_db_txn())
+        .await?;
+    let wallets = models
+        .into_iter()
+        .map(Wallet::try_from)
+        .collect::<Result<_, _>>()?;
+    Ok((wallets, db_txn))
+}
+
+/// Delete a wallet. The transactions synced from it are kept, and so are the hashes of their
+/// transfers, so a wallet added again for the same address does not save them twice.
+pub async fn delete_wallet(
+    owner: &AuthUser,
+    id: &Uuid,
+    db_txn: TransactionWithCallback,
+) -> Result<TransactionWithCallback, WalletErrors> {
+    let result = wallet::Entity::delete_many()
+        .filter(wallet::Column::OwnerId.eq(owner.0))
+        .filter(wallet::Column::Id.eq(*id))
+        .exec(db_txn.get_db_txn())
+        .await
+        .map_err(WalletErrors::DbErr)?;
+    match result.rows_affected {
+        0 => Err(WalletErrors::NotFound(*id)),
+        _ => Ok(db_txn),
+    }
+}
+
+/// Save a transfer as a transaction with a single fragment on the account of the wallet,
+/// unless a transfer of the same hash was already synced for the address of the wallet, by any
+/// wallet. Returns whether one was saved.
+async fn save_transfer(
+    wallet: &Wallet,
+    transfer: &ChainTransfer,
+    db_txn: TransactionWithCallback,
+    states: &DatabaseStates,
+) -> Result<(bool, TransactionWithCallback), SyncWalletErrors> {
+    let save_error = |reason: String| SyncWalletErrors::Save {
+        hash: transfer.hash.clone(),
+        reason,
+    };
+    let existing = wallet_txn::Entity::find()
+        .filter(wallet_txn::Column::OwnerId.eq(wallet.owner.0))
+        .filter(wallet_txn::Column::Chain.eq(wallet.chain.as_str()))
+        .filter(wallet_txn::Column::Address.eq(wallet.address.as_str()))
+        .filter(wallet_txn::Column::TxHash.eq(transfer.hash.as_str()))
+        .one(db_txn.get_db_txn())
+        .await
+        .map_err(|err| save_error(err.to_string()))?;
+    if existing.is_some() || transfer.amount.is_zero() {
+        return Ok((false, db_txn));
+    }
+
+    let side = Some(CreateTxnActionFragmentSide {
+        account: wallet.account_id.0,
+        amount: transfer.amount.abs(),
+        currency: wallet.currency_id.0,
+    });
+    let (title, fragment) = match transfer.amount.is_sign_positive() {
+        true => (
+            format!("Received on {}", wallet.name),
+            CreateTxnActionFragment {
+                from: None,
+                to: side,
+            },
+        ),
+        false => (
+            format!("Sent from {}", wallet.name),
+            CreateTxnActionFragment {
+                from: side,
+                to: None,
+            },
+        ),
+    };
+    let (txn_id, db_txn) = create_txn(
+        CreateTxnAction {
+            date: transfer.date,
+            title,
+            description: transfer.hash.clone(),
+        },
+        &[fragment],
+        None,
+        db_txn,
+        &wallet.owner,
+        states.currency_cache.clone(),
+        states.currency_rate_datums_cache.clone(),
+    )
+    .await
+    .map_err(|err| save_error(EndpointsErrors::from(err).to_string()))?;
+
+    let db_txn = create_wallet_txn(
+        &wallet.owner,
+        wallet.chain,
+        &wallet.address,
+        &transfer.hash,
+        Some(txn_id),
+        db_txn,
+    )
+    .await
+    .map_err(|err| save_error(err.to_string()))?;
+    Ok((true, db_txn))
+}
+
+/// Record that the transfer `tx_hash` of `address` was synced, into `txn_id` if it still exists.
+pub async fn create_wallet_txn(
+    owner: &AuthUser,
+    chain: WalletChain,
+    address: &str,
+    tx_hash: &str,
+    txn_id: Option<Uuid>,
+    db_txn: TransactionWithCallback,
+) -> Result<TransactionWithCallback, DbErr> {
+    wallet_txn::ActiveModel {
+        owner_id: ActiveValue::Set(owner.0),
+        chain: ActiveValue::Set(chain.as_str().to_string()),
+        address: ActiveValue::Set(address.to_string()),
+        tx_hash: ActiveValue::Set(tx_hash.to_string()),
+        txn_id: ActiveValue::Set(txn_id),
+    }
+    .insert(db_txn.get_db_txn())
+    .await?;
+    Ok(db_txn)
+}
+
+/// Record the outcome of a sync on the wallet. `last_sync_date` only moves on success.
+async fn record_sync(
+    wallet: &Wallet,
+    date: Option<DateTime<Utc>>,
+    error: Option<String>,
+    db_txn: &TransactionWithCallback,
+) -> Result<(), DbErr> {
+    let mut update = wallet::Entity::update_many()
+        .col_expr(
+            wallet::Column::LastError,
+            sea_orm::sea_query::Expr::value(error),
+        )
+        .filter(wallet::Column::OwnerId.eq(wallet.owner.0))
+        .filter(wallet::Column::Id.eq(wallet.id));
+    if let Some(date) = date {
+        update = update.col_expr(
+            wallet::Column::LastSyncDate,
+            sea_orm::sea_query::Expr::value(date.naive_utc()),
+        );
+    }
+    update.exec(db_txn.get_db_txn()).await?;
+    Ok(())
+}
+
+/// Save the transfers of a wallet read from `provider` as transactions, page after page.
+/// Each page is saved with the cursor after it in a single transaction, and transfers already
+/// saved are skipped by hash, so syncing again after a failure is harmless.
+/// Returns the number of transactions saved, or the reason the sync stopped, which is also
+/// saved as `lastError`.
+pub async fn sync_wallet(
+    wallet: &Wallet,
+    provider: &dyn ChainProvider,
+    date: DateTime<Utc>,
+    states: &DatabaseStates,
+) -> Result<Result<usize, SyncWalletErrors>, DbErr> {
+    let mut cursor = wallet.cursor.clone();
+    let mut saved_count = 0;
+    let mut outcome = Ok(());
+    for _ in 0..MAX_SYNC_PAGES {
+        let page = match provider
+            .fetch_transfers(&wallet.address, cursor.as_deref())
+            .await
+        {
+            Ok(page) => page,
+            Err(err) => {
+                outcome = Err(err);
+                break;
+            }
+        };
+
+        let db_txn = TransactionWithCallback::from_db_conn(&states.db, vec![]).await?;
+        let page_saved = async {
+            let mut db_txn = db_txn;
+            let mut page_saved_count = 0;
+            for transfer in page.transfers.iter() {
+                let saved;
+                (saved, db_txn) = save_transfer(wallet, transfer, db_txn, states).await?;
+                page_saved_count += usize::from(saved);
+            }
+            Ok((page_saved_count, db_txn))
+        }
+        .await;
+        let (page_saved_count, db_txn) = match page_saved {
+            Ok(page_saved) => page_saved,
+            Err(err) => {
+                outcome = Err(err);
+                break;
+            }
+        };
+
+        if page.next_cursor.is_some() {
+            cursor = page.next_cursor;
+            wallet::Entity::update_many()
+                .col_expr(
+                    wallet::Column::Cursor,
+                    sea_orm::sea_query::Expr::value(cursor.clone()),
+                )
+                .filter(wallet::Column::OwnerId.eq(wallet.owner.0))
+                .filter(wallet::Column::Id.eq(wallet.id))
+                .exec(db_txn.get_db_txn())
+                .await?;
+        }
+        db_txn.commit().await;
+        saved_count += page_saved_count;
+
+        if !page.has_more {
+            break;
+        }
+    }
+
+    let db_txn = TransactionWithCallback::from_db_conn(&states.db, vec![]).await?;
+    let result = match outcome {
+        Ok(()) => {
+            record_sync(wallet, Some(date), None, &db_txn).await?;
+            Ok(saved_count)
+        }
+        Err(err) => {
+            warn!(
+                "Unable to sync wallet {} ({}): {}",
+                wallet.id, wallet.name, err
+            );
+            record_sync(wallet, None, Some(err.to_string()), &db_txn).await?;
+            Err(err)
+        }
+    };
+    db_txn.commit().await;
+    Ok(result)
+}
+
+/// Sync every wallet of every user once, one after another, with the provider of its chain.
+/// Returns the number of transactions saved.
+pub async fn sync_all_wallets(
+    providers: &ChainProviders,
+    states: &DatabaseStates,
+) -> Result<usize, DbErr> {
+    let wallets = wallet::Entity::find()
+        .order_by_asc(wallet::Column::OwnerId)
+        .all(&states.db)
+        .await?;
+    let mut saved = 0;
+    for wallet in wallets {
+        let id = wallet.id;
+        let wallet = match Wallet::try_from(wallet) {
+            Ok(wallet) => wallet,
+            Err(err) => {
+                warn!("Unable to sync wallet {id}: {err}");
+                continue;
+            }
+        };
+        let result = match providers.get(&wallet.chain) {
+            Some(provider) => sync_wallet(&wallet, provider.as_ref(), Utc::now(), states).await?,
+            None => {
+                let err = SyncWalletErrors::NoProvider(wallet.chain);
+                let db_txn = TransactionWithCallback::from_db_conn(&states.db, vec![]).await?;
+                record_sync(&wallet, None, Some(err.to_string()), &db_txn).await?;
+                db_txn.commit().await;
+                Err(err)
+            }
+        };
+        saved += result.unwrap_or(0);
+    }
+    Ok(saved)
+}
@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+pub static SHUTDOWN: LazyLock<Shutdown> = LazyLock::new(Shutdown::default);
+
+/// How far a [`crate::services::TransactionWithCallback`] got before it was dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionStage {
+    Open,
+    Committing,
+    RunningCallbacks,
+}
+
+/// Keeps track of the open database transactions, so a shutdown can wait for them
+/// and report the ones it had to cut off.
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    in_flight: AtomicUsize,
+    cut_off: AtomicUsize,
+    deadline: Mutex<Option<Instant>>,
+}
+
+impl Shutdown {
+    pub fn track(&'static self) -> InFlightTransaction {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlightTransaction {
+            shutdown: self,
+            stage: TransactionStage::Open,
+            started_at: Instant::now(),
+            finished: false,
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Number of transactions dropped unfinished after the shutdown deadline.
+    pub fn cut_off(&self) -> usize {
+        self.cut_off.load(Ordering::SeqCst)
+    }
+
+    /// Start the countdown, transactions still unfinished after `timeout` are reported as cut off.
+    pub fn begin(&self, timeout: Duration) {
+        *self.deadline.lock().unwrap() = Some(Instant::now() + timeout);
+        info!(
+            "Shutting down, waiting up to {}s for {} in-flight transaction(s).",
+            timeout.as_secs(),
+            self.in_flight()
+        );
+    }
+
+    pub fn is_past_deadline(&self) -> bool {
+        self.deadline
+            .lock()
+            .unwrap()
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// Guard held by every open transaction, see [`Shutdown`].
+#[derive(Debug)]
+pub struct InFlightTransaction {
+    shutdown: &'static Shutdown,
+    stage: TransactionStage,
+    started_at: Instant,
+    finished: bool,
+}
+
+impl InFlightTransaction {
+    pub fn set_stage(&mut self, stage: TransactionStage) {
+        self.stage = stage;
+    }
+
+    pub fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for InFlightTransaction {
+    fn drop(&mut self) {
+        self.shutdown.in_flight.fetch_sub(1, Ordering::SeqCst);
+        // Dropping early is the normal rollback path, it is only worth reporting when forced by shutdown
+        if !self.finished && self.shutdown.is_past_deadline() {
+            self.shutdown.cut_off.fetch_add(1, Ordering::SeqCst);
+            warn!(
+                stage = ?self.stage,
+                elapsed_ms = self.started_at.elapsed().as_millis() as u64,
+                "Transaction was cut off by the shutdown timeout."
+            );
+        }
+    }
+}
+
+/// Resolve on the first SIGINT or SIGTERM.
+#[cfg_attr(test, mutants::skip)]
+pub async fn wait_for_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use actix_web::rt::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate())?;
+        let interrupt = Box::pin(actix_web::rt::signal::ctrl_c());
+        let signalled = futures::future::select(interrupt, Box::pin(terminate.recv())).await;
+        match signalled {
+            futures::future::Either::Left((res, _)) => res,
+            futures::future::Either::Right(_) => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    actix_web::rt::signal::ctrl_c().await
+}
@@ -0,0 +1,106 @@
+use crate::env::ServerSSLSection;
+use actix_http::header;
+use actix_web::{web, HttpRequest, HttpResponse};
+use openssl::error::ErrorStack;
+use openssl::pkey::PKey;
+use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslMethod};
+use openssl::x509::X509;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LoadSSLErrors {
+    #[error("Unable to read \"{path}\": {source}")]
+    UnreadableFile {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("\"{path}\" is not a valid PEM certificate chain: {source}")]
+    InvalidCertificateChain { path: String, source: ErrorStack },
+    #[error("\"{path}\" does not contain any certificate.")]
+    EmptyCertificateChain { path: String },
+    #[error("\"{path}\" is not a valid PEM private key: {source}")]
+    InvalidPrivateKey { path: String, source: ErrorStack },
+    #[error("Private key \"{key_path}\" does not match certificate \"{pem_path}\": {source}")]
+    MismatchedPrivateKey {
+        pem_path: String,
+        key_path: String,
+        source: ErrorStack,
+    },
+    #[error("Unable to configure SSL: {0}")]
+    OpenSSL(#[from] ErrorStack),
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, LoadSSLErrors> {
+    std::fs::read(path).map_err(|source| LoadSSLErrors::UnreadableFile {
+        path: path.to_string(),
+        source,
+    })
+}
+
+impl ServerSSLSection {
+    /// Load the certificate chain and private key, and make sure they belong together.
+    /// This is called before the server binds so misconfigured files are reported at startup.
+    pub fn to_ssl_acceptor(&self) -> Result<SslAcceptorBuilder, LoadSSLErrors> {
+        let chain = X509::stack_from_pem(&read_file(&self.pem_path)?).map_err(|source| {
+            LoadSSLErrors::InvalidCertificateChain {
+                path: self.pem_path.clone(),
+                source,
+            }
+        })?;
+        let key = PKey::private_key_from_pem(&read_file(&self.key_path)?).map_err(|source| {
+            LoadSSLErrors::InvalidPrivateKey {
+                path: self.key_path.clone(),
+                source,
+            }
+        })?;
+
+        let mut chain = chain.into_iter();
+        let leaf = chain
+            .next()
+            .ok_or_else(|| LoadSSLErrors::EmptyCertificateChain {
+                path: self.pem_path.clone(),
+            })?;
+
+        let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+        builder.set_certificate(&leaf)?;
+        for intermediate in chain {
+            builder.add_extra_chain_cert(intermediate)?;
+        }
+        let mismatched = |source| LoadSSLErrors::MismatchedPrivateKey {
+            pem_path: self.pem_path.clone(),
+            key_path: self.key_path.clone(),
+            source,
+        };
+        builder.set_private_key(&key).map_err(mismatched)?;
+        builder.check_private_key().map_err(mismatched)?;
+        Ok(builder)
+    }
+}
+
+/// The port HTTPS is served on, used by [`redirect_to_https`] to build the target URL.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpsPort(pub u16);
+
+/// Permanently redirect any plain HTTP request to the same host, path and query on HTTPS.
+pub async fn redirect_to_https(req: HttpRequest, https_port: web::Data<HttpsPort>) -> HttpResponse {
+    let connection_info = req.connection_info();
+    let host = connection_info.host();
+    let hostname = match host.rsplit_once(':') {
+        // Keep IPv6 literals such as "[::1]" intact when there is no port.
+        Some((hostname, port)) if !port.ends_with(']') => hostname,
+        _ => host,
+    };
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|x| x.as_str())
+        .unwrap_or("/");
+    let location = match https_port.0 {
+        443 => format!("https://{hostname}{path_and_query}"),
+        port => format!("https://{hostname}:{port}{path_and_query}"),
+    };
+
+    HttpResponse::PermanentRedirect()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
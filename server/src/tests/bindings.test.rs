@@ -0,0 +1,203 @@
+#[cfg(test)]
+pub mod bindings {
+
+    use crate::tests::bindings_tests::bindings::drivers::*;
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+
+    pub mod drivers {
+        use super::*;
+        use crate::extended_models::user_archive::UserArchive;
+        use crate::routes::{
+            accounts, bootstrap, currencies, currency_rate_datums, currency_rate_sources, probes,
+            txn_tags, txns, users, wallets,
+        };
+        use ts_rs::TS;
+
+        /// Where the frontend imports the bindings from, relative to the server crate.
+        pub const COMMITTED_BINDINGS_DIR: &str = "../frontend/src/types/dtos";
+
+        /// Set to overwrite the committed bindings instead of failing when they are stale.
+        pub const UPDATE_BINDINGS_ENV: &str = "FM_UPDATE_BINDINGS";
+
+        /// First line of every file written by ts-rs, telling them apart from handwritten files.
+        pub const GENERATED_NOTE: &str = "// This file was generated by [ts-rs]";
+
+        macro_rules! export_all_to {
+            ($dir:expr, $($ty:ty),* $(,)?) => {
+                $(<$ty as TS>::export_all_to($dir).expect("Unable to export bindings.");)*
+            };
+        }
+
+        /// Export every DTO, with its dependencies, into `dir`.
+        pub fn export_dtos(dir: &Path) {
+            export_all_to!(
+                dir,
+                bootstrap::ErrorResponseBody,
+                probes::health::GetHealthResponseBody,
+                probes::ready::GetReadyResponseBody,
+                probes::version::GetVersionResponseBody,
+                users::login::LoginRequestBody,
+                users::login::LoginResponseBody,
+                users::register::PostUserRequestBody,
+                users::register::PostUserResponseBody,
+                users::delete_user::DeleteUserResponseBody,
+                users::import_user::PostImportUserQuery,
+                users::import_user::PostImportUserResponseBody,
+                UserArchive,
+                accounts::get_account::GetAccountQuery,
+                accounts::get_account::GetAccountResponse,
+                accounts::post_account::PostAccountRequestBody,
+                accounts::post_account::PostAccountResponseBody,
+                currencies::post_currency::PostCurrencyRequestBody,
+                currencies::post_currency::PostCurrencyResponseBody,
+                currencies::convert_currency::ConvertCurrencyQuery,
+                currencies::convert_currency::ConvertCurrencyResponse,
+                currencies::convert_currency::PostConvertCurrencyRequest,
+                currencies::convert_currency::PostConvertCurrencyResponse,
+                currencies::patch_currency::PatchCurrencyRequestBody,
+                currencies::patch_currency::PatchCurrencyResponseBody,
+                currencies::get_currency::GetCurrencyQuery,
+                currencies::get_currency::GetCurrencyResponse,
+                currencies::get_currency::GetCurrencyResponseV1,
+                currencies::get_currency::GetCurrencyRateExplanation,
+                currency_rate_datums::post_currency_rate_datum::PostCurrencyRateDatumRequest,
+                currency_rate_datums::post_currency_rate_datum::PostCurrencyRateDatumResponse,
+                currency_rate_sources::CurrencyRateSourceItem,
+                currency_rate_sources::post_currency_rate_source::PostCurrencyRateSourceRequest,
+                currency_rate_sources::post_currency_rate_source::PostCurrencyRateSourceResponse,
+                currency_rate_sources::get_currency_rate_sources::GetCurrencyRateSourcesQuery,
+                currency_rate_sources::get_currency_rate_sources::GetCurrencyRateSourcesResponse,
+                currency_rate_sources::patch_currency_rate_source::PatchCurrencyRateSourceRequest,
+                currency_rate_sources::delete_currency_rate_source::DeleteCurrencyRateSourceResponse,
+                txn_tags::create_tag::PostTxnTagRequestBody,
+                txn_tags::create_tag::PostTxnTagResponseBody,
+                txn_tags::get_tags::GetTxnTagsResponseBody,
+                txns::get_txns::GetTxnsResponse,
+                txns::post_txns::PostTxnRequest,
+                txns::post_txns::PostTxnResponse,
+                txns::put_txn::PutTxnResponse,
+                txns::delete_txn::DeleteTxnResponse,
+                wallets::WalletItem,
+                wallets::post_wallet::PostWalletRequest,
+                wallets::post_wallet::PostWalletResponse,
+                wallets::get_wallets::GetWalletsResponse,
+                wallets::delete_wallet::DeleteWalletResponse,
+            );
+        }
+
+        /// Every `*.ts` file of `dir` written by ts-rs, by file name.
+        pub fn read_bindings(dir: &Path) -> BTreeMap<String, String> {
+            let mut bindings = BTreeMap::new();
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+                if !file_name.ends_with(".ts") {
+                    continue;
+                }
+                let content = std::fs::read_to_string(&path).unwrap();
+                if content.starts_with(GENERATED_NOTE) {
+                    bindings.insert(file_name, content);
+                }
+            }
+            bindings
+        }
+
+        /// Name of every type carrying `#[ts(export)]` under `dir`.
+        pub fn exported_type_names(dir: &Path) -> Vec<String> {
+            let mut names = vec![];
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    names.extend(exported_type_names(&path));
+                    continue;
+                }
+                if path.extension().is_none_or(|x| x != "rs") {
+                    continue;
+                }
+                let mut pending_export = false;
+                for line in std::fs::read_to_string(&path).unwrap().lines() {
+                    let line = line.trim();
+                    if line == "#[ts(export)]" {
+                        pending_export = true;
+                        continue;
+                    }
+                    if !pending_export || line.starts_with("#[") || line.starts_with("///") {
+                        continue;
+                    }
+                    let name = line
+                        .strip_prefix("pub struct ")
+                        .or_else(|| line.strip_prefix("pub enum "))
+                        .and_then(|x| x.split([' ', '{', '(', '<']).next());
+                    if let Some(name) = name {
+                        names.push(name.to_string());
+                    }
+                    pending_export = false;
+                }
+            }
+            names
+        }
+
+        pub fn fresh_dir() -> PathBuf {
+            let dir =
+                std::env::temp_dir().join(format!("fm-bindings-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            dir
+        }
+    }
+
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_every_exported_type_is_generated() {
+            let dir = fresh_dir();
+            export_dtos(&dir);
+            let generated = read_bindings(&dir);
+
+            for name in exported_type_names(Path::new("src")) {
+                assert!(
+                    generated.contains_key(&format!("{name}.ts")),
+                    "{name} carries #[ts(export)] but is missing from `export_dtos`."
+                );
+            }
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+
+        #[test]
+        fn test_committed_bindings_are_fresh() {
+            let dir = fresh_dir();
+            export_dtos(&dir);
+            let generated = read_bindings(&dir);
+            std::fs::remove_dir_all(dir).unwrap();
+
+            let committed_dir = Path::new(COMMITTED_BINDINGS_DIR);
+            if std::env::var_os(UPDATE_BINDINGS_ENV).is_some() {
+                for file_name in read_bindings(committed_dir).keys() {
+                    std::fs::remove_file(committed_dir.join(file_name)).unwrap();
+                }
+                for (file_name, content) in &generated {
+                    std::fs::write(committed_dir.join(file_name), content).unwrap();
+                }
+            }
+
+            let committed = read_bindings(committed_dir);
+            let stale = generated
+                .iter()
+                .filter(|(file_name, content)| committed.get(*file_name) != Some(content))
+                .map(|(file_name, _)| file_name.as_str())
+                .chain(
+                    committed
+                        .keys()
+                        .filter(|file_name| !generated.contains_key(*file_name))
+                        .map(String::as_str),
+                )
+                .collect::<Vec<_>>();
+            assert!(
+                stale.is_empty(),
+                "Bindings in {COMMITTED_BINDINGS_DIR} are stale: {stale:?}. \
+                Run `{UPDATE_BINDINGS_ENV}=1 cargo test bindings` to regenerate them."
+            );
+        }
+    }
+}
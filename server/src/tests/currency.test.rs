@@ -1,7 +1,11 @@
 #[cfg(test)]
 pub mod currencies {
 
+    use crate::extended_models::currency::CurrencyKind;
+    use crate::routes::bootstrap::ErrorCode;
+    use crate::routes::currencies::convert_currency::*;
     use crate::routes::currencies::get_currency::*;
+    use crate::routes::currencies::patch_currency::*;
     use crate::routes::currencies::post_currency::*;
     use crate::tests::commons::*;
     use crate::tests::user_tests::users::drivers::*;
@@ -20,7 +24,7 @@ pub mod currencies {
             app: &actix_test::TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<PostCurrencyResponseBody> {
-            let mut req = app.post("/currencies");
+            let mut req = app.post("/api/v1/currencies");
             req = attach_token_to_req(req, token);
             req = req.insert_header(ContentType::json());
             let mut res = send_req_with_body(req, body).await;
@@ -38,12 +42,10 @@ pub mod currencies {
             app: &actix_test::TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<GetCurrencyResponse> {
-            let mut req = app.get("/currencies");
+            let mut req = app.get("/api/v2/currencies");
 
             if let Some(query) = query {
-                req = req
-                    .query(&[("id", query.id), ("date", query.date)])
-                    .expect("unable to unpack query")
+                req = req.query(&query).expect("unable to unpack query")
             }
 
             req = req.insert_header(ContentType::json());
@@ -57,6 +59,75 @@ pub mod currencies {
             res_parsed
         }
 
+        pub async fn driver_patch_currency(
+            id: &str,
+            token: Option<&str>,
+            body: TestBody<PatchCurrencyRequestBody>,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<PatchCurrencyResponseBody> {
+            let mut req = app.patch(format!("/api/v1/currencies/{id}"));
+            req = attach_token_to_req(req, token);
+            req = req.insert_header(ContentType::json());
+            let mut res = send_req_with_body(req, body).await;
+            let res_parsed: AssertTestResponse<PatchCurrencyResponseBody> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_convert_currency(
+            query: ConvertCurrencyQuery,
+            token: &str,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<ConvertCurrencyResponse> {
+            let req = app
+                .get("/api/v2/currencies/convert")
+                .query(&query)
+                .expect("unable to unpack query");
+            let mut res = attach_token_to_req(req, Some(token)).send().await.unwrap();
+            let res_parsed: AssertTestResponse<ConvertCurrencyResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_convert_currency_batch(
+            body: TestBody<PostConvertCurrencyRequest>,
+            token: &str,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<PostConvertCurrencyResponse> {
+            let mut req = app.post("/api/v2/currencies/convert");
+            req = attach_token_to_req(req, Some(token));
+            req = req.insert_header(ContentType::json());
+            let mut res = send_req_with_body(req, body).await;
+            let res_parsed: AssertTestResponse<PostConvertCurrencyResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_get_currencies_v1(
+            token: &str,
+            app: &actix_test::TestServer,
+        ) -> (Option<String>, AssertTestResponse<GetCurrencyResponseV1>) {
+            let req = attach_token_to_req(app.get("/api/v1/currencies"), Some(token));
+            let mut res = req.send().await.unwrap();
+            let deprecation = res
+                .headers()
+                .get("Deprecation")
+                .map(|x| x.to_str().unwrap().to_string());
+            (deprecation, parse_response_body(&mut res).await)
+        }
+
         pub async fn bootstrap_base_curr(
             ticker_name: (&str, &str),
             token: &str,
@@ -70,6 +141,12 @@ pub mod currencies {
                         ticker: ticker_name.0.to_string(),
                         fallback_rate_amount: None,
                         fallback_rate_currency_id: None,
+                        decimals: None,
+                        kind: None,
+                        symbol: None,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: None,
                     },
                 ),
                 srv,
@@ -96,6 +173,12 @@ pub mod currencies {
                         ticker: ticker_name.0.to_string(),
                         fallback_rate_amount: Some(fallback_rate.to_string()),
                         fallback_rate_currency_id: Some(fallback_curr_id.to_string()),
+                        decimals: None,
+                        kind: None,
+                        symbol: None,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: None,
                     },
                 ),
                 srv,
@@ -107,6 +190,37 @@ pub mod currencies {
             .id
         }
 
+        /// Create a currency with a fallback rate of 1 and the given decimal places.
+        pub async fn bootstrap_curr_with_decimals(
+            ticker: &str,
+            decimals: u32,
+            fallback_curr_id: &str,
+            token: &str,
+            srv: &TestServer,
+        ) -> String {
+            driver_post_currency(
+                Some(token),
+                TestBody::Expected(PostCurrencyRequestBody {
+                    name: ticker.to_string(),
+                    ticker: ticker.to_string(),
+                    fallback_rate_amount: Some("1".to_string()),
+                    fallback_rate_currency_id: Some(fallback_curr_id.to_string()),
+                    decimals: Some(decimals),
+                    kind: None,
+                    symbol: None,
+                    rate_strategy: None,
+                    rate_max_gap_days: None,
+                    derive_rates: None,
+                }),
+                srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id
+        }
+
         pub async fn bootstrap_get_curr(
             target_id: Option<String>,
             date: Option<String>,
@@ -117,6 +231,7 @@ pub mod currencies {
                 Some(GetCurrencyQuery {
                     id: target_id,
                     date,
+                    ..Default::default()
                 }),
                 Some(token),
                 srv,
@@ -154,6 +269,37 @@ pub mod currencies {
             assert_eq!(fetch_result.items.first().unwrap().rate_to_base, "5");
         }
 
+        #[actix_web::test]
+        async fn test_currencies_v1_legacy_fields() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let base_curr_id = bootstrap_base_curr(("BASE", "Base Curr"), &token, &srv).await;
+            bootstrap_sec_curr(("Sec", "Sec Curr"), "5", &base_curr_id, &token, &srv).await;
+
+            let (deprecation, res) = driver_get_currencies_v1(&token, &srv).await;
+            assert_eq!(res.status, StatusCode::OK);
+            assert_eq!(deprecation.as_deref(), Some("true"));
+            let items = res.expected.unwrap().items;
+            let sec_curr = items.iter().find(|x| !x.is_base).unwrap();
+            assert_eq!(sec_curr.rate_to_base, "5");
+            assert_eq!(
+                sec_curr.fallback_rate_currency_id.as_deref(),
+                Some(base_curr_id.as_str())
+            );
+
+            // The current version serves the same currencies in camelCase
+            let current = bootstrap_get_curr(None, None, &token, &srv).await;
+            let current = serde_json::to_value(current).unwrap();
+            let sec_curr = current["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|x| x["isBase"] == false)
+                .unwrap();
+            assert_eq!(sec_curr["rateToBase"], "5");
+            assert!(sec_curr["rate_to_base"].is_null());
+        }
+
         #[actix_web::test]
         async fn test_currencies_rate_base() {
             let srv = setup_connection().await;
@@ -308,7 +454,7 @@ pub mod currencies {
             )
             .await;
             bootstrap_post_rate_datum(
-                "0",
+                "1",
                 "2025-01-01T01:00:00.000Z",
                 &sec_curr_id,
                 &thi_curr_id,
@@ -347,7 +493,7 @@ pub mod currencies {
             let cases: Vec<(&str, &str, &str)> = vec![
                 (sec_curr_id.as_str(), "2025-01-01T00:59:00.000Z", "3.14"),
                 (thi_curr_id.as_str(), "2025-01-01T00:59:00.000Z", "18.84"),
-                (thi_curr_id.as_str(), "2025-01-01T01:00:00.000Z", "0"),
+                (thi_curr_id.as_str(), "2025-01-01T01:00:00.000Z", "10"),
                 (thi_curr_id.as_str(), "2025-01-01T01:01:00.000Z", "24"),
                 (thi_curr_id.as_str(), "2025-01-01T01:01:30.000Z", "40"),
             ];
@@ -487,6 +633,12 @@ pub mod currencies {
                             ticker: String::from("Sec"),
                             fallback_rate_amount: None,
                             fallback_rate_currency_id: None,
+                            decimals: None,
+                            kind: None,
+                            symbol: None,
+                            rate_strategy: None,
+                            rate_max_gap_days: None,
+                            derive_rates: None,
                         },
                     ),
                     &srv,
@@ -524,6 +676,12 @@ pub mod currencies {
                             ticker: String::from("123"),
                             fallback_rate_amount: None,
                             fallback_rate_currency_id: None,
+                            decimals: None,
+                            kind: None,
+                            symbol: None,
+                            rate_strategy: None,
+                            rate_max_gap_days: None,
+                            derive_rates: None,
                         },
                     ),
                     &srv,
@@ -603,6 +761,12 @@ pub mod currencies {
                         ticker: String::from("123"),
                         fallback_rate_amount: None,
                         fallback_rate_currency_id: None,
+                        decimals: None,
+                        kind: None,
+                        symbol: None,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: None,
                     },
                 ),
                 &srv,
@@ -616,6 +780,608 @@ pub mod currencies {
             );
         }
 
+        #[actix_web::test]
+        async fn test_invalid_fallback_rate_amounts() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let base_curr_id = bootstrap_base_curr(("BASE", "Base Curr"), &token, &srv).await;
+
+            for amount in ["1,2", "abc", "NaN", "inf", "0", "-1.5", ""] {
+                let resp = driver_post_currency(
+                    Some(&token),
+                    TestBody::Expected(PostCurrencyRequestBody {
+                        name: String::from("Sec Curr"),
+                        ticker: String::from("SEC"),
+                        fallback_rate_amount: Some(amount.to_string()),
+                        fallback_rate_currency_id: Some(base_curr_id.clone()),
+                        decimals: None,
+                        kind: None,
+                        symbol: None,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: None,
+                    }),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::BAD_REQUEST, "{amount}");
+                assert_eq!(
+                    resp.json.unwrap()["code"],
+                    serde_json::to_value(ErrorCode::InvalidDecimalValue).unwrap(),
+                    "{amount}"
+                );
+            }
+
+            // Nothing was stored by the rejected requests
+            bootstrap_sec_curr(("SEC", "Sec Curr"), "0.001", &base_curr_id, &token, &srv).await;
+        }
+
+        #[actix_web::test]
+        async fn test_currency_decimals() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let base_curr_id = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+
+            let resp = driver_post_currency(
+                Some(&token),
+                TestBody::Expected(PostCurrencyRequestBody {
+                    name: "Too precise".to_string(),
+                    ticker: "TOO".to_string(),
+                    fallback_rate_amount: Some("1".to_string()),
+                    fallback_rate_currency_id: Some(base_curr_id.clone()),
+                    decimals: Some(29),
+                    kind: None,
+                    symbol: None,
+                    rate_strategy: None,
+                    rate_max_gap_days: None,
+                    derive_rates: None,
+                }),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                resp.json.unwrap()["code"],
+                serde_json::to_value(ErrorCode::InvalidCurrencyDecimals).unwrap()
+            );
+
+            let btc_id = bootstrap_curr_with_decimals("BTC", 8, &base_curr_id, &token, &srv).await;
+
+            let items = bootstrap_get_curr(None, None, &token, &srv).await.items;
+            let decimals_of = |id: &str| items.iter().find(|x| x.id == id).unwrap().decimals;
+            assert_eq!(items.len(), 2);
+            assert_eq!(decimals_of(&base_curr_id), 2);
+            assert_eq!(decimals_of(&btc_id), 8);
+        }
+
+        #[actix_web::test]
+        async fn test_currency_metadata() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let base_curr_id = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let btc_id = driver_post_currency(
+                Some(&token),
+                TestBody::Expected(PostCurrencyRequestBody {
+                    name: "Bitcoin".to_string(),
+                    ticker: "BTC".to_string(),
+                    fallback_rate_amount: Some("2".to_string()),
+                    fallback_rate_currency_id: Some(base_curr_id.clone()),
+                    decimals: Some(8),
+                    kind: Some(CurrencyKind::Crypto),
+                    symbol: Some("₿".to_string()),
+                    rate_strategy: None,
+                    rate_max_gap_days: None,
+                    derive_rates: None,
+                }),
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id;
+            let sat_id = bootstrap_sec_curr(("SAT", "Satoshi"), "3", &btc_id, &token, &srv).await;
+            let list = |query: GetCurrencyQuery| {
+                let (token, srv) = (&token, &srv);
+                async move {
+                    let items = driver_get_currencies(Some(query), Some(token), srv, true)
+                        .await
+                        .expected
+                        .unwrap()
+                        .items;
+                    let mut ids = items.into_iter().map(|x| x.id).collect::<Vec<_>>();
+                    ids.sort();
+                    ids
+                }
+            };
+            let sorted = |mut ids: Vec<&String>| {
+                ids.sort();
+                ids.into_iter().cloned().collect::<Vec<_>>()
+            };
+
+            // Defaults
+            let items = bootstrap_get_curr(None, None, &token, &srv).await.items;
+            let btc = items.iter().find(|x| x.id == btc_id).unwrap();
+            assert_eq!(btc.kind, CurrencyKind::Crypto);
+            assert_eq!(btc.symbol.as_deref(), Some("₿"));
+            assert!(!btc.archived);
+            let usd = items.iter().find(|x| x.id == base_curr_id).unwrap();
+            assert_eq!(usd.kind, CurrencyKind::Fiat);
+            assert_eq!(usd.symbol, None);
+
+            // Filter by kind
+            let crypto = list(GetCurrencyQuery {
+                kind: Some(CurrencyKind::Crypto),
+                ..Default::default()
+            })
+            .await;
+            assert_eq!(crypto, vec![btc_id.clone()]);
+
+            // Archive the currency SAT falls back to
+            driver_patch_currency(
+                &btc_id,
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody {
+                    archived: Some(true),
+                    symbol: Some(String::new()),
+                    ..Default::default()
+                }),
+                &srv,
+                true,
+            )
+            .await;
+            assert_eq!(
+                list(GetCurrencyQuery::default()).await,
+                sorted(vec![&base_curr_id, &sat_id])
+            );
+            assert_eq!(
+                list(GetCurrencyQuery {
+                    include_archived: Some(true),
+                    ..Default::default()
+                })
+                .await,
+                sorted(vec![&base_curr_id, &btc_id, &sat_id])
+            );
+
+            // Archived currencies are still found by id, and still used for rates
+            let btc = bootstrap_get_curr(Some(btc_id.clone()), None, &token, &srv)
+                .await
+                .items;
+            assert!(btc[0].archived);
+            assert_eq!(btc[0].symbol, None);
+            assert_eq!(btc[0].kind, CurrencyKind::Crypto);
+            let sat = bootstrap_get_curr(Some(sat_id.clone()), None, &token, &srv)
+                .await
+                .items;
+            assert_eq!(sat[0].rate_to_base, "6");
+
+            // An empty patch changes nothing
+            driver_patch_currency(
+                &btc_id,
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody::default()),
+                &srv,
+                true,
+            )
+            .await;
+
+            // Unknown or foreign currencies
+            let resp = driver_patch_currency(
+                &uuid::Uuid::new_v4().to_string(),
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody::default()),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::NOT_FOUND);
+            let other_token = bootstrap_token(("456", "456"), &srv).await;
+            let resp = driver_patch_currency(
+                &btc_id,
+                Some(&other_token),
+                TestBody::Expected(PatchCurrencyRequestBody {
+                    archived: Some(false),
+                    ..Default::default()
+                }),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::NOT_FOUND);
+        }
+
+        #[actix_web::test]
+        async fn test_currency_rate_explanation() {
+            use crate::extended_models::currency::RateMethod;
+
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let eur = bootstrap_sec_curr(("EUR", "Euro"), "1.25", &usd, &token, &srv).await;
+            let gbp = bootstrap_sec_curr(("GBP", "Pound"), "2", &eur, &token, &srv).await;
+            let first_datum = bootstrap_post_rate_datum(
+                "2",
+                "2025-01-01T00:00:00.000Z",
+                &usd,
+                &eur,
+                &token,
+                &srv,
+            )
+            .await;
+            let second_datum = bootstrap_post_rate_datum(
+                "2.27",
+                "2025-01-03T00:00:00.000Z",
+                &usd,
+                &eur,
+                &token,
+                &srv,
+            )
+            .await;
+            let explain_at = |date: &str| {
+                let (token, srv) = (&token, &srv);
+                let date = date.to_string();
+                async move {
+                    driver_get_currencies(
+                        Some(GetCurrencyQuery {
+                            date: Some(date),
+                            explain: Some(true),
+                            ..Default::default()
+                        }),
+                        Some(token),
+                        srv,
+                        true,
+                    )
+                    .await
+                    .expected
+                    .unwrap()
+                    .items
+                }
+            };
+            let explanation_of = |items: &[GetCurrencyResponseItem], id: &str| {
+                let item = items.iter().find(|x| x.id == id).unwrap();
+                serde_json::to_value(item.explanation.as_ref().unwrap()).unwrap()
+            };
+
+            // Interpolated between both datums, GBP falls back to EUR.
+            // Datums 0.27 apart keep the interpolation exact over 2 days of milliseconds.
+            let items = explain_at("2025-01-02T00:00:00.000Z").await;
+            let usd_explanation = explanation_of(&items, &usd);
+            assert_eq!(usd_explanation["method"], "base");
+            assert_eq!(usd_explanation["rateToBase"], "1");
+            let eur_explanation = explanation_of(&items, &eur);
+            assert_eq!(eur_explanation["method"], "interpolated");
+            assert_eq!(eur_explanation["rateToBase"], "2.135");
+            let datums = eur_explanation["datums"].as_array().unwrap();
+            assert_eq!(datums.len(), 2);
+            assert_eq!(datums[0]["id"], first_datum);
+            assert_eq!(datums[0]["date"], "2025-01-01T00:00:00.000Z");
+            assert_eq!(datums[0]["amount"], "2");
+            assert_eq!(datums[0]["refAmountCurrencyId"], usd);
+            assert_eq!(datums[1]["id"], second_datum);
+            let sources = eur_explanation["sources"].as_array().unwrap();
+            assert_eq!(sources.len(), 2);
+            assert!(sources.iter().all(|x| x["currencyId"] == usd));
+            let gbp_explanation = explanation_of(&items, &gbp);
+            assert_eq!(gbp_explanation["method"], "fallback");
+            assert_eq!(gbp_explanation["rateToBase"], "4.27");
+            assert_eq!(gbp_explanation["fallbackRateAmount"], "2");
+            assert_eq!(gbp_explanation["sources"][0], eur_explanation);
+
+            // After the last datum, and before the first one
+            let items = explain_at("2025-02-01T00:00:00.000Z").await;
+            let eur_explanation = explanation_of(&items, &eur);
+            assert_eq!(eur_explanation["method"], "leftOnly");
+            assert_eq!(eur_explanation["rateToBase"], "2.27");
+            assert_eq!(eur_explanation["datums"][0]["id"], second_datum);
+            let items = explain_at("2024-01-01T00:00:00.000Z").await;
+            let eur_explanation = explanation_of(&items, &eur);
+            assert_eq!(
+                serde_json::to_value(RateMethod::Fallback).unwrap(),
+                eur_explanation["method"]
+            );
+            assert_eq!(eur_explanation["rateToBase"], "1.25");
+            assert_eq!(eur_explanation["fallbackRateAmount"], "1.25");
+            assert!(eur_explanation["datums"].as_array().unwrap().is_empty());
+            assert_eq!(eur_explanation["sources"][0]["method"], "base");
+
+            // Explanations are only given when asked for
+            let items = bootstrap_get_curr(None, None, &token, &srv).await.items;
+            assert!(items.iter().all(|x| x.explanation.is_none()));
+        }
+
+        #[actix_web::test]
+        async fn test_currency_rate_strategies() {
+            use crate::extended_models::currency::RateStrategy;
+
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let post_with_strategy =
+                |ticker: &str, strategy, max_gap_days| PostCurrencyRequestBody {
+                    name: ticker.to_string(),
+                    ticker: ticker.to_string(),
+                    fallback_rate_amount: Some("1".to_string()),
+                    fallback_rate_currency_id: Some(usd.clone()),
+                    decimals: None,
+                    kind: None,
+                    symbol: None,
+                    rate_strategy: Some(strategy),
+                    rate_max_gap_days: max_gap_days,
+                    derive_rates: None,
+                };
+            let rate_at = |id: String, date: &str| {
+                let (token, srv) = (&token, &srv);
+                let date = date.to_string();
+                async move {
+                    bootstrap_get_curr(Some(id), Some(date), token, srv)
+                        .await
+                        .items
+                        .remove(0)
+                        .rate_to_base
+                }
+            };
+
+            // Datums of 2 and 2.27, 10 days apart, keep the interpolation exact
+            let mut ids = vec![];
+            for (ticker, strategy, max_gap_days) in [
+                ("LIN", RateStrategy::Linear, None),
+                ("PRV", RateStrategy::PreviousValue, None),
+                ("NRS", RateStrategy::Nearest, None),
+                ("GAP", RateStrategy::LinearWithMaxGap, Some(5)),
+            ] {
+                let id = driver_post_currency(
+                    Some(&token),
+                    TestBody::Expected(post_with_strategy(ticker, strategy, max_gap_days)),
+                    &srv,
+                    true,
+                )
+                .await
+                .expected
+                .unwrap()
+                .id;
+                for (amount, date) in [
+                    ("2", "2025-01-01T00:00:00.000Z"),
+                    ("2.27", "2025-01-11T00:00:00.000Z"),
+                ] {
+                    bootstrap_post_rate_datum(amount, date, &usd, &id, &token, &srv).await;
+                }
+                ids.push(id);
+            }
+            let [lin, prv, nrs, gap] = ids.try_into().unwrap();
+
+            let date = "2025-01-08T00:00:00.000Z";
+            assert_eq!(rate_at(lin.clone(), date).await, "2.189");
+            assert_eq!(rate_at(prv.clone(), date).await, "2");
+            assert_eq!(rate_at(nrs.clone(), date).await, "2.27");
+            // Before the first datum only the nearest strategy uses it
+            let before = "2024-12-01T00:00:00.000Z";
+            assert_eq!(rate_at(nrs.clone(), before).await, "2");
+            assert_eq!(rate_at(lin.clone(), before).await, "1");
+            // The left datum is 7 days old, more than the gap of 5
+            assert_eq!(rate_at(gap.clone(), date).await, "1");
+            assert_eq!(
+                rate_at(gap.clone(), "2025-01-12T00:00:00.000Z").await,
+                "2.27"
+            );
+            let items = bootstrap_get_curr(None, None, &token, &srv).await.items;
+            let gap_item = items.iter().find(|x| x.id == gap).unwrap();
+            assert_eq!(gap_item.rate_strategy, Some(RateStrategy::LinearWithMaxGap));
+            assert_eq!(gap_item.rate_max_gap_days, Some(5));
+            let usd_item = items.iter().find(|x| x.id == usd).unwrap();
+            assert_eq!(usd_item.rate_strategy, None);
+
+            // Max gaps are only taken by linearWithMaxGap, and must be at least a day
+            for (strategy, max_gap_days) in [
+                (RateStrategy::LinearWithMaxGap, None),
+                (RateStrategy::LinearWithMaxGap, Some(0)),
+                (RateStrategy::Nearest, Some(3)),
+            ] {
+                let resp = driver_post_currency(
+                    Some(&token),
+                    TestBody::Expected(post_with_strategy("BAD", strategy, max_gap_days)),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+                assert_eq!(
+                    resp.json.unwrap()["code"],
+                    serde_json::to_value(ErrorCode::InvalidRateMaxGap).unwrap()
+                );
+            }
+
+            // Strategies can be changed, which drops the previous max gap
+            driver_patch_currency(
+                &gap,
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody {
+                    rate_strategy: Some(RateStrategy::Nearest),
+                    ..Default::default()
+                }),
+                &srv,
+                true,
+            )
+            .await;
+            assert_eq!(rate_at(gap.clone(), date).await, "2.27");
+            let resp = driver_patch_currency(
+                &gap,
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody {
+                    rate_max_gap_days: Some(3),
+                    ..Default::default()
+                }),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+            driver_patch_currency(
+                &lin,
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody {
+                    rate_strategy: Some(RateStrategy::LinearWithMaxGap),
+                    rate_max_gap_days: Some(7),
+                    ..Default::default()
+                }),
+                &srv,
+                true,
+            )
+            .await;
+            assert_eq!(rate_at(lin.clone(), date).await, "2.189");
+            driver_patch_currency(
+                &lin,
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody {
+                    rate_max_gap_days: Some(6),
+                    ..Default::default()
+                }),
+                &srv,
+                true,
+            )
+            .await;
+            assert_eq!(rate_at(lin, date).await, "1");
+
+            // Both datums nearest to the date are after it, the earlier one is still used
+            let ln2 = driver_post_currency(
+                Some(&token),
+                TestBody::Expected(post_with_strategy("LN2", RateStrategy::Linear, None)),
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id;
+            for (amount, date) in [
+                ("2.8", "2025-01-09T00:00:00.000Z"),
+                ("5", "2025-01-10T00:00:00.000Z"),
+            ] {
+                bootstrap_post_rate_datum(amount, date, &usd, &prv, &token, &srv).await;
+                bootstrap_post_rate_datum(amount, date, &usd, &ln2, &token, &srv).await;
+            }
+            bootstrap_post_rate_datum("2", "2025-01-01T00:00:00.000Z", &usd, &ln2, &token, &srv)
+                .await;
+            assert_eq!(rate_at(prv, date).await, "2");
+            assert_eq!(rate_at(ln2, date).await, "2.7");
+        }
+
+        #[actix_web::test]
+        async fn test_convert_currency() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let eur = bootstrap_sec_curr(("EUR", "Euro"), "1.25", &usd, &token, &srv).await;
+            let gbp = bootstrap_sec_curr(("GBP", "Pound"), "1.5", &usd, &token, &srv).await;
+            bootstrap_post_rate_datum("2", "2025-01-01T00:00:00.000Z", &usd, &eur, &token, &srv)
+                .await;
+            let query =
+                |from: &str, to: &str, amount: &str, date: Option<&str>| ConvertCurrencyQuery {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    amount: amount.to_string(),
+                    date: date.map(str::to_string),
+                };
+
+            // Single conversion, rounded to the decimal places of the target
+            let converted = driver_convert_currency(
+                query(&eur, &gbp, "10", Some("2024-06-01T00:00:00.000Z")),
+                &token,
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap();
+            assert_eq!(converted.from, eur);
+            assert_eq!(converted.to, gbp);
+            assert_eq!(converted.date, "2024-06-01T00:00:00.000Z");
+            assert_eq!(converted.amount, "10");
+            assert_eq!(converted.from_rate_to_base, "1.25");
+            assert_eq!(converted.to_rate_to_base, "1.5");
+            assert_eq!(converted.converted_amount, "8.33");
+
+            // Batch conversions, in the order of the queries
+            let items = driver_convert_currency_batch(
+                TestBody::Expected(PostConvertCurrencyRequest {
+                    queries: vec![
+                        query(&eur, &gbp, "10", Some("2024-06-01T00:00:00.000Z")),
+                        query(&eur, &gbp, "10", Some("2025-06-01T00:00:00.000Z")),
+                        query(&gbp, &usd, "3", None),
+                        query(&usd, &usd, "-7.5", None),
+                    ],
+                }),
+                &token,
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .items;
+            let converted = items
+                .iter()
+                .map(|x| (x.from_rate_to_base.as_str(), x.converted_amount.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                converted,
+                [
+                    ("1.25", "8.33"),
+                    ("2", "13.33"),
+                    ("1.5", "4.5"),
+                    ("1", "-7.5")
+                ]
+            );
+
+            let empty = driver_convert_currency_batch(
+                TestBody::Expected(PostConvertCurrencyRequest { queries: vec![] }),
+                &token,
+                &srv,
+                true,
+            )
+            .await;
+            assert!(empty.expected.unwrap().items.is_empty());
+
+            // Invalid queries
+            let resp = driver_convert_currency(
+                query(&eur, &uuid::Uuid::new_v4().to_string(), "1", None),
+                &token,
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::NOT_FOUND);
+            assert_eq!(
+                resp.json.unwrap()["code"],
+                serde_json::to_value(ErrorCode::CurrencyNotFound).unwrap()
+            );
+            let resp = driver_convert_currency_batch(
+                TestBody::Expected(PostConvertCurrencyRequest {
+                    queries: vec![
+                        query(&eur, &gbp, "10", None),
+                        query(&eur, &gbp, "ten", None),
+                    ],
+                }),
+                &token,
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                resp.json.unwrap()["code"],
+                serde_json::to_value(ErrorCode::InvalidDecimalValue).unwrap()
+            );
+
+            // Currencies of other users are not found
+            let other_token = bootstrap_token(("456", "456"), &srv).await;
+            let resp =
+                driver_convert_currency(query(&eur, &gbp, "1", None), &other_token, &srv, false)
+                    .await;
+            assert_eq!(resp.status, StatusCode::NOT_FOUND);
+        }
+
         #[actix_web::test]
         async fn test_curd_currencies() {
             let srv = setup_connection().await;
@@ -631,6 +1397,12 @@ pub mod currencies {
                             ticker: String::from("CUR1"),
                             fallback_rate_amount: None,
                             fallback_rate_currency_id: None,
+                            decimals: None,
+                            kind: None,
+                            symbol: None,
+                            rate_strategy: None,
+                            rate_max_gap_days: None,
+                            derive_rates: None,
                         },
                     ),
                     &srv,
@@ -654,6 +1426,12 @@ pub mod currencies {
                             ticker: String::from("CUR1"),
                             fallback_rate_amount: Some(String::from("123")),
                             fallback_rate_currency_id: None,
+                            decimals: None,
+                            kind: None,
+                            symbol: None,
+                            rate_strategy: None,
+                            rate_max_gap_days: None,
+                            derive_rates: None,
                         },
                     ),
                     &srv,
@@ -676,6 +1454,12 @@ pub mod currencies {
                         ticker: String::from("CUR1"),
                         fallback_rate_amount: None,
                         fallback_rate_currency_id: None,
+                        decimals: None,
+                        kind: None,
+                        symbol: None,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: None,
                     },
                 ),
                 &srv,
@@ -699,6 +1483,12 @@ pub mod currencies {
                                 "{}A1234",
                                 &base_currency_id[0..base_currency_id.len() - 5]
                             )),
+                            decimals: None,
+                            kind: None,
+                            symbol: None,
+                            rate_strategy: None,
+                            rate_max_gap_days: None,
+                            derive_rates: None,
                         },
                     ),
                     &srv,
@@ -722,6 +1512,12 @@ pub mod currencies {
                             ticker: String::from("CUR2"),
                             fallback_rate_amount: Some("2".to_string()),
                             fallback_rate_currency_id: Some(format!("{}asd", base_currency_id)),
+                            decimals: None,
+                            kind: None,
+                            symbol: None,
+                            rate_strategy: None,
+                            rate_max_gap_days: None,
+                            derive_rates: None,
                         },
                     ),
                     &srv,
@@ -744,6 +1540,12 @@ pub mod currencies {
                         ticker: String::from("CUR2"),
                         fallback_rate_amount: Some("2".to_string()),
                         fallback_rate_currency_id: Some(base_currency_id.clone()),
+                        decimals: None,
+                        kind: None,
+                        symbol: None,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: None,
                     },
                 ),
                 &srv,
@@ -760,6 +1562,7 @@ pub mod currencies {
                     Some(GetCurrencyQuery {
                         id: Some(base_currency_id.clone()),
                         date: None,
+                        ..Default::default()
                     }),
                     Some(&user_1_token),
                     &srv,
@@ -804,6 +1607,7 @@ pub mod currencies {
                     Some(GetCurrencyQuery {
                         id: Some(base_currency_id.clone()),
                         date: None,
+                        ..Default::default()
                     }),
                     Some(&user_1_token),
                     &srv,
@@ -835,6 +1639,7 @@ pub mod currencies {
                     Some(GetCurrencyQuery {
                         id: Some(String::from("abcd")),
                         date: None,
+                        ..Default::default()
                     }),
                     Some(&user_1_token),
                     &srv,
@@ -850,6 +1655,7 @@ pub mod currencies {
                     Some(GetCurrencyQuery {
                         id: Some(secondary_currency_id.clone()),
                         date: None,
+                        ..Default::default()
                     }),
                     Some(&user_1_token),
                     &srv,
@@ -19,7 +19,7 @@ pub mod currency_rate_datums {
             app: &actix_test::TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<PostCurrencyRateDatumResponse> {
-            let mut req = app.post("/currency_rate_datums");
+            let mut req = app.post("/api/v1/currency_rate_datums");
             req = attach_token_to_req(req, token);
             req = req.insert_header(ContentType::json());
             let mut res = send_req_with_body(req, body).await;
@@ -61,12 +61,15 @@ pub mod currency_rate_datums {
 
     mod tests {
         use super::*;
+        use crate::routes::bootstrap::ErrorCode;
         use crate::{
             routes::{
                 currencies::post_currency::PostCurrencyRequestBody,
                 currency_rate_datums::post_currency_rate_datum::PostCurrencyRateDatumRequest,
             },
-            tests::currency_tests::currencies::drivers::driver_post_currency,
+            tests::currency_tests::currencies::drivers::{
+                bootstrap_base_curr, bootstrap_sec_curr, driver_post_currency,
+            },
         };
 
         #[actix_web::test]
@@ -82,6 +85,12 @@ pub mod currency_rate_datums {
                     fallback_rate_currency_id: None,
                     name: String::from("Base Currency"),
                     ticker: String::from("BASE"),
+                    decimals: None,
+                    kind: None,
+                    symbol: None,
+                    rate_strategy: None,
+                    rate_max_gap_days: None,
+                    derive_rates: None,
                 }),
                 &srv,
                 true,
@@ -99,6 +108,12 @@ pub mod currency_rate_datums {
                     fallback_rate_currency_id: Some(base_currency_id.clone()),
                     name: String::from("Secondary Currency"),
                     ticker: String::from("SEC"),
+                    decimals: None,
+                    kind: None,
+                    symbol: None,
+                    rate_strategy: None,
+                    rate_max_gap_days: None,
+                    derive_rates: None,
                 }),
                 &srv,
                 true,
@@ -209,5 +224,277 @@ pub mod currency_rate_datums {
                 assert_eq!(resp.status, StatusCode::NOT_FOUND);
             }
         }
+
+        #[actix_web::test]
+        async fn test_invalid_currency_rate_datum_amounts() {
+            let srv = setup_connection().await;
+            let user_token = bootstrap_token(("123", "123"), &srv).await;
+            let base_currency_id =
+                bootstrap_base_curr(("BASE", "Base Currency"), &user_token, &srv).await;
+            let second_currency_id = bootstrap_sec_curr(
+                ("SEC", "Secondary Currency"),
+                "10",
+                &base_currency_id,
+                &user_token,
+                &srv,
+            )
+            .await;
+
+            for amount in ["1,2", "abc", "NaN", "0", "-10"] {
+                let resp = driver_post_currency_rate_datum(
+                    Some(&user_token),
+                    TestBody::Expected(PostCurrencyRateDatumRequest {
+                        ref_currency_id: second_currency_id.clone(),
+                        ref_amount_currency_id: base_currency_id.clone(),
+                        amount: amount.to_string(),
+                        date_utc: "2000-01-01T01:01:01.000Z".to_string(),
+                    }),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::BAD_REQUEST, "{amount}");
+                assert_eq!(
+                    resp.json.unwrap()["code"],
+                    serde_json::to_value(ErrorCode::InvalidDecimalValue).unwrap(),
+                    "{amount}"
+                );
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_derived_currency_rate_datums() {
+            use crate::commands::derive_rates::run_derive_rates;
+            use crate::entities::currency_rate_datum;
+            use crate::routes::currencies::patch_currency::PatchCurrencyRequestBody;
+            use crate::routes::txns::post_txns::{
+                PostTxnRequest, PostTxnRequestFragment, PostTxnRequestFragmentSide,
+            };
+            use crate::tests::account_tests::accounts::drivers::bootstrap_post_account;
+            use crate::tests::currency_tests::currencies::drivers::{
+                bootstrap_get_curr, driver_patch_currency,
+            };
+            use crate::tests::txn::txns::drivers::{
+                driver_delete_txn, driver_post_txn, driver_put_txn,
+            };
+            use actix_test::TestServerConfig;
+            use rust_decimal::Decimal;
+            use sea_orm::{EntityTrait, QueryOrder};
+
+            let (srv, states) = setup_connection_with_states(TestServerConfig::default()).await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let hkd = bootstrap_sec_curr(("HKD", "HK Dollar"), "0.1", &usd, &token, &srv).await;
+            let eur = bootstrap_sec_curr(("EUR", "Euro"), "1", &usd, &token, &srv).await;
+            let account = bootstrap_post_account("My account", &token, &srv).await;
+            let enable_derive_rates = |currency_id: String| {
+                let (srv, token) = (&srv, &token);
+                async move {
+                    driver_patch_currency(
+                        &currency_id,
+                        Some(token),
+                        TestBody::Expected(PatchCurrencyRequestBody {
+                            derive_rates: Some(true),
+                            ..Default::default()
+                        }),
+                        srv,
+                        true,
+                    )
+                    .await;
+                }
+            };
+            let side = |currency: &str, amount: &str| {
+                Some(PostTxnRequestFragmentSide {
+                    account: account.clone(),
+                    currency: currency.to_string(),
+                    amount: amount.to_string(),
+                })
+            };
+            let conversion = |date_utc: &str, fragments: Vec<PostTxnRequestFragment>| {
+                TestBody::Expected(PostTxnRequest {
+                    description: String::new(),
+                    title: "Conversion".to_string(),
+                    date_utc: date_utc.to_string(),
+                    fragments,
+                })
+            };
+            let saved_datums = || async {
+                currency_rate_datum::Entity::find()
+                    .order_by_asc(currency_rate_datum::Column::Date)
+                    .all(&states.db)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|datum| {
+                        (
+                            datum.ref_currency_id.to_string(),
+                            datum.amount.normalize(),
+                            datum.source_fragment_id.is_some(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let rate = |value: &str| Decimal::from_str_exact(value).unwrap();
+
+            enable_derive_rates(hkd.clone()).await;
+            let hkd_item = bootstrap_get_curr(Some(hkd.clone()), None, &token, &srv).await;
+            assert!(hkd_item.items[0].derive_rates);
+
+            // Only the first fragment converting HKD is used, the others are not conversions
+            let txn_id = driver_post_txn(
+                Some(&token),
+                conversion(
+                    "2025-01-01T00:00:00.000Z",
+                    vec![
+                        PostTxnRequestFragment {
+                            from: side(&hkd, "100"),
+                            to: side(&usd, "12.8"),
+                        },
+                        PostTxnRequestFragment {
+                            from: side(&usd, "20"),
+                            to: side(&hkd, "100"),
+                        },
+                        PostTxnRequestFragment {
+                            from: side(&hkd, "1"),
+                            to: side(&hkd, "1"),
+                        },
+                        PostTxnRequestFragment {
+                            from: side(&hkd, "1"),
+                            to: None,
+                        },
+                    ],
+                ),
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id;
+            assert_eq!(
+                saved_datums().await,
+                vec![(hkd.clone(), rate("0.128"), true)]
+            );
+            let hkd_item = bootstrap_get_curr(
+                Some(hkd.clone()),
+                Some("2025-01-01T00:00:00.000Z".to_string()),
+                &token,
+                &srv,
+            )
+            .await;
+            assert_eq!(hkd_item.items[0].rate_to_base, "0.128");
+
+            // Editing the transaction derives the rate again
+            driver_put_txn(
+                &txn_id,
+                Some(&token),
+                conversion(
+                    "2025-01-01T00:00:00.000Z",
+                    vec![PostTxnRequestFragment {
+                        from: side(&usd, "13"),
+                        to: side(&hkd, "100"),
+                    }],
+                ),
+                &srv,
+                true,
+            )
+            .await;
+            assert_eq!(
+                saved_datums().await,
+                vec![(hkd.clone(), rate("0.13"), true)]
+            );
+
+            // Dates which already have a datum are left alone
+            bootstrap_post_rate_datum("0.2", "2025-02-01T00:00:00.000Z", &usd, &hkd, &token, &srv)
+                .await;
+            driver_post_txn(
+                Some(&token),
+                conversion(
+                    "2025-02-01T00:00:00.000Z",
+                    vec![PostTxnRequestFragment {
+                        from: side(&hkd, "100"),
+                        to: side(&usd, "15"),
+                    }],
+                ),
+                &srv,
+                true,
+            )
+            .await;
+            assert_eq!(
+                saved_datums().await,
+                vec![
+                    (hkd.clone(), rate("0.13"), true),
+                    (hkd.clone(), rate("0.2"), false),
+                ]
+            );
+
+            // Deleting the transaction deletes its derived datum only
+            driver_delete_txn(&txn_id, Some(&token), &srv, true).await;
+            assert_eq!(
+                saved_datums().await,
+                vec![(hkd.clone(), rate("0.2"), false)]
+            );
+
+            // Transactions saved before a currency derives rates are derived by the command
+            driver_post_txn(
+                Some(&token),
+                conversion(
+                    "2025-03-01T00:00:00.000Z",
+                    vec![PostTxnRequestFragment {
+                        from: side(&eur, "10"),
+                        to: side(&usd, "11"),
+                    }],
+                ),
+                &srv,
+                true,
+            )
+            .await;
+            assert_eq!(saved_datums().await.len(), 1);
+            enable_derive_rates(eur.clone()).await;
+            assert_eq!(run_derive_rates(&states, None).await.unwrap(), 1);
+            assert_eq!(run_derive_rates(&states, Some("123")).await.unwrap(), 0);
+            assert_eq!(
+                saved_datums().await,
+                vec![
+                    (hkd.clone(), rate("0.2"), false),
+                    (eur.clone(), rate("1.1"), true),
+                ]
+            );
+            assert!(run_derive_rates(&states, Some("unknown")).await.is_err());
+
+            // Deleting the transaction a datum was derived from derives it from another one of the same date
+            let mut same_date_ids = vec![];
+            for amount in ["12", "13"] {
+                let id = driver_post_txn(
+                    Some(&token),
+                    conversion(
+                        "2025-04-01T00:00:00.000Z",
+                        vec![PostTxnRequestFragment {
+                            from: side(&eur, "10"),
+                            to: side(&usd, amount),
+                        }],
+                    ),
+                    &srv,
+                    true,
+                )
+                .await
+                .expected
+                .unwrap()
+                .id;
+                same_date_ids.push(id);
+            }
+            assert_eq!(saved_datums().await[2], (eur.clone(), rate("1.2"), true));
+            driver_delete_txn(&same_date_ids[0], Some(&token), &srv, true).await;
+            assert_eq!(
+                saved_datums().await,
+                vec![
+                    (hkd.clone(), rate("0.2"), false),
+                    (eur.clone(), rate("1.1"), true),
+                    (eur.clone(), rate("1.3"), true),
+                ]
+            );
+            driver_delete_txn(&same_date_ids[1], Some(&token), &srv, true).await;
+            assert_eq!(saved_datums().await.len(), 2);
+        }
     }
 }
@@ -0,0 +1,624 @@
+#[cfg(test)]
+pub mod currency_rate_sources {
+
+    use crate::extended_models::currency_rate_source::RateSourceKind;
+    use crate::routes::currency_rate_sources::delete_currency_rate_source::*;
+    use crate::routes::currency_rate_sources::get_currency_rate_sources::*;
+    use crate::routes::currency_rate_sources::patch_currency_rate_source::*;
+    use crate::routes::currency_rate_sources::post_currency_rate_source::*;
+    use crate::routes::currency_rate_sources::CurrencyRateSourceItem;
+    use crate::tests::commons::*;
+    use crate::tests::currency_tests::currencies::drivers::*;
+    use crate::tests::user_tests::users::drivers::*;
+    use actix_http::StatusCode;
+    use actix_web::http::header::ContentType;
+    use drivers::*;
+
+    pub mod drivers {
+        use super::*;
+
+        pub async fn driver_post_currency_rate_source(
+            token: &str,
+            body: TestBody<PostCurrencyRateSourceRequest>,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<PostCurrencyRateSourceResponse> {
+            let mut req = app.post("/api/v2/currencyRateSources");
+            req = attach_token_to_req(req, Some(token));
+            req = req.insert_header(ContentType::json());
+            let mut res = send_req_with_body(req, body).await;
+            let res_parsed: AssertTestResponse<PostCurrencyRateSourceResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_get_currency_rate_sources(
+            query: GetCurrencyRateSourcesQuery,
+            token: &str,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<GetCurrencyRateSourcesResponse> {
+            let req = app
+                .get("/api/v2/currencyRateSources")
+                .query(&query)
+                .expect("unable to unpack query");
+            let mut res = attach_token_to_req(req, Some(token)).send().await.unwrap();
+            let res_parsed: AssertTestResponse<GetCurrencyRateSourcesResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_get_currency_rate_source(
+            id: &str,
+            token: &str,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<CurrencyRateSourceItem> {
+            let req = app.get(format!("/api/v2/currencyRateSources/{id}"));
+            let mut res = attach_token_to_req(req, Some(token)).send().await.unwrap();
+            let res_parsed: AssertTestResponse<CurrencyRateSourceItem> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_patch_currency_rate_source(
+            id: &str,
+            token: &str,
+            body: TestBody<PatchCurrencyRateSourceRequest>,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<CurrencyRateSourceItem> {
+            let mut req = app.patch(format!("/api/v2/currencyRateSources/{id}"));
+            req = attach_token_to_req(req, Some(token));
+            req = req.insert_header(ContentType::json());
+            let mut res = send_req_with_body(req, body).await;
+            let res_parsed: AssertTestResponse<CurrencyRateSourceItem> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_delete_currency_rate_source(
+            id: &str,
+            token: &str,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<DeleteCurrencyRateSourceResponse> {
+            let req = app.delete(format!("/api/v2/currencyRateSources/{id}"));
+            let mut res = attach_token_to_req(req, Some(token)).send().await.unwrap();
+            let res_parsed: AssertTestResponse<DeleteCurrencyRateSourceResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn bootstrap_http_rate_source(
+            ref_currency_id: &str,
+            ref_amount_currency_id: &str,
+            hostname: &str,
+            path: &str,
+            json_query_string: &str,
+            token: &str,
+            app: &actix_test::TestServer,
+        ) -> String {
+            driver_post_currency_rate_source(
+                token,
+                TestBody::Expected(PostCurrencyRateSourceRequest {
+                    name: format!("{hostname}{path}"),
+                    kind: None,
+                    ref_currency_id: ref_currency_id.to_string(),
+                    ref_amount_currency_id: ref_amount_currency_id.to_string(),
+                    hostname: Some(hostname.to_string()),
+                    path: path.to_string(),
+                    json_query_string: json_query_string.to_string(),
+                }),
+                app,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id
+        }
+    }
+
+    mod tests {
+        use super::*;
+        use crate::env::EnvRateSourcesSection;
+        use crate::routes::bootstrap::ErrorCode;
+        use crate::services::currency_rate_sources::{
+            fetch_all_currency_rate_sources, http_source_url, is_public_address, FetchRateErrors,
+            HttpRateSource, RateSource, RateSourceHosts,
+        };
+        use actix_test::TestServerConfig;
+        use actix_web::{web, App, HttpResponse};
+        use rust_decimal::Decimal;
+        use serde_json::json;
+        use std::net::IpAddr;
+        use std::time::Duration;
+
+        fn error_code(
+            json: &Option<std::collections::HashMap<String, serde_json::Value>>,
+        ) -> ErrorCode {
+            serde_json::from_value(json.as_ref().unwrap()["code"].clone()).unwrap()
+        }
+
+        #[actix_web::test]
+        async fn test_crud_currency_rate_sources() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd_id = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let eur_id = bootstrap_sec_curr(("EUR", "Euro"), "1.1", &usd_id, &token, &srv).await;
+            let btc_id = bootstrap_sec_curr(("BTC", "Bitcoin"), "9", &usd_id, &token, &srv).await;
+
+            let eur_source_id = bootstrap_http_rate_source(
+                &eur_id,
+                &usd_id,
+                "rates.example.com",
+                "/latest?base=EUR",
+                "$.rates.USD",
+                &token,
+                &srv,
+            )
+            .await;
+            let btc_source_id = bootstrap_http_rate_source(
+                &btc_id,
+                &usd_id,
+                "http://127.0.0.1:9",
+                "/btc",
+                "$.price",
+                &token,
+                &srv,
+            )
+            .await;
+
+            // Listing, filtered by currency
+            let all = driver_get_currency_rate_sources(Default::default(), &token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(all.len(), 2);
+            let eur_only = driver_get_currency_rate_sources(
+                GetCurrencyRateSourcesQuery {
+                    currency_id: Some(eur_id.clone()),
+                },
+                &token,
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .items;
+            assert_eq!(eur_only.len(), 1);
+            let eur_source = &eur_only[0];
+            assert_eq!(eur_source.id, eur_source_id);
+            assert_eq!(eur_source.kind, RateSourceKind::Http);
+            assert_eq!(eur_source.ref_currency_id, eur_id);
+            assert_eq!(eur_source.ref_amount_currency_id, usd_id);
+            assert_eq!(eur_source.hostname.as_deref(), Some("rates.example.com"));
+            assert_eq!(eur_source.json_query_string, "$.rates.USD");
+            assert_eq!(eur_source.last_fetch_date, None);
+            assert_eq!(eur_source.last_error, None);
+
+            // Patch, omitted fields are kept
+            let patched = driver_patch_currency_rate_source(
+                &eur_source_id,
+                &token,
+                TestBody::Expected(PatchCurrencyRateSourceRequest {
+                    name: Some("ECB".to_string()),
+                    json_query_string: Some("$.rates[\"USD\"]".to_string()),
+                    ..Default::default()
+                }),
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap();
+            assert_eq!(patched.name, "ECB");
+            assert_eq!(patched.json_query_string, "$.rates[\"USD\"]");
+            assert_eq!(patched.path, "/latest?base=EUR");
+            let fetched = driver_get_currency_rate_source(&eur_source_id, &token, &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert_eq!(fetched.name, "ECB");
+
+            // Switching to a file source drops the hostname
+            let patched = driver_patch_currency_rate_source(
+                &btc_source_id,
+                &token,
+                TestBody::Expected(PatchCurrencyRateSourceRequest {
+                    kind: Some(RateSourceKind::File),
+                    path: Some("crypto/btc.json".to_string()),
+                    ..Default::default()
+                }),
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap();
+            assert_eq!(patched.kind, RateSourceKind::File);
+            assert_eq!(patched.hostname, None);
+
+            // Invalid sources
+            let invalid_bodies = [
+                ("hostname", None, "/x", "$.price"),
+                ("jsonQueryString", Some("example.com"), "/x", "$.price["),
+                ("hostname", Some("  "), "/x", "$.price"),
+            ];
+            for (field, hostname, path, query) in invalid_bodies {
+                let res = driver_post_currency_rate_source(
+                    &token,
+                    TestBody::Expected(PostCurrencyRateSourceRequest {
+                        name: "Invalid".to_string(),
+                        kind: None,
+                        ref_currency_id: eur_id.clone(),
+                        ref_amount_currency_id: usd_id.clone(),
+                        hostname: hostname.map(String::from),
+                        path: path.to_string(),
+                        json_query_string: query.to_string(),
+                    }),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(res.status, StatusCode::BAD_REQUEST);
+                assert_eq!(error_code(&res.json), ErrorCode::InvalidRateSource);
+                assert_eq!(res.json.unwrap()["details"]["field"], json!(field));
+            }
+            for path in ["../secrets.json", "/etc/rates.json", ""] {
+                let res = driver_patch_currency_rate_source(
+                    &btc_source_id,
+                    &token,
+                    TestBody::Expected(PatchCurrencyRateSourceRequest {
+                        path: Some(path.to_string()),
+                        ..Default::default()
+                    }),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(res.status, StatusCode::BAD_REQUEST, "{path}");
+                assert_eq!(error_code(&res.json), ErrorCode::InvalidRateSource);
+            }
+            let res = driver_patch_currency_rate_source(
+                &eur_source_id,
+                &token,
+                TestBody::Expected(PatchCurrencyRateSourceRequest {
+                    ref_amount_currency_id: Some(eur_id.clone()),
+                    ..Default::default()
+                }),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(error_code(&res.json), ErrorCode::CyclicRefAmountCurrency);
+            let res = driver_patch_currency_rate_source(
+                &eur_source_id,
+                &token,
+                TestBody::Expected(PatchCurrencyRateSourceRequest {
+                    ref_amount_currency_id: Some(uuid::Uuid::new_v4().to_string()),
+                    ..Default::default()
+                }),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::NOT_FOUND);
+            assert_eq!(error_code(&res.json), ErrorCode::CurrencyNotFound);
+
+            // Other users cannot see the sources
+            let other_token = bootstrap_token(("456", "456"), &srv).await;
+            let res =
+                driver_get_currency_rate_source(&eur_source_id, &other_token, &srv, false).await;
+            assert_eq!(res.status, StatusCode::NOT_FOUND);
+            assert_eq!(error_code(&res.json), ErrorCode::CurrencyRateSourceNotFound);
+
+            // Delete
+            driver_delete_currency_rate_source(&eur_source_id, &token, &srv, true).await;
+            let res = driver_get_currency_rate_source(&eur_source_id, &token, &srv, false).await;
+            assert_eq!(res.status, StatusCode::NOT_FOUND);
+            let res = driver_delete_currency_rate_source(&eur_source_id, &token, &srv, false).await;
+            assert_eq!(res.status, StatusCode::NOT_FOUND);
+            let remaining =
+                driver_get_currency_rate_sources(Default::default(), &token, &srv, true)
+                    .await
+                    .expected
+                    .unwrap()
+                    .items;
+            assert_eq!(remaining.len(), 1);
+        }
+
+        #[actix_web::test]
+        async fn test_fetch_currency_rate_sources() {
+            let (srv, states) = setup_connection_with_states(TestServerConfig::default()).await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd_id = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let eur_id = bootstrap_sec_curr(("EUR", "Euro"), "1.1", &usd_id, &token, &srv).await;
+            let gold_id = bootstrap_sec_curr(("XAU", "Gold"), "2000", &usd_id, &token, &srv).await;
+            let jpy_id = bootstrap_sec_curr(("JPY", "Yen"), "0.01", &usd_id, &token, &srv).await;
+
+            let stub = actix_test::start(|| {
+                App::new()
+                    .route(
+                        "/latest",
+                        web::get().to(|| async {
+                            HttpResponse::Ok().json(json!({ "rates": { "EUR": "1.25" } }))
+                        }),
+                    )
+                    .route(
+                        "/broken",
+                        web::get().to(|| async { HttpResponse::InternalServerError().finish() }),
+                    )
+            });
+            let stub_host = format!("http://{}", stub.addr());
+            let eur_source_id = bootstrap_http_rate_source(
+                &eur_id,
+                &usd_id,
+                &stub_host,
+                "/latest",
+                "$.rates.EUR",
+                &token,
+                &srv,
+            )
+            .await;
+            let jpy_source_id = bootstrap_http_rate_source(
+                &jpy_id,
+                &usd_id,
+                &stub_host,
+                "/broken",
+                "$.rates.JPY",
+                &token,
+                &srv,
+            )
+            .await;
+
+            let directory =
+                std::env::temp_dir().join(format!("fm-rate-sources-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&directory).unwrap();
+            std::fs::write(directory.join("gold.json"), r#"[{ "price": 1800.5 }]"#).unwrap();
+            let gold_source_id = driver_post_currency_rate_source(
+                &token,
+                TestBody::Expected(PostCurrencyRateSourceRequest {
+                    name: "Gold".to_string(),
+                    kind: Some(RateSourceKind::File),
+                    ref_currency_id: gold_id.clone(),
+                    ref_amount_currency_id: usd_id.clone(),
+                    hostname: None,
+                    path: "gold.json".to_string(),
+                    json_query_string: "$[0].price".to_string(),
+                }),
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id;
+
+            // File sources fail without a file directory, the stub is on a loopback address
+            let settings = EnvRateSourcesSection {
+                enabled: true,
+                ..Default::default()
+            };
+            let saved = fetch_all_currency_rate_sources(&settings, &states)
+                .await
+                .unwrap();
+            assert_eq!(saved, 0);
+            let gold_source = driver_get_currency_rate_source(&gold_source_id, &token, &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert_eq!(gold_source.last_fetch_date, None);
+            assert!(gold_source.last_error.unwrap().contains("fileDirectory"));
+            let eur_source = driver_get_currency_rate_source(&eur_source_id, &token, &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert_eq!(eur_source.last_fetch_date, None);
+            assert!(eur_source.last_error.unwrap().contains("allowedHosts"));
+
+            let settings = EnvRateSourcesSection {
+                enabled: true,
+                file_directory: Some(directory.to_str().unwrap().to_string()),
+                allowed_hosts: Some(vec!["127.0.0.1".to_string()]),
+                ..Default::default()
+            };
+            let saved = fetch_all_currency_rate_sources(&settings, &states)
+                .await
+                .unwrap();
+            assert_eq!(saved, 2);
+
+            let sources = driver_get_currency_rate_sources(Default::default(), &token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            let source = |id: &str| sources.iter().find(|x| x.id == id).unwrap().clone();
+            assert!(source(&eur_source_id).last_fetch_date.is_some());
+            assert_eq!(source(&eur_source_id).last_error, None);
+            assert!(source(&gold_source_id).last_fetch_date.is_some());
+            assert_eq!(source(&gold_source_id).last_error, None);
+            assert_eq!(source(&jpy_source_id).last_fetch_date, None);
+            assert_eq!(
+                source(&jpy_source_id).last_error.as_deref(),
+                Some("Responded with status 500.")
+            );
+
+            // The fetched rates are used from now on
+            let items = bootstrap_get_curr(None, None, &token, &srv).await.items;
+            let rate = |id: &str| {
+                let item = items.iter().find(|x| x.id == id).unwrap();
+                Decimal::from_str_exact(&item.rate_to_base)
+                    .unwrap()
+                    .normalize()
+                    .to_string()
+            };
+            assert_eq!(rate(&eur_id), "1.25");
+            assert_eq!(rate(&gold_id), "1800.5");
+            assert_eq!(rate(&jpy_id), "0.01");
+
+            std::fs::remove_dir_all(&directory).unwrap();
+        }
+
+        #[test]
+        fn test_rate_source_hosts() {
+            let address = |x: &str| x.parse::<IpAddr>().unwrap();
+
+            // Only public addresses are reached by default
+            {
+                let hosts = RateSourceHosts::default();
+                for private in [
+                    "127.0.0.1",
+                    "10.1.2.3",
+                    "172.16.0.1",
+                    "192.168.1.1",
+                    "169.254.169.254",
+                    "100.64.0.1",
+                    "0.0.0.0",
+                    "::1",
+                    "::",
+                    "fd00::1",
+                    "fe80::1",
+                    "::ffff:127.0.0.1",
+                    "0.1.2.3",
+                    "192.0.0.8",
+                    "198.18.0.1",
+                    "198.19.255.1",
+                    "224.0.0.1",
+                    "239.1.2.3",
+                    "240.0.0.1",
+                    "255.255.255.255",
+                    "ff02::1",
+                    "ff0e::1",
+                    "64:ff9b::7f00:1",
+                    "64:ff9b::a9fe:a9fe",
+                    "::127.0.0.1",
+                    "::10.0.0.1",
+                ] {
+                    assert!(!is_public_address(address(private)), "{private}");
+                    assert!(!hosts.is_allowed_address("rates.example.com", address(private)));
+                }
+                for public in [
+                    "93.184.216.34",
+                    "100.128.0.1",
+                    "198.20.0.1",
+                    "2606:4700::1111",
+                    "64:ff9b::5db8:d822",
+                    "::93.184.216.34",
+                ] {
+                    assert!(is_public_address(address(public)), "{public}");
+                    assert!(hosts.is_allowed_address("rates.example.com", address(public)));
+                }
+            }
+
+            // Listed hosts and their subdomains may be private, every other host is refused
+            {
+                let hosts = RateSourceHosts {
+                    allowed: Some(vec!["rates.lan".to_string(), "10.0.0.2".to_string()]),
+                    denied: vec![],
+                };
+                assert!(hosts.is_allowed_address("rates.lan", address("192.168.1.2")));
+                assert!(hosts.is_allowed_address("eu.rates.lan", address("192.168.1.2")));
+                assert!(hosts.is_allowed_address("10.0.0.2", address("10.0.0.2")));
+                assert!(!hosts.is_allowed_host("badrates.lan"));
+                assert!(!hosts.is_allowed_host("rates.example.com"));
+            }
+
+            // Denied hosts and addresses win over allowed ones
+            {
+                let hosts = RateSourceHosts {
+                    allowed: Some(vec!["example.com".to_string()]),
+                    denied: vec![
+                        "internal.example.com".to_string(),
+                        "93.184.216.34".to_string(),
+                    ],
+                };
+                assert!(hosts.is_allowed_host("rates.example.com"));
+                assert!(!hosts.is_allowed_host("api.internal.example.com"));
+                assert!(!hosts.is_allowed_address("rates.example.com", address("93.184.216.34")));
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_fetch_forbidden_hosts() {
+            let stub = actix_test::start(|| {
+                App::new()
+                    .route(
+                        "/latest",
+                        web::get().to(|| async {
+                            HttpResponse::Ok().json(json!({ "rates": { "EUR": "1.25" } }))
+                        }),
+                    )
+                    .route(
+                        "/moved",
+                        web::get().to(|| async {
+                            HttpResponse::Found()
+                                .insert_header(("location", "/latest"))
+                                .finish()
+                        }),
+                    )
+            });
+            let source = |host: &str, path: &str, hosts: RateSourceHosts| HttpRateSource {
+                url: http_source_url(&format!("http://{host}:{}", stub.addr().port()), path)
+                    .unwrap(),
+                timeout: Duration::from_secs(5),
+                hosts,
+            };
+            let allowed = |hosts: &[&str]| RateSourceHosts {
+                allowed: Some(hosts.iter().map(|x| x.to_string()).collect()),
+                denied: vec![],
+            };
+
+            // Loopback addresses are refused, whether named or resolved
+            for host in ["127.0.0.1", "localhost"] {
+                let err = source(host, "/latest", RateSourceHosts::default())
+                    .fetch_document()
+                    .await
+                    .unwrap_err();
+                assert!(err.to_string().contains("is not allowed"), "{err}");
+            }
+
+            // Unless listed, by the name the URL gives
+            let document = source("127.0.0.1", "/latest", allowed(&["127.0.0.1"]))
+                .fetch_document()
+                .await
+                .unwrap();
+            assert_eq!(document, json!({ "rates": { "EUR": "1.25" } }));
+            let document = source("localhost", "/latest", allowed(&["localhost"]))
+                .fetch_document()
+                .await
+                .unwrap();
+            assert_eq!(document, json!({ "rates": { "EUR": "1.25" } }));
+            let err = source("localhost", "/latest", allowed(&["127.0.0.1"]))
+                .fetch_document()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, FetchRateErrors::ForbiddenHost(_)), "{err}");
+
+            // Redirects are not followed, they could lead anywhere
+            let err = source("127.0.0.1", "/moved", allowed(&["127.0.0.1"]))
+                .fetch_document()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, FetchRateErrors::Status(302)), "{err}");
+        }
+    }
+}
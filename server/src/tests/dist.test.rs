@@ -0,0 +1,106 @@
+#[cfg(test)]
+pub mod dist {
+
+    use crate::routes::bootstrap::apply_endpoints;
+    use crate::routes::dist::configure_dist_files;
+    use crate::tests::dist_tests::dist::drivers::*;
+    use actix_http::{header, Method, StatusCode};
+    use actix_web::{test, App};
+
+    pub mod drivers {
+        use std::path::PathBuf;
+
+        pub const INDEX_HTML: &str = "<html><body>index</body></html>";
+        pub const APP_JS: &str = "console.log('app');";
+        pub const ROBOTS_TXT: &str = "User-agent: *";
+
+        /// Create a fake frontend build under the temp directory, and return its path.
+        pub fn bootstrap_dist_folder() -> String {
+            let root: PathBuf =
+                std::env::temp_dir().join(format!("fm-dist-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(root.join("assets")).unwrap();
+            std::fs::write(root.join("index.html"), INDEX_HTML).unwrap();
+            std::fs::write(root.join("assets").join("app.3f9a1c.js"), APP_JS).unwrap();
+            std::fs::write(root.join("robots.txt"), ROBOTS_TXT).unwrap();
+            root.to_string_lossy().to_string()
+        }
+    }
+
+    mod tests {
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_serve_dist_files() {
+            let dist_folder_path = bootstrap_dist_folder();
+            let app = test::init_service(
+                apply_endpoints(App::new())
+                    .configure(|cfg| configure_dist_files(cfg, &dist_folder_path)),
+            )
+            .await;
+
+            let get = |path: &str| test::TestRequest::get().uri(path).to_request();
+            let cache_control = |headers: &header::HeaderMap| {
+                headers
+                    .get(header::CACHE_CONTROL)
+                    .map(|x| x.to_str().unwrap().to_string())
+            };
+
+            // Root serves index.html, which must always be revalidated
+            {
+                let res = test::call_service(&app, get("/")).await;
+                assert_eq!(res.status(), StatusCode::OK);
+                assert_eq!(cache_control(res.headers()).unwrap(), "no-cache");
+                assert_eq!(test::read_body(res).await, INDEX_HTML);
+            }
+
+            // Hashed assets are cached forever
+            {
+                let res = test::call_service(&app, get("/assets/app.3f9a1c.js")).await;
+                assert_eq!(res.status(), StatusCode::OK);
+                assert!(cache_control(res.headers()).unwrap().contains("immutable"));
+                assert_eq!(test::read_body(res).await, APP_JS);
+            }
+
+            // Other files are cached briefly
+            {
+                let res = test::call_service(&app, get("/robots.txt")).await;
+                assert_eq!(res.status(), StatusCode::OK);
+                assert_eq!(
+                    cache_control(res.headers()).unwrap(),
+                    "public, max-age=3600"
+                );
+            }
+
+            // Client-side routes fall back to index.html
+            {
+                let res = test::call_service(&app, get("/transactions/123")).await;
+                assert_eq!(res.status(), StatusCode::OK);
+                assert_eq!(cache_control(res.headers()).unwrap(), "no-cache");
+                assert_eq!(test::read_body(res).await, INDEX_HTML);
+            }
+
+            // Missing files are not masked by the fallback
+            {
+                let res = test::call_service(&app, get("/assets/missing.js")).await;
+                assert_eq!(res.status(), StatusCode::NOT_FOUND);
+                assert!(cache_control(res.headers()).is_none());
+            }
+
+            // Only reads fall back to index.html
+            {
+                let req = test::TestRequest::default()
+                    .method(Method::POST)
+                    .uri("/transactions")
+                    .to_request();
+                let res = test::call_service(&app, req).await;
+                assert_ne!(res.status(), StatusCode::OK);
+            }
+
+            // Unknown API routes are not swallowed by the frontend
+            {
+                let res = test::call_service(&app, get("/api/v1/not-a-route")).await;
+                assert_eq!(res.status(), StatusCode::NOT_FOUND);
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+pub mod health {
+
+    use crate::routes::probes::health::GetHealthResponseBody;
+    use crate::routes::probes::ready::GetReadyResponseBody;
+    use crate::routes::probes::version::GetVersionResponseBody;
+    use crate::tests::commons::*;
+    use crate::tests::health_tests::health::drivers::*;
+    use actix_http::StatusCode;
+    use actix_test::TestServer;
+    use finance_manager_migration::{Migrator, MigratorTrait};
+
+    pub mod drivers {
+        use super::*;
+
+        pub async fn driver_get_health(
+            app: &TestServer,
+        ) -> AssertTestResponse<GetHealthResponseBody> {
+            let mut res = app.get("/health").send().await.unwrap();
+            parse_response_body(&mut res).await
+        }
+
+        pub async fn driver_get_ready(
+            app: &TestServer,
+        ) -> AssertTestResponse<GetReadyResponseBody> {
+            let mut res = app.get("/ready").send().await.unwrap();
+            parse_response_body(&mut res).await
+        }
+
+        pub async fn driver_get_version(
+            app: &TestServer,
+        ) -> AssertTestResponse<GetVersionResponseBody> {
+            let mut res = app.get("/version").send().await.unwrap();
+            parse_response_body(&mut res).await
+        }
+    }
+
+    mod tests {
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_probes() {
+            let srv = setup_connection().await;
+            let all_names = Migrator::migrations()
+                .iter()
+                .map(|x| x.name().to_string())
+                .collect::<Vec<_>>();
+
+            // Health never depends on the database
+            {
+                let resp = driver_get_health(&srv).await;
+                assert_eq!(resp.status, StatusCode::OK);
+                assert_eq!(resp.expected.unwrap().status, "ok");
+            }
+
+            // Ready once fully migrated
+            {
+                let resp = driver_get_ready(&srv).await;
+                assert_eq!(resp.status, StatusCode::OK);
+                let body = resp.expected.unwrap();
+                assert!(body.ready);
+                assert!(body.database_reachable);
+                assert!(body.pending_migrations.is_empty());
+            }
+
+            // Version reports the crate version and every applied migration
+            {
+                let resp = driver_get_version(&srv).await;
+                assert_eq!(resp.status, StatusCode::OK);
+                let body = resp.expected.unwrap();
+                assert_eq!(body.version, env!("CARGO_PKG_VERSION"));
+                assert!(!body.git_hash.is_empty());
+                assert_eq!(body.applied_migrations, all_names);
+            }
+
+            // Not ready while a migration is pending
+            {
+                let db = connect_test_database().await;
+                Migrator::down(&db, Some(1)).await.unwrap();
+
+                let resp = driver_get_ready(&srv).await;
+                assert_eq!(resp.status, StatusCode::SERVICE_UNAVAILABLE);
+                let body = resp.expected.unwrap();
+                assert!(!body.ready);
+                assert!(body.database_reachable);
+                assert_eq!(
+                    body.pending_migrations,
+                    vec![all_names.last().unwrap().clone()]
+                );
+
+                Migrator::up(&db, None).await.unwrap();
+                let resp = driver_get_ready(&srv).await;
+                assert_eq!(resp.status, StatusCode::OK);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+use crate::json_query::{JsonQuery, JsonQueryStep, ParseJsonQueryErrors};
+#[cfg(test)]
+use serde_json::json;
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn json_query_parse_steps() {
+    let query = JsonQuery::parse(r#"$.data[1]["price usd"].close"#).unwrap();
+    assert_eq!(
+        query.steps,
+        vec![
+            JsonQueryStep::Key("data".to_string()),
+            JsonQueryStep::Index(1),
+            JsonQueryStep::Key("price usd".to_string()),
+            JsonQueryStep::Key("close".to_string()),
+        ]
+    );
+    // The leading `$` is optional
+    assert_eq!(
+        JsonQuery::parse("rates.USD").unwrap(),
+        JsonQuery::parse("$.rates.USD").unwrap()
+    );
+    assert!(JsonQuery::parse("$").unwrap().steps.is_empty());
+}
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn json_query_parse_invalid() {
+    assert_eq!(
+        JsonQuery::parse("$.rates..USD"),
+        Err(ParseJsonQueryErrors::MissingKey { position: 8 })
+    );
+    assert_eq!(
+        JsonQuery::parse("$.data[0"),
+        Err(ParseJsonQueryErrors::UnclosedBracket { position: 6 })
+    );
+    assert_eq!(
+        JsonQuery::parse("$.data[first]"),
+        Err(ParseJsonQueryErrors::InvalidIndex("first".to_string()))
+    );
+    assert_eq!(
+        JsonQuery::parse("$[0]x"),
+        Err(ParseJsonQueryErrors::UnexpectedChar {
+            found: 'x',
+            position: 4
+        })
+    );
+}
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn json_query_select() {
+    let document = json!({ "data": [{ "close": 1.5 }, { "close": "2.25" }], "base": "USD" });
+    let select = |query: &str| JsonQuery::parse(query).unwrap().select(&document).cloned();
+    assert_eq!(select("$.data[0].close"), Some(json!(1.5)));
+    assert_eq!(select(r#"$["data"][1]['close']"#), Some(json!("2.25")));
+    assert_eq!(select("$"), Some(document.clone()));
+    assert_eq!(select("$.data[2].close"), None);
+    assert_eq!(select("$.base.close"), None);
+    assert_eq!(select("$.data.close"), None);
+}
@@ -80,3 +80,139 @@ pub async fn try_linear_interpolate_exact() {
     let result = try_linear_interpolate(left_xy, right_xy, target);
     assert_eq!(result.unwrap().to_string(), "20.1")
 }
+
+#[cfg(test)]
+use crate::extended_models::currency::RateStrategy;
+#[cfg(test)]
+use crate::linear_interpolator::{select_datums, DatumSelection};
+#[cfg(test)]
+use chrono::{NaiveDateTime, TimeDelta};
+
+#[cfg(test)]
+pub fn make_datum(day: i64, name: &str) -> Option<(NaiveDateTime, &str)> {
+    Some((NaiveDateTime::default() + TimeDelta::days(day), name))
+}
+
+#[cfg(test)]
+pub fn day(day: i64) -> NaiveDateTime {
+    NaiveDateTime::default() + TimeDelta::days(day)
+}
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn select_datums_linear() {
+    let select = |left, right| select_datums(RateStrategy::Linear, None, &day(2), left, right);
+    assert_eq!(
+        select(make_datum(1, "left"), make_datum(4, "right")),
+        DatumSelection::Interpolate("left", "right")
+    );
+    assert_eq!(
+        select(make_datum(1, "left"), None),
+        DatumSelection::Previous("left")
+    );
+    assert_eq!(
+        select(None, make_datum(4, "right")),
+        DatumSelection::Fallback
+    );
+    assert_eq!(select(None, None), DatumSelection::Fallback);
+}
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn select_datums_previous_value() {
+    let select =
+        |left, right| select_datums(RateStrategy::PreviousValue, None, &day(3), left, right);
+    assert_eq!(
+        select(make_datum(1, "left"), make_datum(4, "right")),
+        DatumSelection::Previous("left")
+    );
+    assert_eq!(
+        select(make_datum(1, "left"), None),
+        DatumSelection::Previous("left")
+    );
+    assert_eq!(
+        select(None, make_datum(4, "right")),
+        DatumSelection::Fallback
+    );
+}
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn select_datums_nearest() {
+    let select =
+        |target, left, right| select_datums(RateStrategy::Nearest, None, &day(target), left, right);
+    assert_eq!(
+        select(3, make_datum(1, "left"), make_datum(4, "right")),
+        DatumSelection::Nearest("right")
+    );
+    assert_eq!(
+        select(2, make_datum(1, "left"), make_datum(4, "right")),
+        DatumSelection::Nearest("left")
+    );
+    // Ties go to the left datum
+    assert_eq!(
+        select(3, make_datum(1, "left"), make_datum(5, "right")),
+        DatumSelection::Nearest("left")
+    );
+    assert_eq!(
+        select(9, make_datum(1, "left"), None),
+        DatumSelection::Nearest("left")
+    );
+    // Before the first datum, the first one is the nearest
+    assert_eq!(
+        select(0, None, make_datum(1, "right")),
+        DatumSelection::Nearest("right")
+    );
+    assert_eq!(select(0, None, None), DatumSelection::Fallback);
+}
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn select_datums_linear_with_max_gap() {
+    let select = |target, left, right| {
+        select_datums(
+            RateStrategy::LinearWithMaxGap,
+            Some(TimeDelta::days(3)),
+            &day(target),
+            left,
+            right,
+        )
+    };
+    // Both datums within the gap
+    assert_eq!(
+        select(3, make_datum(1, "left"), make_datum(5, "right")),
+        DatumSelection::Interpolate("left", "right")
+    );
+    // Right datum too far
+    assert_eq!(
+        select(2, make_datum(1, "left"), make_datum(9, "right")),
+        DatumSelection::Previous("left")
+    );
+    // Exactly at the gap
+    assert_eq!(
+        select(4, make_datum(1, "left"), None),
+        DatumSelection::Previous("left")
+    );
+    // Left datum too far, even with a close right datum
+    assert_eq!(
+        select(8, make_datum(1, "left"), make_datum(9, "right")),
+        DatumSelection::Fallback
+    );
+    assert_eq!(
+        select(5, make_datum(1, "left"), None),
+        DatumSelection::Fallback
+    );
+}
+
+#[cfg(test)]
+#[actix_web::test]
+pub async fn select_datums_ignores_max_gap_of_other_strategies() {
+    let result = select_datums(
+        RateStrategy::Linear,
+        Some(TimeDelta::days(1)),
+        &day(10),
+        make_datum(1, "left"),
+        None,
+    );
+    assert_eq!(result, DatumSelection::Previous("left"));
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+pub mod logging {
+
+    use crate::env::{EnvLogRotation, EnvLoggingSection};
+    use crate::logging::{EnvInitLoggerErrors, RollingFileWriter};
+    use chrono::NaiveDate;
+    use std::path::{Path, PathBuf};
+    use tracing::Level;
+
+    pub mod drivers {
+        use super::*;
+
+        /// Create an empty directory under the temp directory, and return the path of a log file in it.
+        pub fn temp_log_path() -> PathBuf {
+            let dir =
+                std::env::temp_dir().join(format!("fm-logging-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            dir.join("server.log")
+        }
+
+        /// Names of the files next to `path`, sorted.
+        pub fn log_files(path: &Path) -> Vec<String> {
+            let mut names = std::fs::read_dir(path.parent().unwrap())
+                .unwrap()
+                .map(|x| x.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        }
+
+        pub fn read_log(path: &Path, suffix: &str) -> String {
+            let mut path = path.as_os_str().to_owned();
+            path.push(suffix);
+            std::fs::read_to_string(path).unwrap()
+        }
+    }
+
+    mod tests {
+        use super::drivers::*;
+        use super::*;
+
+        #[test]
+        fn test_logging_filter() {
+            // Defaults
+            {
+                let section: EnvLoggingSection =
+                    serde_json::from_str(r#"{ "logMode": { "type": "Console" } }"#).unwrap();
+                let filter = section.to_filter().unwrap();
+                assert!(filter.would_enable("finance_manager_server", &Level::INFO));
+                assert!(!filter.would_enable("finance_manager_server", &Level::DEBUG));
+                assert!(!filter.would_enable("sqlx::query", &Level::ERROR));
+            }
+
+            // Configured level and targets, overriding the defaults
+            {
+                let section: EnvLoggingSection = serde_json::from_str(
+                    r#"{
+                        "logMode": { "type": "Console" },
+                        "level": "warn",
+                        "targets": { "sqlx::query": "debug", "finance_manager_server::services": "trace" },
+                        "format": "json"
+                    }"#,
+                )
+                .unwrap();
+                let filter = section.to_filter().unwrap();
+                assert!(!filter.would_enable("actix_server", &Level::INFO));
+                assert!(filter.would_enable("actix_server", &Level::WARN));
+                assert!(filter.would_enable("sqlx::query", &Level::DEBUG));
+                assert!(
+                    filter.would_enable("finance_manager_server::services::users", &Level::TRACE)
+                );
+            }
+
+            // Invalid levels
+            {
+                let section: EnvLoggingSection = serde_json::from_str(
+                    r#"{ "logMode": { "type": "Console" }, "targets": { "sqlx": "loud" } }"#,
+                )
+                .unwrap();
+                match section.to_filter() {
+                    Err(EnvInitLoggerErrors::InvalidLevel { target, level }) => {
+                        assert_eq!(target, "sqlx");
+                        assert_eq!(level, "loud");
+                    }
+                    other => panic!("Expected InvalidLevel, got {:?}", other.map(|_| ())),
+                }
+            }
+        }
+
+        #[test]
+        fn test_size_rotation() {
+            let path = temp_log_path();
+            let rotation = EnvLogRotation::Size {
+                max_bytes: 10,
+                max_files: Some(2),
+            };
+            let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+            let mut writer = RollingFileWriter::open(&path, rotation).unwrap();
+
+            for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+                writer.write_dated(line.as_bytes(), today).unwrap();
+            }
+
+            assert_eq!(
+                log_files(&path),
+                ["server.log", "server.log.1", "server.log.2"]
+            );
+            assert_eq!(read_log(&path, ""), "fourth\n");
+            assert_eq!(read_log(&path, ".1"), "third\n");
+            assert_eq!(read_log(&path, ".2"), "second\n");
+        }
+
+        #[test]
+        fn test_daily_rotation() {
+            let path = temp_log_path();
+            let rotation = EnvLogRotation::Daily { max_files: Some(2) };
+            let day = |day| NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+            let mut writer = RollingFileWriter::open(&path, rotation).unwrap();
+
+            writer.write_dated(b"day 1\n", day(1)).unwrap();
+            writer.write_dated(b"day 1 again\n", day(1)).unwrap();
+            writer.write_dated(b"day 2\n", day(2)).unwrap();
+            writer.write_dated(b"day 4\n", day(4)).unwrap();
+            writer.write_dated(b"day 5\n", day(5)).unwrap();
+
+            assert_eq!(
+                log_files(&path),
+                [
+                    "server.log",
+                    "server.log.2025-01-02",
+                    "server.log.2025-01-04"
+                ]
+            );
+            assert_eq!(read_log(&path, ""), "day 5\n");
+            assert_eq!(read_log(&path, ".2025-01-04"), "day 4\n");
+        }
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+pub mod metrics {
+
+    use crate::metrics::CACHE_NAMES;
+    use crate::routes::currencies::get_currency::GetCurrencyQuery;
+    use crate::tests::commons::*;
+    use crate::tests::currency_tests::currencies::drivers::*;
+    use crate::tests::metrics_tests::metrics::drivers::*;
+    use crate::tests::txn_tag::txn_tags::drivers::driver_get_txn_tags;
+    use crate::tests::user_tests::users::drivers::bootstrap_token;
+    use actix_http::StatusCode;
+    use actix_test::TestServer;
+
+    pub mod drivers {
+        use super::*;
+
+        pub async fn driver_get_metrics(app: &TestServer) -> (StatusCode, String) {
+            let mut res = app.get("/metrics").send().await.unwrap();
+            let body = response_body_to_str(&mut res).await.unwrap();
+            (res.status(), body)
+        }
+
+        /// Find the value of the sample whose line starts with `series`.
+        pub fn sample_value(metrics: &str, series: &str) -> Option<f64> {
+            metrics
+                .lines()
+                .find(|line| line.starts_with(series))
+                .and_then(|line| line.rsplit(' ').next())
+                .and_then(|value| value.parse().ok())
+        }
+    }
+
+    mod tests {
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_metrics() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let base_cid = bootstrap_base_curr(("BASE", "Base"), &token, &srv).await;
+            let sec_cid = bootstrap_sec_curr(("SEC", "Sec"), "5", &base_cid, &token, &srv).await;
+
+            let (_, before) = driver_get_metrics(&srv).await;
+            let depth_count = "fm_currency_rate_recursion_depth_count";
+            let depth_count_before = sample_value(&before, depth_count).unwrap_or(0.0);
+
+            driver_get_currencies(
+                Some(GetCurrencyQuery {
+                    id: Some(sec_cid.clone()),
+                    date: None,
+                    ..Default::default()
+                }),
+                Some(&token),
+                &srv,
+                true,
+            )
+            .await;
+            driver_get_txn_tags(Some(&token), &srv, true).await;
+
+            let (status, metrics) = driver_get_metrics(&srv).await;
+            assert_eq!(status, StatusCode::OK);
+
+            // Requests are labelled by route pattern, never by the raw query
+            assert!(
+                sample_value(
+                    &metrics,
+                    r#"fm_http_requests_total{method="GET",route="/api/v2/currencies",status="200"}"#
+                )
+                .unwrap()
+                    >= 1.0
+            );
+            assert!(!metrics.contains(&sec_cid));
+            assert!(metrics.contains(
+                r#"fm_http_request_duration_seconds_bucket{method="GET",route="/api/v2/currencies","#
+            ));
+
+            // Database pool
+            assert!(
+                sample_value(&metrics, r#"fm_db_pool_connections{state="max"}"#).unwrap() > 0.0
+            );
+
+            // Every cache looked up is exported
+            for cache in CACHE_NAMES {
+                for result in ["hit", "miss"] {
+                    let series =
+                        format!(r#"fm_cache_lookups_total{{cache="{cache}",result="{result}"}}"#);
+                    assert!(sample_value(&metrics, &series).is_some(), "{series}");
+                }
+            }
+            assert!(!metrics.contains(r#"cache="currency_rate_datum""#));
+            assert!(!metrics.contains(r#"cache="txn_tag""#));
+
+            // Resolving the secondary currency walks at least one step
+            assert!(sample_value(&metrics, depth_count).unwrap() > depth_count_before);
+        }
+    }
+}
@@ -0,0 +1,519 @@
+#[cfg(test)]
+pub mod migrate {
+
+    use crate::commands::migrate::{run_migrate, MigrateCommandErrors, MigrateCommands};
+    use crate::commands::{apply_pending_migrations, PendingMigrationsErrors};
+    use crate::entities::{account, currency, currency_rate_datum, fragment, txn, user};
+    use crate::env::AppMode;
+    use crate::tests::commons::connect_test_database;
+    use crate::tests::migrate_tests::migrate::drivers::*;
+    use finance_manager_migration::{Migrator, MigratorTrait};
+    use rust_decimal::Decimal;
+    use sea_orm::sea_query::{Alias, Expr, Query};
+    use sea_orm::{
+        ActiveValue::Set, ConnectionTrait, Database, DatabaseConnection, EntityTrait, Statement,
+    };
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    pub mod drivers {
+        use super::*;
+
+        pub const LEGACY_FALLBACK_AMOUNT: &str = "1.25";
+        pub const LEGACY_DATUM_AMOUNT: &str = "3.5";
+
+        pub const DECIMAL_AMOUNTS_MIGRATION: &str = "m20261019_000001_decimal_amounts";
+        pub const CURRENCY_DECIMALS_MIGRATION: &str = "m20261019_000002_currency_decimals";
+
+        /// Roll back every migration from `name` onwards.
+        pub async fn driver_roll_back_to(db: &DatabaseConnection, name: &str) {
+            let migrations = Migrator::migrations();
+            let index = migrations.iter().position(|x| x.name() == name).unwrap();
+            Migrator::down(db, Some((migrations.len() - index) as u32))
+                .await
+                .unwrap();
+        }
+
+        /// Roll back the decimal amounts migration, and insert amounts as strings.
+        pub async fn driver_insert_legacy_amounts(db: &DatabaseConnection) {
+            Migrator::fresh(db).await.unwrap();
+            driver_roll_back_to(db, DECIMAL_AMOUNTS_MIGRATION).await;
+            let owner = Uuid::new_v4();
+            let base = Uuid::new_v4();
+            let sec = Uuid::new_v4();
+            let date = chrono::NaiveDate::from_ymd_opt(2025, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap();
+            let inserts = [
+                Query::insert()
+                    .into_table(Alias::new("user"))
+                    .columns(["id", "name", "password_hash"].map(Alias::new))
+                    .values_panic([owner.into(), "legacy".into(), "hash".into()])
+                    .to_owned(),
+                Query::insert()
+                    .into_table(Alias::new("currency"))
+                    .columns(
+                        [
+                            "id",
+                            "owner_id",
+                            "name",
+                            "ticker",
+                            "is_base",
+                            "fallback_rate_amount",
+                            "fallback_rate_currency_id",
+                        ]
+                        .map(Alias::new),
+                    )
+                    .values_panic([
+                        base.into(),
+                        owner.into(),
+                        "Base".into(),
+                        "BASE".into(),
+                        true.into(),
+                        Option::<String>::None.into(),
+                        Option::<Uuid>::None.into(),
+                    ])
+                    .values_panic([
+                        sec.into(),
+                        owner.into(),
+                        "Sec".into(),
+                        "SEC".into(),
+                        false.into(),
+                        LEGACY_FALLBACK_AMOUNT.into(),
+                        base.into(),
+                    ])
+                    .to_owned(),
+                Query::insert()
+                    .into_table(Alias::new("currency_rate_datum"))
+                    .columns(
+                        [
+                            "id",
+                            "owner_id",
+                            "amount",
+                            "ref_currency_id",
+                            "ref_amount_currency_id",
+                            "date",
+                        ]
+                        .map(Alias::new),
+                    )
+                    .values_panic([
+                        Uuid::new_v4().into(),
+                        owner.into(),
+                        LEGACY_DATUM_AMOUNT.into(),
+                        sec.into(),
+                        base.into(),
+                        date.into(),
+                    ])
+                    .to_owned(),
+            ];
+            for insert in inserts {
+                db.execute(db.get_database_backend().build(&insert))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        /// Store `value` as a legacy datum amount, and check the decimal amounts migration refuses
+        /// it without converting anything. The legacy amount is restored afterwards.
+        pub async fn driver_assert_amount_refused(db: &DatabaseConnection, value: &str) {
+            let set_amount = |value: &str| {
+                Query::update()
+                    .table(Alias::new("currency_rate_datum"))
+                    .value(Alias::new("amount"), value)
+                    .to_owned()
+            };
+            db.execute(db.get_database_backend().build(&set_amount(value)))
+                .await
+                .unwrap();
+            let err = Migrator::up(db, None).await.unwrap_err().to_string();
+            assert!(
+                err.contains(&format!("currency_rate_datum.amount \"{value}\" in row")),
+                "{err}"
+            );
+            assert!(err.contains("1 amount(s)"), "{err}");
+
+            let row = db
+                .query_one(Statement::from_string(
+                    db.get_database_backend(),
+                    "SELECT amount FROM currency_rate_datum",
+                ))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(row.try_get::<String>("", "amount").unwrap(), value);
+            db.execute(
+                db.get_database_backend()
+                    .build(&set_amount(LEGACY_DATUM_AMOUNT)),
+            )
+            .await
+            .unwrap();
+        }
+
+        /// Apply the decimal amounts migration, and check the amounts are read back as decimals.
+        pub async fn driver_assert_decimal_amounts(db: &DatabaseConnection) {
+            Migrator::up(db, None).await.unwrap();
+            let currencies = currency::Entity::find().all(db).await.unwrap();
+            let amounts = currencies
+                .iter()
+                .filter_map(|x| x.fallback_rate_amount)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                amounts,
+                [Decimal::from_str_exact(LEGACY_FALLBACK_AMOUNT).unwrap()]
+            );
+            let datum = currency_rate_datum::Entity::find()
+                .one(db)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                datum.amount,
+                Decimal::from_str_exact(LEGACY_DATUM_AMOUNT).unwrap()
+            );
+
+            // Rolling back restores the strings
+            driver_roll_back_to(db, DECIMAL_AMOUNTS_MIGRATION).await;
+            let row = db
+                .query_one(Statement::from_string(
+                    db.get_database_backend(),
+                    "SELECT amount FROM currency_rate_datum",
+                ))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                row.try_get::<String>("", "amount").unwrap(),
+                LEGACY_DATUM_AMOUNT
+            );
+            Migrator::up(db, None).await.unwrap();
+        }
+
+        /// Roll back the currency decimals migration, and insert currencies with fragments
+        /// using 3 and 1 decimal places. Returns the ids of both currencies.
+        pub async fn driver_insert_fragments_without_decimals(
+            db: &DatabaseConnection,
+        ) -> (Uuid, Uuid) {
+            Migrator::fresh(db).await.unwrap();
+            driver_roll_back_to(db, CURRENCY_DECIMALS_MIGRATION).await;
+            let owner_id = Uuid::new_v4();
+            let (precise, coarse) = (Uuid::new_v4(), Uuid::new_v4());
+            let (account_id, txn_id) = (Uuid::new_v4(), Uuid::new_v4());
+            let date = chrono::NaiveDateTime::default();
+
+            user::Entity::insert(user::ActiveModel {
+                id: Set(owner_id),
+                name: Set("legacy".to_string()),
+                password_hash: Set("hash".to_string()),
+            })
+            .exec(db)
+            .await
+            .unwrap();
+            let currencies = Query::insert()
+                .into_table(Alias::new("currency"))
+                .columns(
+                    [
+                        "id",
+                        "owner_id",
+                        "name",
+                        "ticker",
+                        "is_base",
+                        "fallback_rate_amount",
+                        "fallback_rate_currency_id",
+                    ]
+                    .map(Alias::new),
+                )
+                .values_panic([
+                    precise.into(),
+                    owner_id.into(),
+                    "Precise".into(),
+                    "PRE".into(),
+                    true.into(),
+                    Option::<Decimal>::None.into(),
+                    Option::<Uuid>::None.into(),
+                ])
+                .values_panic([
+                    coarse.into(),
+                    owner_id.into(),
+                    "Coarse".into(),
+                    "COA".into(),
+                    false.into(),
+                    Decimal::ONE.into(),
+                    precise.into(),
+                ])
+                .to_owned();
+            db.execute(db.get_database_backend().build(&currencies))
+                .await
+                .unwrap();
+            account::Entity::insert(account::ActiveModel {
+                id: Set(account_id),
+                owner_id: Set(owner_id),
+                creation_date: Set(date),
+                name: Set("Wallet".to_string()),
+            })
+            .exec(db)
+            .await
+            .unwrap();
+            txn::Entity::insert(txn::ActiveModel {
+                id: Set(txn_id),
+                owner_id: Set(owner_id),
+                date: Set(date),
+                title: Set("Exchange".to_string()),
+                description: Set(String::new()),
+            })
+            .exec(db)
+            .await
+            .unwrap();
+            fragment::Entity::insert(fragment::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                owner_id: Set(owner_id),
+                from_account: Set(Some(account_id)),
+                from_amount: Set(Some(Decimal::new(125, 3))),
+                from_currency_id: Set(Some(precise)),
+                to_account: Set(Some(account_id)),
+                to_amount: Set(Some(Decimal::new(150, 2))),
+                to_currency_id: Set(Some(coarse)),
+                parent_txn: Set(txn_id),
+            })
+            .exec(db)
+            .await
+            .unwrap();
+            (precise, coarse)
+        }
+
+        /// Apply the currency decimals migration, and check every existing amount still fits.
+        pub async fn driver_assert_currency_decimals(
+            db: &DatabaseConnection,
+            (precise, coarse): (Uuid, Uuid),
+        ) {
+            Migrator::up(db, None).await.unwrap();
+            let decimals = currency::Entity::find()
+                .all(db)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|x| (x.id, x.decimals))
+                .collect::<HashMap<_, _>>();
+            assert_eq!(decimals[&precise], 3);
+            assert_eq!(decimals[&coarse], 2);
+
+            driver_roll_back_to(db, CURRENCY_DECIMALS_MIGRATION).await;
+            Migrator::up(db, None).await.unwrap();
+        }
+    }
+
+    mod tests {
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_decimal_amounts_migration() {
+            let db = connect_test_database().await;
+            driver_insert_legacy_amounts(&db).await;
+            driver_assert_amount_refused(&db, "1,2").await;
+            driver_assert_amount_refused(&db, "abc").await;
+            driver_assert_decimal_amounts(&db).await;
+
+            // Postgres keeps every digit
+            let precise = "12345678901234.56789012345678";
+            db.execute_unprepared(&format!(
+                "UPDATE currency_rate_datum SET amount = {precise}"
+            ))
+            .await
+            .unwrap();
+            let datum = currency_rate_datum::Entity::find()
+                .one(&db)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(datum.amount.to_string(), precise);
+        }
+
+        #[actix_web::test]
+        async fn test_decimal_amounts_migration_sqlite() {
+            let path =
+                std::env::temp_dir().join(format!("fm-migrate-test-{}.db", uuid::Uuid::new_v4()));
+            let db = Database::connect(format!("sqlite://{}?mode=rwc", path.display()))
+                .await
+                .unwrap();
+            driver_insert_legacy_amounts(&db).await;
+            driver_assert_amount_refused(&db, "1,2").await;
+            driver_assert_amount_refused(&db, "abc").await;
+            // A `real` would round it
+            driver_assert_amount_refused(&db, "12345678901234.56789012345678").await;
+            driver_assert_decimal_amounts(&db).await;
+
+            // Foreign keys still hold after the tables were rebuilt
+            let datum = currency_rate_datum::Entity::find()
+                .one(&db)
+                .await
+                .unwrap()
+                .unwrap();
+            let orphan = Query::update()
+                .table(Alias::new("currency_rate_datum"))
+                .value(Alias::new("ref_currency_id"), Uuid::new_v4())
+                .and_where(Expr::col(Alias::new("id")).eq(datum.id))
+                .to_owned();
+            let orphan = db.execute(db.get_database_backend().build(&orphan)).await;
+            assert!(orphan.is_err());
+
+            db.close().await.unwrap();
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[actix_web::test]
+        async fn test_currency_decimals_migration() {
+            let db = connect_test_database().await;
+            let currency_ids = driver_insert_fragments_without_decimals(&db).await;
+            driver_assert_currency_decimals(&db, currency_ids).await;
+        }
+
+        #[actix_web::test]
+        async fn test_currency_decimals_migration_sqlite() {
+            let path =
+                std::env::temp_dir().join(format!("fm-migrate-test-{}.db", uuid::Uuid::new_v4()));
+            let db = Database::connect(format!("sqlite://{}?mode=rwc", path.display()))
+                .await
+                .unwrap();
+            let currency_ids = driver_insert_fragments_without_decimals(&db).await;
+            driver_assert_currency_decimals(&db, currency_ids).await;
+
+            db.close().await.unwrap();
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[actix_web::test]
+        async fn test_migrate_commands() {
+            let db = connect_test_database().await;
+            let all_names = Migrator::migrations()
+                .iter()
+                .map(|x| x.name().to_string())
+                .collect::<Vec<_>>();
+            let last_two = all_names.iter().rev().take(2).cloned().collect::<Vec<_>>();
+            let dev = AppMode::Development;
+
+            // Fresh is refused outside development mode, even as a dry run
+            {
+                let result = run_migrate(
+                    &db,
+                    &AppMode::Production,
+                    MigrateCommands::Fresh { dry_run: true },
+                )
+                .await;
+                assert!(matches!(
+                    result,
+                    Err(MigrateCommandErrors::FreshOutsideDevelopment)
+                ));
+            }
+
+            // Fresh applies everything
+            {
+                let lines = run_migrate(&db, &dev, MigrateCommands::Fresh { dry_run: false })
+                    .await
+                    .unwrap();
+                assert_eq!(lines.len(), all_names.len());
+                assert!(Migrator::get_pending_migrations(&db)
+                    .await
+                    .unwrap()
+                    .is_empty());
+            }
+
+            // Dry run of down reports the latest migrations without rolling them back
+            {
+                let lines = run_migrate(
+                    &db,
+                    &dev,
+                    MigrateCommands::Down {
+                        n: Some(2),
+                        dry_run: true,
+                    },
+                )
+                .await
+                .unwrap();
+                assert_eq!(
+                    lines,
+                    last_two
+                        .iter()
+                        .map(|x| format!("Would roll back {x}"))
+                        .collect::<Vec<_>>()
+                );
+                assert!(Migrator::get_pending_migrations(&db)
+                    .await
+                    .unwrap()
+                    .is_empty());
+            }
+
+            // Down rolls back, and status reflects it
+            {
+                run_migrate(
+                    &db,
+                    &dev,
+                    MigrateCommands::Down {
+                        n: Some(2),
+                        dry_run: false,
+                    },
+                )
+                .await
+                .unwrap();
+                let pending = Migrator::get_pending_migrations(&db).await.unwrap();
+                assert_eq!(pending.len(), 2);
+
+                let lines = run_migrate(&db, &dev, MigrateCommands::Status)
+                    .await
+                    .unwrap();
+                assert_eq!(lines.len(), all_names.len());
+                assert_eq!(lines.iter().filter(|x| x.starts_with("Pending")).count(), 2);
+            }
+
+            // Dry run of up prints the pending names only
+            {
+                let lines = run_migrate(&db, &dev, MigrateCommands::Up { dry_run: true })
+                    .await
+                    .unwrap();
+                assert_eq!(lines.len(), 2);
+                assert!(lines.iter().all(|x| x.starts_with("Would apply")));
+                assert_eq!(
+                    Migrator::get_pending_migrations(&db).await.unwrap().len(),
+                    2
+                );
+            }
+
+            // Up applies them
+            {
+                run_migrate(&db, &dev, MigrateCommands::Up { dry_run: false })
+                    .await
+                    .unwrap();
+                assert!(Migrator::get_pending_migrations(&db)
+                    .await
+                    .unwrap()
+                    .is_empty());
+                let lines = run_migrate(&db, &dev, MigrateCommands::Up { dry_run: false })
+                    .await
+                    .unwrap();
+                assert_eq!(lines, vec!["No migrations affected.".to_string()]);
+            }
+
+            // Commands refuse pending migrations when asked to, and apply them otherwise
+            {
+                Migrator::down(&db, Some(1)).await.unwrap();
+                let result = apply_pending_migrations(&db, true).await;
+                match result {
+                    Err(PendingMigrationsErrors::NotFullyMigrated(names)) => {
+                        assert_eq!(names, vec![last_two[0].clone()]);
+                    }
+                    other => panic!("Unexpected result {other:?}"),
+                }
+                assert_eq!(
+                    Migrator::get_pending_migrations(&db).await.unwrap().len(),
+                    1
+                );
+
+                apply_pending_migrations(&db, false).await.unwrap();
+                assert!(Migrator::get_pending_migrations(&db)
+                    .await
+                    .unwrap()
+                    .is_empty());
+                apply_pending_migrations(&db, true).await.unwrap();
+            }
+        }
+    }
+}
@@ -8,27 +8,77 @@ pub mod user_tests;
 #[path = "./currency_rate_datum.test.rs"]
 pub mod currency_rate_datum;
 
+#[path = "./currency_rate_source.test.rs"]
+pub mod currency_rate_source;
+
+#[path = "./json_query.test.rs"]
+pub mod json_query;
+
 #[path = "./txn_tag.test.rs"]
 pub mod txn_tag;
 
 #[path = "./linear_interpolator.test.rs"]
 pub mod linear_interpolator;
 
-#[path = "./neighbors.test.rs"]
-pub mod neighbors;
-
 #[path = "./txn.test.rs"]
 pub mod txn;
 
+#[path = "./wallet.test.rs"]
+pub mod wallet;
+
+#[path = "./ssl.test.rs"]
+pub mod ssl_tests;
+
+#[path = "./dist.test.rs"]
+pub mod dist_tests;
+
+#[path = "./server.test.rs"]
+pub mod server_tests;
+
+#[path = "./migrate.test.rs"]
+pub mod migrate_tests;
+
+#[path = "./health.test.rs"]
+pub mod health_tests;
+
+#[path = "./metrics.test.rs"]
+pub mod metrics_tests;
+
+#[path = "./request_tracing.test.rs"]
+pub mod request_tracing_tests;
+
+#[path = "./logging.test.rs"]
+pub mod logging_tests;
+
+#[path = "./shutdown.test.rs"]
+pub mod shutdown_tests;
+
+#[path = "./config.test.rs"]
+pub mod config_tests;
+
+#[path = "./openapi.test.rs"]
+pub mod openapi_tests;
+
+#[path = "./bindings.test.rs"]
+pub mod bindings_tests;
+
+#[path = "./repair.test.rs"]
+pub mod repair_tests;
+
 #[cfg(test)]
 pub mod commons {
 
-    use crate::routes::bootstrap::apply_endpoints;
+    use crate::env::{DEFAULT_MAX_BODY_SIZE, DEFAULT_METRICS_PATH};
+    use crate::metrics::{configure_metrics, metrics_middleware};
+    use crate::request_tracing::request_tracing_middleware;
+    use crate::routes::bootstrap::{apply_endpoints, json_config};
     use crate::states::database_states::DatabaseStates;
     use actix_http::StatusCode;
     use actix_test::ClientRequest;
     use actix_test::ClientResponse;
     use actix_test::TestServer;
+    use actix_test::TestServerConfig;
+    use actix_web::middleware::from_fn;
     use actix_web::{web, App};
     use finance_manager_migration::Migrator;
     use futures::prelude::*;
@@ -84,12 +134,7 @@ pub mod commons {
         let body_json_str = response_body_to_str(res).await;
         let parsed_body_expected: Option<ExpectedType> = match body_json_str {
             Some(_) => match body_json_str {
-                Some(ref body_json_str) => {
-                    match serde_json::from_str::<ExpectedType>(body_json_str) {
-                        Ok(expected) => Some(expected),
-                        Err(_) => None,
-                    }
-                }
+                Some(ref body_json_str) => serde_json::from_str::<ExpectedType>(body_json_str).ok(),
                 None => None,
             },
             None => None,
@@ -121,6 +166,11 @@ pub mod commons {
     /// Setup connection to a test database.
     /// WARN: The content in the given database will be cleared.
     pub async fn setup_connection() -> TestServer {
+        setup_connection_with_config(TestServerConfig::default()).await
+    }
+
+    /// Connect to the database assigned to the current test thread.
+    pub async fn connect_test_database() -> DatabaseConnection {
         let tests_threads: u32 = std::env::var("NEXTEST_TEST_GLOBAL_SLOT")
             .expect("Cannot find NEXTEST_TEST_GLOBAL_SLOT.")
             .parse()
@@ -128,23 +178,41 @@ pub mod commons {
 
         let test_db_url = format!("TEST_DB_URL_{tests_threads}");
 
-        let db = Database::connect(std::env::var(test_db_url.clone()).unwrap_or_else(|_| {
+        Database::connect(std::env::var(test_db_url.clone()).unwrap_or_else(|_| {
             panic!(
                 "Env var {} is not defined. Cannot setup database for testing. Notice that the number of database url required is the same as the number of test threads.",
                 test_db_url
             )
         }))
         .await
-        .expect("failed initializing data");
+        .expect("failed initializing data")
+    }
+
+    pub async fn setup_connection_with_config(config: TestServerConfig) -> TestServer {
+        setup_connection_with_states(config).await.0
+    }
+
+    /// Same as [`setup_connection_with_config`], also giving the states shared by the server,
+    /// for tests calling services directly.
+    pub async fn setup_connection_with_states(
+        config: TestServerConfig,
+    ) -> (TestServer, DatabaseStates) {
+        let db = connect_test_database().await;
 
         let _ = <Migrator as finance_manager_migration::MigratorTrait>::fresh(&db).await;
         let states = DatabaseStates::new(db);
-
-        actix_test::start(move || {
-            let app_data = web::Data::new(states.clone());
-            let app = App::new().app_data(app_data);
-            apply_endpoints(app)
-        })
+        let server_states = states.clone();
+
+        let server = actix_test::start_with(config, move || {
+            let app_data = web::Data::new(server_states.clone());
+            let app = App::new()
+                .wrap(from_fn(metrics_middleware))
+                .wrap(from_fn(request_tracing_middleware))
+                .app_data(app_data)
+                .app_data(json_config(DEFAULT_MAX_BODY_SIZE));
+            apply_endpoints(app).configure(|cfg| configure_metrics(cfg, DEFAULT_METRICS_PATH))
+        });
+        (server, states)
     }
 
     #[allow(unused)]
@@ -0,0 +1,206 @@
+#[cfg(test)]
+pub mod openapi {
+
+    use crate::routes::bootstrap::{ErrorCode, ErrorResponseBody, API_PREFIX, API_V2_PREFIX};
+    use crate::routes::openapi::ACCESS_TOKEN_SCHEME;
+    use crate::tests::commons::*;
+    use crate::tests::openapi_tests::openapi::drivers::*;
+    use actix_http::{Method, StatusCode};
+    use actix_test::TestServer;
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+
+    pub mod drivers {
+        use super::*;
+
+        /// Route files which are not part of the API contract.
+        pub const UNDOCUMENTED_ROUTE_FILES: [&str; 1] = ["dev.route.rs"];
+
+        pub const ROUTE_MACROS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+        pub async fn driver_get_openapi(app: &TestServer) -> AssertTestResponse<Value> {
+            let mut res = app.get("/openapi.json").send().await.unwrap();
+            parse_response_body(&mut res).await
+        }
+
+        /// Every `(method, path)` declared with an actix route macro in `src/routes`.
+        pub fn declared_routes() -> BTreeSet<(String, String)> {
+            let mut routes = BTreeSet::new();
+            for entry in std::fs::read_dir("src/routes").unwrap() {
+                let path = entry.unwrap().path();
+                let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+                if !file_name.ends_with(".route.rs")
+                    || UNDOCUMENTED_ROUTE_FILES.contains(&file_name.as_str())
+                {
+                    continue;
+                }
+                for line in std::fs::read_to_string(&path).unwrap().lines() {
+                    for method in ROUTE_MACROS {
+                        let route = line
+                            .trim()
+                            .strip_prefix(&format!("#[{method}(\""))
+                            .and_then(|x| x.strip_suffix("\")]"));
+                        if let Some(route) = route {
+                            routes.insert((method.to_string(), route.to_string()));
+                        }
+                    }
+                }
+            }
+            routes
+        }
+
+        /// Every `(method, path)` of the document.
+        pub fn documented_routes(document: &Value) -> BTreeSet<(String, String)> {
+            let mut routes = BTreeSet::new();
+            for (path, item) in document["paths"].as_object().unwrap() {
+                for method in ROUTE_MACROS {
+                    if item.get(method).is_some() {
+                        routes.insert((method.to_string(), path.clone()));
+                    }
+                }
+            }
+            routes
+        }
+    }
+
+    mod tests {
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_openapi_document() {
+            let srv = setup_connection().await;
+            let res = driver_get_openapi(&srv).await;
+            assert_eq!(res.status, StatusCode::OK);
+            let document = res.expected.unwrap();
+
+            assert!(document["openapi"].as_str().unwrap().starts_with("3."));
+            assert_eq!(document["info"]["version"], env!("CARGO_PKG_VERSION"));
+            assert_eq!(
+                document["components"]["securitySchemes"][ACCESS_TOKEN_SCHEME]["in"],
+                "header"
+            );
+
+            // Operation ids are unique
+            let operation_ids = document["paths"]
+                .as_object()
+                .unwrap()
+                .values()
+                .flat_map(|item| item.as_object().unwrap().values())
+                .map(|operation| operation["operationId"].as_str().unwrap())
+                .collect::<Vec<_>>();
+            let unique_operation_ids = operation_ids.iter().collect::<BTreeSet<_>>();
+            assert_eq!(unique_operation_ids.len(), operation_ids.len());
+
+            // Superseded versions are deprecated
+            let get_currencies_v1 = &document["paths"]["/api/v1/currencies"]["get"];
+            assert_eq!(get_currencies_v1["operationId"], "getCurrencyV1");
+            assert_eq!(get_currencies_v1["deprecated"], true);
+            assert_eq!(
+                get_currencies_v1["responses"]["200"]["content"]["application/json"]["schema"]
+                    ["$ref"],
+                "#/components/schemas/GetCurrencyResponseV1"
+            );
+            assert!(document["paths"]["/api/v2/currencies"]["get"]["deprecated"].is_null());
+
+            // Query parameters
+            let get_currencies = &document["paths"]["/api/v2/currencies"]["get"];
+            let params = get_currencies["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|x| (x["name"].as_str().unwrap(), x["in"].as_str().unwrap()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                params,
+                [
+                    ("id", "query"),
+                    ("date", "query"),
+                    ("kind", "query"),
+                    ("includeArchived", "query"),
+                    ("explain", "query")
+                ]
+            );
+            let import_user = &document["paths"]["/api/v2/users/import"]["post"];
+            assert_eq!(import_user["parameters"][0]["name"], "remapIds");
+            let txn_item = &document["paths"]["/api/v2/txns/{id}"];
+            assert_eq!(txn_item["put"]["operationId"], "putTxn");
+            assert_eq!(txn_item["delete"]["operationId"], "deleteTxn");
+            for method in ["put", "delete"] {
+                assert_eq!(txn_item[method]["parameters"][0]["name"], "id");
+                assert_eq!(txn_item[method]["parameters"][0]["in"], "path");
+                assert_eq!(
+                    txn_item[method]["responses"]["404"]["$ref"],
+                    "#/components/responses/NotFoundResponse"
+                );
+            }
+            assert_eq!(
+                txn_item["put"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+                "#/components/schemas/PostTxnRequest"
+            );
+
+            // Authentication requirements
+            assert!(get_currencies["security"][0][ACCESS_TOKEN_SCHEME].is_array());
+            assert!(document["paths"]["/api/v2/login"]["post"]["security"].is_null());
+            assert!(document["paths"]["/health"]["get"]["security"].is_null());
+
+            // Error responses
+            assert_eq!(
+                get_currencies["responses"]["401"]["$ref"],
+                "#/components/responses/UnauthorizedResponse"
+            );
+            let error_body = &document["components"]["schemas"]["ErrorResponseBody"];
+            assert_eq!(
+                error_body["properties"]["code"]["$ref"],
+                "#/components/schemas/ErrorCode"
+            );
+            let codes = document["components"]["schemas"]["ErrorCode"]["enum"]
+                .as_array()
+                .unwrap();
+            assert!(codes.contains(&serde_json::to_value(ErrorCode::RouteNotFound).unwrap()));
+        }
+
+        #[actix_web::test]
+        async fn test_openapi_covers_every_route() {
+            let srv = setup_connection().await;
+            let document = driver_get_openapi(&srv).await.expected.unwrap();
+            let documented = documented_routes(&document);
+
+            // Every declared route is documented, under every API version
+            let declared = declared_routes();
+            let mut expected = BTreeSet::new();
+            for (method, path) in &declared {
+                if documented.contains(&(method.clone(), path.clone())) {
+                    expected.insert((method.clone(), path.clone()));
+                    continue;
+                }
+                for prefix in [API_PREFIX, API_V2_PREFIX] {
+                    let api_path = format!("{prefix}{path}");
+                    assert!(
+                        documented.contains(&(method.clone(), api_path.clone())),
+                        "{} {} is missing from the OpenAPI document.",
+                        method.to_uppercase(),
+                        api_path
+                    );
+                    expected.insert((method.clone(), api_path));
+                }
+            }
+            assert_eq!(documented, expected);
+
+            // Every documented route is served
+            for (method, path) in documented {
+                let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+                let mut res = srv
+                    .request(method.clone(), srv.url(&path))
+                    .send()
+                    .await
+                    .unwrap();
+                let res = parse_response_body::<ErrorResponseBody>(&mut res).await;
+                assert!(
+                    res.expected
+                        .is_none_or(|body| body.code != ErrorCode::RouteNotFound),
+                    "{method} {path} is documented but not served."
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(test)]
+pub mod repair {
+
+    use crate::commands::repair::{find_invalid_amounts, run_repair, RepairCommands};
+    use crate::entities::{currency, currency_rate_datum, user};
+    use crate::tests::commons::connect_test_database;
+    use finance_manager_migration::{Migrator, MigratorTrait};
+    use rust_decimal::Decimal;
+    use sea_orm::{ActiveValue::Set, ConnectionTrait, DatabaseConnection, EntityTrait};
+    use uuid::Uuid;
+
+    pub mod drivers {
+        use super::*;
+
+        /// Insert a user owning a base currency, bypassing the validation of the services.
+        /// Returns the user id and the base currency id.
+        pub async fn driver_insert_user_with_base(db: &DatabaseConnection) -> (Uuid, Uuid) {
+            let owner_id = Uuid::new_v4();
+            user::Entity::insert(user::ActiveModel {
+                id: Set(owner_id),
+                name: Set("repair".to_string()),
+                password_hash: Set("hash".to_string()),
+            })
+            .exec(db)
+            .await
+            .unwrap();
+            let base_id = driver_insert_currency(db, owner_id, "BASE", None).await;
+            (owner_id, base_id)
+        }
+
+        pub async fn driver_insert_currency(
+            db: &DatabaseConnection,
+            owner_id: Uuid,
+            ticker: &str,
+            fallback: Option<(Decimal, Uuid)>,
+        ) -> Uuid {
+            let id = Uuid::new_v4();
+            currency::Entity::insert(currency::ActiveModel {
+                id: Set(id),
+                owner_id: Set(owner_id),
+                name: Set(ticker.to_string()),
+                ticker: Set(ticker.to_string()),
+                is_base: Set(fallback.is_none()),
+                fallback_rate_amount: Set(fallback.map(|x| x.0)),
+                fallback_rate_currency_id: Set(fallback.map(|x| x.1)),
+                decimals: Set(2),
+                kind: Set("fiat".to_string()),
+                symbol: Set(None),
+                archived: Set(false),
+                rate_strategy: Set("linear".to_string()),
+                rate_max_gap_days: Set(None),
+                derive_rates: Set(false),
+            })
+            .exec(db)
+            .await
+            .unwrap();
+            id
+        }
+
+        pub async fn driver_insert_datum(
+            db: &DatabaseConnection,
+            owner_id: Uuid,
+            amount: Decimal,
+            ref_currency_id: Uuid,
+            ref_amount_currency_id: Uuid,
+            day: i64,
+        ) -> Uuid {
+            let id = Uuid::new_v4();
+            currency_rate_datum::Entity::insert(currency_rate_datum::ActiveModel {
+                id: Set(id),
+                owner_id: Set(owner_id),
+                amount: Set(amount),
+                ref_currency_id: Set(ref_currency_id),
+                ref_amount_currency_id: Set(ref_amount_currency_id),
+                date: Set(chrono::NaiveDateTime::default() + chrono::Duration::days(day)),
+                source_fragment_id: Set(None),
+            })
+            .exec(db)
+            .await
+            .unwrap();
+            id
+        }
+    }
+
+    mod tests {
+        use super::drivers::*;
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_repair_amounts_reports_invalid_rows() {
+            let db = connect_test_database().await;
+            Migrator::fresh(&db).await.unwrap();
+            let (owner_id, base_id) = driver_insert_user_with_base(&db).await;
+
+            // Valid rows only
+            let valid_id =
+                driver_insert_currency(&db, owner_id, "VAL", Some((Decimal::new(125, 2), base_id)))
+                    .await;
+            driver_insert_datum(&db, owner_id, Decimal::new(3, 0), valid_id, base_id, 1).await;
+            let report = run_repair(&db, RepairCommands::Amounts).await.unwrap();
+            assert!(report.invalid_rows.is_empty());
+            assert_eq!(report.to_lines(), vec!["Every amount is valid."]);
+
+            // Rows written before amounts were validated
+            let zero_id =
+                driver_insert_currency(&db, owner_id, "ZERO", Some((Decimal::ZERO, base_id))).await;
+            let negative_id =
+                driver_insert_datum(&db, owner_id, Decimal::new(-2, 0), valid_id, base_id, 2).await;
+            let nan_id =
+                driver_insert_datum(&db, owner_id, Decimal::ONE, valid_id, base_id, 3).await;
+            db.execute_unprepared(&format!(
+                "UPDATE currency_rate_datum SET amount = 'NaN' WHERE id = '{nan_id}'"
+            ))
+            .await
+            .unwrap();
+
+            let invalid_rows = find_invalid_amounts(&db).await.unwrap();
+            let mut found = invalid_rows
+                .iter()
+                .map(|row| (row.table.as_str(), row.id, row.value.as_str()))
+                .collect::<Vec<_>>();
+            found.sort();
+            let mut expected = vec![
+                ("currency", zero_id, "0"),
+                ("currency_rate_datum", negative_id, "-2"),
+                ("currency_rate_datum", nan_id, "NaN"),
+            ];
+            expected.sort();
+            assert_eq!(found, expected);
+            assert!(invalid_rows.iter().all(|row| row.owner_id == owner_id));
+
+            let report = run_repair(&db, RepairCommands::Amounts).await.unwrap();
+            assert_eq!(report.to_lines().len(), 3);
+            assert!(report.to_lines().contains(&format!(
+                "Invalid currency.fallback_rate_amount \"0\" in row {zero_id} owned by user {owner_id}."
+            )));
+        }
+
+        #[actix_web::test]
+        async fn test_repair_amounts_before_decimal_amounts_migration() {
+            use crate::tests::migrate_tests::migrate::drivers::driver_insert_legacy_amounts;
+            use sea_orm::sea_query::{Alias, Expr, Query};
+
+            let db = connect_test_database().await;
+            driver_insert_legacy_amounts(&db).await;
+            let set_fallback = |value: &str| {
+                Query::update()
+                    .table(Alias::new("currency"))
+                    .value(Alias::new("fallback_rate_amount"), value)
+                    .and_where(Expr::col(Alias::new("fallback_rate_amount")).is_not_null())
+                    .to_owned()
+            };
+            db.execute(db.get_database_backend().build(&set_fallback("1,2")))
+                .await
+                .unwrap();
+
+            // The migration refuses the legacy amount, the report finds it
+            assert!(Migrator::up(&db, None).await.is_err());
+            let report = run_repair(&db, RepairCommands::Amounts).await.unwrap();
+            assert_eq!(report.invalid_rows.len(), 1);
+            let row = &report.invalid_rows[0];
+            assert_eq!(
+                (row.table.as_str(), row.column.as_str(), row.value.as_str()),
+                ("currency", "fallback_rate_amount", "1,2")
+            );
+
+            db.execute(db.get_database_backend().build(&set_fallback("1.2")))
+                .await
+                .unwrap();
+            let report = run_repair(&db, RepairCommands::Amounts).await.unwrap();
+            assert!(report.invalid_rows.is_empty());
+            Migrator::up(&db, None).await.unwrap();
+            let fallback = currency::Entity::find()
+                .all(&db)
+                .await
+                .unwrap()
+                .into_iter()
+                .find_map(|x| x.fallback_rate_amount);
+            assert_eq!(fallback, Some(Decimal::new(12, 1)));
+        }
+    }
+}
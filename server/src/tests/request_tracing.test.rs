@@ -0,0 +1,128 @@
+#[cfg(test)]
+pub mod request_tracing {
+
+    use crate::env::{EnvLogFormat, EnvLogRotation};
+    use crate::logging::{fmt_layer, RollingFileWriter};
+    use crate::request_tracing::{request_tracing_middleware, REQUEST_ID_HEADER};
+    use crate::routes::bootstrap::apply_endpoints;
+    use crate::states::database_states::DatabaseStates;
+    use crate::tests::commons::*;
+    use crate::tests::user_tests::users::drivers::bootstrap_token;
+    use actix_http::StatusCode;
+    use actix_test::TestServer;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, web, App};
+    use std::sync::Mutex;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    pub mod drivers {
+        use super::*;
+
+        /// Send `GET /health` with an optional `X-Request-Id`, and return the status and the echoed id.
+        pub async fn driver_get_request_id(
+            request_id: Option<&str>,
+            app: &TestServer,
+        ) -> (StatusCode, String) {
+            let mut req = app.get("/health");
+            if let Some(request_id) = request_id {
+                req = req.insert_header((REQUEST_ID_HEADER, request_id));
+            }
+            let res = req.send().await.unwrap();
+            let echoed = res
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .expect("X-Request-Id is missing from the response.")
+                .to_str()
+                .unwrap()
+                .to_string();
+            (res.status(), echoed)
+        }
+    }
+
+    mod tests {
+        use super::drivers::*;
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_request_id_header() {
+            let srv = setup_connection().await;
+
+            // Generated when absent
+            {
+                let (status, request_id) = driver_get_request_id(None, &srv).await;
+                assert_eq!(status, StatusCode::OK);
+                assert!(uuid::Uuid::parse_str(&request_id).is_ok());
+                let (_, other_request_id) = driver_get_request_id(None, &srv).await;
+                assert_ne!(request_id, other_request_id);
+            }
+
+            // Propagated when given
+            {
+                let (_, request_id) = driver_get_request_id(Some("client-id-123"), &srv).await;
+                assert_eq!(request_id, "client-id-123");
+            }
+
+            // Replaced when unsafe to log
+            {
+                let too_long = "a".repeat(129);
+                let (_, request_id) = driver_get_request_id(Some(&too_long), &srv).await;
+                assert!(uuid::Uuid::parse_str(&request_id).is_ok());
+                let (_, request_id) = driver_get_request_id(Some("a b"), &srv).await;
+                assert!(uuid::Uuid::parse_str(&request_id).is_ok());
+            }
+
+            // Present on errors too
+            {
+                let res = srv.get("/api/v1/not-a-route").send().await.unwrap();
+                assert_eq!(res.status(), StatusCode::NOT_FOUND);
+                assert!(res.headers().contains_key(REQUEST_ID_HEADER));
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_request_span_fields() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+
+            let log_path =
+                std::env::temp_dir().join(format!("fm-tracing-test-{}.log", uuid::Uuid::new_v4()));
+            let writer = RollingFileWriter::open(&log_path, EnvLogRotation::Never).unwrap();
+            let subscriber = tracing_subscriber::registry().with(fmt_layer(
+                EnvLogFormat::Json,
+                Mutex::new(writer),
+                false,
+            ));
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            let db = connect_test_database().await;
+            let app = test::init_service(apply_endpoints(
+                App::new()
+                    .wrap(from_fn(request_tracing_middleware))
+                    .app_data(web::Data::new(DatabaseStates::new(db))),
+            ))
+            .await;
+            let req = test::TestRequest::get()
+                .uri("/api/v1/currencies")
+                .insert_header(("authorization", token.as_str()))
+                .insert_header((REQUEST_ID_HEADER, "span-test"))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            let logs = std::fs::read_to_string(&log_path).unwrap();
+            let completed = logs
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+                .find(|line| line["fields"]["message"] == "Request completed.")
+                .expect("No request was logged.");
+            let span = &completed["span"];
+            assert_eq!(span["name"], "request");
+            assert_eq!(span["request_id"], "span-test");
+            assert_eq!(span["method"], "GET");
+            assert_eq!(span["route"], "/api/v1/currencies");
+            assert_eq!(span["status"], 200);
+            assert!(uuid::Uuid::parse_str(span["user_id"].as_str().unwrap()).is_ok());
+            assert!(completed["fields"]["elapsed_ms"].is_u64());
+        }
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(test)]
+pub mod server {
+
+    use crate::env::EnvServerSection;
+    use crate::routes::bootstrap::{apply_endpoints, json_config, ErrorCode, ErrorResponseBody};
+    use crate::routes::currencies::post_currency::PostCurrencyRequestBody;
+    use crate::tests::commons::*;
+    use crate::tests::currency_tests::currencies::drivers::driver_post_currency;
+    use crate::tests::user_tests::users::drivers::bootstrap_token;
+    use actix_http::{Method, StatusCode};
+    use actix_web::http::header;
+    use actix_web::{test, App};
+    use serde_json::json;
+
+    pub mod drivers {
+        use super::*;
+
+        pub fn server_section(cors_origins: Option<Vec<&str>>) -> EnvServerSection {
+            EnvServerSection {
+                port: None,
+                dist_folder_path: String::new(),
+                ssl: None,
+                bind_hosts: None,
+                cors_origins: cors_origins.map(|x| x.into_iter().map(String::from).collect()),
+                max_body_size: None,
+                workers: None,
+                shutdown_timeout: None,
+            }
+        }
+    }
+
+    mod tests {
+        use super::drivers::*;
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_json_body_errors() {
+            let app =
+                test::init_service(apply_endpoints(App::new().app_data(json_config(64)))).await;
+
+            // Malformed JSON is reported in the project's error format
+            {
+                let req = test::TestRequest::post()
+                    .uri("/api/v1/login")
+                    .insert_header(header::ContentType::json())
+                    .set_payload("{")
+                    .to_request();
+                let res = test::call_service(&app, req).await;
+                assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+                let body: ErrorResponseBody = test::read_body_json(res).await;
+                assert_eq!(body.code, ErrorCode::InvalidJsonBody);
+                assert!(body.message.starts_with("Invalid JSON body: "));
+            }
+
+            // Wrong content type
+            {
+                let req = test::TestRequest::post()
+                    .uri("/api/v1/login")
+                    .insert_header(header::ContentType::plaintext())
+                    .set_payload("{}")
+                    .to_request();
+                let res = test::call_service(&app, req).await;
+                assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+                let body: ErrorResponseBody = test::read_body_json(res).await;
+                assert_eq!(body.code, ErrorCode::UnsupportedContentType);
+            }
+
+            // Body over the configured limit
+            {
+                let req = test::TestRequest::post()
+                    .uri("/api/v1/login")
+                    .insert_header(header::ContentType::json())
+                    .set_payload(format!(
+                        r#"{{"username": "{}", "password": "123"}}"#,
+                        "a".repeat(128)
+                    ))
+                    .to_request();
+                let res = test::call_service(&app, req).await;
+                assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+                let body: ErrorResponseBody = test::read_body_json(res).await;
+                assert_eq!(body.code, ErrorCode::PayloadTooLarge);
+                assert_eq!(body.message, "Request body exceeds the limit of 64 bytes.");
+                assert_eq!(body.details, Some(json!({ "limit": 64 })));
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_error_response_body() {
+            let srv = setup_connection().await;
+
+            // Missing token
+            {
+                let mut res = srv.get("/api/v1/accounts").send().await.unwrap();
+                let resp: AssertTestResponse<ErrorResponseBody> =
+                    parse_response_body(&mut res).await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+                assert_eq!(resp.expected.unwrap().code, ErrorCode::Unauthorized);
+            }
+
+            // Unknown token
+            {
+                let mut res = attach_token_to_req(
+                    srv.get("/api/v1/accounts"),
+                    Some(&uuid::Uuid::new_v4().to_string()),
+                )
+                .send()
+                .await
+                .unwrap();
+                let resp: AssertTestResponse<ErrorResponseBody> =
+                    parse_response_body(&mut res).await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+                assert_eq!(resp.expected.unwrap().code, ErrorCode::Unauthorized);
+            }
+
+            // Unknown route
+            {
+                let mut res = srv.get("/api/v1/not-a-route").send().await.unwrap();
+                let resp: AssertTestResponse<ErrorResponseBody> =
+                    parse_response_body(&mut res).await;
+                assert_eq!(resp.status, StatusCode::NOT_FOUND);
+                assert_eq!(resp.expected.unwrap().code, ErrorCode::RouteNotFound);
+            }
+
+            // The missing entity is reported in details
+            {
+                let token = bootstrap_token(("123", "123"), &srv).await;
+                let missing_id = uuid::Uuid::new_v4();
+                let resp = driver_post_currency(
+                    Some(&token),
+                    TestBody::Expected(PostCurrencyRequestBody {
+                        name: "Sec".to_string(),
+                        ticker: "SEC".to_string(),
+                        fallback_rate_amount: Some("1".to_string()),
+                        fallback_rate_currency_id: Some(missing_id.to_string()),
+                        decimals: None,
+                        kind: None,
+                        symbol: None,
+                        rate_strategy: None,
+                        rate_max_gap_days: None,
+                        derive_rates: None,
+                    }),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::NOT_FOUND);
+                let body: ErrorResponseBody =
+                    serde_json::from_value(json!(resp.json.unwrap())).unwrap();
+                assert_eq!(body.code, ErrorCode::CurrencyNotFound);
+                assert_eq!(body.details, Some(json!({ "id": missing_id })));
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_cors_origins() {
+            let preflight = |origin: &str| {
+                test::TestRequest::default()
+                    .method(Method::OPTIONS)
+                    .uri("/api/v1/login")
+                    .insert_header((header::ORIGIN, origin))
+                    .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+                    .to_request()
+            };
+
+            // Only listed origins are allowed
+            {
+                let section = server_section(Some(vec!["https://allowed.example"]));
+                let app =
+                    test::init_service(apply_endpoints(App::new().wrap(section.to_cors()))).await;
+
+                let res = test::call_service(&app, preflight("https://allowed.example")).await;
+                assert_eq!(res.status(), StatusCode::OK);
+                assert_eq!(
+                    res.headers()
+                        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                        .unwrap(),
+                    "https://allowed.example"
+                );
+
+                let res = test::call_service(&app, preflight("https://other.example")).await;
+                assert!(res
+                    .headers()
+                    .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                    .is_none());
+            }
+
+            // Wildcard allows any origin
+            {
+                let section = server_section(Some(vec!["*"]));
+                let app =
+                    test::init_service(apply_endpoints(App::new().wrap(section.to_cors()))).await;
+                let res = test::call_service(&app, preflight("https://other.example")).await;
+                assert_eq!(res.status(), StatusCode::OK);
+                assert!(res
+                    .headers()
+                    .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                    .is_some());
+            }
+
+            // No origins configured rejects every cross-origin request
+            {
+                let section = server_section(None);
+                let app =
+                    test::init_service(apply_endpoints(App::new().wrap(section.to_cors()))).await;
+                let res = test::call_service(&app, preflight("https://allowed.example")).await;
+                assert!(res
+                    .headers()
+                    .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                    .is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+pub mod shutdown {
+
+    use crate::services::TransactionWithCallback;
+    use crate::shutdown::{Shutdown, TransactionStage, SHUTDOWN};
+    use crate::tests::commons::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    pub mod drivers {
+        use super::*;
+
+        /// A tracker of its own, so deadlines set by a test do not leak into the others.
+        pub fn new_shutdown() -> &'static Shutdown {
+            Box::leak(Box::default())
+        }
+    }
+
+    mod tests {
+        use super::drivers::*;
+        use super::*;
+
+        #[actix_web::test]
+        async fn test_in_flight_transactions() {
+            let db = connect_test_database().await;
+            let before = SHUTDOWN.in_flight();
+
+            // Committed, callbacks run before the transaction stops being tracked
+            {
+                let called = Rc::new(Cell::new(false));
+                let mut db_txn = TransactionWithCallback::from_db_conn(&db, vec![])
+                    .await
+                    .unwrap();
+                assert_eq!(SHUTDOWN.in_flight(), before + 1);
+                let callback_called = called.clone();
+                db_txn.add_callback(async move {
+                    assert_eq!(SHUTDOWN.in_flight(), before + 1);
+                    callback_called.set(true);
+                });
+                db_txn.commit().await;
+                assert!(called.get());
+                assert_eq!(SHUTDOWN.in_flight(), before);
+            }
+
+            // Rolled back
+            {
+                let db_txn = TransactionWithCallback::from_db_conn(&db, vec![])
+                    .await
+                    .unwrap();
+                assert_eq!(SHUTDOWN.in_flight(), before + 1);
+                db_txn.rollback().await;
+                assert_eq!(SHUTDOWN.in_flight(), before);
+            }
+
+            // Dropped
+            {
+                let db_txn = TransactionWithCallback::from_db_conn(&db, vec![])
+                    .await
+                    .unwrap();
+                drop(db_txn);
+                assert_eq!(SHUTDOWN.in_flight(), before);
+            }
+            assert_eq!(SHUTDOWN.cut_off(), 0);
+        }
+
+        #[test]
+        fn test_cut_off_transactions() {
+            let shutdown = new_shutdown();
+
+            // Dropped before shutdown, a normal rollback
+            drop(shutdown.track());
+            assert_eq!(shutdown.cut_off(), 0);
+
+            let finished = shutdown.track();
+            let mut committing = shutdown.track();
+            committing.set_stage(TransactionStage::Committing);
+            let open = shutdown.track();
+            assert_eq!(shutdown.in_flight(), 3);
+
+            // Still within the timeout
+            shutdown.begin(Duration::from_secs(3600));
+            assert!(!shutdown.is_past_deadline());
+            finished.finish();
+            assert_eq!(shutdown.cut_off(), 0);
+
+            // Past the timeout, whatever is dropped unfinished was cut off
+            shutdown.begin(Duration::ZERO);
+            assert!(shutdown.is_past_deadline());
+            drop(committing);
+            drop(open);
+            assert_eq!(shutdown.cut_off(), 2);
+            assert_eq!(shutdown.in_flight(), 0);
+        }
+    }
+}
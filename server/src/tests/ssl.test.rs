@@ -0,0 +1,160 @@
+#[cfg(test)]
+pub mod ssl {
+
+    use crate::env::ServerSSLSection;
+    use crate::ssl::{redirect_to_https, HttpsPort, LoadSSLErrors};
+    use crate::tests::commons::setup_connection_with_config;
+    use crate::tests::ssl_tests::ssl::drivers::*;
+    use actix_http::{header, StatusCode};
+    use actix_test::TestServerConfig;
+    use actix_web::{test, web, App};
+
+    pub mod drivers {
+        use openssl::asn1::Asn1Time;
+        use openssl::bn::{BigNum, MsbOption};
+        use openssl::hash::MessageDigest;
+        use openssl::pkey::{PKey, Private};
+        use openssl::rsa::Rsa;
+        use openssl::x509::{X509NameBuilder, X509};
+        use std::path::PathBuf;
+
+        pub fn generate_key() -> PKey<Private> {
+            PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap()
+        }
+
+        /// Generate a self-signed certificate for `localhost` signed by the given key.
+        pub fn generate_self_signed_cert(key: &PKey<Private>) -> X509 {
+            let mut name = X509NameBuilder::new().unwrap();
+            name.append_entry_by_text("CN", "localhost").unwrap();
+            let name = name.build();
+
+            let mut serial = BigNum::new().unwrap();
+            serial.rand(64, MsbOption::MAYBE_ZERO, false).unwrap();
+
+            let mut builder = X509::builder().unwrap();
+            builder.set_version(2).unwrap();
+            builder
+                .set_serial_number(&serial.to_asn1_integer().unwrap())
+                .unwrap();
+            builder.set_subject_name(&name).unwrap();
+            builder.set_issuer_name(&name).unwrap();
+            builder.set_pubkey(key).unwrap();
+            builder
+                .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+                .unwrap();
+            builder
+                .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+                .unwrap();
+            builder.sign(key, MessageDigest::sha256()).unwrap();
+            builder.build()
+        }
+
+        /// Write the given bytes into a unique file under the temp directory, and return its path.
+        pub fn write_temp_file(contents: &[u8]) -> String {
+            let path: PathBuf =
+                std::env::temp_dir().join(format!("fm-ssl-test-{}.pem", uuid::Uuid::new_v4()));
+            std::fs::write(&path, contents).unwrap();
+            path.to_string_lossy().to_string()
+        }
+
+        /// Write a freshly generated self-signed certificate and its key into temp files.
+        /// Returns `(pem_path, key_path)`.
+        pub fn bootstrap_cert_files() -> (String, String) {
+            let key = generate_key();
+            let cert = generate_self_signed_cert(&key);
+            (
+                write_temp_file(&cert.to_pem().unwrap()),
+                write_temp_file(&key.private_key_to_pem_pkcs8().unwrap()),
+            )
+        }
+    }
+
+    mod tests {
+        use super::*;
+
+        fn section(pem_path: &str, key_path: &str) -> ServerSSLSection {
+            ServerSSLSection {
+                pem_path: pem_path.to_string(),
+                key_path: key_path.to_string(),
+                http_redirect_port: None,
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_serve_https() {
+            let (pem_path, key_path) = bootstrap_cert_files();
+            let acceptor = section(&pem_path, &key_path).to_ssl_acceptor().unwrap();
+            let srv =
+                setup_connection_with_config(TestServerConfig::default().openssl(acceptor.build()))
+                    .await;
+
+            assert!(srv.url("/").starts_with("https://"));
+            let res = srv.get("/api/v1/users/export").send().await.unwrap();
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        #[actix_web::test]
+        async fn test_invalid_ssl_files() {
+            let (pem_path, key_path) = bootstrap_cert_files();
+            let (_other_pem_path, other_key_path) = bootstrap_cert_files();
+            let garbage_path = write_temp_file(b"not a pem file");
+            let missing_path = std::env::temp_dir()
+                .join(format!("fm-ssl-test-{}.pem", uuid::Uuid::new_v4()))
+                .to_string_lossy()
+                .to_string();
+
+            assert!(matches!(
+                section(&missing_path, &key_path).to_ssl_acceptor(),
+                Err(LoadSSLErrors::UnreadableFile { .. })
+            ));
+            assert!(matches!(
+                section(&pem_path, &missing_path).to_ssl_acceptor(),
+                Err(LoadSSLErrors::UnreadableFile { .. })
+            ));
+            assert!(matches!(
+                section(&garbage_path, &key_path).to_ssl_acceptor(),
+                Err(LoadSSLErrors::InvalidCertificateChain { .. })
+                    | Err(LoadSSLErrors::EmptyCertificateChain { .. })
+            ));
+            assert!(matches!(
+                section(&pem_path, &garbage_path).to_ssl_acceptor(),
+                Err(LoadSSLErrors::InvalidPrivateKey { .. })
+            ));
+            assert!(matches!(
+                section(&pem_path, &other_key_path).to_ssl_acceptor(),
+                Err(LoadSSLErrors::MismatchedPrivateKey { .. })
+            ));
+        }
+
+        #[actix_web::test]
+        async fn test_redirect_to_https() {
+            let app = test::init_service(
+                App::new()
+                    .app_data(web::Data::new(HttpsPort(8443)))
+                    .default_service(web::to(redirect_to_https)),
+            )
+            .await;
+
+            let cases = [
+                (
+                    "example.com:8080",
+                    "https://example.com:8443/currencies?id=123",
+                ),
+                ("example.com", "https://example.com:8443/currencies?id=123"),
+                ("[::1]:8080", "https://[::1]:8443/currencies?id=123"),
+            ];
+            for (host, expected_location) in cases {
+                let req = test::TestRequest::get()
+                    .uri("/currencies?id=123")
+                    .insert_header((header::HOST, host))
+                    .to_request();
+                let res = test::call_service(&app, req).await;
+                assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+                assert_eq!(
+                    res.headers().get(header::LOCATION).unwrap(),
+                    expected_location
+                );
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
 #[cfg(test)]
 pub mod txns {
+    use crate::routes::txns::delete_txn::DeleteTxnResponse;
     use crate::routes::txns::get_txns::GetTxnsResponse;
     use crate::routes::txns::post_txns::PostTxnRequest;
     use crate::routes::txns::post_txns::PostTxnResponse;
+    use crate::routes::txns::put_txn::PutTxnResponse;
     use crate::tests::commons::attach_token_to_req;
     use crate::tests::commons::parse_response_body;
     use crate::tests::commons::send_req_with_body;
@@ -21,7 +23,7 @@ pub mod txns {
             app: &actix_test::TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<PostTxnResponse> {
-            let mut req = app.post("/txns");
+            let mut req = app.post("/api/v1/txns");
             req = attach_token_to_req(req, token);
             req = req.insert_header(ContentType::json());
             let mut res = send_req_with_body(req, body).await;
@@ -38,12 +40,46 @@ pub mod txns {
             res_parsed
         }
 
+        pub async fn driver_put_txn(
+            id: &str,
+            token: Option<&str>,
+            body: TestBody<PostTxnRequest>,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<PutTxnResponse> {
+            let mut req = app.put(format!("/api/v1/txns/{id}"));
+            req = attach_token_to_req(req, token);
+            req = req.insert_header(ContentType::json());
+            let mut res = send_req_with_body(req, body).await;
+            let res_parsed = parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_delete_txn(
+            id: &str,
+            token: Option<&str>,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<DeleteTxnResponse> {
+            let mut req = app.delete(format!("/api/v1/txns/{id}"));
+            req = attach_token_to_req(req, token);
+            let mut res = req.send().await.unwrap();
+            let res_parsed = parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
         pub async fn driver_get_txns(
             token: Option<&str>,
             app: &actix_test::TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<GetTxnsResponse> {
-            let mut req = app.get("/txns");
+            let mut req = app.get("/api/v1/txns");
             req = attach_token_to_req(req, token);
             req = req.insert_header(ContentType::json());
             let mut res = req.send().await.unwrap();
@@ -63,16 +99,22 @@ pub mod txns {
 
     mod tests {
 
+        use super::drivers::driver_delete_txn;
         use super::drivers::driver_get_txns;
         use super::drivers::driver_post_txn;
+        use super::drivers::driver_put_txn;
         use super::*;
+        use crate::maths::round_amount;
+        use crate::routes::bootstrap::ErrorCode;
         use crate::routes::txns::post_txns::PostTxnRequestFragment;
         use crate::routes::txns::post_txns::PostTxnRequestFragmentSide;
         use crate::tests::account_tests::accounts::drivers::bootstrap_post_account;
         use crate::tests::commons::setup_connection;
         use crate::tests::currency_tests::currencies::drivers::bootstrap_base_curr;
+        use crate::tests::currency_tests::currencies::drivers::bootstrap_curr_with_decimals;
         use crate::tests::currency_tests::currencies::drivers::bootstrap_sec_curr;
         use crate::tests::user_tests::users::drivers::bootstrap_token;
+        use rust_decimal::Decimal;
 
         #[actix_web::test]
         async fn test_curd_txns() {
@@ -267,5 +309,200 @@ pub mod txns {
                 assert_eq!(resp.status, StatusCode::NOT_FOUND);
             }
         }
+
+        #[actix_web::test]
+        async fn test_fragment_amount_precision() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let jpy = bootstrap_curr_with_decimals("JPY", 0, &usd, &token, &srv).await;
+            let btc = bootstrap_curr_with_decimals("BTC", 8, &usd, &token, &srv).await;
+            let account = bootstrap_post_account("My account", &token, &srv).await;
+            let txn_with_amount = |currency: &str, amount: &str| PostTxnRequest {
+                description: String::new(),
+                title: format!("{amount} {currency}"),
+                date_utc: "2025-01-01T01:02:00.000Z".to_string(),
+                fragments: vec![PostTxnRequestFragment {
+                    from: None,
+                    to: Some(PostTxnRequestFragmentSide {
+                        account: account.clone(),
+                        currency: currency.to_string(),
+                        amount: amount.to_string(),
+                    }),
+                }],
+            };
+
+            for (currency, amount, decimals) in [
+                (&usd, "1.005", 2),
+                (&jpy, "1.5", 0),
+                (&btc, "0.000000001", 8),
+            ] {
+                let resp = driver_post_txn(
+                    Some(&token),
+                    TestBody::Expected(txn_with_amount(currency, amount)),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::BAD_REQUEST, "{amount}");
+                let json = resp.json.unwrap();
+                assert_eq!(
+                    json["code"],
+                    serde_json::to_value(ErrorCode::AmountPrecisionExceeded).unwrap()
+                );
+                assert_eq!(json["details"]["decimals"], decimals);
+                assert_eq!(json["details"]["id"], currency.as_str());
+            }
+
+            // Trailing zeros do not count
+            for (currency, amount) in [(&usd, "1.50"), (&jpy, "100.0"), (&btc, "0.00000001")] {
+                driver_post_txn(
+                    Some(&token),
+                    TestBody::Expected(txn_with_amount(currency, amount)),
+                    &srv,
+                    true,
+                )
+                .await;
+            }
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert_eq!(txns.items.len(), 3);
+        }
+
+        #[actix_web::test]
+        async fn test_edit_delete_txns() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let base_cid = bootstrap_base_curr(("BASE", "Base"), &token, &srv).await;
+            let account = bootstrap_post_account("My account", &token, &srv).await;
+            let txn = |title: &str, amount: &str| PostTxnRequest {
+                description: String::new(),
+                title: title.to_string(),
+                date_utc: "2025-01-01T01:02:00.000Z".to_string(),
+                fragments: vec![PostTxnRequestFragment {
+                    from: None,
+                    to: Some(PostTxnRequestFragmentSide {
+                        account: account.clone(),
+                        currency: base_cid.clone(),
+                        amount: amount.to_string(),
+                    }),
+                }],
+            };
+            let id = driver_post_txn(Some(&token), TestBody::Expected(txn("a", "1")), &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .id;
+
+            // Authentication, ids and ownership are checked before anything is changed
+            {
+                let resp =
+                    driver_put_txn(&id, None, TestBody::Expected(txn("b", "2")), &srv, false).await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+                let resp = driver_delete_txn(&id, None, &srv, false).await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+
+                let resp = driver_delete_txn("not-an-id", Some(&token), &srv, false).await;
+                assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+                assert_eq!(
+                    resp.json.unwrap()["code"],
+                    serde_json::to_value(ErrorCode::InvalidUuid).unwrap()
+                );
+
+                let other_token = bootstrap_token(("1234", "1234"), &srv).await;
+                let resp = driver_put_txn(
+                    &id,
+                    Some(&other_token),
+                    TestBody::Expected(txn("b", "2")),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::NOT_FOUND);
+                let resp = driver_delete_txn(&id, Some(&other_token), &srv, false).await;
+                assert_eq!(resp.status, StatusCode::NOT_FOUND);
+
+                let txns = driver_get_txns(Some(&token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert_eq!(txns.items.len(), 1);
+                assert_eq!(txns.items[0].title, "a");
+            }
+
+            // The transaction and its fragments are replaced
+            driver_put_txn(
+                &id,
+                Some(&token),
+                TestBody::Expected(txn("b", "2")),
+                &srv,
+                true,
+            )
+            .await;
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert_eq!(txns.items.len(), 1);
+            assert_eq!(txns.items[0].id, id);
+            assert_eq!(txns.items[0].title, "b");
+            assert_eq!(txns.items[0].fragments.len(), 1);
+            assert_eq!(txns.items[0].fragments[0].to.as_ref().unwrap().amount, "2");
+
+            // Invalid fragments leave the transaction as it was
+            let resp = driver_put_txn(
+                &id,
+                Some(&token),
+                TestBody::Expected(txn("c", "2.001")),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert_eq!(txns.items[0].title, "b");
+
+            driver_delete_txn(&id, Some(&token), &srv, true).await;
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert!(txns.items.is_empty());
+
+            // Unknown transactions
+            let resp = driver_delete_txn(&id, Some(&token), &srv, false).await;
+            assert_eq!(resp.status, StatusCode::NOT_FOUND);
+            assert_eq!(
+                resp.json.unwrap()["code"],
+                serde_json::to_value(ErrorCode::TxnNotFound).unwrap()
+            );
+            let resp = driver_put_txn(
+                &id,
+                Some(&token),
+                TestBody::Expected(txn("b", "2")),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(resp.status, StatusCode::NOT_FOUND);
+        }
+
+        #[test]
+        fn test_amount_rounding_is_half_to_even() {
+            let round = |amount: &str, decimals: u32| {
+                round_amount(&Decimal::from_str_exact(amount).unwrap(), decimals).to_string()
+            };
+            assert_eq!(round("0.125", 2), "0.12");
+            assert_eq!(round("0.135", 2), "0.14");
+            assert_eq!(round("2.5", 0), "2");
+            assert_eq!(round("-2.5", 0), "-2");
+            assert_eq!(round("0.126", 2), "0.13");
+            assert_eq!(round("1.5", 2), "1.5");
+        }
     }
 }
@@ -22,7 +22,7 @@ pub mod txn_tags {
             app: &TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<PostTxnTagResponseBody> {
-            let mut req = app.post("/txnTags");
+            let mut req = app.post("/api/v1/txnTags");
             req = req.insert_header(ContentType::json());
             req = attach_token_to_req(req, token);
             let mut res = send_req_with_body(req, body).await;
@@ -39,7 +39,7 @@ pub mod txn_tags {
             app: &TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<GetTxnTagsResponseBody> {
-            let mut req = app.get("/txnTags");
+            let mut req = app.get("/api/v1/txnTags");
             req = req.insert_header(ContentType::json());
             req = attach_token_to_req(req, token);
             let mut res = req
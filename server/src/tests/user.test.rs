@@ -1,6 +1,9 @@
 #[cfg(test)]
 pub mod users {
 
+    use crate::extended_models::user_archive::UserArchive;
+    use crate::routes::users::delete_user::DeleteUserResponseBody;
+    use crate::routes::users::import_user::PostImportUserResponseBody;
     use crate::routes::users::register::PostUserRequestBody;
     use crate::routes::users::register::PostUserResponseBody;
     use crate::tests::commons::*;
@@ -42,7 +45,7 @@ pub mod users {
             app: &TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<LoginResponseBody> {
-            let mut req = app.post("/login");
+            let mut req = app.post("/api/v1/login");
             req = req.insert_header(ContentType::json());
             let mut res = send_req_with_body(req, body).await;
             let res_parsed: AssertTestResponse<LoginResponseBody> =
@@ -53,12 +56,62 @@ pub mod users {
             res_parsed
         }
 
+        pub async fn driver_export_user(
+            token: Option<&str>,
+            app: &TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<UserArchive> {
+            let mut req = app.get("/api/v1/users/export");
+            req = attach_token_to_req(req, token);
+            let mut res = req.send().await.unwrap();
+            let res_parsed: AssertTestResponse<UserArchive> = parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_import_user(
+            body: TestBody<UserArchive>,
+            remap_ids: bool,
+            token: Option<&str>,
+            app: &TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<PostImportUserResponseBody> {
+            let mut req = app.post(format!("/api/v1/users/import?remapIds={remap_ids}"));
+            req = attach_token_to_req(req, token);
+            req = req.insert_header(ContentType::json());
+            let mut res = send_req_with_body(req, body).await;
+            let res_parsed: AssertTestResponse<PostImportUserResponseBody> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_delete_user(
+            token: Option<&str>,
+            app: &TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<DeleteUserResponseBody> {
+            let mut req = app.delete("/api/v1/users");
+            req = attach_token_to_req(req, token);
+            let mut res = req.send().await.unwrap();
+            let res_parsed: AssertTestResponse<DeleteUserResponseBody> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
         pub async fn driver_post_user(
             body: TestBody<PostUserRequestBody>,
             app: &TestServer,
             assert_default: bool,
         ) -> AssertTestResponse<PostUserResponseBody> {
-            let mut req = app.post("/users");
+            let mut req = app.post("/api/v1/users");
             req = req.insert_header(ContentType::json());
             let mut res = send_req_with_body(req, body).await;
             let res_parsed: AssertTestResponse<PostUserResponseBody> =
@@ -72,6 +125,522 @@ pub mod users {
 
     mod tests {
         use super::*;
+        use crate::extended_models::currency::{CurrencyKind, RateStrategy};
+        use crate::extended_models::user_archive::USER_ARCHIVE_VERSION;
+        use crate::extended_models::user_archive::{UserArchiveTxnTag, UserArchiveWalletTxn};
+        use crate::extended_models::wallet::WalletChain;
+        use crate::extractors::auth_user::AuthUser;
+        use crate::routes::currencies::patch_currency::PatchCurrencyRequestBody;
+        use crate::routes::txn_tags::create_tag::PostTxnTagRequestBody;
+        use crate::routes::txns::post_txns::{
+            PostTxnRequest, PostTxnRequestFragment, PostTxnRequestFragmentSide,
+        };
+        use crate::tests::account_tests::accounts::drivers::bootstrap_post_account;
+        use crate::tests::currency_rate_datum::currency_rate_datums::drivers::bootstrap_post_rate_datum;
+        use crate::tests::currency_rate_source::currency_rate_sources::drivers::bootstrap_http_rate_source;
+        use crate::tests::currency_tests::currencies::drivers::{
+            bootstrap_base_curr, bootstrap_sec_curr, driver_patch_currency,
+        };
+        use crate::tests::txn::txns::drivers::driver_post_txn;
+        use crate::tests::txn_tag::txn_tags::drivers::driver_post_txn_tag;
+        use crate::tests::wallet::wallets::drivers::bootstrap_wallet;
+
+        #[actix_web::test]
+        async fn test_export_and_delete_user() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let other_token = bootstrap_token(("1234", "1234"), &srv).await;
+
+            let base_cid = bootstrap_base_curr(("BASE", "Base"), &token, &srv).await;
+            let sec_cid = bootstrap_sec_curr(("SEC", "Sec"), "5", &base_cid, &token, &srv).await;
+            let account_id = bootstrap_post_account("My account", &token, &srv).await;
+            bootstrap_post_rate_datum(
+                "10",
+                "2025-01-01T01:00:00.000Z",
+                &base_cid,
+                &sec_cid,
+                &token,
+                &srv,
+            )
+            .await;
+            driver_post_txn_tag(
+                TestBody::Expected(PostTxnTagRequestBody {
+                    name: "My Tag".to_string(),
+                }),
+                Some(&token),
+                &srv,
+                true,
+            )
+            .await;
+            driver_post_txn(
+                Some(&token),
+                TestBody::Expected(PostTxnRequest {
+                    description: "my description".to_string(),
+                    title: "my title".to_string(),
+                    date_utc: "2025-01-01T01:02:00.000Z".to_string(),
+                    fragments: vec![PostTxnRequestFragment {
+                        from: Some(PostTxnRequestFragmentSide {
+                            account: account_id.clone(),
+                            currency: sec_cid.clone(),
+                            amount: "1.5".to_string(),
+                        }),
+                        to: None,
+                    }],
+                }),
+                &srv,
+                true,
+            )
+            .await;
+            let _other_base_cid = bootstrap_base_curr(("BASE", "Base"), &other_token, &srv).await;
+
+            // Export without token
+            {
+                let resp = driver_export_user(None, &srv, false).await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+            }
+
+            // Export contains every owned row, and nothing of other users
+            {
+                let archive = driver_export_user(Some(&token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert_eq!(archive.version, USER_ARCHIVE_VERSION);
+                assert_eq!(archive.accounts.len(), 1);
+                assert_eq!(archive.accounts.first().unwrap().id.to_string(), account_id);
+                assert_eq!(archive.currencies.len(), 2);
+                assert_eq!(archive.currency_rate_datums.len(), 1);
+                assert_eq!(archive.currency_rate_datums.first().unwrap().amount, "10");
+                assert_eq!(archive.txn_tags.len(), 1);
+                assert_eq!(archive.txns.len(), 1);
+                let txn = archive.txns.first().unwrap();
+                assert_eq!(txn.date, "2025-01-01T01:02:00.000Z");
+                assert_eq!(txn.fragments.len(), 1);
+                let from = txn.fragments.first().unwrap().from.clone().unwrap();
+                assert_eq!(from.amount, "1.5");
+                assert_eq!(from.currency.to_string(), sec_cid);
+                assert!(txn.fragments.first().unwrap().to.is_none());
+            }
+
+            // Delete without token
+            {
+                let resp = driver_delete_user(None, &srv, false).await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+            }
+
+            // Delete the user, the token should no longer work afterwards
+            {
+                driver_delete_user(Some(&token), &srv, true).await;
+                let resp = driver_export_user(Some(&token), &srv, false).await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+            }
+
+            // Other users are left untouched
+            {
+                let archive = driver_export_user(Some(&other_token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert_eq!(archive.currencies.len(), 1);
+            }
+
+            // The username is free to be registered again
+            {
+                let token = bootstrap_token(("123", "123"), &srv).await;
+                let archive = driver_export_user(Some(&token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert!(archive.currencies.is_empty());
+                assert!(archive.txns.is_empty());
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_import_user() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+
+            let base_cid = bootstrap_base_curr(("BASE", "Base"), &token, &srv).await;
+            let sec_cid = bootstrap_sec_curr(("SEC", "Sec"), "5", &base_cid, &token, &srv).await;
+            let third_cid = bootstrap_sec_curr(("TRD", "Third"), "2", &sec_cid, &token, &srv).await;
+            driver_patch_currency(
+                &sec_cid,
+                Some(&token),
+                TestBody::Expected(PatchCurrencyRequestBody {
+                    kind: Some(CurrencyKind::Security),
+                    symbol: Some("§".to_string()),
+                    archived: Some(true),
+                    rate_strategy: Some(RateStrategy::LinearWithMaxGap),
+                    rate_max_gap_days: Some(30),
+                    derive_rates: None,
+                }),
+                &srv,
+                true,
+            )
+            .await;
+            let account_id = bootstrap_post_account("My account", &token, &srv).await;
+            bootstrap_post_rate_datum(
+                "10",
+                "2025-01-01T01:00:00.000Z",
+                &base_cid,
+                &sec_cid,
+                &token,
+                &srv,
+            )
+            .await;
+            driver_post_txn_tag(
+                TestBody::Expected(PostTxnTagRequestBody {
+                    name: "My Tag".to_string(),
+                }),
+                Some(&token),
+                &srv,
+                true,
+            )
+            .await;
+            driver_post_txn(
+                Some(&token),
+                TestBody::Expected(PostTxnRequest {
+                    description: "my description".to_string(),
+                    title: "my title".to_string(),
+                    date_utc: "2025-01-01T01:02:00.000Z".to_string(),
+                    fragments: vec![PostTxnRequestFragment {
+                        from: Some(PostTxnRequestFragmentSide {
+                            account: account_id.clone(),
+                            currency: third_cid.clone(),
+                            amount: "1.5".to_string(),
+                        }),
+                        to: None,
+                    }],
+                }),
+                &srv,
+                true,
+            )
+            .await;
+
+            bootstrap_http_rate_source(
+                &sec_cid,
+                &base_cid,
+                "example.com",
+                "/rates",
+                "rate",
+                &token,
+                &srv,
+            )
+            .await;
+            bootstrap_wallet(
+                "Bitcoin",
+                WalletChain::Btc,
+                &account_id,
+                &third_cid,
+                &token,
+                &srv,
+            )
+            .await;
+
+            let mut archive = driver_export_user(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap();
+            assert_eq!(archive.currency_rate_sources.as_ref().unwrap().len(), 1);
+            assert_eq!(archive.wallets.as_ref().unwrap().len(), 1);
+            assert!(archive.wallet_txns.as_ref().unwrap().is_empty());
+            // As if the txn was synced from the wallet
+            archive.wallet_txns = Some(vec![UserArchiveWalletTxn {
+                chain: WalletChain::Btc,
+                address: "Bitcoin-address".to_string(),
+                tx_hash: "hash".to_string(),
+                txn_id: Some(archive.txns[0].id),
+            }]);
+
+            // Import without token
+            {
+                let resp = driver_import_user(
+                    TestBody::Expected(archive.clone()),
+                    false,
+                    None,
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+            }
+
+            // Import with remapped ids, the content is identical but every id is fresh
+            {
+                let other_token = bootstrap_token(("1234", "1234"), &srv).await;
+                let summary = driver_import_user(
+                    TestBody::Expected(archive.clone()),
+                    true,
+                    Some(&other_token),
+                    &srv,
+                    true,
+                )
+                .await
+                .expected
+                .unwrap();
+                assert_eq!(summary.currencies, 3);
+                assert_eq!(summary.txns, 1);
+
+                let imported = driver_export_user(Some(&other_token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert_eq!(imported.accounts.len(), 1);
+                assert_ne!(imported.accounts[0].id, archive.accounts[0].id);
+                assert_eq!(imported.currencies.len(), 3);
+                assert!(imported
+                    .currencies
+                    .iter()
+                    .all(|c| archive.currencies.iter().all(|x| x.id != c.id)));
+                assert_eq!(imported.currency_rate_datums.len(), 1);
+                assert_eq!(imported.currency_rate_datums[0].amount, "10");
+                assert_eq!(imported.txn_tags.len(), 1);
+                assert_eq!(imported.txns.len(), 1);
+                let txn = &imported.txns[0];
+                assert_eq!(txn.date, "2025-01-01T01:02:00.000Z");
+                let from = txn.fragments[0].from.clone().unwrap();
+                assert_eq!(from.amount, "1.5");
+                assert_eq!(from.account, imported.accounts[0].id);
+                let third = imported
+                    .currencies
+                    .iter()
+                    .find(|c| c.ticker == "TRD")
+                    .unwrap();
+                assert_eq!(from.currency, third.id);
+                let sec = imported
+                    .currencies
+                    .iter()
+                    .find(|c| c.ticker == "SEC")
+                    .unwrap();
+                assert_eq!(sec.kind, Some(CurrencyKind::Security));
+                assert_eq!(sec.symbol.as_deref(), Some("§"));
+                assert_eq!(sec.archived, Some(true));
+                assert_eq!(sec.rate_strategy, Some(RateStrategy::LinearWithMaxGap));
+                assert_eq!(sec.rate_max_gap_days, Some(30));
+                assert_eq!(summary.currency_rate_sources, 1);
+                assert_eq!(summary.wallets, 1);
+                assert_eq!(summary.wallet_txns, 1);
+                let source = &imported.currency_rate_sources.as_ref().unwrap()[0];
+                assert_ne!(
+                    source.id,
+                    archive.currency_rate_sources.as_ref().unwrap()[0].id
+                );
+                assert_eq!(source.ref_currency_id, sec.id);
+                assert_eq!(source.hostname.as_deref(), Some("example.com"));
+                let wallet = &imported.wallets.as_ref().unwrap()[0];
+                assert_ne!(wallet.id, archive.wallets.as_ref().unwrap()[0].id);
+                assert_eq!(wallet.account_id, imported.accounts[0].id);
+                assert_eq!(wallet.currency_id, third.id);
+                let wallet_txns = imported.wallet_txns.as_ref().unwrap();
+                assert_eq!(wallet_txns.len(), 1);
+                assert_eq!(wallet_txns[0].tx_hash, "hash");
+                assert_eq!(wallet_txns[0].txn_id, Some(txn.id));
+            }
+
+            // Delete the original user, then import the archive back while keeping the ids
+            {
+                driver_delete_user(Some(&token), &srv, true).await;
+                let token = bootstrap_token(("123", "123"), &srv).await;
+                driver_import_user(
+                    TestBody::Expected(archive.clone()),
+                    false,
+                    Some(&token),
+                    &srv,
+                    true,
+                )
+                .await;
+                let imported = driver_export_user(Some(&token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert_eq!(imported.accounts[0].id, archive.accounts[0].id);
+                assert_eq!(imported.txns[0].id, archive.txns[0].id);
+                let mut imported_cids =
+                    imported.currencies.iter().map(|c| c.id).collect::<Vec<_>>();
+                let mut archive_cids = archive.currencies.iter().map(|c| c.id).collect::<Vec<_>>();
+                imported_cids.sort();
+                archive_cids.sort();
+                assert_eq!(imported_cids, archive_cids);
+                assert_eq!(
+                    imported.currency_rate_sources.unwrap()[0].id,
+                    archive.currency_rate_sources.as_ref().unwrap()[0].id
+                );
+                assert_eq!(
+                    imported.wallets.unwrap()[0].id,
+                    archive.wallets.as_ref().unwrap()[0].id
+                );
+                let wallet_txns = imported.wallet_txns.unwrap();
+                assert_eq!(wallet_txns.len(), 1);
+                assert_eq!(wallet_txns[0].txn_id, Some(archive.txns[0].id));
+            }
+        }
+
+        #[actix_web::test]
+        async fn test_import_invalid_user_archive() {
+            let (srv, states) =
+                setup_connection_with_states(actix_test::TestServerConfig::default()).await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+
+            let base_cid = bootstrap_base_curr(("BASE", "Base"), &token, &srv).await;
+            let sec_cid = bootstrap_sec_curr(("SEC", "Sec"), "5", &base_cid, &token, &srv).await;
+            let account_id = bootstrap_post_account("My account", &token, &srv).await;
+            driver_post_txn(
+                Some(&token),
+                TestBody::Expected(PostTxnRequest {
+                    description: "my description".to_string(),
+                    title: "my title".to_string(),
+                    date_utc: "2025-01-01T01:02:00.000Z".to_string(),
+                    fragments: vec![PostTxnRequestFragment {
+                        from: None,
+                        to: Some(PostTxnRequestFragmentSide {
+                            account: account_id.clone(),
+                            currency: sec_cid.clone(),
+                            amount: "1".to_string(),
+                        }),
+                    }],
+                }),
+                &srv,
+                true,
+            )
+            .await;
+
+            let archive = driver_export_user(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap();
+            let other_token = bootstrap_token(("1234", "1234"), &srv).await;
+            let sec_index = archive.currencies.iter().position(|c| !c.is_base).unwrap();
+            let base_index = archive.currencies.iter().position(|c| c.is_base).unwrap();
+
+            let mut bad_archives: Vec<UserArchive> = vec![];
+
+            // Unsupported version
+            {
+                let mut item = archive.clone();
+                item.version = USER_ARCHIVE_VERSION + 1;
+                bad_archives.push(item);
+            }
+            // Fallback to an unknown currency
+            {
+                let mut item = archive.clone();
+                item.currencies[sec_index].fallback_rate_currency_id = Some(uuid::Uuid::new_v4());
+                bad_archives.push(item);
+            }
+            // Fallback cycle, base currency falls back to the secondary currency
+            {
+                let mut item = archive.clone();
+                item.currencies[base_index].is_base = false;
+                item.currencies[base_index].fallback_rate_amount = Some("1".to_string());
+                item.currencies[base_index].fallback_rate_currency_id =
+                    Some(item.currencies[sec_index].id);
+                bad_archives.push(item);
+            }
+            // Fragment referencing an unknown account
+            {
+                let mut item = archive.clone();
+                item.txns[0].fragments[0].to.as_mut().unwrap().account = uuid::Uuid::new_v4();
+                bad_archives.push(item);
+            }
+            // Fragment with an invalid amount
+            {
+                let mut item = archive.clone();
+                item.txns[0].fragments[0].to.as_mut().unwrap().amount = "abc".to_string();
+                bad_archives.push(item);
+            }
+            // Repeated account id
+            {
+                let mut item = archive.clone();
+                item.accounts.push(item.accounts[0].clone());
+                bad_archives.push(item);
+            }
+            // Wallet txn referencing an unknown txn
+            {
+                let mut item = archive.clone();
+                item.wallet_txns = Some(vec![UserArchiveWalletTxn {
+                    chain: WalletChain::Btc,
+                    address: "address".to_string(),
+                    tx_hash: "hash".to_string(),
+                    txn_id: Some(uuid::Uuid::new_v4()),
+                }]);
+                bad_archives.push(item);
+            }
+
+            for (count, item) in bad_archives.into_iter().enumerate() {
+                let resp = driver_import_user(
+                    TestBody::Expected(item),
+                    false,
+                    Some(&other_token),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(
+                    resp.status,
+                    StatusCode::BAD_REQUEST,
+                    "test_import_invalid_user_archive: expect item at index {} to fail.",
+                    count
+                );
+            }
+
+            // Nothing is written when the archive is rejected
+            {
+                let imported = driver_export_user(Some(&other_token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert!(imported.accounts.is_empty());
+                assert!(imported.currencies.is_empty());
+            }
+
+            // Fragments more precise than their currency are only refused once the currencies
+            // and tags were inserted, nothing of them remains before importing again
+            {
+                let mut item = archive.clone();
+                item.txn_tags.push(UserArchiveTxnTag {
+                    id: uuid::Uuid::new_v4(),
+                    name: "Rolled back".to_string(),
+                });
+                let decimals = item.currencies[sec_index].decimals.unwrap() as usize;
+                item.txns[0].fragments[0].to.as_mut().unwrap().amount =
+                    format!("1.{}1", "0".repeat(decimals));
+                let resp = driver_import_user(
+                    TestBody::Expected(item),
+                    false,
+                    Some(&other_token),
+                    &srv,
+                    false,
+                )
+                .await;
+                assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+                let other_owner = driver_export_user(Some(&other_token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap()
+                    .owner;
+                let cached_tags = states
+                    .txn_tags_cache
+                    .lock()
+                    .await
+                    .query_txn_tag(&AuthUser(other_owner));
+                assert!(cached_tags.is_empty());
+
+                driver_import_user(
+                    TestBody::Expected(archive.clone()),
+                    true,
+                    Some(&other_token),
+                    &srv,
+                    true,
+                )
+                .await;
+                let imported = driver_export_user(Some(&other_token), &srv, true)
+                    .await
+                    .expected
+                    .unwrap();
+                assert_eq!(imported.currencies.len(), 2);
+                assert_eq!(imported.txns.len(), 1);
+            }
+        }
 
         #[actix_web::test]
         async fn test_malformed_logins() {
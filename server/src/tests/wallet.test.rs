@@ -0,0 +1,674 @@
+#[cfg(test)]
+pub mod wallets {
+    use crate::extended_models::wallet::WalletChain;
+    use crate::routes::wallets::delete_wallet::*;
+    use crate::routes::wallets::get_wallets::*;
+    use crate::routes::wallets::post_wallet::*;
+    use crate::tests::account_tests::accounts::drivers::bootstrap_post_account;
+    use crate::tests::commons::*;
+    use crate::tests::currency_tests::currencies::drivers::*;
+    use crate::tests::user_tests::users::drivers::*;
+    use actix_http::StatusCode;
+    use actix_web::http::header::ContentType;
+    use drivers::*;
+
+    pub mod drivers {
+        use super::*;
+        use crate::services::wallets::{
+            ChainProvider, ChainTransfer, ChainTransfersPage, SyncWalletErrors, ESPLORA_PAGE_SIZE,
+        };
+        use chrono::NaiveDate;
+        use futures::future::LocalBoxFuture;
+        use rust_decimal::Decimal;
+        use std::str::FromStr;
+
+        pub async fn driver_post_wallet(
+            token: &str,
+            body: TestBody<PostWalletRequest>,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<PostWalletResponse> {
+            let mut req = app.post("/api/v2/wallets");
+            req = attach_token_to_req(req, Some(token));
+            req = req.insert_header(ContentType::json());
+            let mut res = send_req_with_body(req, body).await;
+            let res_parsed: AssertTestResponse<PostWalletResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_get_wallets(
+            token: &str,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<GetWalletsResponse> {
+            let req = app.get("/api/v2/wallets");
+            let mut res = attach_token_to_req(req, Some(token)).send().await.unwrap();
+            let res_parsed: AssertTestResponse<GetWalletsResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn driver_delete_wallet(
+            id: &str,
+            token: &str,
+            app: &actix_test::TestServer,
+            assert_default: bool,
+        ) -> AssertTestResponse<DeleteWalletResponse> {
+            let req = app.delete(format!("/api/v2/wallets/{id}"));
+            let mut res = attach_token_to_req(req, Some(token)).send().await.unwrap();
+            let res_parsed: AssertTestResponse<DeleteWalletResponse> =
+                parse_response_body(&mut res).await;
+            if assert_default {
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+            res_parsed
+        }
+
+        pub async fn bootstrap_wallet(
+            name: &str,
+            chain: WalletChain,
+            account_id: &str,
+            currency_id: &str,
+            token: &str,
+            app: &actix_test::TestServer,
+        ) -> String {
+            driver_post_wallet(
+                token,
+                TestBody::Expected(PostWalletRequest {
+                    name: name.to_string(),
+                    chain,
+                    address: format!("{name}-address"),
+                    account_id: account_id.to_string(),
+                    currency_id: currency_id.to_string(),
+                }),
+                app,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id
+        }
+
+        pub fn transfer(hash: &str, day: u32, amount: &str) -> ChainTransfer {
+            ChainTransfer {
+                hash: hash.to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 1, day)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+                amount: Decimal::from_str(amount).unwrap(),
+            }
+        }
+
+        /// An in-process provider serving fixed pages, the cursor being the index of the next page.
+        pub struct FakeChainProvider {
+            pub pages: Vec<Vec<ChainTransfer>>,
+            /// Fail when asked for this page.
+            pub failing_page: Option<usize>,
+        }
+
+        impl ChainProvider for FakeChainProvider {
+            fn fetch_transfers<'a>(
+                &'a self,
+                _address: &'a str,
+                cursor: Option<&'a str>,
+            ) -> LocalBoxFuture<'a, Result<ChainTransfersPage, SyncWalletErrors>> {
+                Box::pin(async move {
+                    let index = cursor.map_or(0, |x| x.parse::<usize>().unwrap());
+                    if self.failing_page == Some(index) {
+                        return Err(SyncWalletErrors::Request("unreachable".to_string()));
+                    }
+                    Ok(match self.pages.get(index) {
+                        Some(transfers) => ChainTransfersPage {
+                            transfers: transfers.clone(),
+                            next_cursor: Some((index + 1).to_string()),
+                            has_more: index + 1 < self.pages.len(),
+                        },
+                        None => ChainTransfersPage::default(),
+                    })
+                })
+            }
+        }
+
+        /// A transaction as listed by an Esplora API, moving satoshis to and from `address`.
+        pub fn esplora_transaction(
+            txid: &str,
+            hour: i64,
+            address: &str,
+            received: i64,
+            spent: i64,
+        ) -> serde_json::Value {
+            let input = match spent {
+                0 => serde_json::json!({ "prevout": null }),
+                _ => {
+                    serde_json::json!({ "prevout": { "scriptpubkey_address": address, "value": spent } })
+                }
+            };
+            serde_json::json!({
+                "txid": txid,
+                "vin": [input],
+                "vout": [
+                    { "scriptpubkey_address": address, "value": received },
+                    { "scriptpubkey_address": "someone-else", "value": 1000 },
+                    { "value": 0 }
+                ],
+                "status": { "confirmed": true, "block_time": 1704067200 + hour * 3600 }
+            })
+        }
+
+        /// Transactions served by [start_esplora_stub], newest first, and the number of pages served.
+        #[derive(Default)]
+        pub struct EsploraStub {
+            pub transactions: std::sync::Mutex<Vec<serde_json::Value>>,
+            pub pages_served: std::sync::atomic::AtomicUsize,
+        }
+
+        /// An in-process Esplora API under `/api`, paging like the real one.
+        pub fn start_esplora_stub(
+            stub: actix_web::web::Data<EsploraStub>,
+        ) -> actix_test::TestServer {
+            use actix_web::{web, App, HttpResponse};
+
+            async fn page(
+                stub: web::Data<EsploraStub>,
+                path: web::Path<Vec<String>>,
+            ) -> HttpResponse {
+                stub.pages_served
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let transactions = stub.transactions.lock().unwrap();
+                let start = match path.get(1) {
+                    None => 0,
+                    Some(last_seen) => {
+                        transactions
+                            .iter()
+                            .position(|x| x["txid"] == last_seen.as_str())
+                            .unwrap()
+                            + 1
+                    }
+                };
+                let page = transactions
+                    .iter()
+                    .skip(start)
+                    .take(ESPLORA_PAGE_SIZE)
+                    .filter(|x| x["vout"][0]["scriptpubkey_address"] == path[0].as_str())
+                    .collect::<Vec<_>>();
+                HttpResponse::Ok().json(page)
+            }
+
+            actix_test::start(move || {
+                App::new()
+                    .app_data(stub.clone())
+                    .route("/api/address/{address}/txs/chain", web::get().to(page))
+                    .route(
+                        "/api/address/{address}/txs/chain/{last_seen}",
+                        web::get().to(page),
+                    )
+            })
+        }
+    }
+
+    mod tests {
+        use super::*;
+        use crate::env::EnvWalletsSection;
+        use crate::routes::bootstrap::ErrorCode;
+        use crate::services::wallets::{
+            chain_providers, sync_all_wallets, ChainProviders, ESPLORA_PAGE_SIZE,
+        };
+        use crate::tests::txn::txns::drivers::{driver_delete_txn, driver_get_txns};
+        use actix_test::TestServerConfig;
+        use actix_web::web;
+        use std::sync::atomic::Ordering;
+
+        fn error_code(
+            json: &Option<std::collections::HashMap<String, serde_json::Value>>,
+        ) -> ErrorCode {
+            serde_json::from_value(json.as_ref().unwrap()["code"].clone()).unwrap()
+        }
+
+        fn btc_providers(provider: FakeChainProvider) -> ChainProviders {
+            let mut providers: ChainProviders = ChainProviders::new();
+            providers.insert(WalletChain::Btc, Box::new(provider));
+            providers
+        }
+
+        #[test]
+        fn test_stored_wallet_chains() {
+            for chain in [WalletChain::Btc, WalletChain::Ltc] {
+                assert_eq!(WalletChain::from_stored(chain.as_str()).unwrap(), chain);
+            }
+            // A corrupted row is never read as another chain
+            assert!(WalletChain::from_stored("xno").is_err());
+            assert!(WalletChain::from_stored("").is_err());
+        }
+
+        #[actix_web::test]
+        async fn test_crud_wallets() {
+            let srv = setup_connection().await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd_id = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let btc_id = bootstrap_curr_with_decimals("BTC", 8, &usd_id, &token, &srv).await;
+            let account_id = bootstrap_post_account("Cold storage", &token, &srv).await;
+
+            let request = |name: &str, address: &str, account_id: &str| PostWalletRequest {
+                name: name.to_string(),
+                chain: WalletChain::Btc,
+                address: address.to_string(),
+                account_id: account_id.to_string(),
+                currency_id: btc_id.clone(),
+            };
+
+            // Invalid wallets are rejected
+            let res = driver_post_wallet(
+                &token,
+                TestBody::Expected(request("Savings", "  ", &account_id)),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::BAD_REQUEST);
+            assert_eq!(error_code(&res.json), ErrorCode::InvalidWallet);
+            assert_eq!(res.json.unwrap()["details"]["field"], "address");
+            // Only chains a provider exists for are accepted
+            let mut body = serde_json::to_value(request("Savings", "bc1q", &account_id)).unwrap();
+            body["chain"] = serde_json::json!("xno");
+            let res = driver_post_wallet(
+                &token,
+                TestBody::Bytes(body.to_string().into_bytes().into_boxed_slice()),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::BAD_REQUEST);
+            let res = driver_post_wallet(
+                &token,
+                TestBody::Expected(request(
+                    "Savings",
+                    "bc1q",
+                    &uuid::Uuid::new_v4().to_string(),
+                )),
+                &srv,
+                false,
+            )
+            .await;
+            assert_eq!(res.status, StatusCode::NOT_FOUND);
+            assert_eq!(error_code(&res.json), ErrorCode::AccountNotFound);
+
+            let savings_id = driver_post_wallet(
+                &token,
+                TestBody::Expected(request("Savings", " bc1q ", &account_id)),
+                &srv,
+                true,
+            )
+            .await
+            .expected
+            .unwrap()
+            .id;
+            let daily_id = bootstrap_wallet(
+                "Daily",
+                WalletChain::Btc,
+                &account_id,
+                &btc_id,
+                &token,
+                &srv,
+            )
+            .await;
+
+            // Listed by name, with the address trimmed and never synced
+            let items = driver_get_wallets(&token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(
+                items.iter().map(|x| x.id.as_str()).collect::<Vec<_>>(),
+                vec![daily_id.as_str(), savings_id.as_str()]
+            );
+            assert_eq!(items[1].address, "bc1q");
+            assert_eq!(items[1].account_id, account_id);
+            assert_eq!(items[1].currency_id, btc_id);
+            assert_eq!(items[1].last_sync_date, None);
+            assert_eq!(items[1].last_error, None);
+
+            // Other users do not see them
+            let other_token = bootstrap_token(("456", "456"), &srv).await;
+            let other_items = driver_get_wallets(&other_token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert!(other_items.is_empty());
+            let res = driver_delete_wallet(&savings_id, &other_token, &srv, false).await;
+            assert_eq!(res.status, StatusCode::NOT_FOUND);
+            assert_eq!(error_code(&res.json), ErrorCode::WalletNotFound);
+
+            driver_delete_wallet(&savings_id, &token, &srv, true).await;
+            let items = driver_get_wallets(&token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].id, daily_id);
+        }
+
+        #[actix_web::test]
+        async fn test_sync_wallets() {
+            let (srv, states) = setup_connection_with_states(TestServerConfig::default()).await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd_id = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let btc_id = bootstrap_curr_with_decimals("BTC", 8, &usd_id, &token, &srv).await;
+            let ltc_id = bootstrap_curr_with_decimals("LTC", 8, &usd_id, &token, &srv).await;
+            let account_id = bootstrap_post_account("Crypto", &token, &srv).await;
+            let btc_wallet_id = bootstrap_wallet(
+                "Bitcoin",
+                WalletChain::Btc,
+                &account_id,
+                &btc_id,
+                &token,
+                &srv,
+            )
+            .await;
+            let ltc_wallet_id = bootstrap_wallet(
+                "Litecoin",
+                WalletChain::Ltc,
+                &account_id,
+                &ltc_id,
+                &token,
+                &srv,
+            )
+            .await;
+
+            let mut pages = vec![
+                vec![transfer("a", 1, "0.5"), transfer("zero", 2, "0")],
+                vec![transfer("b", 3, "-0.1")],
+            ];
+            let saved = sync_all_wallets(
+                &btc_providers(FakeChainProvider {
+                    pages: pages.clone(),
+                    failing_page: None,
+                }),
+                &states,
+            )
+            .await
+            .unwrap();
+            assert_eq!(saved, 2);
+
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(txns.len(), 2);
+            let received = txns.iter().find(|x| x.description == "a").unwrap();
+            assert_eq!(received.title, "Received on Bitcoin");
+            let to = received.fragments[0].to.as_ref().unwrap();
+            assert!(received.fragments[0].from.is_none());
+            assert_eq!(to.account.to_string(), account_id);
+            assert_eq!(to.currency.to_string(), btc_id);
+            assert_eq!(to.amount, "0.5");
+            let sent = txns.iter().find(|x| x.description == "b").unwrap();
+            assert_eq!(sent.title, "Sent from Bitcoin");
+            assert_eq!(sent.fragments[0].from.as_ref().unwrap().amount, "0.1");
+
+            let wallets = driver_get_wallets(&token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            let btc_wallet = wallets.iter().find(|x| x.id == btc_wallet_id).unwrap();
+            assert!(btc_wallet.last_sync_date.is_some());
+            assert_eq!(btc_wallet.last_error, None);
+            let ltc_wallet = wallets.iter().find(|x| x.id == ltc_wallet_id).unwrap();
+            assert_eq!(ltc_wallet.last_sync_date, None);
+            assert!(ltc_wallet
+                .last_error
+                .as_ref()
+                .unwrap()
+                .starts_with("No chain provider"));
+
+            // The next sync resumes from the cursor, and skips transfers already saved
+            pages.push(vec![transfer("b", 3, "-0.1"), transfer("c", 4, "1")]);
+            let saved = sync_all_wallets(
+                &btc_providers(FakeChainProvider {
+                    pages: pages.clone(),
+                    failing_page: Some(0),
+                }),
+                &states,
+            )
+            .await
+            .unwrap();
+            assert_eq!(saved, 1);
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(txns.len(), 3);
+
+            // Failures keep the last sync date and the cursor
+            let last_sync_date = driver_get_wallets(&token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items
+                .into_iter()
+                .find(|x| x.id == btc_wallet_id)
+                .unwrap()
+                .last_sync_date;
+            pages.push(vec![transfer("d", 5, "2")]);
+            let saved = sync_all_wallets(
+                &btc_providers(FakeChainProvider {
+                    pages: pages.clone(),
+                    failing_page: Some(3),
+                }),
+                &states,
+            )
+            .await
+            .unwrap();
+            assert_eq!(saved, 0);
+            let btc_wallet = driver_get_wallets(&token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items
+                .into_iter()
+                .find(|x| x.id == btc_wallet_id)
+                .unwrap();
+            assert_eq!(btc_wallet.last_sync_date, last_sync_date);
+            assert_eq!(
+                btc_wallet.last_error.as_deref(),
+                Some("Request failed: unreachable")
+            );
+            let saved = sync_all_wallets(
+                &btc_providers(FakeChainProvider {
+                    pages: pages.clone(),
+                    failing_page: None,
+                }),
+                &states,
+            )
+            .await
+            .unwrap();
+            assert_eq!(saved, 1);
+
+            // Synced transactions can be deleted, and outlive their wallet
+            driver_delete_txn(&txns[0].id, Some(&token), &srv, true).await;
+            driver_delete_wallet(&btc_wallet_id, &token, &srv, true).await;
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(txns.len(), 3);
+
+            // A wallet added again for the address saves neither the synced transfers again,
+            // nor the deleted one
+            bootstrap_wallet(
+                "Bitcoin",
+                WalletChain::Btc,
+                &account_id,
+                &btc_id,
+                &token,
+                &srv,
+            )
+            .await;
+            let saved = sync_all_wallets(
+                &btc_providers(FakeChainProvider {
+                    pages: pages.clone(),
+                    failing_page: None,
+                }),
+                &states,
+            )
+            .await
+            .unwrap();
+            assert_eq!(saved, 0);
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(txns.len(), 3);
+        }
+
+        #[actix_web::test]
+        async fn test_sync_wallets_with_esplora() {
+            let (srv, states) = setup_connection_with_states(TestServerConfig::default()).await;
+            let token = bootstrap_token(("123", "123"), &srv).await;
+            let usd_id = bootstrap_base_curr(("USD", "Dollar"), &token, &srv).await;
+            let btc_id = bootstrap_curr_with_decimals("BTC", 8, &usd_id, &token, &srv).await;
+            let account_id = bootstrap_post_account("Crypto", &token, &srv).await;
+            let btc_wallet_id = bootstrap_wallet(
+                "Bitcoin",
+                WalletChain::Btc,
+                &account_id,
+                &btc_id,
+                &token,
+                &srv,
+            )
+            .await;
+            let ltc_wallet_id = bootstrap_wallet(
+                "Litecoin",
+                WalletChain::Ltc,
+                &account_id,
+                &btc_id,
+                &token,
+                &srv,
+            )
+            .await;
+
+            // More transactions than fit in a page, newest first
+            let address = "Bitcoin-address";
+            let mut transactions = vec![esplora_transaction("spend", 40, address, 15000, 50000)];
+            transactions.extend(
+                (0..29)
+                    .rev()
+                    .map(|i| esplora_transaction(&format!("r{i}"), i, address, (i + 1) * 1000, 0)),
+            );
+            let stub = web::Data::new(EsploraStub {
+                transactions: std::sync::Mutex::new(transactions),
+                ..Default::default()
+            });
+            let esplora = start_esplora_stub(stub.clone());
+            let providers = chain_providers(Some(&EnvWalletsSection {
+                btc_esplora_url: Some(format!("http://{}/api", esplora.addr())),
+                ltc_esplora_url: None,
+                timeout: None,
+            }))
+            .unwrap();
+
+            // The oldest transaction is unconfirmed at first, only the first page is saved
+            let confirmed_r0 = std::mem::replace(
+                &mut stub.transactions.lock().unwrap()[29],
+                serde_json::json!({
+                    "txid": "r0",
+                    "vin": [],
+                    "vout": [{ "scriptpubkey_address": address, "value": 1000 }],
+                    "status": { "confirmed": false }
+                }),
+            );
+            let saved = sync_all_wallets(&providers, &states).await.unwrap();
+            assert_eq!(saved, 0);
+            assert_eq!(stub.pages_served.load(Ordering::SeqCst), 2);
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(txns.len(), ESPLORA_PAGE_SIZE);
+
+            // The next sync carries on from the page it failed on
+            stub.transactions.lock().unwrap()[29] = confirmed_r0;
+            let saved = sync_all_wallets(&providers, &states).await.unwrap();
+            assert_eq!(saved, 30 - ESPLORA_PAGE_SIZE);
+            assert_eq!(stub.pages_served.load(Ordering::SeqCst), 3);
+            let txns = driver_get_txns(Some(&token), &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            assert_eq!(txns.len(), 30);
+            let first = txns.iter().find(|x| x.description == "r0").unwrap();
+            assert_eq!(first.title, "Received on Bitcoin");
+            assert_eq!(first.date.to_string(), "2024-01-01T00:00:00.000Z");
+            assert_eq!(first.fragments[0].to.as_ref().unwrap().amount, "0.00001");
+            // Fees are part of the amount spent
+            let spend = txns.iter().find(|x| x.description == "spend").unwrap();
+            assert_eq!(spend.title, "Sent from Bitcoin");
+            assert_eq!(spend.fragments[0].from.as_ref().unwrap().amount, "0.00035");
+
+            // Only the transactions newer than the last one synced are read
+            stub.transactions
+                .lock()
+                .unwrap()
+                .insert(0, esplora_transaction("new", 50, address, 2000, 0));
+            let saved = sync_all_wallets(&providers, &states).await.unwrap();
+            assert_eq!(saved, 1);
+            assert_eq!(stub.pages_served.load(Ordering::SeqCst), 4);
+
+            // Unconfirmed transactions have no block time
+            stub.transactions.lock().unwrap().insert(
+                0,
+                serde_json::json!({
+                    "txid": "pending",
+                    "vin": [],
+                    "vout": [{ "scriptpubkey_address": address, "value": 1 }],
+                    "status": { "confirmed": false }
+                }),
+            );
+            let saved = sync_all_wallets(&providers, &states).await.unwrap();
+            assert_eq!(saved, 0);
+            let wallets = driver_get_wallets(&token, &srv, true)
+                .await
+                .expected
+                .unwrap()
+                .items;
+            let btc_wallet = wallets.iter().find(|x| x.id == btc_wallet_id).unwrap();
+            assert!(btc_wallet
+                .last_error
+                .as_ref()
+                .unwrap()
+                .starts_with("Invalid response"));
+            let ltc_wallet = wallets.iter().find(|x| x.id == ltc_wallet_id).unwrap();
+            assert!(ltc_wallet
+                .last_error
+                .as_ref()
+                .unwrap()
+                .starts_with("No chain provider"));
+
+            // Invalid provider URLs are refused
+            assert!(chain_providers(Some(&EnvWalletsSection {
+                btc_esplora_url: Some("ftp://example.com/api".to_string()),
+                ltc_esplora_url: None,
+                timeout: None,
+            }))
+            .is_err());
+        }
+    }
+}